@@ -0,0 +1,25 @@
+use std::env;
+use std::process::Command;
+
+// Detects whether `std::hint::black_box` (stable since Rust 1.66) can be used
+// on the compiler building this crate. Older compilers fall back to the
+// volatile-read implementation in `lib.rs`.
+fn main() {
+    println!("cargo:rustc-check-cfg=cfg(criterion_hint_black_box)");
+    println!("cargo:rerun-if-changed=build.rs");
+
+    if rustc_minor_version().unwrap_or(0) >= 66 {
+        println!("cargo:rustc-cfg=criterion_hint_black_box");
+    }
+}
+
+fn rustc_minor_version() -> Option<u32> {
+    let rustc = env::var_os("RUSTC")?;
+    let output = Command::new(rustc).arg("--version").output().ok()?;
+    let version = String::from_utf8(output.stdout).ok()?;
+    let mut pieces = version.split('.');
+    if pieces.next() != Some("rustc 1") {
+        return None;
+    }
+    pieces.next()?.parse().ok()
+}
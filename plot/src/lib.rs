@@ -411,6 +411,7 @@ pub struct Figure {
     font: Option<Cow<'static, str>>,
     font_size: Option<f64>,
     key: Option<key::Properties>,
+    label: Option<Cow<'static, str>>,
     output: Cow<'static, Path>,
     plots: Vec<Plot>,
     size: Option<(usize, usize)>,
@@ -429,6 +430,7 @@ impl Figure {
             font: None,
             font_size: None,
             key: None,
+            label: None,
             output: Cow::Borrowed(Path::new("output.plot")),
             plots: Vec::new(),
             size: None,
@@ -457,6 +459,13 @@ impl Figure {
             s.push_str(&format!("set title '{}'\n", title))
         }
 
+        if let Some(ref label) = self.label {
+            s.push_str(&format!(
+                "set label '{}' at screen 0.99, screen 0.01 right front\n",
+                label.replace('\'', "''")
+            ))
+        }
+
         for axis in self.axes.iter() {
             s.push_str(&axis.script());
         }
@@ -475,8 +484,23 @@ impl Figure {
 
         s.push_str(&format!("set terminal {} dashed", self.terminal.display()));
 
-        if let Some((width, height)) = self.size {
-            s.push_str(&format!(" size {}, {}", width, height))
+        match self.terminal {
+            Terminal::Svg => {
+                if let Some((width, height)) = self.size {
+                    s.push_str(&format!(" size {}, {}", width, height))
+                }
+            }
+            Terminal::Png(dpi) => {
+                let scale = f64::from(dpi) / 96.;
+                if let Some((width, height)) = self.size {
+                    s.push_str(&format!(
+                        " size {}, {}",
+                        (width as f64 * scale).round(),
+                        (height as f64 * scale).round()
+                    ))
+                }
+                s.push_str(&format!(" fontscale {} linewidth {}", scale, scale))
+            }
         }
 
         if let Some(ref name) = self.font {
@@ -650,6 +674,16 @@ impl Set<FontSize> for Figure {
     }
 }
 
+impl Set<Label> for Figure {
+    /// Attaches a label to the bottom right corner of the figure
+    ///
+    /// **Note** Single quotes in the label are escaped
+    fn set(&mut self, label: Label) -> &mut Figure {
+        self.label = Some(label.0);
+        self
+    }
+}
+
 impl Set<Output> for Figure {
     /// Changes the output file
     ///
@@ -672,7 +706,15 @@ impl Set<Terminal> for Figure {
     /// Changes the output terminal
     ///
     /// **Note** By default, the terminal is set to `Svg`
+    ///
+    /// # Panics
+    ///
+    /// Panics if the resolution of a `Png` terminal is zero
     fn set(&mut self, terminal: Terminal) -> &mut Figure {
+        if let Terminal::Png(dpi) = terminal {
+            assert!(dpi > 0);
+        }
+
         self.terminal = terminal;
         self
     }
@@ -867,10 +909,14 @@ pub enum Scale {
 pub struct ScaleFactor(pub f64);
 
 /// Output terminal
-#[allow(missing_docs)]
 #[derive(Clone, Copy)]
+#[non_exhaustive]
 pub enum Terminal {
+    /// SVG image
     Svg,
+    /// PNG image drawn with cairo, at the given resolution in dots per inch. The figure size is
+    /// taken to be in pixels at 96 DPI, and the image, fonts and lines are scaled to match.
+    Png(u32),
 }
 
 /// Not public version of `std::default::Default`, used to not leak default constructors into the
@@ -1090,4 +1136,37 @@ mod test {
             assert!(super::parse_version(string).is_err());
         }
     }
+
+    #[test]
+    fn test_png_terminal_is_scaled_by_resolution() {
+        use crate::prelude::*;
+
+        let mut figure = Figure::new();
+        figure.set(Size(1280, 720)).set(Terminal::Png(192));
+        let script = String::from_utf8(figure.script()).unwrap();
+        assert!(
+            script.contains("set terminal pngcairo dashed size 2560, 1440 fontscale 2 linewidth 2")
+        );
+    }
+
+    #[test]
+    fn test_label_is_placed_in_corner() {
+        use crate::prelude::*;
+
+        let mut figure = Figure::new();
+        figure.set(Label("abc1234 2026-10-17"));
+        let script = String::from_utf8(figure.script()).unwrap();
+        assert!(script
+            .contains("set label 'abc1234 2026-10-17' at screen 0.99, screen 0.01 right front\n"));
+    }
+
+    #[test]
+    fn test_label_quotes_are_escaped() {
+        use crate::prelude::*;
+
+        let mut figure = Figure::new();
+        figure.set(Label("it's 'fast'"));
+        let script = String::from_utf8(figure.script()).unwrap();
+        assert!(script.contains("set label 'it''s ''fast''' at screen 0.99"));
+    }
 }
@@ -0,0 +1,147 @@
+//! Pins the current thread to a single core while a benchmark is measured, enabled with
+//! `Criterion::pin_to_core`.
+
+/// Restores the thread's original affinity when dropped.
+pub(crate) struct Pinned {
+    #[cfg(target_os = "linux")]
+    original: libc::cpu_set_t,
+    #[cfg(windows)]
+    original: usize,
+}
+
+impl Drop for Pinned {
+    fn drop(&mut self) {
+        if let Err(err) = imp::restore(self) {
+            warn!("Failed to restore the thread's CPU affinity: {}", err);
+        }
+    }
+}
+
+/// Pins the current thread to `core` until the returned value is dropped. If the affinity can't
+/// be changed, a warning is printed and the thread isn't pinned.
+pub(crate) fn pin_to_core(core: usize) -> Option<Pinned> {
+    match imp::pin(core) {
+        Ok(pinned) => Some(pinned),
+        Err(err) => {
+            warn!("Failed to pin the benchmark to core {}: {}", core, err);
+            None
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod imp {
+    use super::Pinned;
+    use std::io;
+    use std::mem;
+
+    pub fn pin(core: usize) -> Result<Pinned, String> {
+        if core >= libc::CPU_SETSIZE as usize {
+            return Err(format!("core indices must be below {}", libc::CPU_SETSIZE));
+        }
+        unsafe {
+            let mut original: libc::cpu_set_t = mem::zeroed();
+            if libc::sched_getaffinity(0, mem::size_of::<libc::cpu_set_t>(), &mut original) != 0 {
+                return Err(io::Error::last_os_error().to_string());
+            }
+
+            let mut set: libc::cpu_set_t = mem::zeroed();
+            libc::CPU_SET(core, &mut set);
+            if libc::sched_setaffinity(0, mem::size_of::<libc::cpu_set_t>(), &set) != 0 {
+                return Err(io::Error::last_os_error().to_string());
+            }
+            Ok(Pinned { original })
+        }
+    }
+
+    pub fn restore(pinned: &Pinned) -> Result<(), String> {
+        let result = unsafe {
+            libc::sched_setaffinity(0, mem::size_of::<libc::cpu_set_t>(), &pinned.original)
+        };
+        if result != 0 {
+            return Err(io::Error::last_os_error().to_string());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(windows)]
+mod imp {
+    use super::Pinned;
+    use std::ffi::c_void;
+    use std::io;
+    use std::mem;
+
+    extern "system" {
+        fn GetCurrentThread() -> *mut c_void;
+        fn SetThreadAffinityMask(thread: *mut c_void, mask: usize) -> usize;
+    }
+
+    pub fn pin(core: usize) -> Result<Pinned, String> {
+        if core >= mem::size_of::<usize>() * 8 {
+            return Err(format!(
+                "core indices must be below {}",
+                mem::size_of::<usize>() * 8
+            ));
+        }
+        // SetThreadAffinityMask returns the previous mask, or zero if it failed.
+        let original = unsafe { SetThreadAffinityMask(GetCurrentThread(), 1 << core) };
+        if original == 0 {
+            return Err(io::Error::last_os_error().to_string());
+        }
+        Ok(Pinned { original })
+    }
+
+    pub fn restore(pinned: &Pinned) -> Result<(), String> {
+        if unsafe { SetThreadAffinityMask(GetCurrentThread(), pinned.original) } == 0 {
+            return Err(io::Error::last_os_error().to_string());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(not(any(target_os = "linux", windows)))]
+mod imp {
+    use super::Pinned;
+
+    pub fn pin(_: usize) -> Result<Pinned, String> {
+        Err("thread affinity is not supported on this platform".to_owned())
+    }
+
+    pub fn restore(_: &Pinned) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod test {
+    use super::*;
+    use std::mem;
+
+    fn current_affinity() -> Vec<usize> {
+        unsafe {
+            let mut set: libc::cpu_set_t = mem::zeroed();
+            assert_eq!(
+                libc::sched_getaffinity(0, mem::size_of::<libc::cpu_set_t>(), &mut set),
+                0
+            );
+            (0..libc::CPU_SETSIZE as usize)
+                .filter(|&core| libc::CPU_ISSET(core, &set))
+                .collect()
+        }
+    }
+
+    #[test]
+    fn test_pin_to_core() {
+        let original = current_affinity();
+        let core = *original.last().unwrap();
+        {
+            let _pinned = pin_to_core(core).unwrap();
+            assert_eq!(current_affinity(), vec![core]);
+        }
+        assert_eq!(current_affinity(), original);
+
+        assert!(pin_to_core(libc::CPU_SETSIZE as usize).is_none());
+        assert_eq!(current_affinity(), original);
+    }
+}
@@ -3,80 +3,174 @@ use crate::stats::univariate::{self, mixed};
 use crate::stats::Distribution;
 
 use crate::benchmark::BenchmarkConfig;
-use crate::error::Result;
+use crate::error::{Error, Result};
 use crate::estimate::{
     build_change_estimates, ChangeDistributions, ChangeEstimates, ChangePointEstimates, Estimates,
+    Statistic,
 };
 use crate::measurement::Measurement;
 use crate::report::BenchmarkId;
 use crate::{fs, Criterion, SavedSample};
 
-// Common comparison procedure
-#[cfg_attr(feature = "cargo-clippy", allow(clippy::type_complexity))]
-pub(crate) fn common<M: Measurement>(
+// The bootstrap distributions behind a comparison, saved to `change/distributions.json` when
+// enabled with `Criterion::save_distributions`.
+#[derive(Serialize)]
+struct SavedChangeDistributions<'a> {
+    t_statistic: f64,
+    t_distribution: &'a [f64],
+    mean: &'a [f64],
+    median: &'a [f64],
+}
+
+// The measurement recorded in the baseline's `benchmark.json`. Files saved by older versions
+// don't have it.
+#[derive(Deserialize)]
+struct SavedMeasurement {
+    measurement: Option<String>,
+}
+
+// The saved sample and estimates of the baseline that a benchmark is compared against.
+pub(crate) struct BaseSample {
+    pub(crate) iters: Vec<f64>,
+    pub(crate) times: Vec<f64>,
+    pub(crate) avg_times: Vec<f64>,
+    pub(crate) estimates: Estimates,
+}
+
+// The statistics of a comparison against the baseline.
+pub(crate) struct ComparisonStatistics {
+    pub(crate) t_statistic: f64,
+    pub(crate) t_distribution: Distribution<f64>,
+    pub(crate) effect_size: f64,
+    pub(crate) relative_estimates: ChangeEstimates,
+    pub(crate) relative_distributions: ChangeDistributions,
+    pub(crate) base: BaseSample,
+}
+
+// Loads the baseline of the benchmark, converting it to the current measurement if it was recorded
+// with a different one.
+pub(crate) fn load_base<M: Measurement>(
     id: &BenchmarkId,
-    avg_times: &Sample<f64>,
-    config: &BenchmarkConfig,
     criterion: &Criterion<M>,
-) -> Result<(
-    f64,
-    Distribution<f64>,
-    ChangeEstimates,
-    ChangeDistributions,
-    Vec<f64>,
-    Vec<f64>,
-    Vec<f64>,
-    Estimates,
-)> {
-    let mut sample_file = criterion.output_directory.clone();
-    sample_file.push(id.as_directory_name());
-    sample_file.push(&criterion.baseline_directory);
-    sample_file.push("sample.json");
-    let sample: SavedSample = fs::load(&sample_file)?;
-    let SavedSample { iters, times, .. } = sample;
-
-    let mut estimates_file = criterion.output_directory.clone();
-    estimates_file.push(id.as_directory_name());
-    estimates_file.push(&criterion.baseline_directory);
+) -> Result<BaseSample> {
+    let (root, baseline) = criterion.comparison_baseline();
+    let mut sample_dir = root.to_owned();
+    sample_dir.push(id.as_directory_name());
+    sample_dir.push(baseline);
+
+    let benchmark_file = sample_dir.join("benchmark.json");
+    let base_measurement = if benchmark_file.exists() {
+        fs::load::<SavedMeasurement, _>(&benchmark_file)?.measurement
+    } else {
+        None
+    };
+    let current = criterion.measurement.type_name();
+    let factor = match base_measurement {
+        Some(base_measurement) if base_measurement != current => {
+            match criterion.measurement_conversion {
+                Some(factor) => factor,
+                None => {
+                    return Err(Error::MeasurementMismatch {
+                        id: id.as_title().to_owned(),
+                        baseline: base_measurement,
+                        current: current.to_owned(),
+                    })
+                }
+            }
+        }
+        _ => 1.0,
+    };
+
+    let sample: SavedSample = fs::load(&fs::sample_file(&sample_dir))?;
+    let SavedSample {
+        iters, mut times, ..
+    } = sample;
+
+    let mut estimates_file = sample_dir;
     estimates_file.push("estimates.json");
-    let base_estimates: Estimates = fs::load(&estimates_file)?;
+    let mut estimates: Estimates = fs::load(&estimates_file)?;
+
+    if factor != 1.0 {
+        times.iter_mut().for_each(|time| *time *= factor);
+        estimates.scale(factor);
+    }
 
-    let base_avg_times: Vec<f64> = iters
+    let avg_times: Vec<f64> = iters
         .iter()
         .zip(times.iter())
         .map(|(iters, elapsed)| elapsed / iters)
         .collect();
-    let base_avg_time_sample = Sample::new(&base_avg_times);
 
+    Ok(BaseSample {
+        iters,
+        times,
+        avg_times,
+        estimates,
+    })
+}
+
+// Common comparison procedure
+pub(crate) fn statistics(
+    avg_times: &Sample<f64>,
+    base: BaseSample,
+    config: &BenchmarkConfig,
+) -> ComparisonStatistics {
+    let base_avg_time_sample = Sample::new(&base.avg_times);
+    let (t_statistic, t_distribution, effect_size) =
+        t_test(avg_times, base_avg_time_sample, config);
+
+    let (relative_estimates, relative_distributions) =
+        estimates(avg_times, base_avg_time_sample, config);
+
+    ComparisonStatistics {
+        t_statistic,
+        t_distribution,
+        effect_size,
+        relative_estimates,
+        relative_distributions,
+        base,
+    }
+}
+
+// Saves the relative estimates to `change/estimates.json`, and the bootstrap distributions behind
+// them if enabled.
+pub(crate) fn save<M: Measurement>(
+    id: &BenchmarkId,
+    comparison: &ComparisonStatistics,
+    criterion: &Criterion<M>,
+) -> Result<()> {
     let mut change_dir = criterion.output_directory.clone();
     change_dir.push(id.as_directory_name());
     change_dir.push("change");
     fs::mkdirp(&change_dir)?;
-    let (t_statistic, t_distribution) = t_test(avg_times, base_avg_time_sample, config);
 
-    let (estimates, relative_distributions) =
-        estimates(id, avg_times, base_avg_time_sample, config, criterion);
-    Ok((
-        t_statistic,
-        t_distribution,
-        estimates,
-        relative_distributions,
-        iters,
-        times,
-        base_avg_times.clone(),
-        base_estimates,
-    ))
+    fs::save(
+        &comparison.relative_estimates,
+        &change_dir.join("estimates.json"),
+    )?;
+
+    if criterion.save_distributions {
+        let distributions = SavedChangeDistributions {
+            t_statistic: comparison.t_statistic,
+            t_distribution: &comparison.t_distribution,
+            mean: &comparison.relative_distributions.mean,
+            median: &comparison.relative_distributions.median,
+        };
+        fs::save(&distributions, &change_dir.join("distributions.json"))?;
+    }
+    Ok(())
 }
 
-// Performs a two sample t-test
+// Performs a two sample t-test, and computes the effect size (Cohen's d) of the difference
 fn t_test(
     avg_times: &Sample<f64>,
     base_avg_times: &Sample<f64>,
     config: &BenchmarkConfig,
-) -> (f64, Distribution<f64>) {
-    let nresamples = config.nresamples;
+) -> (f64, Distribution<f64>, f64) {
+    let nresamples = config.compare_resamples();
 
     let t_statistic = avg_times.t(base_avg_times);
+    let effect_size = avg_times.cohens_d(base_avg_times);
     let t_distribution = elapsed!(
         "Bootstrapping the T distribution",
         mixed::bootstrap(avg_times, base_avg_times, nresamples, |a, b| (a.t(b),))
@@ -94,50 +188,153 @@ fn t_test(
             .into_boxed_slice(),
     );
 
-    (t_statistic, t_distribution)
+    (t_statistic, t_distribution, effect_size)
+}
+
+// Computes one of the statistics which can be set with `Criterion::comparison_statistics`.
+fn value(statistic: Statistic, sample: &Sample<f64>, config: &BenchmarkConfig) -> f64 {
+    match statistic {
+        Statistic::Mean => sample.mean(),
+        Statistic::Median => sample.percentiles().median(),
+        Statistic::MedianAbsDev => sample.median_abs_dev(None),
+        Statistic::StdDev => sample.std_dev(None),
+        Statistic::TrimmedMean => sample.trimmed_mean(config.trimmed_mean_fraction),
+        Statistic::Percentile(p) => sample.percentiles().at(f64::from(p)),
+        Statistic::Mode | Statistic::Slope | Statistic::Typical => {
+            unreachable!("{} can't be compared", statistic)
+        }
+    }
+}
+
+// The change from `base` to `new` relative to the magnitude of `base`, so that an increase is
+// positive even if the measured values are negative.
+fn relative_change(new: f64, base: f64) -> f64 {
+    (new - base) / base.abs()
 }
 
 // Estimates the relative change in the statistics of the population
-fn estimates<M: Measurement>(
-    id: &BenchmarkId,
+fn estimates(
     avg_times: &Sample<f64>,
     base_avg_times: &Sample<f64>,
     config: &BenchmarkConfig,
-    criterion: &Criterion<M>,
 ) -> (ChangeEstimates, ChangeDistributions) {
     fn stats(a: &Sample<f64>, b: &Sample<f64>) -> (f64, f64) {
         (
-            a.mean() / b.mean() - 1.,
-            a.percentiles().median() / b.percentiles().median() - 1.,
+            relative_change(a.mean(), b.mean()),
+            relative_change(a.percentiles().median(), b.percentiles().median()),
         )
     }
 
     let cl = config.confidence_level;
-    let nresamples = config.nresamples;
+    let nresamples = config.compare_resamples();
 
     let (dist_mean, dist_median) = elapsed!(
         "Bootstrapping the relative statistics",
         univariate::bootstrap(avg_times, base_avg_times, nresamples, stats)
     );
 
+    // The mean and median are always compared. Any other statistics are bootstrapped separately,
+    // since the number of them isn't known statically.
+    let other_statistics = config
+        .comparison_statistics
+        .iter()
+        .cloned()
+        .filter(|&statistic| statistic != Statistic::Mean && statistic != Statistic::Median)
+        .collect::<Vec<_>>();
+    let relative = |statistic, a: &Sample<f64>, b: &Sample<f64>| {
+        relative_change(value(statistic, a, config), value(statistic, b, config))
+    };
+    let dist_other = other_statistics
+        .iter()
+        .map(|&statistic| {
+            let msg = format!("Bootstrapping the relative {}", statistic);
+            let (distribution,) = elapsed!(
+                msg.as_str(),
+                univariate::bootstrap(avg_times, base_avg_times, nresamples, |a, b| {
+                    (relative(statistic, a, b),)
+                })
+            );
+            (statistic, distribution)
+        })
+        .collect();
+
     let distributions = ChangeDistributions {
         mean: dist_mean,
         median: dist_median,
+        other: dist_other,
     };
 
     let (mean, median) = stats(avg_times, base_avg_times);
-    let points = ChangePointEstimates { mean, median };
+    let other = other_statistics
+        .iter()
+        .map(|&statistic| (statistic, relative(statistic, avg_times, base_avg_times)))
+        .collect();
+    let points = ChangePointEstimates {
+        mean,
+        median,
+        other,
+    };
 
     let estimates = build_change_estimates(&distributions, &points, cl);
 
-    {
-        log_if_err!({
-            let mut estimates_path = criterion.output_directory.clone();
-            estimates_path.push(id.as_directory_name());
-            estimates_path.push("change");
-            estimates_path.push("estimates.json");
-            fs::save(&estimates, &estimates_path)
-        });
-    }
     (estimates, distributions)
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::stats::Tails;
+    use crate::{RegressionMethod, SamplingMode};
+    use std::time::Duration;
+
+    fn config(significance_level: f64) -> BenchmarkConfig {
+        BenchmarkConfig {
+            confidence_level: 0.95,
+            measurement_time: Duration::from_secs(5),
+            noise_threshold: 0.01,
+            nresamples: 10_000,
+            nresamples_compare: None,
+            sample_size: 100,
+            significance_level,
+            warm_up_time: Duration::from_secs(3),
+            warm_up_iterations: None,
+            sampling_mode: SamplingMode::Auto,
+            trimmed_mean_fraction: 0.1,
+            estimate_trimmed_mean: false,
+            estimate_mode: false,
+            robust_dispersion: false,
+            regression_method: RegressionMethod::LeastSquares,
+            comparison_statistics: vec![],
+            value_transform: None,
+        }
+    }
+
+    #[test]
+    fn test_significance_level_is_used_as_p_value_threshold() {
+        let base = (0..100)
+            .map(|i| 100.0 + ((i * 37) % 39) as f64 - 19.0)
+            .collect::<Vec<f64>>();
+        let new = base.iter().map(|x| x * 1.04).collect::<Vec<f64>>();
+
+        for &(significance_level, expected) in &[(0.05, true), (0.001, false)] {
+            let config = config(significance_level);
+            let (t_value, t_distribution, _) =
+                t_test(Sample::new(&new), Sample::new(&base), &config);
+            let p_value = t_distribution.p_value(t_value, &Tails::Two);
+            assert_eq!(
+                p_value < config.significance_level,
+                expected,
+                "p = {} at significance level {}",
+                p_value,
+                significance_level
+            );
+        }
+    }
+
+    #[test]
+    fn test_relative_change_of_negative_values() {
+        assert_eq!(relative_change(110.0, 100.0), 0.1);
+        assert_eq!(relative_change(-90.0, -100.0), 0.1);
+        assert_eq!(relative_change(-110.0, -100.0), -0.1);
+    }
+}
@@ -1,21 +1,30 @@
+use std::panic;
 use std::path::Path;
+use std::thread::{self, JoinHandle};
 
 use crate::stats::bivariate::regression::Slope;
 use crate::stats::bivariate::Data;
+use crate::stats::tuple::Tuple;
 use crate::stats::univariate::outliers::tukey;
 use crate::stats::univariate::Sample;
-use crate::stats::{Distribution, Tails};
+use crate::stats::{self, Distribution, Tails};
 
 use crate::benchmark::BenchmarkConfig;
-use crate::connection::OutgoingMessage;
+use crate::connection::{Connection, OutgoingMessage};
 use crate::estimate::{
-    build_estimates, ConfidenceInterval, Distributions, Estimate, Estimates, PointEstimates,
+    build_estimates, relative_standard_error, ConfidenceInterval, Distributions, Estimate,
+    Estimates, PointEstimates, ThroughputEstimate,
 };
+use crate::format;
 use crate::fs;
+use crate::kde;
 use crate::measurement::Measurement;
 use crate::report::{BenchmarkId, Report, ReportContext};
-use crate::routine::Routine;
-use crate::{Baseline, Criterion, SavedSample, Throughput};
+use crate::routine::{Hooks, Routine};
+use crate::system::SystemSamples;
+use crate::{
+    ActualSamplingMode, Baseline, Criterion, DurationExt, RegressionMethod, SavedSample, Throughput,
+};
 
 macro_rules! elapsed {
     ($msg:expr, $block:expr) => {{
@@ -26,8 +35,9 @@ macro_rules! elapsed {
         info!(
             "{} took {}",
             $msg,
-            crate::format::time(crate::DurationExt::to_nanos(elapsed) as f64)
+            crate::format::time(crate::DurationExt::to_nanos(elapsed) as f64, None)
         );
+        crate::trace::record($msg, start, *elapsed);
 
         out
     }};
@@ -35,7 +45,31 @@ macro_rules! elapsed {
 
 mod compare;
 
+// Whether the benchmark is selected by the tag filter (see `Criterion::with_tag_filter`). The tags
+// added with `Bencher::tag` are only known once the routine has run, so when benchmarking, unless
+// the benchmark already has the selected tag, a single iteration of the routine is run to find
+// them. Listing, testing and profiling never run the routine just for this, and only use the
+// tags of the benchmark's group.
+pub(crate) fn tag_filter_matches<M: Measurement, T: ?Sized>(
+    id: &BenchmarkId,
+    routine: &mut dyn Routine<M, T>,
+    criterion: &Criterion<M>,
+    parameter: &T,
+    hooks: &mut Hooks<'_>,
+) -> Result<bool, String> {
+    match &criterion.tag_filter {
+        Some(tag) if !id.tags.contains(tag) && criterion.mode.is_benchmark() => {
+            hooks.around(routine, |routine| {
+                routine.test(&criterion.measurement, parameter)
+            })?;
+            Ok(criterion.tags_match(routine.tags()))
+        }
+        _ => Ok(criterion.tags_match(&id.tags)),
+    }
+}
+
 // Common analysis procedure
+#[allow(clippy::too_many_arguments)]
 pub(crate) fn common<M: Measurement, T: ?Sized>(
     id: &BenchmarkId,
     routine: &mut dyn Routine<M, T>,
@@ -43,75 +77,224 @@ pub(crate) fn common<M: Measurement, T: ?Sized>(
     criterion: &Criterion<M>,
     report_context: &ReportContext,
     parameter: &T,
-    throughput: Option<Throughput>,
+    mut throughput: Option<Throughput>,
+    hooks: &mut Hooks<'_>,
 ) {
-    criterion.report.benchmark_start(id, report_context);
-
-    if let Baseline::Compare = criterion.baseline {
-        if !base_dir_exists(
-            id,
-            &criterion.baseline_directory,
-            &criterion.output_directory,
-        ) {
-            panic!(
-                "Baseline '{base}' must exist before comparison is allowed; try --save-baseline {base}",
-                base=criterion.baseline_directory,
-            );
-        }
+    if let Some((_, iters)) = &criterion.isolated_sample {
+        crate::isolated::measure_and_exit(routine, criterion, parameter, hooks, *iters);
+    }
+    if criterion.connection.is_none() {
+        crate::interrupt::install();
     }
+    let timer_overhead = if criterion.load_baseline.is_none() {
+        Some(timer_overhead(criterion, report_context))
+    } else {
+        None
+    };
+    start_benchmark(id, criterion, report_context);
 
-    let (sampling_mode, iters, times);
+    let (sampling_mode, iters, times, system);
     if let Some(baseline) = &criterion.load_baseline {
-        let mut sample_path = criterion.output_directory.clone();
-        sample_path.push(id.as_directory_name());
-        sample_path.push(baseline);
-        sample_path.push("sample.json");
-        let loaded = fs::load::<SavedSample, _>(&sample_path);
-
-        match loaded {
-            Err(err) => panic!(
-                "Baseline '{base}' must exist before it can be loaded; try --save-baseline {base}. Error: {err}",
-                base = baseline, err = err
-            ),
-            Ok(samples) => {
-                sampling_mode = samples.sampling_mode;
-                iters = samples.iters.into_boxed_slice();
-                times = samples.times.into_boxed_slice();
-            }
-        }
+        let samples = load_sample(id, criterion, baseline);
+        sampling_mode = samples.0;
+        iters = samples.1;
+        times = samples.2;
+        system = None;
     } else {
-        let sample = routine.sample(
-            &criterion.measurement,
-            id,
-            config,
-            criterion,
-            report_context,
-            parameter,
-        );
+        let system_start = if criterion.monitor_system {
+            Some(crate::system::sample())
+        } else {
+            None
+        };
+        let sample = match hooks.around(routine, |routine| {
+            routine.sample(
+                &criterion.measurement,
+                id,
+                config,
+                criterion,
+                report_context,
+                parameter,
+            )
+        }) {
+            Ok(sample) => sample,
+            Err(err) => {
+                criterion.report.error(id, report_context, &err);
+                return;
+            }
+        };
+        system = system_start.map(|start| SystemSamples {
+            start,
+            end: crate::system::sample(),
+        });
         sampling_mode = sample.0;
         iters = sample.1;
-        times = sample.2;
+        // Streams and routines which count their elements report the number of elements per
+        // iteration as the throughput, unless one is set.
+        throughput =
+            throughput.or_else(|| routine.elements_per_iteration().map(Throughput::Elements));
+        let measured = match timer_overhead {
+            Some(timer_overhead) if criterion.subtract_timer_overhead => {
+                subtract_timer_overhead(sample.2, timer_overhead, routine.timer_pairs())
+            }
+            _ => sample.2,
+        };
+        times = match &config.value_transform {
+            Some(transform) => transform_times(&*transform.function, &iters, &measured),
+            None => measured,
+        };
 
         if let Some(conn) = &criterion.connection {
-            conn.send(&OutgoingMessage::MeasurementComplete {
-                id: id.into(),
-                iters: &iters,
-                times: &times,
-                plot_config: (&report_context.plot_config).into(),
-                sampling_method: sampling_mode.into(),
-                benchmark_config: config.into(),
-            })
-            .unwrap();
-
-            conn.serve_value_formatter(criterion.measurement.formatter())
-                .unwrap();
+            send_measurement(
+                conn,
+                criterion,
+                id,
+                config,
+                report_context,
+                sampling_mode,
+                &iters,
+                &times,
+            );
             return;
         }
     }
 
-    criterion.report.analysis(id, report_context);
+    // Throughput is meaningless for measurements which can be negative.
+    if criterion.measurement.formatter().is_signed() {
+        throughput = None;
+    }
+
+    // The tags added with `Bencher::tag` are saved along with the benchmark's own.
+    let mut id = id.clone();
+    for tag in routine.tags() {
+        if !id.tags.contains(tag) {
+            id.tags.push(tag.clone());
+        }
+    }
+    let id = &id;
+
+    // The secondary quantities of a `MultiMeasurement` are analyzed as benchmarks of their own.
+    // They are only recorded when the benchmark is actually run.
+    let secondary_values = routine.secondary_values();
+    let secondaries = if secondary_values.len() == times.len() {
+        criterion
+            .measurement
+            .secondary_names()
+            .into_iter()
+            .enumerate()
+            .map(|(index, name)| {
+                let values = secondary_values
+                    .iter()
+                    .map(|sample| sample[index])
+                    .collect::<Box<[f64]>>();
+                (index, id.for_quantity(name), values)
+            })
+            .collect::<Vec<_>>()
+    } else {
+        vec![]
+    };
+    let cold_start = match routine.cold_start() {
+        Some(value) if criterion.report_cold_start => Some(match &config.value_transform {
+            Some(transform) => (transform.function)(value),
+            None => value,
+        }),
+        _ => None,
+    };
+    let iteration_values = routine.iteration_values();
+    if !iteration_values.is_empty()
+        && criterion.connection.is_none()
+        && criterion.load_baseline.is_none()
+    {
+        log_if_err!({
+            let mut iterations_dir = criterion.output_directory.clone();
+            iterations_dir.push(id.as_directory_name());
+            iterations_dir.push("new");
+            let times = match &config.value_transform {
+                Some(transform) => iteration_values
+                    .iter()
+                    .map(|&value| (transform.function)(value))
+                    .collect(),
+                None => iteration_values.to_vec(),
+            };
+            fs::retry(|| {
+                fs::mkdirp(&iterations_dir)?;
+                fs::save(
+                    &SavedIterations { times: &times },
+                    &iterations_dir.join("iterations.json"),
+                )
+            })
+        });
+    }
+
+    analyze(
+        criterion,
+        Analysis {
+            id: id.clone(),
+            config: config.clone(),
+            report_context: report_context.clone(),
+            throughput,
+            sampling_mode,
+            avg_times: average_times(&iters, &times),
+            iters: iters.clone(),
+            times,
+            timer_overhead,
+            timer_pairs: routine.timer_pairs().to_vec(),
+            system,
+            cold_start,
+            quantity: None,
+        },
+    );
+    for (index, id, times) in secondaries {
+        crate::EXECUTED_BENCHMARKS
+            .lock()
+            .unwrap()
+            .directory_names
+            .insert(id.as_directory_name().to_owned());
+        let report_context = ReportContext {
+            value_unit: None,
+            ..report_context.clone()
+        };
+        analyze(
+            criterion,
+            Analysis {
+                id,
+                config: config.clone(),
+                report_context,
+                throughput: None,
+                sampling_mode,
+                avg_times: average_times(&iters, &times),
+                iters: iters.clone(),
+                times,
+                timer_overhead: None,
+                timer_pairs: vec![],
+                system: None,
+                cold_start: None,
+                quantity: Some(index),
+            },
+        );
+    }
+
+    if crate::interrupt::requested() {
+        criterion.exit_interrupted();
+    }
+}
+
+// Computes the statistics of a measured benchmark, on a background thread if enabled, and reports
+// them.
+fn analyze<M: Measurement>(criterion: &Criterion<M>, mut analysis: Analysis) {
+    // With background analysis, the previous benchmark is finished once this one has been measured,
+    // so that its analysis overlaps this one's measurement.
+    finish_pending_analysis(criterion);
 
-    if times.iter().any(|&f| f == 0.0) {
+    let id = &analysis.id;
+    criterion.report.analysis(id, &analysis.report_context);
+
+    // Subtracting the timer overhead may legitimately clamp samples to zero, and secondary
+    // quantities, such as counters, may well be zero.
+    if analysis.quantity.is_none()
+        && !criterion.measurement.allows_zero()
+        && !criterion.subtract_timer_overhead
+        && analysis.times.iter().any(|&f| f == 0.0)
+    {
         error!(
             "At least one measurement of benchmark {} took zero time per \
             iteration. This should not be possible. If using iter_custom, please verify \
@@ -121,120 +304,534 @@ pub(crate) fn common<M: Measurement, T: ?Sized>(
         return;
     }
 
-    let avg_times = iters
+    if let Some((lower, upper)) = criterion.winsorize {
+        analysis.avg_times = Sample::new(&analysis.avg_times).winsorize(lower, upper);
+        analysis.times = analysis
+            .iters
+            .iter()
+            .zip(analysis.avg_times.iter())
+            .map(|(&iters, &avg_time)| avg_time * iters)
+            .collect();
+    }
+
+    let weights = criterion.sample_weights.as_ref().map(|weight| {
+        (0..analysis.avg_times.len())
+            .map(weight)
+            .collect::<Vec<f64>>()
+    });
+    let (baseline_root, baseline) = criterion.comparison_baseline();
+    let base = if base_dir_exists(id, baseline, baseline_root) {
+        match compare::load_base(id, criterion) {
+            Ok(base) => Some(base),
+            Err(e) => {
+                crate::error::log_error(&e);
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    if criterion.background_analysis && !criterion.single_threaded {
+        let config = analysis.config.clone();
+        let sampling_mode = analysis.sampling_mode;
+        let iters = analysis.iters.to_vec();
+        let times = analysis.times.to_vec();
+        let avg_times = analysis.avg_times.clone();
+        let seed = criterion.bootstrap_seed;
+        let handle = thread::spawn(move || {
+            statistics(
+                &avg_times,
+                weights.as_deref(),
+                &Data::new(&iters, &times),
+                sampling_mode,
+                &config,
+                base,
+                seed,
+            )
+        });
+        *criterion.pending_analysis.borrow_mut() = Some(PendingAnalysis {
+            analysis,
+            statistics: handle,
+        });
+    } else {
+        let statistics = stats::with_single_threaded(criterion.single_threaded, || {
+            statistics(
+                &analysis.avg_times,
+                weights.as_deref(),
+                &Data::new(&analysis.iters, &analysis.times),
+                analysis.sampling_mode,
+                &analysis.config,
+                base,
+                criterion.bootstrap_seed,
+            )
+        });
+        finish_analysis(criterion, analysis, statistics);
+    }
+}
+
+fn average_times(iters: &[f64], times: &[f64]) -> Vec<f64> {
+    iters
         .iter()
         .zip(times.iter())
         .map(|(&iters, &elapsed)| elapsed / iters)
-        .collect::<Vec<f64>>();
-    let avg_times = Sample::new(&avg_times);
+        .collect()
+}
+
+// Analyzes and reports samples which were measured outside of Criterion.rs; see
+// `BenchmarkGroup::analyze`.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn samples<M: Measurement>(
+    id: &BenchmarkId,
+    config: &BenchmarkConfig,
+    criterion: &Criterion<M>,
+    report_context: &ReportContext,
+    mut throughput: Option<Throughput>,
+    sampling_mode: ActualSamplingMode,
+    iters: Box<[f64]>,
+    times: Box<[f64]>,
+) {
+    if criterion.connection.is_none() {
+        crate::interrupt::install();
+    }
+    start_benchmark(id, criterion, report_context);
+
+    let (sampling_mode, iters, times) = match &criterion.load_baseline {
+        Some(baseline) => load_sample(id, criterion, baseline),
+        None => {
+            let times = match &config.value_transform {
+                Some(transform) => transform_times(&*transform.function, &iters, &times),
+                None => times,
+            };
+            if let Some(conn) = &criterion.connection {
+                send_measurement(
+                    conn,
+                    criterion,
+                    id,
+                    config,
+                    report_context,
+                    sampling_mode,
+                    &iters,
+                    &times,
+                );
+                return;
+            }
+            (sampling_mode, iters, times)
+        }
+    };
+
+    // Throughput is meaningless for measurements which can be negative.
+    if criterion.measurement.formatter().is_signed() {
+        throughput = None;
+    }
+
+    analyze(
+        criterion,
+        Analysis {
+            id: id.clone(),
+            config: config.clone(),
+            report_context: report_context.clone(),
+            throughput,
+            sampling_mode,
+            avg_times: average_times(&iters, &times),
+            iters,
+            times,
+            timer_overhead: None,
+            timer_pairs: vec![],
+            system: None,
+            cold_start: None,
+            quantity: None,
+        },
+    );
+
+    if crate::interrupt::requested() {
+        criterion.exit_interrupted();
+    }
+}
+
+// Reports the start of a benchmark and checks that it can be run with the configured baselines.
+fn start_benchmark<M: Measurement>(
+    id: &BenchmarkId,
+    criterion: &Criterion<M>,
+    report_context: &ReportContext,
+) {
+    if let Some(pattern) = &criterion.clear_pattern {
+        let dir = criterion.output_directory.join(id.as_directory_name());
+        if criterion.connection.is_none() && pattern.is_match(id.id()) && dir.exists() {
+            human_println!("Clearing the history of benchmark {}", id.as_title());
+            log_if_err!(fs::rmdir_all(&dir));
+        }
+    }
+
+    if criterion.progress_bar {
+        crate::progress::benchmark_started(&criterion.output_directory);
+    }
+    criterion.report.benchmark_start(id, report_context);
+    crate::trace::set_benchmark(id.as_title());
+
+    {
+        let mut executed = crate::EXECUTED_BENCHMARKS.lock().unwrap();
+        executed.group_ids.insert(id.group_id.clone());
+        executed
+            .directory_names
+            .insert(id.as_directory_name().to_owned());
+        executed.prune_stale |= criterion.prune_stale;
+    }
+
+    if let Some(baseline_path) = &criterion.baseline_path {
+        let mut sample_path = baseline_path.clone();
+        sample_path.push(id.as_directory_name());
+        sample_path.push(&criterion.baseline_directory);
+        let sample_path = fs::sample_file(&sample_path);
+        if !sample_path.is_file() {
+            panic!(
+                "Baseline '{base}' for benchmark {id} was not found in external baseline path; expected {path:?} to exist",
+                base = criterion.baseline_directory,
+                id = id.as_title(),
+                path = sample_path,
+            );
+        }
+    }
+
+    if let Baseline::Compare = criterion.baseline {
+        if !base_dir_exists(id, &criterion.baseline_directory, criterion.baseline_root()) {
+            panic!(
+                "Baseline '{base}' must exist before comparison is allowed; try --save-baseline {base}",
+                base=criterion.baseline_directory,
+            );
+        }
+    }
+}
+
+// Loads the samples of the given baseline of a benchmark, for `--load-baseline`.
+fn load_sample<M: Measurement>(
+    id: &BenchmarkId,
+    criterion: &Criterion<M>,
+    baseline: &str,
+) -> (ActualSamplingMode, Box<[f64]>, Box<[f64]>) {
+    let mut sample_path = criterion.baseline_root().to_owned();
+    sample_path.push(id.as_directory_name());
+    sample_path.push(baseline);
+    let loaded = fs::load::<SavedSample, _>(&fs::sample_file(&sample_path));
+
+    match loaded {
+        Err(err) => panic!(
+            "Baseline '{base}' must exist before it can be loaded; try --save-baseline {base}. Error: {err}",
+            base = baseline, err = err
+        ),
+        Ok(samples) => (
+            samples.sampling_mode,
+            samples.iters.into_boxed_slice(),
+            samples.times.into_boxed_slice(),
+        ),
+    }
+}
+
+// Sends the samples of a benchmark to cargo-criterion, which analyzes and reports them itself.
+#[allow(clippy::too_many_arguments)]
+fn send_measurement<M: Measurement>(
+    conn: &Connection,
+    criterion: &Criterion<M>,
+    id: &BenchmarkId,
+    config: &BenchmarkConfig,
+    report_context: &ReportContext,
+    sampling_mode: ActualSamplingMode,
+    iters: &[f64],
+    times: &[f64],
+) {
+    conn.send(&OutgoingMessage::MeasurementComplete {
+        id: id.into(),
+        iters,
+        times,
+        plot_config: (&report_context.plot_config).into(),
+        sampling_method: sampling_mode.into(),
+        benchmark_config: config.into(),
+    })
+    .unwrap();
+
+    conn.serve_value_formatter(criterion.measurement.formatter())
+        .unwrap();
+}
+
+// The contents of `new/benchmark.json`. It is read back as a `BenchmarkId`, which ignores the
+// measurement and the system samples.
+#[derive(Serialize)]
+struct SavedBenchmark<'a> {
+    #[serde(flatten)]
+    id: &'a BenchmarkId,
+    measurement: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    system: Option<SystemSamples>,
+}
+
+// The value of each iteration of a benchmark, with `Criterion::record_per_iteration`.
+#[derive(Serialize)]
+struct SavedIterations<'a> {
+    times: &'a [f64],
+}
+
+// A measured benchmark, and everything needed to report it once its statistics are computed.
+struct Analysis {
+    id: BenchmarkId,
+    config: BenchmarkConfig,
+    report_context: ReportContext,
+    throughput: Option<Throughput>,
+    sampling_mode: ActualSamplingMode,
+    iters: Box<[f64]>,
+    times: Box<[f64]>,
+    avg_times: Vec<f64>,
+    timer_overhead: Option<f64>,
+    timer_pairs: Vec<u64>,
+    system: Option<SystemSamples>,
+    // The value of the first iteration, with `Criterion::report_cold_start`.
+    cold_start: Option<f64>,
+    // The index of the secondary quantity of the measurement this is an analysis of, if any.
+    quantity: Option<usize>,
+}
+
+// The bootstrapped statistics of a benchmark. These are the expensive part of the analysis and only
+// depend on the measurements, so they can be computed on a background thread.
+struct Statistics {
+    distributions: Distributions,
+    estimates: Estimates,
+    comparison: Option<compare::ComparisonStatistics>,
+}
+
+/// A benchmark whose statistics are being computed on a background thread, enabled with
+/// `Criterion::background_analysis`.
+pub(crate) struct PendingAnalysis {
+    analysis: Analysis,
+    statistics: JoinHandle<Statistics>,
+}
+
+/// Waits for the statistics of the benchmark analyzed in the background, if any, and reports it.
+/// This must be done before anything reads the results of the benchmark.
+pub(crate) fn finish_pending_analysis<M: Measurement>(criterion: &Criterion<M>) {
+    let pending = criterion.pending_analysis.borrow_mut().take();
+    if let Some(PendingAnalysis {
+        analysis,
+        statistics,
+    }) = pending
+    {
+        let statistics = statistics
+            .join()
+            .unwrap_or_else(|err| panic::resume_unwind(err));
+        finish_analysis(criterion, analysis, statistics);
+    }
+}
+
+// Computes the statistics of a benchmark, and of its comparison with the baseline if there is one.
+fn statistics(
+    avg_times: &[f64],
+    weights: Option<&[f64]>,
+    data: &Data<'_, f64, f64>,
+    sampling_mode: ActualSamplingMode,
+    config: &BenchmarkConfig,
+    base: Option<compare::BaseSample>,
+    seed: Option<u64>,
+) -> Statistics {
+    if let Some(seed) = seed {
+        stats::set_seed(seed);
+    }
+    let avg_times = Sample::new(avg_times);
+    let (mut distributions, mut estimates) = estimates(avg_times, weights, config);
+    if sampling_mode.is_linear() {
+        let (distribution, slope) = regression(data, config);
+
+        estimates.slope = Some(slope);
+        distributions.slope = Some(distribution);
+    }
+
+    let comparison = base.map(|base| compare::statistics(avg_times, base, config));
+
+    Statistics {
+        distributions,
+        estimates,
+        comparison,
+    }
+}
+
+// Checks, saves and reports the results of a benchmark.
+fn finish_analysis<M: Measurement>(
+    criterion: &Criterion<M>,
+    analysis: Analysis,
+    statistics: Statistics,
+) {
+    let Analysis {
+        id,
+        config,
+        report_context,
+        throughput,
+        sampling_mode,
+        iters,
+        times,
+        avg_times,
+        timer_overhead,
+        timer_pairs,
+        system,
+        cold_start,
+        quantity,
+    } = analysis;
+    let Statistics {
+        distributions,
+        estimates,
+        comparison,
+    } = statistics;
+    let id = &id;
+    let report_context = &report_context;
 
     if criterion.connection.is_none() && criterion.load_baseline.is_none() {
         log_if_err!({
             let mut new_dir = criterion.output_directory.clone();
             new_dir.push(id.as_directory_name());
             new_dir.push("new");
-            fs::mkdirp(&new_dir)
+            fs::retry(|| fs::mkdirp(&new_dir))
         });
     }
 
     let data = Data::new(&iters, &times);
-    let labeled_sample = tukey::classify(avg_times);
+    let labeled_sample = tukey::classify(Sample::new(&avg_times));
     if criterion.connection.is_none() {
         log_if_err!({
             let mut tukey_file = criterion.output_directory.to_owned();
             tukey_file.push(id.as_directory_name());
             tukey_file.push("new");
             tukey_file.push("tukey.json");
-            fs::save(&labeled_sample.fences(), &tukey_file)
+            fs::retry(|| fs::save(&labeled_sample.fences(), &tukey_file))
         });
     }
-    let (mut distributions, mut estimates) = estimates(avg_times, config);
-    if sampling_mode.is_linear() {
-        let (distribution, slope) = regression(&data, config);
 
-        estimates.slope = Some(slope);
-        distributions.slope = Some(distribution);
+    // Transformed values can't be compared with the timer overhead.
+    if let (Some(timer_overhead), None) = (timer_overhead, &config.value_transform) {
+        check_timer_overhead(
+            id,
+            &estimates,
+            timer_overhead,
+            &iters,
+            &timer_pairs,
+            criterion,
+        );
     }
+    if quantity.is_none() {
+        check_max_times(id, &estimates, criterion);
+    }
+    check_min_throughputs(id, &estimates, throughput.as_ref(), criterion);
+    check_noise(id, &estimates, criterion);
 
     if criterion.connection.is_none() && criterion.load_baseline.is_none() {
         log_if_err!({
-            let mut sample_file = criterion.output_directory.clone();
-            sample_file.push(id.as_directory_name());
-            sample_file.push("new");
-            sample_file.push("sample.json");
-            fs::save(
-                &SavedSample {
-                    sampling_mode,
-                    iters: data.x().as_ref().to_vec(),
-                    times: data.y().as_ref().to_vec(),
-                },
-                &sample_file,
-            )
+            let mut sample_dir = criterion.output_directory.clone();
+            sample_dir.push(id.as_directory_name());
+            sample_dir.push("new");
+            let sample = SavedSample {
+                sampling_mode,
+                iters: data.x().as_ref().to_vec(),
+                times: data.y().as_ref().to_vec(),
+            };
+            fs::retry(|| {
+                fs::save_sample(
+                    &sample,
+                    &sample_dir,
+                    criterion.sample_format,
+                    criterion.compress_samples,
+                )
+            })
         });
         log_if_err!({
             let mut estimates_file = criterion.output_directory.clone();
             estimates_file.push(id.as_directory_name());
             estimates_file.push("new");
             estimates_file.push("estimates.json");
-            fs::save(&estimates, &estimates_file)
+            fs::retry(|| fs::save(&estimates, &estimates_file))
         });
     }
 
-    let compare_data = if base_dir_exists(
-        id,
-        &criterion.baseline_directory,
-        &criterion.output_directory,
-    ) {
-        let result = compare::common(id, avg_times, config, criterion);
-        match result {
-            Ok((
-                t_value,
+    let compare_data = comparison.and_then(|comparison| {
+        log_if_err!(fs::retry(|| compare::save(id, &comparison, criterion)));
+
+        let compare::ComparisonStatistics {
+            t_statistic: t_value,
+            t_distribution,
+            effect_size,
+            relative_estimates,
+            relative_distributions,
+            base,
+        } = comparison;
+        let p_value = match &criterion.comparison_test {
+            Some(test) => {
+                let p_value = test(&avg_times, &base.avg_times);
+                assert!(
+                    (0.0..=1.0).contains(&p_value),
+                    "Criterion::comparison_test returned {}, which isn't a p-value",
+                    p_value
+                );
+                p_value
+            }
+            None => t_distribution.p_value(t_value, &Tails::Two),
+        };
+        if t_distribution.is_empty() || !relative_estimates.mean.point_estimate.is_finite() {
+            // This can happen with measurements that allow zero values (eg. if
+            // neither run allocated any memory); the relative change is undefined.
+            info!(
+                "Relative change of {} is undefined; skipping comparison",
+                id.as_title()
+            );
+            None
+        } else {
+            let r_squared = |data: &Data<'_, f64, f64>, estimates: &Estimates| {
+                Slope(estimates.typical().point_estimate).r_squared(data)
+            };
+            let base_data = Data::new(&base.iters, &base.times);
+            let r_squared =
+                r_squared(&data, &estimates).min(r_squared(&base_data, &base.estimates));
+            Some(crate::report::ComparisonData {
+                p_value,
                 t_distribution,
+                t_value,
+                effect_size,
                 relative_estimates,
                 relative_distributions,
-                base_iter_counts,
-                base_sample_times,
-                base_avg_times,
-                base_estimates,
-            )) => {
-                let p_value = t_distribution.p_value(t_value, &Tails::Two);
-                Some(crate::report::ComparisonData {
-                    p_value,
-                    t_distribution,
-                    t_value,
-                    relative_estimates,
-                    relative_distributions,
-                    significance_threshold: config.significance_level,
-                    noise_threshold: config.noise_threshold,
-                    base_iter_counts,
-                    base_sample_times,
-                    base_avg_times,
-                    base_estimates,
-                })
-            }
-            Err(e) => {
-                crate::error::log_error(&e);
-                None
-            }
+                significance_threshold: config.significance_level,
+                noise_threshold: config.noise_threshold,
+                r_squared,
+                min_r_squared: criterion.min_r2_for_regression,
+                base_iter_counts: base.iters,
+                base_sample_times: base.times,
+                base_avg_times: base.avg_times,
+                base_estimates: base.estimates,
+            })
         }
-    } else {
-        None
-    };
+    });
+    if let Some(comparison) = &compare_data {
+        crate::record_outcome(id, comparison);
+    }
 
+    let throughput_estimate = throughput.as_ref().map(|throughput| {
+        ThroughputEstimate::new(throughput, estimates.typical(), distributions.typical())
+    });
     let measurement_data = crate::report::MeasurementData {
-        data: Data::new(&*iters, &*times),
+        data,
         avg_times: labeled_sample,
         absolute_estimates: estimates,
         distributions,
         comparison: compare_data,
         throughput,
+        throughput_estimate,
+        robust_dispersion: config.robust_dispersion,
+        regression_method: config.regression_method,
+        system,
+        cold_start,
     };
 
+    let formatter = match quantity {
+        Some(index) => criterion.measurement.secondary_formatter(index),
+        None => criterion.measurement.formatter(),
+    };
     criterion.report.measurement_complete(
         id,
         report_context,
         &measurement_data,
-        criterion.measurement.formatter(),
+        &report_context.time_formatter(formatter),
     );
 
     if criterion.connection.is_none() && criterion.load_baseline.is_none() {
@@ -243,23 +840,136 @@ pub(crate) fn common<M: Measurement, T: ?Sized>(
             benchmark_file.push(id.as_directory_name());
             benchmark_file.push("new");
             benchmark_file.push("benchmark.json");
-            fs::save(&id, &benchmark_file)
+            let benchmark = SavedBenchmark {
+                id,
+                measurement: criterion.measurement.type_name(),
+                system,
+            };
+            fs::retry(|| fs::save(&benchmark, &benchmark_file))
         });
     }
 
     if criterion.connection.is_none() {
-        if let Baseline::Save = criterion.baseline {
-            copy_new_dir_to_base(
-                id.as_directory_name(),
-                &criterion.baseline_directory,
-                &criterion.output_directory,
-            );
+        let save_to = match criterion.baseline {
+            Baseline::Save => Some(&criterion.baseline_directory),
+            Baseline::Compare => criterion.save_baseline_to.as_ref(),
+        };
+        if let Some(save_to) = save_to {
+            copy_new_dir_to_base(id.as_directory_name(), save_to, &criterion.output_directory);
+        }
+        if criterion.track_best && criterion.load_baseline.is_none() {
+            update_best(id, &measurement_data.absolute_estimates, criterion);
+        }
+    }
+}
+
+// Saves the results of this run as the best results of the benchmark, if there were none or its
+// median is lower than the best median so far. See `Criterion::track_best`.
+fn update_best<M: Measurement>(id: &BenchmarkId, estimates: &Estimates, criterion: &Criterion<M>) {
+    let (root, best) = criterion.comparison_baseline();
+    let mut best_estimates = root.join(id.as_directory_name());
+    best_estimates.push(best);
+    best_estimates.push("estimates.json");
+    let improved = match fs::load::<Estimates, _>(&best_estimates) {
+        Ok(best) => estimates.median.point_estimate < best.median.point_estimate,
+        Err(_) => true,
+    };
+    if improved {
+        copy_new_dir_to_base(id.as_directory_name(), best, &criterion.output_directory);
+    }
+}
+
+// Records a failure for each budget set with `Criterion::assert_max` that this benchmark's median
+// exceeds. The failures are reported by `Criterion::run`.
+fn check_max_times<M: Measurement>(
+    id: &BenchmarkId,
+    estimates: &Estimates,
+    criterion: &Criterion<M>,
+) {
+    let median = estimates.median.point_estimate;
+    for (pattern, max) in &criterion.max_times {
+        let max_ns = max.to_nanos() as f64;
+        if pattern.is_match(id.id()) && median > max_ns {
+            crate::record_assertion_failure(format!(
+                "Benchmark {} has a median time of {}, exceeding its budget of {}",
+                id.as_title(),
+                format::time_with_precision(median, criterion.time_unit, criterion.time_precision),
+                format::time_with_precision(max_ns, criterion.time_unit, criterion.time_precision),
+            ));
+        }
+    }
+}
+
+// Records a failure if the standard error of this benchmark's mean, relative to the mean, exceeds
+// the threshold set with `Criterion::fail_on_noise`.
+fn check_noise<M: Measurement>(id: &BenchmarkId, estimates: &Estimates, criterion: &Criterion<M>) {
+    let threshold = match criterion.max_relative_standard_error {
+        Some(threshold) => threshold,
+        None => return,
+    };
+    let relative_se = match estimates.mean.relative_standard_error {
+        Some(relative_se) => relative_se,
+        None => return,
+    };
+    if relative_se > threshold {
+        crate::record_assertion_failure(format!(
+            "Benchmark {} is too noisy: the standard error of its mean is {} of the mean, above \
+            the limit of {}. Try increasing the sample size or reducing the load on the system.",
+            id.as_title(),
+            format::percent(relative_se),
+            format::percent(threshold),
+        ));
+    }
+}
+
+// Records a failure for each minimum set with `Criterion::assert_min_throughput` that this
+// benchmark's throughput falls below.
+fn check_min_throughputs<M: Measurement>(
+    id: &BenchmarkId,
+    estimates: &Estimates,
+    throughput: Option<&Throughput>,
+    criterion: &Criterion<M>,
+) {
+    // The slope excludes the constant overhead of each sample, so prefer it over the mean.
+    let time = estimates
+        .slope
+        .as_ref()
+        .unwrap_or(&estimates.mean)
+        .point_estimate;
+    let formatter = criterion.measurement.formatter();
+    for (pattern, min) in &criterion.min_throughputs {
+        if !pattern.is_match(id.id()) {
+            continue;
+        }
+        let (count, min_per_sec) = match (throughput, min) {
+            (Some(&Throughput::Bytes(bytes)), &Throughput::Bytes(min))
+            | (Some(&Throughput::Elements(bytes)), &Throughput::Elements(min)) => (bytes, min),
+            _ => {
+                warn!(
+                    "Benchmark {} has no throughput of the same kind as its minimum; skipping \
+                    its minimum throughput check",
+                    id.as_title()
+                );
+                continue;
+            }
+        };
+        let achieved = count as f64 * 1e9 / time;
+        if achieved < min_per_sec as f64 {
+            // The minimum processed in one second (1e9 ns) formats as the required rate.
+            crate::record_assertion_failure(format!(
+                "Benchmark {} has a throughput of {}, below its minimum of {}",
+                id.as_title(),
+                formatter
+                    .format_throughput(throughput.unwrap(), time)
+                    .trim_start(),
+                formatter.format_throughput(min, 1e9).trim_start(),
+            ));
         }
     }
 }
 
-fn base_dir_exists(id: &BenchmarkId, baseline: &str, output_directory: &Path) -> bool {
-    let mut base_dir = output_directory.to_owned();
+fn base_dir_exists(id: &BenchmarkId, baseline: &str, baseline_root: &Path) -> bool {
+    let mut base_dir = baseline_root.to_owned();
     base_dir.push(id.as_directory_name());
     base_dir.push(baseline);
     base_dir.exists()
@@ -272,13 +982,17 @@ fn regression(
 ) -> (Distribution<f64>, Estimate) {
     let cl = config.confidence_level;
 
+    let fit = match config.regression_method {
+        RegressionMethod::LeastSquares => Slope::fit,
+        RegressionMethod::TheilSen => Slope::theil_sen,
+    };
     let distribution = elapsed!(
         "Bootstrapped linear regression",
-        data.bootstrap(config.nresamples, |d| (Slope::fit(&d).0,))
+        data.bootstrap(config.nresamples, |d| (fit(&d).0,))
     )
     .0;
 
-    let point = Slope::fit(data);
+    let point = fit(data);
     let (lb, ub) = distribution.confidence_interval(config.confidence_level);
     let se = distribution.std_dev(None);
 
@@ -292,23 +1006,172 @@ fn regression(
             },
             point_estimate: point.0,
             standard_error: se,
+            relative_standard_error: relative_standard_error(point.0, se),
         },
     )
 }
 
+// Number of batches used to calibrate the timer overhead, and the number of times the measurement is
+// started and ended in each batch.
+const CALIBRATION_BATCHES: usize = 11;
+const CALIBRATION_PAIRS: u64 = 1000;
+// Benchmarks whose median is less than this multiple of the timer overhead per iteration are
+// reported as dominated by the cost of measurement.
+const TIMER_OVERHEAD_WARNING_RATIO: f64 = 5.0;
+
+// Returns the overhead of starting and ending the measurement once, calibrating it the first time
+// each type of measurement is used in this run.
+fn timer_overhead<M: Measurement>(criterion: &Criterion<M>, report_context: &ReportContext) -> f64 {
+    let m = &criterion.measurement;
+    let mut overheads = crate::TIMER_OVERHEADS.lock().unwrap();
+    let key = m.type_name();
+    if let Some(&overhead) = overheads.get(key) {
+        return overhead;
+    }
+
+    let mut batches: Vec<f64> = (0..CALIBRATION_BATCHES)
+        .map(|_| {
+            let mut total = m.zero();
+            for _ in 0..CALIBRATION_PAIRS {
+                let start = m.start();
+                let value = m.end(start);
+                total = m.add(&total, &value);
+            }
+            m.to_f64(&total) / CALIBRATION_PAIRS as f64
+        })
+        .collect();
+    batches.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    let overhead = batches[CALIBRATION_BATCHES / 2];
+
+    overheads.insert(key, overhead);
+    criterion.report.timer_overhead(
+        report_context,
+        overhead,
+        criterion.subtract_timer_overhead,
+        m.formatter(),
+    );
+    overhead
+}
+
+// Subtracts the timer overhead from each sample, once for every time the measurement was started
+// and ended while taking it, clamping to zero.
+fn subtract_timer_overhead(
+    times: Box<[f64]>,
+    timer_overhead: f64,
+    timer_pairs: &[u64],
+) -> Box<[f64]> {
+    if timer_pairs.len() != times.len() {
+        return times;
+    }
+    times
+        .iter()
+        .zip(timer_pairs)
+        .map(|(&time, &pairs)| (time - timer_overhead * pairs as f64).max(0.0))
+        .collect()
+}
+
+// Applies a transform set with `BenchmarkGroup::value_transform` to the mean value per iteration of
+// each sample.
+fn transform_times(transform: &dyn Fn(f64) -> f64, iters: &[f64], times: &[f64]) -> Box<[f64]> {
+    iters
+        .iter()
+        .zip(times.iter())
+        .map(|(&n, &time)| transform(time / n) * n)
+        .collect()
+}
+
+// Warns if the timer overhead makes up a large part of the measured time per iteration. How much
+// overhead each iteration carries depends on how often the timing loop started and ended the
+// measurement in each sample.
+fn check_timer_overhead<M: Measurement>(
+    id: &BenchmarkId,
+    estimates: &Estimates,
+    timer_overhead: f64,
+    iters: &[f64],
+    timer_pairs: &[u64],
+    criterion: &Criterion<M>,
+) {
+    // `iter_custom` does its own timing, so its overhead is unknown.
+    if timer_pairs.len() != iters.len() || timer_pairs.contains(&0) {
+        return;
+    }
+
+    let overhead_per_iter = iters
+        .iter()
+        .zip(timer_pairs)
+        .map(|(&n, &pairs)| timer_overhead * pairs as f64 / n)
+        .sum::<f64>()
+        / iters.len() as f64;
+    let median = estimates.median.point_estimate;
+    if median < TIMER_OVERHEAD_WARNING_RATIO * overhead_per_iter {
+        let formatter = criterion.measurement.formatter();
+        warn!(
+            "Benchmark {} has a median of {} per iteration, less than {} times the timer overhead of {} per iteration; its results are dominated by the cost of measurement",
+            id.as_title(),
+            formatter.format_value(median),
+            TIMER_OVERHEAD_WARNING_RATIO,
+            formatter.format_value(overhead_per_iter)
+        );
+    }
+}
+
+// Number of points at which the KDE is evaluated to find the mode. This is repeated for every
+// bootstrap resample, so it's much coarser than the KDE plots.
+const MODE_KDE_POINTS: usize = 64;
+// Evaluating the KDE is far more expensive than the other statistics, so the mode is bootstrapped
+// from at most this many resamples.
+const MODE_MAX_RESAMPLES: usize = 10_000;
+
+// Percentile at which samples are clamped when computing the winsorized standard deviation.
+const WINSORIZE_PERCENTILE: f64 = 5.0;
+
+// Bootstraps the statistic from the sample, drawing each data point with a probability
+// proportional to its weight if weights are given.
+fn bootstrap<T, S>(
+    sample: &Sample<f64>,
+    weights: Option<&[f64]>,
+    nresamples: usize,
+    statistic: S,
+) -> T::Distributions
+where
+    S: Fn(&Sample<f64>) -> T + Sync,
+    T: Tuple + Send,
+    T::Distributions: Send,
+    T::Builder: Send,
+{
+    match weights {
+        Some(weights) => sample.bootstrap_weighted(weights, nresamples, statistic),
+        None => sample.bootstrap(nresamples, statistic),
+    }
+}
+
 // Estimates the statistics of the population from the sample
-fn estimates(avg_times: &Sample<f64>, config: &BenchmarkConfig) -> (Distributions, Estimates) {
-    fn stats(sample: &Sample<f64>) -> (f64, f64, f64, f64) {
+fn estimates(
+    avg_times: &Sample<f64>,
+    weights: Option<&[f64]>,
+    config: &BenchmarkConfig,
+) -> (Distributions, Estimates) {
+    let robust_dispersion = config.robust_dispersion;
+    let stats = |sample: &Sample<f64>| -> (f64, f64, f64, f64) {
         let mean = sample.mean();
-        let std_dev = sample.std_dev(Some(mean));
+        let std_dev = if robust_dispersion {
+            sample.winsorized_var(WINSORIZE_PERCENTILE).sqrt()
+        } else {
+            sample.std_dev(Some(mean))
+        };
         let median = sample.percentiles().median();
         let mad = sample.median_abs_dev(Some(median));
 
         (mean, std_dev, median, mad)
-    }
+    };
 
     let cl = config.confidence_level;
     let nresamples = config.nresamples;
+    let trimmed_mean_fraction = if config.estimate_trimmed_mean {
+        Some(config.trimmed_mean_fraction)
+    } else {
+        None
+    };
 
     let (mean, std_dev, median, mad) = stats(avg_times);
     let points = PointEstimates {
@@ -316,19 +1179,50 @@ fn estimates(avg_times: &Sample<f64>, config: &BenchmarkConfig) -> (Distribution
         median,
         std_dev,
         median_abs_dev: mad,
+        mode: if config.estimate_mode {
+            Some(kde::mode(avg_times, MODE_KDE_POINTS))
+        } else {
+            None
+        },
+        trimmed_mean: trimmed_mean_fraction.map(|fraction| avg_times.trimmed_mean(fraction)),
     };
 
     let (dist_mean, dist_stddev, dist_median, dist_mad) = elapsed!(
         "Bootstrapping the absolute statistics.",
-        avg_times.bootstrap(nresamples, stats)
+        bootstrap(avg_times, weights, nresamples, stats)
     );
+    let dist_trimmed_mean = trimmed_mean_fraction.map(|fraction| {
+        let (dist_trimmed_mean,) = elapsed!(
+            "Bootstrapping the trimmed mean.",
+            bootstrap(avg_times, weights, nresamples, |sample| {
+                (sample.trimmed_mean(fraction),)
+            })
+        );
+        dist_trimmed_mean
+    });
+    let dist_mode = if config.estimate_mode {
+        let (dist_mode,) = elapsed!(
+            "Bootstrapping the mode.",
+            bootstrap(
+                avg_times,
+                weights,
+                nresamples.min(MODE_MAX_RESAMPLES),
+                |sample| { (kde::mode(sample, MODE_KDE_POINTS),) }
+            )
+        );
+        Some(dist_mode)
+    } else {
+        None
+    };
 
     let distributions = Distributions {
         mean: dist_mean,
         slope: None,
         median: dist_median,
         median_abs_dev: dist_mad,
+        mode: dist_mode,
         std_dev: dist_stddev,
+        trimmed_mean: dist_trimmed_mean,
     };
 
     let estimates = build_estimates(&distributions, &points, cl);
@@ -345,25 +1239,25 @@ fn copy_new_dir_to_base(id: &str, baseline: &str, output_directory: &Path) {
         return;
     };
     if !base_dir.exists() {
-        try_else_return!(fs::mkdirp(&base_dir));
+        try_else_return!(fs::retry(|| fs::mkdirp(&base_dir)));
     }
 
     // TODO: consider using walkdir or similar to generically copy.
-    try_else_return!(fs::cp(
-        &new_dir.join("estimates.json"),
-        &base_dir.join("estimates.json")
-    ));
-    try_else_return!(fs::cp(
-        &new_dir.join("sample.json"),
-        &base_dir.join("sample.json")
-    ));
-    try_else_return!(fs::cp(
-        &new_dir.join("tukey.json"),
-        &base_dir.join("tukey.json")
-    ));
-    try_else_return!(fs::cp(
-        &new_dir.join("benchmark.json"),
-        &base_dir.join("benchmark.json")
-    ));
-    try_else_return!(fs::cp(&new_dir.join("raw.csv"), &base_dir.join("raw.csv")));
+    let cp = |file: &str| fs::retry(|| fs::cp(&new_dir.join(file), &base_dir.join(file)));
+    try_else_return!(cp("estimates.json"));
+    try_else_return!(fs::retry(|| fs::cp_sample(&new_dir, &base_dir)));
+    try_else_return!(cp("tukey.json"));
+    try_else_return!(cp("benchmark.json"));
+    try_else_return!(cp("raw.csv"));
+    try_else_return!(cp("report.txt"));
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn test_subtract_timer_overhead() {
+        let times = vec![100.0, 100.0, 5.0, 50.0].into_boxed_slice();
+        let corrected = super::subtract_timer_overhead(times, 10.0, &[1, 3, 1, 0]);
+        assert_eq!(&*corrected, &[90.0, 70.0, 0.0, 50.0]);
+    }
 }
@@ -1,4 +1,7 @@
+use std::any::Any;
+use std::fmt;
 use std::iter::IntoIterator;
+use std::rc::Rc;
 use std::time::Duration;
 use std::time::Instant;
 
@@ -6,6 +9,8 @@ use crate::black_box;
 use crate::measurement::{Measurement, WallTime};
 use crate::BatchSize;
 
+#[cfg(feature = "async")]
+use futures::stream::{Stream, StreamExt};
 #[cfg(feature = "async")]
 use std::future::Future;
 
@@ -38,6 +43,12 @@ pub struct Bencher<'a, M: Measurement = WallTime> {
     pub(crate) value: M::Value,        // The measured value
     pub(crate) measurement: &'a M,     // Reference to the measurement object
     pub(crate) elapsed_time: Duration, // How much time did it take to perform the iteration? Used for the warmup period.
+    pub(crate) error: Option<String>, // Error returned by the routine, if any. Aborts the benchmark.
+    pub(crate) setup_value: Option<Rc<dyn Any>>, // Value created by the group's setup closure, if any.
+    pub(crate) timer_pairs: u64, // Number of times the measurement was started and ended in the last call.
+    pub(crate) elements: Option<u64>, // Number of elements processed in the last call, if reported.
+    pub(crate) iteration_values: Option<Vec<f64>>, // The value of each iteration, with `Criterion::record_per_iteration`.
+    pub(crate) tags: Vec<String>, // Tags added with `Bencher::tag` during the last call.
 }
 impl<'a, M: Measurement> Bencher<'a, M> {
     /// Times a `routine` by executing it many times and timing the total elapsed time.
@@ -83,14 +94,141 @@ impl<'a, M: Measurement> Bencher<'a, M> {
     {
         self.iterated = true;
         let time_start = Instant::now();
+        if let Some(values) = &mut self.iteration_values {
+            // Times each iteration on its own, so the sample is the sum of the iterations.
+            self.timer_pairs = self.iters;
+            let mut value = self.measurement.zero();
+            for _ in 0..self.iters {
+                let start = self.measurement.start();
+                black_box(routine());
+                let iteration = self.measurement.end(start);
+                values.push(self.measurement.to_f64(&iteration));
+                value = self.measurement.add(&value, &iteration);
+            }
+            self.value = value;
+        } else {
+            self.timer_pairs = 1;
+            let start = self.measurement.start();
+            for _ in 0..self.iters {
+                black_box(routine());
+            }
+            self.value = self.measurement.end(start);
+        }
+        self.elapsed_time = time_start.elapsed();
+    }
+
+    /// Times a fallible `routine` by executing it many times and timing the total elapsed time.
+    ///
+    /// This is like `iter`, but for routines which return a `Result`. The `Ok` value is passed
+    /// through `black_box`, so there is no need to unwrap it inside the benchmark. If the routine
+    /// returns an `Err`, the timing loop stops immediately and the benchmark is aborted; the error
+    /// is reported along with the benchmark ID and Criterion.rs moves on to the next benchmark.
+    ///
+    /// # Timing model
+    ///
+    /// ```text
+    /// elapsed = Instant::now + iters * (routine + match + mem::drop(O) + Range::next)
+    /// ```
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// #[macro_use] extern crate criterion;
+    ///
+    /// use criterion::*;
+    ///
+    /// // The function to benchmark
+    /// fn parse(input: &str) -> Result<u64, std::num::ParseIntError> {
+    ///     input.parse()
+    /// }
+    ///
+    /// fn bench(c: &mut Criterion) {
+    ///     c.bench_function("try_iter", move |b| {
+    ///         b.try_iter(|| parse(black_box("12345")))
+    ///     });
+    /// }
+    ///
+    /// criterion_group!(benches, bench);
+    /// criterion_main!(benches);
+    /// ```
+    ///
+    #[inline(never)]
+    pub fn try_iter<O, E, R>(&mut self, mut routine: R)
+    where
+        R: FnMut() -> Result<O, E>,
+        E: fmt::Display,
+    {
+        self.iterated = true;
+        self.timer_pairs = 1;
+        let time_start = Instant::now();
         let start = self.measurement.start();
         for _ in 0..self.iters {
-            black_box(routine());
+            if let Err(err) = routine().map(black_box) {
+                self.error = Some(err.to_string());
+                break;
+            }
         }
         self.value = self.measurement.end(start);
         self.elapsed_time = time_start.elapsed();
     }
 
+    /// Times a stream of items by creating an iterator with `make` and driving it to exhaustion,
+    /// many times, and timing the total elapsed time. Each item is passed through `black_box` and
+    /// dropped as soon as it's yielded, so nothing needs to be collected.
+    ///
+    /// Prefer this timing loop for lazy producers such as streaming decoders, parsers and
+    /// generators, where collecting the items into a `Vec` would dominate the measurement.
+    ///
+    /// The number of items yielded is counted. If the benchmark has no throughput set, the mean
+    /// number of items per iteration is reported as a `Throughput::Elements`, giving the rate per
+    /// item without having to know the number of items in advance.
+    ///
+    /// # Timing model
+    ///
+    /// ```text
+    /// elapsed = Instant::now + iters * (make + items * (Iterator::next + mem::drop(Item)))
+    /// ```
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// #[macro_use] extern crate criterion;
+    ///
+    /// use criterion::*;
+    ///
+    /// fn bench(c: &mut Criterion) {
+    ///     let input = "1,2,3,4,5,6,7,8,9,10";
+    ///     c.bench_function("iter_stream", move |b| {
+    ///         b.iter_stream(|| black_box(input).split(',').map(|s| s.parse::<u64>()))
+    ///     });
+    /// }
+    ///
+    /// criterion_group!(benches, bench);
+    /// criterion_main!(benches);
+    /// ```
+    ///
+    #[inline(never)]
+    pub fn iter_stream<I, R>(&mut self, mut make: R)
+    where
+        R: FnMut() -> I,
+        I: Iterator,
+    {
+        self.iterated = true;
+        self.timer_pairs = 1;
+        let mut elements = 0u64;
+        let time_start = Instant::now();
+        let start = self.measurement.start();
+        for _ in 0..self.iters {
+            for item in make() {
+                black_box(item);
+                elements += 1;
+            }
+        }
+        self.value = self.measurement.end(start);
+        self.elapsed_time = time_start.elapsed();
+        *self.elements.get_or_insert(0) += elements;
+    }
+
     /// Times a `routine` by executing it many times and relying on `routine` to measure its own execution time.
     ///
     /// Prefer this timing loop in cases where `routine` has to do its own measurements to
@@ -133,12 +271,106 @@ impl<'a, M: Measurement> Bencher<'a, M> {
         R: FnMut(u64) -> M::Value,
     {
         self.iterated = true;
+        // The routine does its own timing, so its timer overhead is unknown.
+        self.timer_pairs = 0;
         let time_start = Instant::now();
         self.value = routine(self.iters);
         self.elapsed_time = time_start.elapsed();
     }
 
-    #[doc(hidden)]
+    /// Like `iter_custom`, but Criterion.rs starts the measurement just before calling `routine`
+    /// and hands it the `start` intermediate value along with the measurement, leaving `routine`
+    /// to decide where the measurement ends. This is useful with custom measurements, when only
+    /// part of the work done by `routine` should be measured.
+    ///
+    /// `routine` receives the number of iterations, the intermediate value returned by
+    /// `Measurement::start` and the measurement itself. It must run its code `iters` times and
+    /// return the value measured over all of those iterations, typically by passing the
+    /// intermediate value to `Measurement::end`.
+    ///
+    /// # Timing model
+    /// Custom, the timing model is whatever is returned as the value from `routine`.
+    ///
+    /// # Example
+    /// ```rust
+    /// #[macro_use] extern crate criterion;
+    /// use criterion::*;
+    /// use criterion::black_box;
+    /// use criterion::measurement::Measurement;
+    ///
+    /// fn compute() -> Vec<u64> {
+    ///     // ...
+    ///     # vec![]
+    /// }
+    ///
+    /// fn bench(c: &mut Criterion) {
+    ///     c.bench_function("iter", move |b| {
+    ///         b.iter_custom_measured(|iters, start, measurement| {
+    ///             let results: Vec<_> = (0..iters).map(|_| black_box(compute())).collect();
+    ///             let value = measurement.end(start);
+    ///             // Dropping the results is not measured.
+    ///             drop(results);
+    ///             value
+    ///         })
+    ///     });
+    /// }
+    ///
+    /// criterion_group!(benches, bench);
+    /// criterion_main!(benches);
+    /// ```
+    ///
+    #[inline(never)]
+    pub fn iter_custom_measured<R>(&mut self, mut routine: R)
+    where
+        R: FnMut(u64, M::Intermediate, &M) -> M::Value,
+    {
+        self.iterated = true;
+        self.timer_pairs = 1;
+        let time_start = Instant::now();
+        let start = self.measurement.start();
+        self.value = routine(self.iters, start, self.measurement);
+        self.elapsed_time = time_start.elapsed();
+    }
+
+    /// Times a `routine` that requires some input, by running an untimed `setup` function before
+    /// each iteration to generate that input. Use this when constructing the input is expensive
+    /// and should not be included in the measurement, for example when the routine consumes a
+    /// freshly-built data structure.
+    ///
+    /// This is equivalent to `iter_batched` with `BatchSize::PerIteration`. Only the routine itself
+    /// is measured, so the setup time is excluded even if the timer is coarse; however, the
+    /// measurement overhead of starting and stopping the timer on every iteration makes this
+    /// unsuitable for very fast routines. Prefer `iter_batched` with a larger batch size for those.
+    ///
+    /// # Timing model
+    ///
+    /// ```text
+    /// elapsed = iters * (Instant::now + routine)
+    /// ```
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// #[macro_use] extern crate criterion;
+    ///
+    /// use criterion::*;
+    ///
+    /// fn create_scrambled_data() -> Vec<u64> {
+    ///     # vec![]
+    ///     // ...
+    /// }
+    ///
+    /// fn bench(c: &mut Criterion) {
+    ///     c.bench_function("with_setup", move |b| {
+    ///         // This will avoid timing the create_scrambled_data call.
+    ///         b.iter_with_setup(|| create_scrambled_data(), |mut data| data.sort())
+    ///     });
+    /// }
+    ///
+    /// criterion_group!(benches, bench);
+    /// criterion_main!(benches);
+    /// ```
+    ///
     pub fn iter_with_setup<I, O, S, R>(&mut self, setup: S, routine: R)
     where
         S: FnMut() -> I,
@@ -147,6 +379,59 @@ impl<'a, M: Measurement> Bencher<'a, M> {
         self.iter_batched(setup, routine, BatchSize::PerIteration);
     }
 
+    /// Times a `routine` that takes ownership of its input, calling the untimed `setup` function
+    /// to build a fresh input for every iteration.
+    ///
+    /// If a routine borrows an input which is shared between iterations, it may modify or cache
+    /// state in that input (for example, by sorting it in place, or by memoizing a result), so
+    /// that later iterations do less work than the first and the benchmark reports a bogus
+    /// speedup. Giving each iteration an input that no other iteration has seen prevents this.
+    /// This is the recommended pattern for benchmarks with mutable or cacheable inputs.
+    ///
+    /// This is equivalent to `iter_batched` with `BatchSize::SmallInput`. If the inputs are large,
+    /// use `iter_batched` with `BatchSize::LargeInput` instead to limit memory usage.
+    ///
+    /// # Timing model
+    ///
+    /// ```text
+    /// elapsed = (Instant::now * num_batches) + (iters * (routine + O::drop)) + Vec::extend
+    /// ```
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// #[macro_use] extern crate criterion;
+    ///
+    /// use criterion::*;
+    /// use std::collections::HashMap;
+    ///
+    /// struct Memoizer {
+    ///     cache: HashMap<u64, u64>,
+    /// }
+    ///
+    /// fn bench(c: &mut Criterion) {
+    ///     c.bench_function("fresh_input", |b| {
+    ///         // Each iteration starts with an empty cache, rather than one filled in by the
+    ///         // previous iterations.
+    ///         b.iter_with_fresh_input(
+    ///             || Memoizer { cache: HashMap::new() },
+    ///             |mut memo| *memo.cache.entry(20).or_insert_with(|| (1..=20).product()),
+    ///         )
+    ///     });
+    /// }
+    ///
+    /// criterion_group!(benches, bench);
+    /// criterion_main!(benches);
+    /// ```
+    ///
+    pub fn iter_with_fresh_input<I, O, S, R>(&mut self, setup: S, routine: R)
+    where
+        S: Fn() -> I,
+        R: FnMut(I) -> O,
+    {
+        self.iter_batched(setup, routine, BatchSize::SmallInput);
+    }
+
     /// Times a `routine` by collecting its output on each iteration. This avoids timing the
     /// destructor of the value returned by `routine`.
     ///
@@ -250,6 +535,7 @@ impl<'a, M: Measurement> Bencher<'a, M> {
         self.iterated = true;
         let batch_size = size.iters_per_batch(self.iters);
         assert!(batch_size != 0, "Batch size must not be zero.");
+        self.timer_pairs = (self.iters + batch_size - 1) / batch_size;
         let time_start = Instant::now();
         self.value = self.measurement.zero();
 
@@ -340,6 +626,7 @@ impl<'a, M: Measurement> Bencher<'a, M> {
         self.iterated = true;
         let batch_size = size.iters_per_batch(self.iters);
         assert!(batch_size != 0, "Batch size must not be zero.");
+        self.timer_pairs = (self.iters + batch_size - 1) / batch_size;
         let time_start = Instant::now();
         self.value = self.measurement.zero();
 
@@ -377,6 +664,88 @@ impl<'a, M: Measurement> Bencher<'a, M> {
         self.elapsed_time = time_start.elapsed();
     }
 
+    /// Returns the value created by the benchmark group's `setup` closure. The same value is shared
+    /// by every sample of the benchmark; use a `RefCell` or `Cell` in it if the routine needs to
+    /// modify it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the benchmark group has no `setup` closure, or if it returns a type other than `S`.
+    pub fn setup_value<S: 'static>(&self) -> Rc<S> {
+        let value = self
+            .setup_value
+            .clone()
+            .expect("Bencher::setup_value requires a setup closure; see BenchmarkGroup::setup");
+        value
+            .downcast::<S>()
+            .unwrap_or_else(|_| panic!("The setup closure does not return the requested type"))
+    }
+
+    /// Reports the number of elements processed by the iterations timed in this call of the
+    /// benchmark routine, for workloads whose amount of work per iteration depends on the data,
+    /// such as sorting inputs which are already partially sorted. The counts reported for all of
+    /// the samples are added up, and the mean number of elements per iteration is reported as the
+    /// throughput, unless a throughput is set for the benchmark. Calling this more than once in the
+    /// same call of the routine adds up the counts.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// #[macro_use] extern crate criterion;
+    ///
+    /// use criterion::*;
+    ///
+    /// fn bench(c: &mut Criterion) {
+    ///     c.bench_function("dedup", |b| {
+    ///         let mut elements = 0;
+    ///         b.iter(|| {
+    ///             let mut data = vec![1, 1, 2, 3, 3, 3, 4];
+    ///             data.dedup();
+    ///             elements += data.len() as u64;
+    ///         });
+    ///         b.set_throughput_elements(elements);
+    ///     });
+    /// }
+    ///
+    /// criterion_group!(benches, bench);
+    /// criterion_main!(benches);
+    /// ```
+    pub fn set_throughput_elements(&mut self, elements: u64) {
+        *self.elements.get_or_insert(0) += elements;
+    }
+
+    /// Tags this benchmark, like [`BenchmarkGroup::tag`](crate::BenchmarkGroup::tag) does for all
+    /// of the benchmarks in a group. The tags are saved with the benchmark's results, and the
+    /// benchmark is selected by `--filter-tag` if either it or its group has the tag.
+    ///
+    /// Since these tags are only known once the routine has run, `--filter-tag` runs a single
+    /// iteration of each benchmark whose group doesn't have the selected tag to find them. With
+    /// `--list`, `--test` or `--profile-time`, the routine isn't run for this, so only the group's
+    /// tags select benchmarks.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// #[macro_use] extern crate criterion;
+    ///
+    /// use criterion::*;
+    ///
+    /// fn bench(c: &mut Criterion) {
+    ///     c.bench_function("parse_header", |b| {
+    ///         b.tag("parser");
+    ///         b.iter(|| "Content-Length: 42".split_once(": "));
+    ///     });
+    /// }
+    ///
+    /// criterion_group!(benches, bench);
+    /// criterion_main!(benches);
+    /// ```
+    pub fn tag(&mut self, tag: &str) {
+        if !self.tags.iter().any(|t| t == tag) {
+            self.tags.push(tag.to_owned());
+        }
+    }
+
     // Benchmarks must actually call one of the iter methods. This causes benchmarks to fail loudly
     // if they don't.
     pub(crate) fn assert_iterated(&mut self) {
@@ -449,12 +818,146 @@ impl<'a, 'b, A: AsyncExecutor, M: Measurement> AsyncBencher<'a, 'b, A, M> {
         runner.block_on(async {
             b.iterated = true;
             let time_start = Instant::now();
+            if let Some(values) = &mut b.iteration_values {
+                // Times each iteration on its own, so the sample is the sum of the iterations.
+                b.timer_pairs = b.iters;
+                let mut value = b.measurement.zero();
+                for _ in 0..b.iters {
+                    let start = b.measurement.start();
+                    black_box(routine().await);
+                    let iteration = b.measurement.end(start);
+                    values.push(b.measurement.to_f64(&iteration));
+                    value = b.measurement.add(&value, &iteration);
+                }
+                b.value = value;
+            } else {
+                b.timer_pairs = 1;
+                let start = b.measurement.start();
+                for _ in 0..b.iters {
+                    black_box(routine().await);
+                }
+                b.value = b.measurement.end(start);
+            }
+            b.elapsed_time = time_start.elapsed();
+        });
+    }
+
+    /// Times a fallible `routine` by executing it many times and timing the total elapsed time.
+    ///
+    /// This is like `iter`, but for routines which return a `Result`. The `Ok` value is passed
+    /// through `black_box`, so there is no need to unwrap it inside the benchmark. If the routine
+    /// returns an `Err`, the timing loop stops immediately and the benchmark is aborted; the error
+    /// is reported along with the benchmark ID and Criterion.rs moves on to the next benchmark.
+    ///
+    /// # Timing model
+    ///
+    /// ```text
+    /// elapsed = Instant::now + iters * (routine + match + mem::drop(O) + Range::next)
+    /// ```
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// #[macro_use] extern crate criterion;
+    ///
+    /// use criterion::*;
+    /// use criterion::async_executor::FuturesExecutor;
+    ///
+    /// // The function to benchmark
+    /// async fn parse(input: &str) -> Result<u64, std::num::ParseIntError> {
+    ///     input.parse()
+    /// }
+    ///
+    /// fn bench(c: &mut Criterion) {
+    ///     c.bench_function("try_iter", move |b| {
+    ///         b.to_async(FuturesExecutor).try_iter(|| async { parse(black_box("12345")).await })
+    ///     });
+    /// }
+    ///
+    /// criterion_group!(benches, bench);
+    /// criterion_main!(benches);
+    /// ```
+    ///
+    #[inline(never)]
+    pub fn try_iter<O, E, R, F>(&mut self, mut routine: R)
+    where
+        R: FnMut() -> F,
+        F: Future<Output = Result<O, E>>,
+        E: fmt::Display,
+    {
+        let AsyncBencher { b, runner } = self;
+        runner.block_on(async {
+            b.iterated = true;
+            b.timer_pairs = 1;
+            let time_start = Instant::now();
+            let start = b.measurement.start();
+            for _ in 0..b.iters {
+                if let Err(err) = routine().await.map(black_box) {
+                    b.error = Some(err.to_string());
+                    break;
+                }
+            }
+            b.value = b.measurement.end(start);
+            b.elapsed_time = time_start.elapsed();
+        });
+    }
+
+    /// Times a stream of items by creating a `Stream` with `make` and driving it to exhaustion,
+    /// many times, and timing the total elapsed time. Each item is passed through `black_box` and
+    /// dropped as soon as it's yielded, so nothing needs to be collected.
+    ///
+    /// The number of items yielded is counted. If the benchmark has no throughput set, the mean
+    /// number of items per iteration is reported as a `Throughput::Elements`.
+    ///
+    /// # Timing model
+    ///
+    /// ```text
+    /// elapsed = Instant::now + iters * (make + items * (Stream::poll_next + mem::drop(Item)))
+    /// ```
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// #[macro_use] extern crate criterion;
+    ///
+    /// use criterion::*;
+    /// use criterion::async_executor::FuturesExecutor;
+    /// use futures::stream;
+    ///
+    /// fn bench(c: &mut Criterion) {
+    ///     c.bench_function("iter_stream", move |b| {
+    ///         b.to_async(FuturesExecutor).iter_stream(|| stream::iter(0..black_box(100)))
+    ///     });
+    /// }
+    ///
+    /// criterion_group!(benches, bench);
+    /// criterion_main!(benches);
+    /// ```
+    ///
+    #[inline(never)]
+    pub fn iter_stream<S, R>(&mut self, mut make: R)
+    where
+        R: FnMut() -> S,
+        S: Stream,
+    {
+        let AsyncBencher { b, runner } = self;
+        runner.block_on(async {
+            b.iterated = true;
+            b.timer_pairs = 1;
+            let mut elements = 0u64;
+            let time_start = Instant::now();
             let start = b.measurement.start();
             for _ in 0..b.iters {
-                black_box(routine().await);
+                let stream = make();
+                futures::pin_mut!(stream);
+                while let Some(item) = stream.next().await {
+                    black_box(item);
+                    elements += 1;
+                }
             }
             b.value = b.measurement.end(start);
             b.elapsed_time = time_start.elapsed();
+            *b.elements.get_or_insert(0) += elements;
         });
     }
 
@@ -506,13 +1009,62 @@ impl<'a, 'b, A: AsyncExecutor, M: Measurement> AsyncBencher<'a, 'b, A, M> {
         let AsyncBencher { b, runner } = self;
         runner.block_on(async {
             b.iterated = true;
+            // The routine does its own timing, so its timer overhead is unknown.
+            b.timer_pairs = 0;
             let time_start = Instant::now();
             b.value = routine(b.iters).await;
             b.elapsed_time = time_start.elapsed();
         })
     }
 
-    #[doc(hidden)]
+    /// Times a `routine` that requires some input, by running an untimed `setup` function before
+    /// each iteration to generate that input. Use this when constructing the input is expensive
+    /// and should not be included in the measurement, for example when the routine consumes a
+    /// freshly-built data structure.
+    ///
+    /// This is equivalent to `iter_batched` with `BatchSize::PerIteration`. Only the routine itself
+    /// is measured, so the setup time is excluded even if the timer is coarse; however, the
+    /// measurement overhead of starting and stopping the timer on every iteration makes this
+    /// unsuitable for very fast routines. Prefer `iter_batched` with a larger batch size for those.
+    ///
+    /// # Timing model
+    ///
+    /// ```text
+    /// elapsed = iters * (Instant::now + routine)
+    /// ```
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// #[macro_use] extern crate criterion;
+    ///
+    /// use criterion::*;
+    /// use criterion::async_executor::FuturesExecutor;
+    ///
+    /// fn create_scrambled_data() -> Vec<u64> {
+    ///     # vec![]
+    ///     // ...
+    /// }
+    ///
+    /// // The sorting algorithm to test
+    /// async fn sort(data: &mut [u64]) {
+    ///     // ...
+    /// }
+    ///
+    /// fn bench(c: &mut Criterion) {
+    ///     c.bench_function("with_setup", move |b| {
+    ///         // This will avoid timing the create_scrambled_data call.
+    ///         b.to_async(FuturesExecutor).iter_with_setup(
+    ///             || create_scrambled_data(),
+    ///             |mut data| async move { sort(&mut data).await },
+    ///         )
+    ///     });
+    /// }
+    ///
+    /// criterion_group!(benches, bench);
+    /// criterion_main!(benches);
+    /// ```
+    ///
     pub fn iter_with_setup<I, O, S, R, F>(&mut self, setup: S, routine: R)
     where
         S: FnMut() -> I,
@@ -522,6 +1074,60 @@ impl<'a, 'b, A: AsyncExecutor, M: Measurement> AsyncBencher<'a, 'b, A, M> {
         self.iter_batched(setup, routine, BatchSize::PerIteration);
     }
 
+    /// Times a `routine` that takes ownership of its input, calling the untimed `setup` function
+    /// to build a fresh input for every iteration. This prevents the routine from modifying or
+    /// caching state in an input shared between iterations, which would make later iterations
+    /// faster than the first. See [`Bencher::iter_with_fresh_input`] for details.
+    ///
+    /// This is equivalent to `iter_batched` with `BatchSize::SmallInput`.
+    ///
+    /// # Timing model
+    ///
+    /// ```text
+    /// elapsed = (Instant::now * num_batches) + (iters * (routine + O::drop)) + Vec::extend
+    /// ```
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// #[macro_use] extern crate criterion;
+    ///
+    /// use criterion::*;
+    /// use criterion::async_executor::FuturesExecutor;
+    ///
+    /// fn create_scrambled_data() -> Vec<u64> {
+    ///     # vec![]
+    ///     // ...
+    /// }
+    ///
+    /// // The sorting algorithm to test
+    /// async fn sort(data: &mut [u64]) {
+    ///     // ...
+    /// }
+    ///
+    /// fn bench(c: &mut Criterion) {
+    ///     c.bench_function("fresh_input", move |b| {
+    ///         // Each iteration sorts its own unsorted copy of the data.
+    ///         b.to_async(FuturesExecutor).iter_with_fresh_input(
+    ///             || create_scrambled_data(),
+    ///             |mut data| async move { sort(&mut data).await },
+    ///         )
+    ///     });
+    /// }
+    ///
+    /// criterion_group!(benches, bench);
+    /// criterion_main!(benches);
+    /// ```
+    ///
+    pub fn iter_with_fresh_input<I, O, S, R, F>(&mut self, setup: S, routine: R)
+    where
+        S: Fn() -> I,
+        R: FnMut(I) -> F,
+        F: Future<Output = O>,
+    {
+        self.iter_batched(setup, routine, BatchSize::SmallInput);
+    }
+
     /// Times a `routine` by collecting its output on each iteration. This avoids timing the
     /// destructor of the value returned by `routine`.
     ///
@@ -632,6 +1238,7 @@ impl<'a, 'b, A: AsyncExecutor, M: Measurement> AsyncBencher<'a, 'b, A, M> {
             b.iterated = true;
             let batch_size = size.iters_per_batch(b.iters);
             assert!(batch_size != 0, "Batch size must not be zero.");
+            b.timer_pairs = (b.iters + batch_size - 1) / batch_size;
             let time_start = Instant::now();
             b.value = b.measurement.zero();
 
@@ -730,6 +1337,7 @@ impl<'a, 'b, A: AsyncExecutor, M: Measurement> AsyncBencher<'a, 'b, A, M> {
             b.iterated = true;
             let batch_size = size.iters_per_batch(b.iters);
             assert!(batch_size != 0, "Batch size must not be zero.");
+            b.timer_pairs = (b.iters + batch_size - 1) / batch_size;
             let time_start = Instant::now();
             b.value = b.measurement.zero();
 
@@ -2,27 +2,63 @@
 
 use crate::analysis;
 use crate::connection::OutgoingMessage;
+use crate::estimate::Statistic;
 use crate::measurement::{Measurement, WallTime};
 use crate::report::{BenchmarkId, Report, ReportContext};
-use crate::routine::{Function, Routine};
-use crate::{Bencher, Criterion, DurationExt, Mode, PlotConfiguration, SamplingMode, Throughput};
+use crate::routine::{Function, Hooks, Routine};
+use crate::{
+    Bencher, Criterion, DurationExt, Mode, PlotConfiguration, RegressionMethod, SamplingMode,
+    Throughput, WarmUp,
+};
 use std::cell::RefCell;
 use std::fmt::Debug;
 use std::marker::Sized;
+use std::sync::Arc;
 use std::time::Duration;
 
 // TODO: Move the benchmark config stuff to a separate module for easier use.
 
 /// Struct containing all of the configuration options for a benchmark.
+#[derive(Clone)]
 pub struct BenchmarkConfig {
     pub confidence_level: f64,
     pub measurement_time: Duration,
     pub noise_threshold: f64,
     pub nresamples: usize,
+    pub nresamples_compare: Option<usize>,
     pub sample_size: usize,
     pub significance_level: f64,
     pub warm_up_time: Duration,
+    pub warm_up_iterations: Option<u64>,
     pub sampling_mode: SamplingMode,
+    pub trimmed_mean_fraction: f64,
+    pub estimate_trimmed_mean: bool,
+    pub estimate_mode: bool,
+    pub robust_dispersion: bool,
+    pub regression_method: RegressionMethod,
+    pub comparison_statistics: Vec<Statistic>,
+    pub(crate) value_transform: Option<ValueTransform>,
+}
+impl BenchmarkConfig {
+    pub(crate) fn warm_up(&self) -> WarmUp {
+        match self.warm_up_iterations {
+            Some(iters) => WarmUp::Iterations(iters),
+            None => WarmUp::Time(self.warm_up_time),
+        }
+    }
+
+    /// The number of resamples used to compare the benchmark against its baseline.
+    pub(crate) fn compare_resamples(&self) -> usize {
+        self.nresamples_compare.unwrap_or(self.nresamples)
+    }
+}
+
+/// A transformation of the measured values of a benchmark, set with
+/// `BenchmarkGroup::value_transform`, and the unit of the transformed values.
+#[derive(Clone)]
+pub(crate) struct ValueTransform {
+    pub(crate) function: Arc<dyn Fn(f64) -> f64 + Send + Sync>,
+    pub(crate) unit: &'static str,
 }
 
 /// Struct representing a partially-complete per-benchmark configuration.
@@ -32,11 +68,13 @@ pub(crate) struct PartialBenchmarkConfig {
     pub(crate) measurement_time: Option<Duration>,
     pub(crate) noise_threshold: Option<f64>,
     pub(crate) nresamples: Option<usize>,
+    pub(crate) nresamples_compare: Option<usize>,
     pub(crate) sample_size: Option<usize>,
     pub(crate) significance_level: Option<f64>,
     pub(crate) warm_up_time: Option<Duration>,
     pub(crate) sampling_mode: Option<SamplingMode>,
     pub(crate) plot_config: PlotConfiguration,
+    pub(crate) value_transform: Option<ValueTransform>,
 }
 
 impl PartialBenchmarkConfig {
@@ -46,12 +84,28 @@ impl PartialBenchmarkConfig {
             measurement_time: self.measurement_time.unwrap_or(defaults.measurement_time),
             noise_threshold: self.noise_threshold.unwrap_or(defaults.noise_threshold),
             nresamples: self.nresamples.unwrap_or(defaults.nresamples),
+            nresamples_compare: self.nresamples_compare.or(defaults.nresamples_compare),
             sample_size: self.sample_size.unwrap_or(defaults.sample_size),
             significance_level: self
                 .significance_level
                 .unwrap_or(defaults.significance_level),
             warm_up_time: self.warm_up_time.unwrap_or(defaults.warm_up_time),
+            // A warm-up time set for this benchmark overrides a default number of iterations.
+            warm_up_iterations: match self.warm_up_time {
+                Some(_) => None,
+                None => defaults.warm_up_iterations,
+            },
             sampling_mode: self.sampling_mode.unwrap_or(defaults.sampling_mode),
+            trimmed_mean_fraction: defaults.trimmed_mean_fraction,
+            estimate_trimmed_mean: defaults.estimate_trimmed_mean,
+            estimate_mode: defaults.estimate_mode,
+            robust_dispersion: defaults.robust_dispersion,
+            regression_method: defaults.regression_method,
+            comparison_statistics: defaults.comparison_statistics.clone(),
+            value_transform: self
+                .value_transform
+                .clone()
+                .or_else(|| defaults.value_transform.clone()),
         }
     }
 }
@@ -151,7 +205,7 @@ macro_rules! benchmark_config {
         pub fn nresamples(mut self, n: usize) -> Self {
             assert!(n > 0);
             if n <= 1000 {
-                println!("\nWarning: It is not recommended to reduce nresamples below 1000.");
+                human_println!("\nWarning: It is not recommended to reduce nresamples below 1000.");
             }
 
             self.config.nresamples = Some(n);
@@ -188,7 +242,9 @@ macro_rules! benchmark_config {
         pub fn confidence_level(mut self, cl: f64) -> Self {
             assert!(cl > 0.0 && cl < 1.0);
             if cl < 0.5 {
-                println!("\nWarning: It is not recommended to reduce confidence level below 0.5.");
+                human_println!(
+                    "\nWarning: It is not recommended to reduce confidence level below 0.5."
+                );
             }
 
             self.config.confidence_level = Some(cl);
@@ -300,10 +356,7 @@ where
 
 impl<M: Measurement> BenchmarkDefinition<M> for Benchmark<M> {
     fn run(self, group_id: &str, c: &mut Criterion<M>) {
-        let report_context = ReportContext {
-            output_directory: c.output_directory.clone(),
-            plot_config: self.config.plot_config.clone(),
-        };
+        let report_context = c.report_context(self.config.plot_config.clone(), None);
 
         let config = self.config.to_complete(&c.config);
         let num_routines = self.routines.len();
@@ -335,10 +388,8 @@ impl<M: Measurement> BenchmarkDefinition<M> for Benchmark<M> {
             id.ensure_title_unique(&c.all_titles);
             c.all_titles.insert(id.as_title().to_owned());
 
-            let do_run = c.filter_matches(id.id());
-            any_matched |= do_run;
-
-            execute_benchmark(
+            let do_run = c.filter_matches(&id);
+            any_matched |= execute_benchmark(
                 do_run,
                 &id,
                 c,
@@ -352,6 +403,7 @@ impl<M: Measurement> BenchmarkDefinition<M> for Benchmark<M> {
             all_ids.push(id);
         }
 
+        analysis::finish_pending_analysis(c);
         if let Some(conn) = &c.connection {
             conn.send(&OutgoingMessage::FinishedBenchmarkGroup { group: group_id })
                 .unwrap();
@@ -360,8 +412,11 @@ impl<M: Measurement> BenchmarkDefinition<M> for Benchmark<M> {
         }
 
         if all_ids.len() > 1 && any_matched && c.mode.is_benchmark() {
-            c.report
-                .summarize(&report_context, &all_ids, c.measurement.formatter());
+            c.report.summarize(
+                &report_context,
+                &all_ids,
+                &report_context.time_formatter(c.measurement.formatter()),
+            );
         }
         if any_matched {
             c.report.group_separator();
@@ -461,10 +516,7 @@ where
     M: Measurement + 'static,
 {
     fn run(self, group_id: &str, c: &mut Criterion<M>) {
-        let report_context = ReportContext {
-            output_directory: c.output_directory.clone(),
-            plot_config: self.config.plot_config.clone(),
-        };
+        let report_context = c.report_context(self.config.plot_config.clone(), None);
 
         let config = self.config.to_complete(&c.config);
         let num_parameters = self.values.len();
@@ -505,10 +557,8 @@ where
                 id.ensure_title_unique(&c.all_titles);
                 c.all_titles.insert(id.as_title().to_owned());
 
-                let do_run = c.filter_matches(id.id());
-                any_matched |= do_run;
-
-                execute_benchmark(
+                let do_run = c.filter_matches(&id);
+                any_matched |= execute_benchmark(
                     do_run,
                     &id,
                     c,
@@ -523,6 +573,7 @@ where
             }
         }
 
+        analysis::finish_pending_analysis(c);
         if let Some(conn) = &c.connection {
             conn.send(&OutgoingMessage::FinishedBenchmarkGroup { group: group_id })
                 .unwrap();
@@ -531,8 +582,11 @@ where
         }
 
         if all_ids.len() > 1 && any_matched && c.mode.is_benchmark() {
-            c.report
-                .summarize(&report_context, &all_ids, c.measurement.formatter());
+            c.report.summarize(
+                &report_context,
+                &all_ids,
+                &report_context.time_formatter(c.measurement.formatter()),
+            );
         }
         if any_matched {
             c.report.group_separator();
@@ -540,6 +594,8 @@ where
     }
 }
 
+// Runs the benchmark according to the mode, unless it isn't selected by the filters. Returns
+// whether it ran.
 #[cfg_attr(feature = "cargo-clippy", allow(clippy::too_many_arguments))]
 fn execute_benchmark<T, M>(
     do_run: bool,
@@ -550,10 +606,20 @@ fn execute_benchmark<T, M>(
     report_context: &ReportContext,
     parameter: &T,
     throughput: Option<Throughput>,
-) where
+) -> bool
+where
     T: Debug,
     M: Measurement,
 {
+    let do_run = do_run
+        && match analysis::tag_filter_matches(id, routine, c, parameter, &mut Hooks::default()) {
+            Ok(matches) => matches,
+            Err(err) => {
+                c.report.error(id, report_context, &err);
+                false
+            }
+        };
+
     match &c.mode {
         Mode::Benchmark => {
             if let Some(conn) = &c.connection {
@@ -575,6 +641,7 @@ fn execute_benchmark<T, M>(
                     report_context,
                     parameter,
                     throughput,
+                    &mut Hooks::default(),
                 );
             }
         }
@@ -587,14 +654,21 @@ fn execute_benchmark<T, M>(
             if do_run {
                 // In test mode, run the benchmark exactly once, then exit.
                 c.report.test_start(id, report_context);
-                routine.test(&c.measurement, parameter);
-                c.report.test_pass(id, report_context);
+                match routine.test(&c.measurement, parameter) {
+                    Ok(()) => c.report.test_pass(id, report_context),
+                    Err(err) => c.report.error(id, report_context, &err),
+                }
             }
         }
         &Mode::Profile(duration) => {
             if do_run {
-                routine.profile(&c.measurement, id, c, report_context, duration, parameter);
+                if let Err(err) =
+                    routine.profile(&c.measurement, id, c, report_context, duration, parameter)
+                {
+                    c.report.error(id, report_context, &err);
+                }
             }
         }
     }
+    do_run
 }
@@ -1,14 +1,23 @@
 use crate::analysis;
-use crate::benchmark::PartialBenchmarkConfig;
+use crate::benchmark::{BenchmarkConfig, PartialBenchmarkConfig, ValueTransform};
 use crate::connection::OutgoingMessage;
 use crate::measurement::Measurement;
 use crate::report::BenchmarkId as InternalBenchmarkId;
 use crate::report::Report;
 use crate::report::ReportContext;
-use crate::routine::{Function, Routine};
-use crate::{Bencher, Criterion, DurationExt, Mode, PlotConfiguration, SamplingMode, Throughput};
+use crate::routine::{Function, Hooks, Routine, SampleCollector};
+use crate::{
+    ActualSamplingMode, Bencher, Criterion, DurationExt, Mode, PlotConfiguration, SamplingMode,
+    Throughput,
+};
+use std::any::Any;
+use std::rc::Rc;
+use std::str::FromStr;
+use std::sync::Arc;
 use std::time::Duration;
 
+type ThroughputFn<'a> = Box<dyn Fn(&str) -> Option<Throughput> + 'a>;
+
 /// Structure used to group together a set of related benchmarks, along with custom configuration
 /// settings for groups of benchmarks. All benchmarks performed using a benchmark group will be
 /// grouped together in the final report.
@@ -82,6 +91,12 @@ pub struct BenchmarkGroup<'a, M: Measurement> {
     any_matched: bool,
     partial_config: PartialBenchmarkConfig,
     throughput: Option<Throughput>,
+    throughput_fn: Option<ThroughputFn<'a>>,
+    tags: Vec<String>,
+    working_set_bytes: Option<u64>,
+    hooks: Hooks<'a>,
+    // The runner's own measurement, while this group uses the one set with `measurement`.
+    replaced_measurement: Option<M>,
 }
 impl<'a, M: Measurement> BenchmarkGroup<'a, M> {
     /// Changes the size of the sample for this benchmark
@@ -145,13 +160,31 @@ impl<'a, M: Measurement> BenchmarkGroup<'a, M> {
     pub fn nresamples(&mut self, n: usize) -> &mut Self {
         assert!(n > 0);
         if n <= 1000 {
-            println!("\nWarning: It is not recommended to reduce nresamples below 1000.");
+            human_println!("\nWarning: It is not recommended to reduce nresamples below 1000.");
         }
 
         self.partial_config.nresamples = Some(n);
         self
     }
 
+    /// Changes the number of resamples used to compare the benchmarks in this group with their
+    /// baseline. See `Criterion::nresamples_compare`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of resamples is set to zero
+    pub fn nresamples_compare(&mut self, n: usize) -> &mut Self {
+        assert!(n > 0);
+        if n <= 1000 {
+            human_println!(
+                "\nWarning: It is not recommended to reduce nresamples_compare below 1000."
+            );
+        }
+
+        self.partial_config.nresamples_compare = Some(n);
+        self
+    }
+
     /// Changes the noise threshold for benchmarks in this group. The noise threshold
     /// is used to filter out small changes in performance from one run to the next, even if they
     /// are statistically significant. Sometimes benchmarking the same code twice will result in
@@ -176,13 +209,19 @@ impl<'a, M: Measurement> BenchmarkGroup<'a, M> {
     /// [confidence interval](https://en.wikipedia.org/wiki/Confidence_interval). The default is
     /// 0.95, meaning that the confidence interval should capture the true value 95% of the time.
     ///
+    /// The confidence level only affects the width of the reported confidence intervals. It does
+    /// not affect whether a change is considered statistically significant; that is controlled by
+    /// the significance level.
+    ///
     /// # Panics
     ///
     /// Panics if the confidence level is set to a value outside the `(0, 1)` range
     pub fn confidence_level(&mut self, cl: f64) -> &mut Self {
         assert!(cl > 0.0 && cl < 1.0);
         if cl < 0.5 {
-            println!("\nWarning: It is not recommended to reduce confidence level below 0.5.");
+            human_println!(
+                "\nWarning: It is not recommended to reduce confidence level below 0.5."
+            );
         }
 
         self.partial_config.confidence_level = Some(cl);
@@ -216,6 +255,48 @@ impl<'a, M: Measurement> BenchmarkGroup<'a, M> {
         self
     }
 
+    /// Transforms the measured values of the following benchmarks in this group before they are
+    /// analyzed, eg. to normalize the time per iteration by the size of the input. `transform` is
+    /// applied to the mean value per iteration of each sample, so all of the statistics, plots and
+    /// comparisons of these benchmarks are of the transformed values, which are displayed as
+    /// plain numbers followed by `unit`. Like `throughput`, it applies to all benchmarks run after
+    /// it is set, so a different factor can be used for each input.
+    ///
+    /// The saved samples are also transformed, so a benchmark should always be compared against a
+    /// baseline saved with the same transform.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// #[macro_use] extern crate criterion;
+    /// use self::criterion::*;
+    ///
+    /// fn bench(c: &mut Criterion) {
+    ///     let mut group = c.benchmark_group("sum");
+    ///     for len in [1024usize, 4096].iter() {
+    ///         let input = vec![1u64; *len];
+    ///         let len = *len as f64;
+    ///         group.value_transform("ns/elem", move |ns| ns / len);
+    ///         group.bench_with_input(BenchmarkId::from_parameter(len), &input,
+    ///             |b, i| b.iter(|| i.iter().sum::<u64>()));
+    ///     }
+    ///     group.finish();
+    /// }
+    ///
+    /// criterion_group!(benches, bench);
+    /// criterion_main!(benches);
+    /// ```
+    pub fn value_transform<F>(&mut self, unit: &'static str, transform: F) -> &mut Self
+    where
+        F: Fn(f64) -> f64 + Send + Sync + 'static,
+    {
+        self.partial_config.value_transform = Some(ValueTransform {
+            function: Arc::new(transform),
+            unit,
+        });
+        self
+    }
+
     /// Changes the plot configuration for this benchmark group.
     pub fn plot_config(&mut self, new_config: PlotConfiguration) -> &mut Self {
         self.partial_config.plot_config = new_config;
@@ -229,12 +310,141 @@ impl<'a, M: Measurement> BenchmarkGroup<'a, M> {
         self
     }
 
+    // Sets or clears the throughput of the following benchmarks.
+    pub(crate) fn set_throughput(&mut self, throughput: Option<Throughput>) -> &mut Self {
+        self.throughput = throughput;
+        self
+    }
+
+    /// Derives the throughput of each benchmark in this group from its parameter, so that it doesn't
+    /// need to be set with `throughput` before each benchmark. The parameter given to
+    /// `BenchmarkId::new` or `BenchmarkId::from_parameter` is parsed back from its string form as a
+    /// `P` and passed to `f`.
+    ///
+    /// Benchmarks without a parameter, or whose parameter doesn't parse as a `P`, use the throughput
+    /// set with `throughput`, if any.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// #[macro_use] extern crate criterion;
+    /// use self::criterion::*;
+    ///
+    /// fn bench(c: &mut Criterion) {
+    ///     let mut group = c.benchmark_group("sum");
+    ///     group.throughput_fn(|size: &u64| Throughput::Elements(*size));
+    ///     for size in [1024u64, 2048, 4096].iter() {
+    ///         let input = vec![1u64; *size as usize];
+    ///         group.bench_with_input(BenchmarkId::from_parameter(size), &input,
+    ///             |b, i| b.iter(|| i.iter().sum::<u64>()));
+    ///     }
+    ///     group.finish();
+    /// }
+    ///
+    /// criterion_group!(benches, bench);
+    /// criterion_main!(benches);
+    /// ```
+    pub fn throughput_fn<P, F>(&mut self, f: F) -> &mut Self
+    where
+        P: FromStr,
+        F: Fn(&P) -> Throughput + 'a,
+    {
+        self.throughput_fn = Some(Box::new(move |parameter: &str| {
+            parameter.parse().ok().map(|parameter| f(&parameter))
+        }));
+        self
+    }
+
+    /// Sets the size, in bytes, of the data touched by the following benchmarks in this group,
+    /// such as the combined size of their inputs and outputs. Like `throughput`, it applies to
+    /// all benchmarks run after it is set, so it is usually set once per input.
+    ///
+    /// The working set size is shown in the HTML report, and if the benchmarks in a group have
+    /// different working set sizes, the summary report plots the time per element (or per byte)
+    /// against it. Time jumps in this plot show where the working set stops fitting in one of the
+    /// CPU caches.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// #[macro_use] extern crate criterion;
+    /// use self::criterion::*;
+    ///
+    /// fn bench(c: &mut Criterion) {
+    ///     let mut group = c.benchmark_group("sum");
+    ///     for len in [1usize << 10, 1 << 16, 1 << 22].iter() {
+    ///         let input = vec![1u64; *len];
+    ///         group.throughput(Throughput::Elements(*len as u64));
+    ///         group.working_set_bytes((*len * std::mem::size_of::<u64>()) as u64);
+    ///         group.bench_with_input(BenchmarkId::from_parameter(len), &input,
+    ///             |b, i| b.iter(|| i.iter().sum::<u64>()));
+    ///     }
+    ///     group.finish();
+    /// }
+    ///
+    /// criterion_group!(benches, bench);
+    /// criterion_main!(benches);
+    /// ```
+    pub fn working_set_bytes(&mut self, bytes: u64) -> &mut Self {
+        self.working_set_bytes = Some(bytes);
+        self
+    }
+
     /// Set the sampling mode for this benchmark group.
     pub fn sampling_mode(&mut self, new_mode: SamplingMode) -> &mut Self {
         self.partial_config.sampling_mode = Some(new_mode);
         self
     }
 
+    /// Tags the benchmarks in this group. Tags can be used to select benchmarks with the
+    /// `--filter-tag` command-line option, and benchmarks are also listed by tag in the HTML
+    /// report. A group can have any number of tags; they apply to all benchmarks run in the group
+    /// after the tag is added.
+    pub fn tag(&mut self, tag: &str) -> &mut Self {
+        if !self.tags.iter().any(|t| t == tag) {
+            self.tags.push(tag.to_owned());
+        }
+        self
+    }
+
+    /// Changes the measurement used by the benchmarks in this group. The reports of these
+    /// benchmarks, including the group's summary, display their values with this measurement's
+    /// formatter. The runner's own measurement is used again once the group is finished.
+    ///
+    /// Since the measurement must have the same type as the runner's, this is most useful with
+    /// `BoxedMeasurement`, which allows benchmarks measured in wall-clock time and, for example,
+    /// in CPU cycles to be run by the same `criterion_main!`. This should be called before any
+    /// benchmarks are run in the group, so that they all use the same units.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// #[macro_use] extern crate criterion;
+    /// use self::criterion::*;
+    /// use self::criterion::measurement::{BoxedMeasurement, HighResWallTime, WallTime};
+    ///
+    /// fn bench(c: &mut Criterion<BoxedMeasurement>) {
+    ///     let mut group = c.benchmark_group("high-res");
+    ///     group.measurement(BoxedMeasurement::new(HighResWallTime));
+    ///     group.bench_function("sum", |b| b.iter(|| (0..100u64).sum::<u64>()));
+    ///     group.finish();
+    /// }
+    ///
+    /// criterion_group! {
+    ///     name = benches;
+    ///     config = Criterion::default().with_measurement(BoxedMeasurement::new(WallTime));
+    ///     targets = bench
+    /// }
+    /// criterion_main!(benches);
+    /// ```
+    pub fn measurement(&mut self, measurement: M) -> &mut Self {
+        // The analysis of the previous benchmark must be reported with its own measurement.
+        analysis::finish_pending_analysis(self.criterion);
+        let replaced = std::mem::replace(&mut self.criterion.measurement, measurement);
+        self.replaced_measurement.get_or_insert(replaced);
+        self
+    }
+
     pub(crate) fn new(criterion: &mut Criterion<M>, group_name: String) -> BenchmarkGroup<'_, M> {
         BenchmarkGroup {
             criterion,
@@ -243,9 +453,67 @@ impl<'a, M: Measurement> BenchmarkGroup<'a, M> {
             any_matched: false,
             partial_config: PartialBenchmarkConfig::default(),
             throughput: None,
+            throughput_fn: None,
+            tags: vec![],
+            working_set_bytes: None,
+            hooks: Hooks::default(),
+            replaced_measurement: None,
         }
     }
 
+    /// Sets a closure which is called once before the first sample of each benchmark in this
+    /// group, for example to open a database connection or allocate a large buffer. The value it
+    /// returns is shared by all of the samples of the benchmark, and can be accessed in the
+    /// benchmark routine with `Bencher::setup_value`.
+    ///
+    /// ```no_run
+    /// #[macro_use] extern crate criterion;
+    /// use self::criterion::*;
+    /// use std::cell::RefCell;
+    ///
+    /// fn bench(c: &mut Criterion) {
+    ///     let mut group = c.benchmark_group("buffer");
+    ///     group.setup(|| RefCell::new(vec![0u8; 1 << 20]));
+    ///     group.bench_function("fill", |b| {
+    ///         let buffer = b.setup_value::<RefCell<Vec<u8>>>();
+    ///         b.iter(|| buffer.borrow_mut().iter_mut().for_each(|x| *x = 1))
+    ///     });
+    ///     group.finish();
+    /// }
+    /// ```
+    pub fn setup<S, F>(&mut self, mut setup: F) -> &mut Self
+    where
+        S: 'static,
+        F: FnMut() -> S + 'a,
+    {
+        self.hooks.setup = Some(Box::new(move || Rc::new(setup()) as Rc<dyn Any>));
+        self
+    }
+
+    /// Sets a closure which is called with the value created by the `setup` closure after the
+    /// last sample of each benchmark in this group. It is called even if the benchmark panics.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `setup` closure returns a type other than `S`, or if the benchmark routine
+    /// still holds on to the value.
+    pub fn teardown<S, F>(&mut self, mut teardown: F) -> &mut Self
+    where
+        S: 'static,
+        F: FnMut(S) + 'a,
+    {
+        self.hooks.teardown = Some(Box::new(move |value: Rc<dyn Any>| {
+            let value = value
+                .downcast::<S>()
+                .unwrap_or_else(|_| panic!("The setup closure does not return the teardown type"));
+            match Rc::try_unwrap(value) {
+                Ok(value) => teardown(value),
+                Err(_) => panic!("The setup value is still in use after the benchmark finished"),
+            }
+        }));
+        self
+    }
+
     /// Benchmark the given parameterless function inside this benchmark group.
     pub fn bench_function<ID: IntoBenchmarkId, F>(&mut self, id: ID, mut f: F) -> &mut Self
     where
@@ -270,26 +538,182 @@ impl<'a, M: Measurement> BenchmarkGroup<'a, M> {
         self
     }
 
-    fn run_bench<F, I>(&mut self, id: BenchmarkId, input: &I, f: F)
+    /// Returns an iterator which measures the samples of a benchmark one at a time, so that
+    /// arbitrary code can run between samples. This is meant for custom drivers which need more
+    /// control over the measurement than `bench_function` gives; the collected samples are then
+    /// analyzed and reported with `analyze`.
+    ///
+    /// The samples are measured with the configuration of this group. If the benchmark doesn't
+    /// match the filter, the iterator is empty, and in test mode it measures a single iteration.
+    /// The group's `setup` and `teardown` closures don't run around collected samples.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// #[macro_use] extern crate criterion;
+    /// use self::criterion::*;
+    ///
+    /// fn bench(c: &mut Criterion) {
+    ///     let mut group = c.benchmark_group("custom-driver");
+    ///     let mut samples = vec![];
+    ///     for sample in group.sample_collector("sum", |b| b.iter(|| (0..100u64).sum::<u64>())) {
+    ///         // Arbitrary code can run between samples here.
+    ///         samples.push(sample.unwrap());
+    ///     }
+    ///     group.analyze("sum", &samples);
+    ///     group.finish();
+    /// }
+    ///
+    /// criterion_group!(benches, bench);
+    /// criterion_main!(benches);
+    /// ```
+    pub fn sample_collector<ID: IntoBenchmarkId, F>(&self, id: ID, f: F) -> SampleCollector<'_, M>
     where
-        F: FnMut(&mut Bencher<'_, M>, &I),
-        I: ?Sized,
+        F: FnMut(&mut Bencher<'_, M>) + 'a,
     {
         let config = self.partial_config.to_complete(&self.criterion.config);
-        let report_context = ReportContext {
-            output_directory: self.criterion.output_directory.clone(),
-            plot_config: self.partial_config.plot_config.clone(),
+        let id = self.internal_id(id.into_benchmark_id());
+        let iters = if !(self.criterion.filter_matches(&id) && self.criterion.tags_match(&id.tags))
+        {
+            Some(vec![])
+        } else {
+            match self.criterion.mode {
+                Mode::Benchmark | Mode::Profile(_) => None,
+                Mode::Test => Some(vec![1]),
+                Mode::List => Some(vec![]),
+            }
         };
+        SampleCollector::new(&self.criterion.measurement, config, iters, f)
+    }
+
+    /// Analyzes and reports samples collected with `sample_collector`, as if they had been
+    /// measured by `bench_function`. Each sample is an iteration count and the measured value of
+    /// all of those iterations.
+    ///
+    /// The samples don't have to be measured by Criterion.rs: timings collected by other means,
+    /// such as hardware counters read out of firmware, get the same statistics, comparisons and
+    /// reports, as long as the values are in the units of the group's measurement (as returned by
+    /// `Measurement::to_f64`). The group's configuration applies, except for the settings that
+    /// control how samples are measured. If every sample has the same iteration count, they are
+    /// analyzed as flat samples, and otherwise as linear ones.
+    ///
+    /// At least two samples are needed for the analysis, and every sample must have a non-zero
+    /// iteration count and a finite value; otherwise an error is reported instead. Since the
+    /// samples can't be measured again, nothing is done for them when profiling.
+    pub fn analyze<ID: IntoBenchmarkId>(&mut self, id: ID, samples: &[(u64, f64)]) -> &mut Self {
+        if let Mode::Profile(_) = self.criterion.mode {
+            return self;
+        }
+        let config = self.partial_config.to_complete(&self.criterion.config);
+        let report_context = self.report_context(&config);
+        let (id, do_run) = self.register(id.into_benchmark_id());
+        let do_run = do_run && self.criterion.tags_match(&id.tags);
+        self.any_matched |= do_run;
 
+        if do_run {
+            match self.criterion.mode {
+                Mode::Benchmark if samples.len() < 2 => self.criterion.report.error(
+                    &id,
+                    &report_context,
+                    "At least two samples must be collected to analyze a benchmark",
+                ),
+                Mode::Benchmark if samples.iter().any(|&(iters, _)| iters == 0) => {
+                    self.criterion.report.error(
+                        &id,
+                        &report_context,
+                        "Every sample must have a non-zero iteration count",
+                    )
+                }
+                Mode::Benchmark if samples.iter().any(|&(_, value)| !value.is_finite()) => {
+                    self.criterion.report.error(
+                        &id,
+                        &report_context,
+                        "Every sample must have a finite value",
+                    )
+                }
+                Mode::Benchmark => {
+                    if let Some(conn) = &self.criterion.connection {
+                        conn.send(&OutgoingMessage::BeginningBenchmark { id: (&id).into() })
+                            .unwrap();
+                    }
+                    // Without the warm-up, the sampling mode can only be told apart by the
+                    // iteration counts: flat sampling uses the same count for every sample.
+                    let sampling_mode = if samples.windows(2).all(|w| w[0].0 == w[1].0) {
+                        ActualSamplingMode::Flat
+                    } else {
+                        ActualSamplingMode::Linear
+                    };
+                    analysis::samples(
+                        &id,
+                        &config,
+                        self.criterion,
+                        &report_context,
+                        id.throughput.clone(),
+                        sampling_mode,
+                        samples.iter().map(|&(iters, _)| iters as f64).collect(),
+                        samples.iter().map(|&(_, value)| value).collect(),
+                    );
+                }
+                Mode::List => println!("{}: bench", id),
+                Mode::Test => {
+                    self.criterion.report.test_start(&id, &report_context);
+                    self.criterion.report.test_pass(&id, &report_context);
+                }
+                Mode::Profile(_) => {}
+            }
+        } else if let Some(conn) = &self.criterion.connection {
+            conn.send(&OutgoingMessage::SkippingBenchmark { id: (&id).into() })
+                .unwrap();
+        }
+
+        self.all_ids.push(id);
+        self
+    }
+
+    fn internal_id(&self, id: BenchmarkId) -> InternalBenchmarkId {
+        let throughput = match (&self.throughput_fn, &id.parameter) {
+            (Some(throughput_fn), Some(parameter)) => throughput_fn(parameter),
+            _ => None,
+        };
         let mut id = InternalBenchmarkId::new(
             self.group_name.clone(),
             id.function_name,
             id.parameter,
-            self.throughput.clone(),
+            throughput.or_else(|| self.throughput.clone()),
         );
+        // Signed measurements don't report throughput; see `SignedMeasurement`.
+        if self.criterion.measurement.formatter().is_signed() {
+            id.throughput = None;
+        }
+        id.tags = self.tags.clone();
+        id.working_set_bytes = self.working_set_bytes.or(self.criterion.working_set_bytes);
+        id
+    }
 
+    // The context of the reports on the benchmarks of this group.
+    fn report_context(&self, config: &BenchmarkConfig) -> ReportContext {
+        self.criterion.report_context(
+            self.partial_config.plot_config.clone(),
+            config
+                .value_transform
+                .as_ref()
+                .map(|transform| transform.unit),
+        )
+    }
+
+    // Makes the ID of a new benchmark of this group unique, and returns it along with whether the
+    // ID filter selects the benchmark. The tag filter is checked by the caller.
+    fn register(&mut self, id: BenchmarkId) -> (InternalBenchmarkId, bool) {
+        let mut id = self.internal_id(id);
+
+        // The tags and working set size are not part of the ID.
         assert!(
-            !self.all_ids.contains(&id),
+            !self
+                .all_ids
+                .iter()
+                .any(|other| other.group_id == id.group_id
+                    && other.function_id == id.function_id
+                    && other.value_str == id.value_str),
             "Benchmark IDs must be unique within a group."
         );
 
@@ -300,9 +724,38 @@ impl<'a, M: Measurement> BenchmarkGroup<'a, M> {
         id.ensure_title_unique(&self.criterion.all_titles);
         self.criterion.all_titles.insert(id.as_title().to_owned());
 
-        let do_run = self.criterion.filter_matches(id.id());
+        let do_run = self.criterion.filter_matches(&id);
+        (id, do_run)
+    }
+
+    fn run_bench<F, I>(&mut self, id: BenchmarkId, input: &I, f: F)
+    where
+        F: FnMut(&mut Bencher<'_, M>, &I),
+        I: ?Sized,
+    {
+        self.run_routine(id, input, &mut Function::new(f));
+    }
+
+    pub(crate) fn run_routine<I: ?Sized>(
+        &mut self,
+        id: BenchmarkId,
+        input: &I,
+        func: &mut dyn Routine<M, I>,
+    ) {
+        let config = self.partial_config.to_complete(&self.criterion.config);
+        let report_context = self.report_context(&config);
+        let (id, mut do_run) = self.register(id);
+        if do_run {
+            let criterion = &*self.criterion;
+            match analysis::tag_filter_matches(&id, func, criterion, input, &mut self.hooks) {
+                Ok(matches) => do_run = matches,
+                Err(err) => {
+                    criterion.report.error(&id, &report_context, &err);
+                    do_run = false;
+                }
+            }
+        }
         self.any_matched |= do_run;
-        let mut func = Function::new(f);
 
         match &self.criterion.mode {
             Mode::Benchmark => {
@@ -318,12 +771,13 @@ impl<'a, M: Measurement> BenchmarkGroup<'a, M> {
                 if do_run {
                     analysis::common(
                         &id,
-                        &mut func,
+                        func,
                         &config,
                         self.criterion,
                         &report_context,
                         input,
-                        self.throughput.clone(),
+                        id.throughput.clone(),
+                        &mut self.hooks,
                     );
                 }
             }
@@ -336,20 +790,32 @@ impl<'a, M: Measurement> BenchmarkGroup<'a, M> {
                 if do_run {
                     // In test mode, run the benchmark exactly once, then exit.
                     self.criterion.report.test_start(&id, &report_context);
-                    func.test(&self.criterion.measurement, input);
-                    self.criterion.report.test_pass(&id, &report_context);
+                    let measurement = &self.criterion.measurement;
+                    match self
+                        .hooks
+                        .around(func, |func| func.test(measurement, input))
+                    {
+                        Ok(()) => self.criterion.report.test_pass(&id, &report_context),
+                        Err(err) => self.criterion.report.error(&id, &report_context, &err),
+                    }
                 }
             }
             &Mode::Profile(duration) => {
                 if do_run {
-                    func.profile(
-                        &self.criterion.measurement,
-                        &id,
-                        self.criterion,
-                        &report_context,
-                        duration,
-                        input,
-                    );
+                    let criterion = &*self.criterion;
+                    let result = self.hooks.around(func, |func| {
+                        func.profile(
+                            &criterion.measurement,
+                            &id,
+                            criterion,
+                            &report_context,
+                            duration,
+                            input,
+                        )
+                    });
+                    if let Err(err) = result {
+                        self.criterion.report.error(&id, &report_context, &err);
+                    }
                 }
             }
         }
@@ -369,6 +835,7 @@ impl<'a, M: Measurement> Drop for BenchmarkGroup<'a, M> {
     fn drop(&mut self) {
         // I don't really like having a bunch of non-trivial code in drop, but this is the only way
         // to really write linear types like this in Rust...
+        analysis::finish_pending_analysis(self.criterion);
         if let Some(conn) = &mut self.criterion.connection {
             conn.send(&OutgoingMessage::FinishedBenchmarkGroup {
                 group: &self.group_name,
@@ -380,20 +847,26 @@ impl<'a, M: Measurement> Drop for BenchmarkGroup<'a, M> {
         }
 
         if self.all_ids.len() > 1 && self.any_matched && self.criterion.mode.is_benchmark() {
-            let report_context = ReportContext {
-                output_directory: self.criterion.output_directory.clone(),
-                plot_config: self.partial_config.plot_config.clone(),
-            };
+            let report_context = self.criterion.report_context(
+                self.partial_config.plot_config.clone(),
+                self.partial_config
+                    .value_transform
+                    .as_ref()
+                    .map(|transform| transform.unit),
+            );
 
             self.criterion.report.summarize(
                 &report_context,
                 &self.all_ids,
-                self.criterion.measurement.formatter(),
+                &report_context.time_formatter(self.criterion.measurement.formatter()),
             );
         }
         if self.any_matched {
             self.criterion.report.group_separator();
         }
+        if let Some(measurement) = self.replaced_measurement.take() {
+            self.criterion.measurement = measurement;
+        }
     }
 }
 
@@ -0,0 +1,166 @@
+//! Reads benchmark settings from a TOML file; see `Criterion::from_config_file`.
+
+use std::env;
+use std::path::{Path, PathBuf};
+use toml::value::{Table, Value};
+
+const FILE_NAME: &str = "criterion.toml";
+
+/// Returns the `criterion.toml` in the workspace root, if there is one. The root is given by
+/// `CARGO_WORKSPACE_DIR`, which can be set in the `[env]` section of `.cargo/config.toml`, or is
+/// otherwise the package directory, `CARGO_MANIFEST_DIR`, which Cargo sets when running the
+/// benchmarks. The current directory and its parents are never searched.
+pub(crate) fn find() -> Option<PathBuf> {
+    let root = env::var_os("CARGO_WORKSPACE_DIR").or_else(|| env::var_os("CARGO_MANIFEST_DIR"))?;
+    find_in(Path::new(&root))
+}
+
+fn find_in(root: &Path) -> Option<PathBuf> {
+    let path = root.join(FILE_NAME);
+    if path.is_file() {
+        Some(path)
+    } else {
+        None
+    }
+}
+
+/// Parses the settings in `text`. They are either the top-level keys of the file or, so that the
+/// file can be shared with other tools, the keys of its `[criterion]` table.
+pub(crate) fn parse(text: &str) -> Result<Table, String> {
+    let mut table: Table = toml::from_str(text).map_err(|err| err.to_string())?;
+    match table.remove("criterion") {
+        Some(Value::Table(settings)) => Ok(settings),
+        Some(_) => Err("`criterion` must be a table".to_owned()),
+        None => Ok(table),
+    }
+}
+
+pub(crate) fn as_u64(value: &Value) -> Option<u64> {
+    match *value {
+        Value::Integer(i) if i >= 0 => Some(i as u64),
+        _ => None,
+    }
+}
+
+/// Integers are accepted where a float is expected, as `5` is more natural than `5.0`.
+pub(crate) fn as_f64(value: &Value) -> Option<f64> {
+    match *value {
+        Value::Integer(i) => Some(i as f64),
+        Value::Float(f) => Some(f),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Criterion, SamplingMode};
+    use std::time::Duration;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_parse() {
+        let text = r#"
+# Settings shared by the whole team.
+sample_size = 50
+measurement_time = 2.5  # seconds
+nresamples = 100_000
+plots = false
+sampling_mode = "flat"
+path = "a \"quoted\" # value!"
+"#;
+        let settings = parse(text).unwrap();
+        assert_eq!(settings["sample_size"], Value::Integer(50));
+        assert_eq!(settings["measurement_time"], Value::Float(2.5));
+        assert_eq!(settings["nresamples"], Value::Integer(100_000));
+        assert_eq!(settings["plots"], Value::Boolean(false));
+        assert_eq!(settings["sampling_mode"].as_str(), Some("flat"));
+        assert_eq!(settings["path"].as_str(), Some("a \"quoted\" # value!"));
+    }
+
+    #[test]
+    fn test_parse_criterion_table() {
+        let text = r#"
+criterion_home = "./target/criterion"
+
+[criterion]
+sample_size = 50
+sampling_mode = '''
+flat'''
+
+[colors]
+comparison_colors = "1"
+"#;
+        let settings = parse(text).unwrap();
+        assert_eq!(settings.len(), 2);
+        assert_eq!(settings["sample_size"], Value::Integer(50));
+        assert_eq!(settings["sampling_mode"].as_str(), Some("flat"));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(parse("sample_size = ten").is_err());
+        assert!(parse("sample_size").is_err());
+        assert_eq!(
+            parse("criterion = 1").unwrap_err(),
+            "`criterion` must be a table"
+        );
+    }
+
+    #[test]
+    fn test_from_config_file() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("criterion.toml");
+        std::fs::write(
+            &path,
+            "sample_size = 20\nmeasurement_time = 2.5\nsampling_mode = \"flat\"\nbootstrap_seed = 7\n",
+        )
+        .unwrap();
+
+        let criterion = Criterion::from_config_file(&path);
+        assert_eq!(criterion.config.sample_size, 20);
+        assert_eq!(
+            criterion.config.measurement_time,
+            Duration::from_millis(2500)
+        );
+        assert_eq!(criterion.config.warm_up_time, Duration::from_secs(3));
+        assert!(matches!(criterion.config.sampling_mode, SamplingMode::Flat));
+        assert_eq!(criterion.bootstrap_seed, Some(7));
+    }
+
+    #[test]
+    fn test_find_in() {
+        let dir = tempdir().unwrap();
+        assert_eq!(find_in(dir.path()), None);
+        std::fs::create_dir(dir.path().join("criterion.toml")).unwrap();
+        assert_eq!(find_in(dir.path()), None);
+
+        let root = tempdir().unwrap();
+        let path = root.path().join("criterion.toml");
+        std::fs::write(&path, "sample_size = 20\n").unwrap();
+        assert_eq!(find_in(root.path()), Some(path));
+    }
+
+    #[test]
+    fn test_default_config_file_errors_are_ignored() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("criterion.toml");
+        std::fs::write(&path, "sample_size = 20\nsample_count = 20\n").unwrap();
+
+        let criterion = Criterion::with_default_config_file(&path);
+        assert_eq!(criterion.config.sample_size, 100);
+
+        std::fs::write(&path, "sample_size = 20\n").unwrap();
+        let criterion = Criterion::with_default_config_file(&path);
+        assert_eq!(criterion.config.sample_size, 20);
+    }
+
+    #[test]
+    #[should_panic(expected = "unknown setting `sample_count`")]
+    fn test_from_config_file_unknown_setting() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("criterion.toml");
+        std::fs::write(&path, "sample_count = 20\n").unwrap();
+        Criterion::from_config_file(&path);
+    }
+}
@@ -1,4 +1,6 @@
 use crate::error::Result;
+use crate::estimate::{ChangeEstimates, Estimates};
+use crate::fs;
 use crate::measurement::ValueFormatter;
 use crate::report::{BenchmarkId, MeasurementData, Report, ReportContext};
 use crate::Throughput;
@@ -18,6 +20,69 @@ struct CsvRow<'a> {
     iteration_count: u64,
 }
 
+#[derive(Serialize)]
+struct SummaryRow<'a> {
+    group: &'a str,
+    function: Option<&'a str>,
+    value: Option<&'a str>,
+    mean: f64,
+    median: f64,
+    std_dev: f64,
+    median_abs_dev: f64,
+    unit: &'static str,
+    throughput_num: Option<u64>,
+    throughput_type: Option<&'a str>,
+    change_percent: Option<f64>,
+}
+
+/// Writes the point estimates of every benchmark in `output_directory` to a single CSV file, one
+/// row per benchmark, for `--export-csv`. The change is the relative change in the mean since the
+/// last comparison with the baseline, if there was one.
+pub(crate) fn export_summary(
+    output_directory: &Path,
+    path: &Path,
+    formatter: &dyn ValueFormatter,
+) -> Result<()> {
+    let mut ids = fs::list_existing_benchmarks(&output_directory)?;
+    ids.sort_unstable_by(|a, b| a.id().cmp(b.id()));
+
+    let mut writer = Writer::from_path(path)?;
+    for id in &ids {
+        let dir = output_directory.join(id.as_directory_name());
+        let estimates: Estimates = fs::load(&dir.join("new").join("estimates.json"))?;
+        let change = fs::load::<ChangeEstimates, _>(&dir.join("change").join("estimates.json"))
+            .ok()
+            .map(|change| change.mean.point_estimate * 100.0);
+
+        let mut values = [
+            estimates.mean.point_estimate,
+            estimates.median.point_estimate,
+            estimates.std_dev.point_estimate,
+            estimates.median_abs_dev.point_estimate,
+        ];
+        let unit = formatter.scale_for_machines(&mut values);
+        let (throughput_num, throughput_type) = match id.throughput {
+            Some(Throughput::Bytes(bytes)) => (Some(bytes), Some("bytes")),
+            Some(Throughput::Elements(elems)) => (Some(elems), Some("elements")),
+            None => (None, None),
+        };
+        writer.serialize(SummaryRow {
+            group: &id.group_id,
+            function: id.function_id.as_deref(),
+            value: id.value_str.as_deref(),
+            mean: values[0],
+            median: values[1],
+            std_dev: values[2],
+            median_abs_dev: values[3],
+            unit,
+            throughput_num,
+            throughput_type,
+            change_percent: change,
+        })?;
+    }
+    Ok(())
+}
+
 struct CsvReportWriter<W: Write> {
     writer: Writer<W>,
 }
@@ -88,3 +153,68 @@ impl Report for FileCsvReport {
         log_if_err!(self.write_file(&path, id, measurements, formatter));
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::measurement::{Measurement, WallTime};
+    use tempfile::tempdir;
+
+    fn estimate(point_estimate: f64) -> serde_json::Value {
+        serde_json::json!({
+            "confidence_interval": {
+                "confidence_level": 0.95,
+                "lower_bound": point_estimate,
+                "upper_bound": point_estimate,
+            },
+            "point_estimate": point_estimate,
+            "standard_error": 0.0,
+        })
+    }
+
+    #[test]
+    fn test_export_summary() {
+        let dir = tempdir().unwrap();
+        let benchmarks = [
+            ("group", Some("b"), Some(Throughput::Elements(10)), true),
+            ("group", Some("a"), None, false),
+        ];
+        for &(group, function, ref throughput, compared) in &benchmarks {
+            let id = BenchmarkId::new(
+                group.to_owned(),
+                function.map(str::to_owned),
+                None,
+                throughput.clone(),
+            );
+            let bench_dir = dir.path().join(id.as_directory_name());
+            fs::mkdirp(&bench_dir.join("new")).unwrap();
+            fs::save(&id, &bench_dir.join("new/benchmark.json")).unwrap();
+            let estimates = serde_json::json!({
+                "mean": estimate(4.0),
+                "median": estimate(3.0),
+                "median_abs_dev": estimate(2.0),
+                "slope": null,
+                "std_dev": estimate(1.0),
+            });
+            fs::save(&estimates, &bench_dir.join("new/estimates.json")).unwrap();
+            if compared {
+                fs::mkdirp(&bench_dir.join("change")).unwrap();
+                let change =
+                    serde_json::json!({ "mean": estimate(-0.25), "median": estimate(0.0) });
+                fs::save(&change, &bench_dir.join("change/estimates.json")).unwrap();
+            }
+        }
+
+        let path = dir.path().join("summary.csv");
+        export_summary(dir.path(), &path, WallTime.formatter()).unwrap();
+        let csv = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(
+            csv.lines().collect::<Vec<_>>(),
+            vec![
+                "group,function,value,mean,median,std_dev,median_abs_dev,unit,throughput_num,throughput_type,change_percent",
+                "group,a,,4.0,3.0,1.0,2.0,ns,,,",
+                "group,b,,4.0,3.0,1.0,2.0,ns,10,elements,-25.0",
+            ]
+        );
+    }
+}
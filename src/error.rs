@@ -1,3 +1,4 @@
+use bincode::Error as BincodeError;
 use csv::Error as CsvError;
 use serde_json::Error as SerdeError;
 use std::error::Error as StdError;
@@ -21,7 +22,16 @@ pub enum Error {
         path: PathBuf,
         inner: SerdeError,
     },
+    BincodeError {
+        path: PathBuf,
+        inner: BincodeError,
+    },
     CsvError(CsvError),
+    MeasurementMismatch {
+        id: String,
+        baseline: String,
+        current: String,
+    },
 }
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -37,7 +47,22 @@ impl fmt::Display for Error {
                 "Failed to read or write file {:?} due to serialization error: {}",
                 path, inner
             ),
+            Error::BincodeError { path, inner } => write!(
+                f,
+                "Failed to read or write file {:?} due to serialization error: {}",
+                path, inner
+            ),
             Error::CsvError(inner) => write!(f, "CSV error: {}", inner),
+            Error::MeasurementMismatch {
+                id,
+                baseline,
+                current,
+            } => write!(
+                f,
+                "Benchmark {} was measured with {}, but its baseline was measured with {}; \
+                 use Criterion::measurement_conversion to compare them",
+                id, current, baseline
+            ),
         }
     }
 }
@@ -47,7 +72,9 @@ impl StdError for Error {
             Error::AccessError { .. } => "AccessError",
             Error::CopyError { .. } => "CopyError",
             Error::SerdeError { .. } => "SerdeError",
+            Error::BincodeError { .. } => "BincodeError",
             Error::CsvError(_) => "CsvError",
+            Error::MeasurementMismatch { .. } => "MeasurementMismatch",
         }
     }
 
@@ -56,7 +83,9 @@ impl StdError for Error {
             Error::AccessError { inner, .. } => Some(inner),
             Error::CopyError { inner, .. } => Some(inner),
             Error::SerdeError { inner, .. } => Some(inner),
+            Error::BincodeError { inner, .. } => Some(inner),
             Error::CsvError(inner) => Some(inner),
+            Error::MeasurementMismatch { .. } => None,
         }
     }
 }
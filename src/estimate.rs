@@ -1,15 +1,46 @@
+use std::collections::BTreeMap;
 use std::fmt;
 
+use crate::measurement::ValueFormatter;
 use crate::stats::Distribution;
+use crate::Throughput;
 
+/// A statistic estimated from the measurements of a benchmark.
 #[derive(Clone, Copy, Eq, Ord, PartialEq, PartialOrd, Deserialize, Serialize, Debug)]
 pub enum Statistic {
+    /// The mean time per iteration.
     Mean,
+    /// The median time per iteration.
     Median,
+    /// The median absolute deviation of the times per iteration.
     MedianAbsDev,
+    /// The peak of the kernel density estimate of the times per iteration.
+    Mode,
+    /// The slope of the linear regression of the total times against the iteration counts.
     Slope,
+    /// The standard deviation of the times per iteration.
     StdDev,
+    /// The mean time per iteration after discarding a fraction of the samples from each end.
+    TrimmedMean,
+    /// The slope if it was estimated, or else the mean.
     Typical,
+    /// The given percentile of the times per iteration, from 0 to 100.
+    Percentile(u8),
+}
+impl Statistic {
+    /// Returns whether the change in this statistic can be estimated by comparing the times per
+    /// iteration of two runs.
+    pub(crate) fn is_comparable(self) -> bool {
+        match self {
+            Statistic::Mean
+            | Statistic::Median
+            | Statistic::MedianAbsDev
+            | Statistic::StdDev
+            | Statistic::TrimmedMean => true,
+            Statistic::Percentile(p) => p <= 100,
+            Statistic::Mode | Statistic::Slope | Statistic::Typical => false,
+        }
+    }
 }
 
 impl fmt::Display for Statistic {
@@ -18,9 +49,12 @@ impl fmt::Display for Statistic {
             Statistic::Mean => f.pad("mean"),
             Statistic::Median => f.pad("median"),
             Statistic::MedianAbsDev => f.pad("MAD"),
+            Statistic::Mode => f.pad("mode"),
             Statistic::Slope => f.pad("slope"),
             Statistic::StdDev => f.pad("SD"),
+            Statistic::TrimmedMean => f.pad("trimmed mean"),
             Statistic::Typical => f.pad("typical"),
+            Statistic::Percentile(p) => f.pad(&format!("p{}", p)),
         }
     }
 }
@@ -40,6 +74,84 @@ pub struct Estimate {
     pub point_estimate: f64,
     /// The standard error of this estimate
     pub standard_error: f64,
+    /// The standard error relative to the magnitude of the point estimate, or `None` if the point
+    /// estimate is zero. Files saved by older versions don't have it.
+    #[serde(default)]
+    pub relative_standard_error: Option<f64>,
+}
+
+/// The throughput of a benchmark and its confidence interval, in elements or bytes per unit of the
+/// measured value (eg. per nanosecond). It is computed once by the analysis, from the bootstrap
+/// distribution of the typical value per iteration, so that all of the reports show the same
+/// interval.
+#[derive(Clone, Debug)]
+pub(crate) struct ThroughputEstimate {
+    pub throughput: Throughput,
+    pub confidence_interval: ConfidenceInterval,
+    pub point_estimate: f64,
+}
+impl ThroughputEstimate {
+    /// Computes the throughput from the bootstrap distribution of the typical value per iteration
+    /// and its point estimate.
+    pub fn new(
+        throughput: &Throughput,
+        typical: &Estimate,
+        distribution: &Distribution<f64>,
+    ) -> ThroughputEstimate {
+        let per_iteration = throughput_per_iteration(throughput);
+        let rates = distribution
+            .iter()
+            .map(|&value| per_iteration / value)
+            .collect::<Vec<f64>>();
+        let confidence_level = typical.confidence_interval.confidence_level;
+        let (lower_bound, upper_bound) =
+            Distribution::from(rates.into_boxed_slice()).confidence_interval(confidence_level);
+        ThroughputEstimate {
+            throughput: throughput.clone(),
+            confidence_interval: ConfidenceInterval {
+                confidence_level,
+                lower_bound,
+                upper_bound,
+            },
+            point_estimate: per_iteration / typical.point_estimate,
+        }
+    }
+
+    /// The values per iteration at which the lower bound, the point estimate and the upper bound
+    /// of the throughput are reached, which is the form `ValueFormatter` converts to throughput.
+    pub fn values(&self) -> [f64; 3] {
+        let per_iteration = throughput_per_iteration(&self.throughput);
+        [
+            per_iteration / self.confidence_interval.lower_bound,
+            per_iteration / self.point_estimate,
+            per_iteration / self.confidence_interval.upper_bound,
+        ]
+    }
+
+    /// Formats the lower bound, the point estimate and the upper bound of the throughput.
+    pub fn format(&self, formatter: &dyn ValueFormatter) -> [String; 3] {
+        let [lower, point, upper] = self.values();
+        [
+            formatter.format_throughput(&self.throughput, lower),
+            formatter.format_throughput(&self.throughput, point),
+            formatter.format_throughput(&self.throughput, upper),
+        ]
+    }
+}
+
+fn throughput_per_iteration(throughput: &Throughput) -> f64 {
+    match *throughput {
+        Throughput::Bytes(bytes) => bytes as f64,
+        Throughput::Elements(elements) => elements as f64,
+    }
+}
+
+pub(crate) fn relative_standard_error(point_estimate: f64, standard_error: f64) -> Option<f64> {
+    if point_estimate == 0.0 {
+        None
+    } else {
+        Some(standard_error / point_estimate.abs())
+    }
 }
 
 pub fn build_estimates(
@@ -49,6 +161,7 @@ pub fn build_estimates(
 ) -> Estimates {
     let to_estimate = |point_estimate, distribution: &Distribution<f64>| {
         let (lb, ub) = distribution.confidence_interval(cl);
+        let standard_error = distribution.std_dev(None);
 
         Estimate {
             confidence_interval: ConfidenceInterval {
@@ -57,7 +170,8 @@ pub fn build_estimates(
                 upper_bound: ub,
             },
             point_estimate,
-            standard_error: distribution.std_dev(None),
+            standard_error,
+            relative_standard_error: relative_standard_error(point_estimate, standard_error),
         }
     };
 
@@ -65,8 +179,16 @@ pub fn build_estimates(
         mean: to_estimate(points.mean, &distributions.mean),
         median: to_estimate(points.median, &distributions.median),
         median_abs_dev: to_estimate(points.median_abs_dev, &distributions.median_abs_dev),
+        mode: points
+            .mode
+            .zip(distributions.mode.as_ref())
+            .map(|(point, distribution)| to_estimate(point, distribution)),
         slope: None,
         std_dev: to_estimate(points.std_dev, &distributions.std_dev),
+        trimmed_mean: points
+            .trimmed_mean
+            .zip(distributions.trimmed_mean.as_ref())
+            .map(|(point, distribution)| to_estimate(point, distribution)),
     }
 }
 
@@ -77,6 +199,7 @@ pub fn build_change_estimates(
 ) -> ChangeEstimates {
     let to_estimate = |point_estimate, distribution: &Distribution<f64>| {
         let (lb, ub) = distribution.confidence_interval(cl);
+        let standard_error = distribution.std_dev(None);
 
         Estimate {
             confidence_interval: ConfidenceInterval {
@@ -85,13 +208,22 @@ pub fn build_change_estimates(
                 upper_bound: ub,
             },
             point_estimate,
-            standard_error: distribution.std_dev(None),
+            standard_error,
+            relative_standard_error: relative_standard_error(point_estimate, standard_error),
         }
     };
 
     ChangeEstimates {
         mean: to_estimate(points.mean, &distributions.mean),
         median: to_estimate(points.median, &distributions.median),
+        other: points
+            .other
+            .iter()
+            .zip(&distributions.other)
+            .map(|(&(statistic, point), (_, distribution))| {
+                (statistic.to_string(), to_estimate(point, distribution))
+            })
+            .collect(),
     }
 }
 
@@ -99,7 +231,9 @@ pub struct PointEstimates {
     pub mean: f64,
     pub median: f64,
     pub median_abs_dev: f64,
+    pub mode: Option<f64>,
     pub std_dev: f64,
+    pub trimmed_mean: Option<f64>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -107,10 +241,33 @@ pub struct Estimates {
     pub mean: Estimate,
     pub median: Estimate,
     pub median_abs_dev: Estimate,
+    /// The peak of the kernel density estimate. This is `None` unless `Criterion::estimate_mode`
+    /// is enabled.
+    pub mode: Option<Estimate>,
     pub slope: Option<Estimate>,
     pub std_dev: Estimate,
+    /// The mean after discarding a fraction of the samples from each end. This is `None` unless
+    /// `Criterion::estimate_trimmed_mean` is enabled.
+    pub trimmed_mean: Option<Estimate>,
 }
 impl Estimates {
+    // Multiplies every estimate by `factor`, to convert them to another unit.
+    pub(crate) fn scale(&mut self, factor: f64) {
+        let scale = |estimate: &mut Estimate| {
+            estimate.point_estimate *= factor;
+            estimate.standard_error *= factor;
+            estimate.confidence_interval.lower_bound *= factor;
+            estimate.confidence_interval.upper_bound *= factor;
+        };
+        scale(&mut self.mean);
+        scale(&mut self.median);
+        scale(&mut self.median_abs_dev);
+        scale(&mut self.std_dev);
+        self.mode.iter_mut().for_each(scale);
+        self.slope.iter_mut().for_each(scale);
+        self.trimmed_mean.iter_mut().for_each(scale);
+    }
+
     pub fn typical(&self) -> &Estimate {
         self.slope.as_ref().unwrap_or(&self.mean)
     }
@@ -119,9 +276,12 @@ impl Estimates {
             Statistic::Mean => Some(&self.mean),
             Statistic::Median => Some(&self.median),
             Statistic::MedianAbsDev => Some(&self.median_abs_dev),
+            Statistic::Mode => self.mode.as_ref(),
             Statistic::Slope => self.slope.as_ref(),
             Statistic::StdDev => Some(&self.std_dev),
+            Statistic::TrimmedMean => self.trimmed_mean.as_ref(),
             Statistic::Typical => Some(self.typical()),
+            Statistic::Percentile(_) => None,
         }
     }
 }
@@ -130,8 +290,10 @@ pub struct Distributions {
     pub mean: Distribution<f64>,
     pub median: Distribution<f64>,
     pub median_abs_dev: Distribution<f64>,
+    pub mode: Option<Distribution<f64>>,
     pub slope: Option<Distribution<f64>>,
     pub std_dev: Distribution<f64>,
+    pub trimmed_mean: Option<Distribution<f64>>,
 }
 impl Distributions {
     pub fn typical(&self) -> &Distribution<f64> {
@@ -142,9 +304,12 @@ impl Distributions {
             Statistic::Mean => Some(&self.mean),
             Statistic::Median => Some(&self.median),
             Statistic::MedianAbsDev => Some(&self.median_abs_dev),
+            Statistic::Mode => self.mode.as_ref(),
             Statistic::Slope => self.slope.as_ref(),
             Statistic::StdDev => Some(&self.std_dev),
+            Statistic::TrimmedMean => self.trimmed_mean.as_ref(),
             Statistic::Typical => Some(self.typical()),
+            Statistic::Percentile(_) => None,
         }
     }
 }
@@ -152,19 +317,28 @@ impl Distributions {
 pub struct ChangePointEstimates {
     pub mean: f64,
     pub median: f64,
+    /// The statistics set with `Criterion::comparison_statistics`.
+    pub other: Vec<(Statistic, f64)>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ChangeEstimates {
     pub mean: Estimate,
     pub median: Estimate,
+    /// The statistics set with `Criterion::comparison_statistics`, by name. They are saved in
+    /// alphabetical order, whatever order they were set in.
+    #[serde(flatten)]
+    pub other: BTreeMap<String, Estimate>,
 }
 impl ChangeEstimates {
     pub fn get(&self, stat: Statistic) -> &Estimate {
         match stat {
             Statistic::Mean => &self.mean,
             Statistic::Median => &self.median,
-            _ => panic!("Unexpected statistic"),
+            _ => self
+                .other
+                .get(&stat.to_string())
+                .expect("Unexpected statistic"),
         }
     }
 }
@@ -172,13 +346,76 @@ impl ChangeEstimates {
 pub struct ChangeDistributions {
     pub mean: Distribution<f64>,
     pub median: Distribution<f64>,
+    /// The statistics set with `Criterion::comparison_statistics`.
+    pub other: Vec<(Statistic, Distribution<f64>)>,
 }
 impl ChangeDistributions {
     pub fn get(&self, stat: Statistic) -> &Distribution<f64> {
         match stat {
             Statistic::Mean => &self.mean,
             Statistic::Median => &self.median,
-            _ => panic!("Unexpected statistic"),
+            _ => self
+                .other
+                .iter()
+                .find(|(statistic, _)| *statistic == stat)
+                .map(|(_, distribution)| distribution)
+                .expect("Unexpected statistic"),
         }
     }
+
+    /// Returns the statistics whose change was estimated.
+    pub fn statistics(&self) -> Vec<Statistic> {
+        let mut statistics = vec![Statistic::Mean, Statistic::Median];
+        statistics.extend(self.other.iter().map(|&(statistic, _)| statistic));
+        statistics
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_load_estimate_without_relative_standard_error() {
+        let json = r#"{
+            "confidence_interval": {"confidence_level": 0.95, "lower_bound": 9.0, "upper_bound": 11.0},
+            "point_estimate": 10.0,
+            "standard_error": 0.5
+        }"#;
+        let estimate: Estimate = serde_json::from_str(json).unwrap();
+        assert_eq!(estimate.relative_standard_error, None);
+    }
+
+    #[test]
+    fn test_relative_standard_error() {
+        assert_eq!(relative_standard_error(10.0, 0.5), Some(0.05));
+        assert_eq!(relative_standard_error(-10.0, 0.5), Some(0.05));
+        assert_eq!(relative_standard_error(0.0, 0.5), None);
+    }
+
+    #[test]
+    fn test_throughput_estimate() {
+        let typical = Estimate {
+            confidence_interval: ConfidenceInterval {
+                confidence_level: 0.9,
+                lower_bound: 5.0,
+                upper_bound: 95.0,
+            },
+            point_estimate: 50.0,
+            standard_error: 1.0,
+            relative_standard_error: None,
+        };
+        let resamples = (1..=100).map(f64::from).collect::<Vec<f64>>();
+        let distribution = Distribution::from(resamples.into_boxed_slice());
+        let estimate = ThroughputEstimate::new(&Throughput::Bytes(100), &typical, &distribution);
+
+        assert_eq!(estimate.point_estimate, 2.0);
+        let interval = &estimate.confidence_interval;
+        assert!(interval.lower_bound < estimate.point_estimate);
+        assert!(interval.upper_bound > estimate.point_estimate);
+        // The lowest throughput is reached at the slowest resamples.
+        let [lower, point, upper] = estimate.values();
+        assert!(lower > point && point > upper);
+        assert_eq!(point, 50.0);
+    }
 }
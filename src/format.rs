@@ -1,3 +1,5 @@
+use crate::TimeUnit;
+
 pub fn change(pct: f64, signed: bool) -> String {
     if signed {
         format!("{:>+6}%", signed_short(pct * 1e2))
@@ -6,35 +8,49 @@ pub fn change(pct: f64, signed: bool) -> String {
     }
 }
 
-pub fn time(ns: f64) -> String {
-    if ns < 1.0 {
-        format!("{:>6} ps", short(ns * 1e3))
-    } else if ns < 10f64.powi(3) {
-        format!("{:>6} ns", short(ns))
-    } else if ns < 10f64.powi(6) {
-        format!("{:>6} µs", short(ns / 1e3))
-    } else if ns < 10f64.powi(9) {
-        format!("{:>6} ms", short(ns / 1e6))
+pub fn time(ns: f64, unit: Option<TimeUnit>) -> String {
+    time_with_precision(ns, unit, None)
+}
+
+pub fn time_with_precision(ns: f64, unit: Option<TimeUnit>, precision: Option<usize>) -> String {
+    let unit = unit.unwrap_or_else(|| TimeUnit::for_nanoseconds(ns));
+    let value = ns * unit.factor();
+    // A fixed unit can be much larger than the time, eg. seconds for a benchmark taking
+    // nanoseconds, which would leave no significant digits to print at the given precision.
+    let smallest_printable = 0.1f64.powi(precision.unwrap_or(2) as i32);
+    let value = if value != 0.0 && value.abs() < smallest_printable {
+        format!("{:.*e}", precision.unwrap_or(4), value)
     } else {
-        format!("{:>6} s", short(ns / 1e9))
+        with_precision(value, precision)
+    };
+    format!("{:>6} {}", value, unit.symbol())
+}
+
+/// Formats `n` with the given number of decimal places, or as `short` does if there is none.
+pub fn with_precision(n: f64, precision: Option<usize>) -> String {
+    match precision {
+        Some(precision) => format!("{:.*}", precision, n),
+        None => short(n),
     }
 }
 
 pub fn short(n: f64) -> String {
-    if n < 10.0 {
+    let n_abs = n.abs();
+
+    if n_abs < 10.0 {
         format!("{:.4}", n)
-    } else if n < 100.0 {
+    } else if n_abs < 100.0 {
         format!("{:.3}", n)
-    } else if n < 1000.0 {
+    } else if n_abs < 1000.0 {
         format!("{:.2}", n)
-    } else if n < 10000.0 {
+    } else if n_abs < 10000.0 {
         format!("{:.1}", n)
     } else {
         format!("{:.0}", n)
     }
 }
 
-fn signed_short(n: f64) -> String {
+pub fn signed_short(n: f64) -> String {
     let n_abs = n.abs();
 
     if n_abs < 10.0 {
@@ -72,6 +88,25 @@ pub fn iter_count(iterations: u64) -> String {
     }
 }
 
+pub fn bytes(n: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut value = n as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if value.fract() == 0.0 {
+        format!("{} {}", value, UNITS[unit])
+    } else {
+        format!("{} {}", short(value), UNITS[unit])
+    }
+}
+
+pub fn percent(fraction: f64) -> String {
+    format!("{:.2}%", fraction * 1e2)
+}
+
 pub fn integer(n: f64) -> String {
     format!("{}", n as u64)
 }
@@ -80,6 +115,37 @@ pub fn integer(n: f64) -> String {
 mod test {
     use super::*;
 
+    #[test]
+    fn time_with_fixed_unit() {
+        assert_eq!(time(1500.0, None), "1.5000 µs");
+        assert_eq!(time(1500.0, Some(TimeUnit::Nanoseconds)), "1500.0 ns");
+        assert_eq!(time(2.5e9, Some(TimeUnit::Milliseconds)), "2500.0 ms");
+        assert_eq!(time(5.0, Some(TimeUnit::Seconds)), "5.0000e-9 s");
+        assert_eq!(time(0.0, Some(TimeUnit::Seconds)), "0.0000 s");
+        assert_eq!(
+            time_with_precision(5.0, Some(TimeUnit::Seconds), Some(2)),
+            "5.00e-9 s"
+        );
+    }
+
+    #[test]
+    fn time_with_fixed_precision() {
+        assert_eq!(time_with_precision(1500.0, None, Some(3)), " 1.500 µs");
+        assert_eq!(time_with_precision(1400.0, None, Some(0)), "     1 µs");
+        assert_eq!(
+            time_with_precision(2.5e9, Some(TimeUnit::Milliseconds), Some(2)),
+            "2500.00 ms"
+        );
+        assert_eq!(time_with_precision(1500.0, None, None), time(1500.0, None));
+    }
+
+    #[test]
+    fn bytes_with_binary_prefix() {
+        assert_eq!(bytes(512), "512 B");
+        assert_eq!(bytes(32 * 1024), "32 KiB");
+        assert_eq!(bytes(1536 * 1024), "1.5000 MiB");
+    }
+
     #[test]
     fn short_max_len() {
         let mut float = 1.0;
@@ -91,6 +157,13 @@ mod test {
         }
     }
 
+    #[test]
+    fn short_negative() {
+        assert_eq!(short(-12345.0), "-12345");
+        assert_eq!(short(-1.5), "-1.5000");
+        assert_eq!(time(-1500.0, None), "-1.5000 µs");
+    }
+
     #[test]
     fn signed_short_max_len() {
         let mut float = -1.0;
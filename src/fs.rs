@@ -1,13 +1,63 @@
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
 use serde::de::DeserializeOwned;
 use serde::Serialize;
+use std::collections::HashSet;
 use std::ffi::OsStr;
 use std::fs::{self, File};
-use std::io::Read;
-use std::path::Path;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::thread;
+use std::time::Duration;
 use walkdir::{DirEntry, WalkDir};
 
 use crate::error::{Error, Result};
 use crate::report::BenchmarkId;
+use crate::SampleFormat;
+
+// The names a benchmark's sample may be saved under, depending on the format and compression.
+const SAMPLE_FILES: [&str; 4] = [
+    "sample.json",
+    "sample.json.gz",
+    "sample.bin",
+    "sample.bin.gz",
+];
+
+// How long `retry` waits before each retry of a failed filesystem operation.
+const RETRY_DELAYS: [Duration; 2] = [Duration::from_millis(50), Duration::from_millis(200)];
+
+// Files with a `.gz` extension are transparently compressed and decompressed.
+fn is_gzip(path: &Path) -> bool {
+    path.extension() == Some(OsStr::new("gz"))
+}
+
+// Files with a `.bin` extension (before any `.gz`) are encoded with bincode rather than JSON.
+fn is_bincode(path: &Path) -> bool {
+    let name = path.file_name().and_then(OsStr::to_str).unwrap_or("");
+    name.ends_with(".bin") || name.ends_with(".bin.gz")
+}
+
+/// Calls `f` until it succeeds, retrying a couple of times with a short backoff if it fails to
+/// access a file. Saving results can fail transiently, eg. on network filesystems or on Windows,
+/// where antivirus software briefly locks freshly written files. Other errors are returned
+/// immediately, as are I/O errors which persist after the last retry.
+pub fn retry<T, F>(mut f: F) -> Result<T>
+where
+    F: FnMut() -> Result<T>,
+{
+    for delay in RETRY_DELAYS.iter() {
+        match f() {
+            Err(e @ Error::AccessError { .. }) | Err(e @ Error::CopyError { .. }) => {
+                info!("{}; retrying in {:?}", e, delay);
+                thread::sleep(*delay);
+            }
+            result => return result,
+        }
+    }
+    f()
+}
 
 pub fn load<A, P: ?Sized>(path: &P) -> Result<A>
 where
@@ -19,12 +69,23 @@ where
         inner,
         path: path.to_owned(),
     })?;
-    let mut string = String::new();
-    let _ = f.read_to_string(&mut string);
-    let result: A = serde_json::from_str(string.as_str()).map_err(|inner| Error::SerdeError {
-        inner,
-        path: path.to_owned(),
-    })?;
+    let mut bytes = Vec::new();
+    let _ = if is_gzip(path) {
+        GzDecoder::new(f).read_to_end(&mut bytes)
+    } else {
+        f.read_to_end(&mut bytes)
+    };
+    let result: A = if is_bincode(path) {
+        bincode::deserialize(&bytes).map_err(|inner| Error::BincodeError {
+            inner,
+            path: path.to_owned(),
+        })?
+    } else {
+        serde_json::from_slice(&bytes).map_err(|inner| Error::SerdeError {
+            inner,
+            path: path.to_owned(),
+        })?
+    };
 
     Ok(result)
 }
@@ -48,6 +109,14 @@ where
     Ok(())
 }
 
+pub fn rmdir_all(path: &Path) -> Result<()> {
+    fs::remove_dir_all(path).map_err(|inner| Error::AccessError {
+        inner,
+        path: path.to_owned(),
+    })?;
+    Ok(())
+}
+
 pub fn cp(from: &Path, to: &Path) -> Result<()> {
     fs::copy(from, to).map_err(|inner| Error::CopyError {
         inner,
@@ -62,29 +131,116 @@ where
     D: Serialize,
     P: AsRef<Path>,
 {
-    let buf = serde_json::to_string(&data).map_err(|inner| Error::SerdeError {
-        path: path.as_ref().to_owned(),
-        inner,
-    })?;
-    save_string(&buf, path)
+    let path = path.as_ref();
+    if is_bincode(path) {
+        let buf = bincode::serialize(&data).map_err(|inner| Error::BincodeError {
+            path: path.to_owned(),
+            inner,
+        })?;
+        save_bytes(&buf, path)
+    } else {
+        let buf = serde_json::to_string(&data).map_err(|inner| Error::SerdeError {
+            path: path.to_owned(),
+            inner,
+        })?;
+        save_bytes(buf.as_bytes(), path)
+    }
 }
 
 pub fn save_string<P>(data: &str, path: &P) -> Result<()>
 where
     P: AsRef<Path>,
 {
-    use std::io::Write;
+    save_bytes(data.as_bytes(), path.as_ref())
+}
 
-    File::create(path)
-        .and_then(|mut f| f.write_all(data.as_bytes()))
-        .map_err(|inner| Error::AccessError {
+fn save_bytes(data: &[u8], path: &Path) -> Result<()> {
+    write_atomically(path, |f| {
+        if is_gzip(path) {
+            let mut encoder = GzEncoder::new(f, Compression::default());
+            encoder.write_all(data)?;
+            encoder.finish().map(|_| ())
+        } else {
+            f.write_all(data)
+        }
+    })
+}
+
+// Writes `path` through a temporary file in the same directory, which is renamed into place once
+// `write` succeeds. Readers, such as the next run or the plotting processes, therefore never see a
+// partially written file, even if the process is killed halfway through.
+fn write_atomically<F>(path: &Path, write: F) -> Result<()>
+where
+    F: FnOnce(&mut File) -> io::Result<()>,
+{
+    let file_name = path.file_name().and_then(OsStr::to_str).unwrap_or("file");
+    let temp_path = path.with_file_name(format!(".{}.{}.tmp", file_name, process::id()));
+    let result = File::create(&temp_path)
+        .and_then(|mut f| write(&mut f))
+        .and_then(|_| fs::rename(&temp_path, path));
+    if let Err(inner) = result {
+        let _ = fs::remove_file(&temp_path);
+        return Err(Error::AccessError {
             inner,
-            path: path.as_ref().to_owned(),
-        })?;
+            path: path.to_owned(),
+        });
+    }
+    Ok(())
+}
+
+/// Returns the path of the sample saved in `dir`, in whichever format it was saved.
+pub fn sample_file(dir: &Path) -> PathBuf {
+    SAMPLE_FILES
+        .iter()
+        .map(|file| dir.join(file))
+        .find(|path| path.is_file())
+        .unwrap_or_else(|| dir.join(SAMPLE_FILES[0]))
+}
+
+/// Saves a sample to `dir` in the given format, compressing it if `compress` is true. Samples
+/// saved in other formats by earlier runs are removed, so that they can't be loaded in place of
+/// this one.
+pub fn save_sample<D>(data: &D, dir: &Path, format: SampleFormat, compress: bool) -> Result<()>
+where
+    D: Serialize,
+{
+    let file = match (format, compress) {
+        (SampleFormat::Json, false) => SAMPLE_FILES[0],
+        (SampleFormat::Json, true) => SAMPLE_FILES[1],
+        (SampleFormat::Binary, false) => SAMPLE_FILES[2],
+        (SampleFormat::Binary, true) => SAMPLE_FILES[3],
+    };
+    save(data, &dir.join(file))?;
+    remove_samples_except(dir, file)
+}
+
+/// Copies the sample saved in `from` to `to`, in the same format.
+pub fn cp_sample(from: &Path, to: &Path) -> Result<()> {
+    let sample = sample_file(from);
+    let file = sample.file_name().and_then(OsStr::to_str).unwrap();
+    cp(&sample, &to.join(file))?;
+    remove_samples_except(to, file)
+}
 
+fn remove_samples_except(dir: &Path, keep: &str) -> Result<()> {
+    for file in SAMPLE_FILES.iter().filter(|&&file| file != keep) {
+        remove_if_exists(&dir.join(file))?;
+    }
+    Ok(())
+}
+
+fn remove_if_exists(path: &Path) -> Result<()> {
+    if path.is_file() {
+        fs::remove_file(path).map_err(|inner| Error::AccessError {
+            inner,
+            path: path.to_owned(),
+        })?;
+    }
     Ok(())
 }
 
+/// Returns the IDs of the benchmarks saved in `directory`, ordered by their directory names, so
+/// that the reports built from them don't depend on the order the file system lists them in.
 pub fn list_existing_benchmarks<P>(directory: &P) -> Result<Vec<BenchmarkId>>
 where
     P: AsRef<Path>,
@@ -99,6 +255,7 @@ where
     let mut ids = vec![];
 
     for entry in WalkDir::new(directory)
+        .sort_by_file_name()
         .into_iter()
         // Ignore errors.
         .filter_map(::std::result::Result::ok)
@@ -110,3 +267,119 @@ where
 
     Ok(ids)
 }
+
+/// Returns the directories of the benchmarks in `directory` which belong to one of the groups in
+/// `group_ids` but whose directory names are not in `executed`.
+pub fn stale_benchmark_directories<P>(
+    directory: &P,
+    group_ids: &HashSet<String>,
+    executed: &HashSet<String>,
+) -> Result<Vec<PathBuf>>
+where
+    P: AsRef<Path>,
+{
+    Ok(list_existing_benchmarks(directory)?
+        .iter()
+        .filter(|id| group_ids.contains(&id.group_id))
+        .filter(|id| !executed.contains(id.as_directory_name()))
+        .map(|id| directory.as_ref().join(id.as_directory_name()))
+        .collect())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_stale_benchmark_directories() {
+        let dir = tempdir().unwrap();
+        for &(group, function) in &[("group", "kept"), ("group", "removed"), ("other", "x")] {
+            let id = BenchmarkId::new(group.to_owned(), Some(function.to_owned()), None, None);
+            let new_dir = dir.path().join(id.as_directory_name()).join("new");
+            mkdirp(&new_dir).unwrap();
+            save(&id, &new_dir.join("benchmark.json")).unwrap();
+        }
+
+        let group_ids = vec!["group".to_owned()].into_iter().collect();
+        let executed = vec!["group/kept".to_owned()].into_iter().collect();
+        let stale = stale_benchmark_directories(&dir.path(), &group_ids, &executed).unwrap();
+        assert_eq!(stale, vec![dir.path().join("group/removed")]);
+    }
+
+    #[test]
+    fn test_list_existing_benchmarks_sorted() {
+        let dir = tempdir().unwrap();
+        for function in &["b", "c", "a"] {
+            let id = BenchmarkId::new("group".to_owned(), Some((*function).to_owned()), None, None);
+            let new_dir = dir.path().join(id.as_directory_name()).join("new");
+            mkdirp(&new_dir).unwrap();
+            save(&id, &new_dir.join("benchmark.json")).unwrap();
+        }
+
+        let titles: Vec<_> = list_existing_benchmarks(&dir.path())
+            .unwrap()
+            .iter()
+            .map(|id| id.as_title().to_owned())
+            .collect();
+        assert_eq!(titles, ["group/a", "group/b", "group/c"]);
+    }
+
+    #[test]
+    fn test_interrupted_save_keeps_previous_file() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("estimates.json");
+        save(&vec![1, 2, 3], &path).unwrap();
+
+        // Simulate the process dying partway through writing the new contents.
+        let result = write_atomically(&path, |f| {
+            f.write_all(b"[4, 5")?;
+            Err(io::Error::new(io::ErrorKind::Interrupted, "killed"))
+        });
+        assert!(result.is_err());
+
+        let saved: Vec<i32> = load(&path).unwrap();
+        assert_eq!(saved, vec![1, 2, 3]);
+        let files: Vec<_> = fs::read_dir(dir.path()).unwrap().collect();
+        assert_eq!(files.len(), 1, "the temporary file was not removed");
+    }
+
+    #[test]
+    fn test_retry() {
+        let access_error = || Error::AccessError {
+            path: PathBuf::from("estimates.json"),
+            inner: io::Error::new(io::ErrorKind::PermissionDenied, "locked"),
+        };
+
+        let mut calls = 0;
+        let result = retry(|| {
+            calls += 1;
+            if calls < 3 {
+                Err(access_error())
+            } else {
+                Ok(calls)
+            }
+        });
+        assert_eq!(result.unwrap(), 3);
+
+        let mut calls = 0;
+        let result: Result<()> = retry(|| {
+            calls += 1;
+            Err(access_error())
+        });
+        assert!(result.is_err());
+        assert_eq!(calls, RETRY_DELAYS.len() + 1);
+
+        // Serialization errors won't go away by themselves, so they aren't retried.
+        let mut calls = 0;
+        let result: Result<()> = retry(|| {
+            calls += 1;
+            Err(Error::SerdeError {
+                path: PathBuf::from("estimates.json"),
+                inner: serde_json::from_str::<u32>("x").unwrap_err(),
+            })
+        });
+        assert!(result.is_err());
+        assert_eq!(calls, 1);
+    }
+}
@@ -1,27 +1,51 @@
-use crate::report::{make_filename_safe, BenchmarkId, MeasurementData, Report, ReportContext};
+use crate::report::{
+    compare_parameters, compare_to_threshold, effect_size_label, join_statistics,
+    make_filename_safe, mean_regressed, regressed_statistics, BenchmarkId, ComparisonResult,
+    MeasurementData, Report, ReportContext,
+};
 use crate::stats::bivariate::regression::Slope;
+use crate::stats::univariate::Sample;
+use crate::system::SystemSamples;
 
-use crate::estimate::Estimate;
+use crate::estimate::{Estimate, Statistic};
 use crate::format;
 use crate::fs;
+use crate::kde;
 use crate::measurement::ValueFormatter;
-use crate::plot::{PlotContext, PlotData, Plotter};
-use crate::SavedSample;
+use crate::plot::{PlotContext, PlotData, Plotter, RelativeThroughput, WorkingSet};
+use crate::{PlotSet, RegressionMethod, SavedSample, SummaryOrder};
 use criterion_plot::Size;
 use serde::Serialize;
-use std::cell::RefCell;
+use std::cell::{RefCell, RefMut};
 use std::cmp::Ordering;
-use std::collections::{BTreeSet, HashMap};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::path::{Path, PathBuf};
 use tinytemplate::TinyTemplate;
 
-const THUMBNAIL_SIZE: Option<Size> = Some(Size(450, 300));
+const THUMBNAIL_SIZE: Size = Size(450, 300);
+const DEFAULT_PLOT_SIZE: Size = Size(1280, 720);
+/// The relative change of the CPU frequency during a benchmark above which it is flagged in the
+/// report.
+const FREQUENCY_CHANGE_THRESHOLD: f64 = 0.1;
+/// The number of points of the probability density listed in the data table of accessible reports.
+const DENSITY_TABLE_POINTS: usize = 50;
+
+/// Scales the thumbnail size in proportion to the configured plot size, if any.
+fn thumbnail_size(report_context: &ReportContext) -> Size {
+    match report_context.plot_size {
+        Some((width, height)) => Size(
+            THUMBNAIL_SIZE.0 * width / DEFAULT_PLOT_SIZE.0,
+            THUMBNAIL_SIZE.1 * height / DEFAULT_PLOT_SIZE.1,
+        ),
+        None => THUMBNAIL_SIZE,
+    }
+}
 
 fn debug_context<S: Serialize>(path: &Path, context: &S) {
     if crate::debug_enabled() {
         let mut context_path = PathBuf::from(path);
         context_path.set_extension("json");
-        println!("Writing report context to {:?}", context_path);
+        human_println!("Writing report context to {:?}", context_path);
         let result = fs::save(context, &context_path);
         if let Err(e) = result {
             error!("Failed to write report context debug output: {}", e);
@@ -41,15 +65,157 @@ struct Context {
     r2: ConfidenceInterval,
     mean: ConfidenceInterval,
     std_dev: ConfidenceInterval,
+    robust_dispersion: bool,
+    cv: Option<CoefficientOfVariation>,
+    regression_method: Option<String>,
     median: ConfidenceInterval,
     mad: ConfidenceInterval,
+    mode: Option<ConfidenceInterval>,
+    trimmed_mean: Option<ConfidenceInterval>,
     throughput: Option<ConfidenceInterval>,
+    working_set: Option<String>,
+    cold_start: Option<String>,
 
     additional_plots: Vec<Plot>,
+    accessible: Option<AccessiblePlots>,
+    system: Option<SystemContext>,
 
     comparison: Option<Comparison>,
 }
 
+#[derive(Serialize)]
+struct SystemContext {
+    cpu_frequency: Option<SystemValue>,
+    load_average: Option<SystemValue>,
+}
+
+#[derive(Serialize)]
+struct SystemValue {
+    start: String,
+    end: String,
+    change: String,
+    unstable: bool,
+}
+impl SystemContext {
+    fn new(samples: &SystemSamples) -> SystemContext {
+        let value = |start: Option<f64>, end: Option<f64>, format: &dyn Fn(f64) -> String| {
+            let (start, end) = (start?, end?);
+            let change = if start > 0.0 { end / start - 1.0 } else { 0.0 };
+            Some((
+                change,
+                SystemValue {
+                    start: format(start),
+                    end: format(end),
+                    change: format::change(change, true).trim().to_owned(),
+                    unstable: false,
+                },
+            ))
+        };
+        let cpu_frequency = value(
+            samples.start.cpu_frequency_mhz,
+            samples.end.cpu_frequency_mhz,
+            &|mhz| format!("{:.0} MHz", mhz),
+        );
+        let load_average = value(
+            samples.start.load_average,
+            samples.end.load_average,
+            &|load| format!("{:.2}", load),
+        );
+        SystemContext {
+            // A large change of frequency suggests that the CPU was throttled.
+            cpu_frequency: cpu_frequency.map(|(change, value)| SystemValue {
+                unstable: change.abs() > FREQUENCY_CHANGE_THRESHOLD,
+                ..value
+            }),
+            load_average: load_average.map(|(_, value)| value),
+        }
+    }
+}
+
+/// Captions and data tables describing the main plots of a benchmark report, for
+/// `Criterion::accessible_html`.
+#[derive(Serialize)]
+struct AccessiblePlots {
+    pdf_caption: String,
+    density: Vec<DensityPoint>,
+    samples_caption: String,
+    samples: Vec<SamplePoint>,
+}
+
+#[derive(Serialize)]
+struct DensityPoint {
+    time: String,
+    // Relative to the peak of the density, as the absolute values depend on the time unit.
+    density: String,
+}
+
+#[derive(Serialize)]
+struct SamplePoint {
+    iterations: String,
+    time: String,
+    time_per_iteration: String,
+}
+impl AccessiblePlots {
+    fn new(
+        id: &BenchmarkId,
+        measurements: &MeasurementData<'_>,
+        formatter: &dyn ValueFormatter,
+    ) -> AccessiblePlots {
+        let estimates = &measurements.absolute_estimates;
+        let sample_count = measurements.iter_counts().len();
+
+        let (xs, ys) = kde::sweep(&measurements.avg_times, DENSITY_TABLE_POINTS, None);
+        let peak = ys.iter().cloned().fold(0.0, f64::max);
+        let density = xs
+            .iter()
+            .zip(ys.iter())
+            .map(|(&x, &y)| DensityPoint {
+                time: formatter.format_value(x),
+                density: format!("{:.3}", if peak > 0.0 { y / peak } else { 0.0 }),
+            })
+            .collect();
+
+        let samples = measurements
+            .iter_counts()
+            .iter()
+            .zip(measurements.sample_times().iter())
+            .map(|(&iters, &time)| SamplePoint {
+                iterations: format!("{}", iters),
+                time: formatter.format_value(time),
+                time_per_iteration: formatter.format_value(time / iters),
+            })
+            .collect();
+
+        let samples_caption = match &estimates.slope {
+            Some(slope) => format!(
+                "Total time of each of the {} samples of {} against its iteration count. The \
+                fitted line has a slope of {} per iteration.",
+                sample_count,
+                id.as_title(),
+                formatter.format_value(slope.point_estimate)
+            ),
+            None => format!(
+                "Time per iteration of each of the {} samples of {}.",
+                sample_count,
+                id.as_title()
+            ),
+        };
+
+        AccessiblePlots {
+            pdf_caption: format!(
+                "Estimated probability density of the time per iteration of {}, from {} samples. \
+                The mean is {}.",
+                id.as_title(),
+                sample_count,
+                formatter.format_value(estimates.mean.point_estimate)
+            ),
+            density,
+            samples_caption,
+            samples,
+        }
+    }
+}
+
 #[derive(Serialize)]
 struct IndividualBenchmark {
     name: String,
@@ -84,6 +250,8 @@ struct SummaryContext {
 
     violin_plot: Option<String>,
     line_chart: Option<String>,
+    relative_throughput_chart: Option<String>,
+    working_set_chart: Option<String>,
 
     benchmarks: Vec<IndividualBenchmark>,
 }
@@ -95,6 +263,19 @@ struct ConfidenceInterval {
     point: String,
 }
 
+#[derive(Serialize)]
+struct CoefficientOfVariation {
+    value: String,
+    threshold: String,
+    unstable: bool,
+}
+
+#[derive(Serialize)]
+struct StatisticChange {
+    name: String,
+    change: ConfidenceInterval,
+}
+
 #[derive(Serialize)]
 struct Plot {
     name: String,
@@ -114,10 +295,13 @@ struct Comparison {
     p_value: String,
     inequality: String,
     significance_level: String,
+    effect_size: String,
+    effect_size_label: String,
     explanation: String,
 
     change: ConfidenceInterval,
     thrpt_change: Option<ConfidenceInterval>,
+    statistic_changes: Vec<StatisticChange>,
     additional_plots: Vec<Plot>,
 }
 
@@ -210,24 +394,9 @@ impl<'a> BenchmarkGroup<'a> {
             individual_links.insert((function_id, value), individual_link);
         }
 
-        fn parse_opt(os: &Option<&str>) -> Option<f64> {
-            os.and_then(|s| s.parse::<f64>().ok())
-        }
-
-        // If all of the value strings can be parsed into a number, sort/dedupe
-        // numerically. Otherwise sort lexicographically.
-        if values.iter().all(|os| parse_opt(os).is_some()) {
-            values.sort_unstable_by(|v1, v2| {
-                let num1 = parse_opt(v1);
-                let num2 = parse_opt(v2);
-
-                num1.partial_cmp(&num2).unwrap_or(Ordering::Less)
-            });
-            values.dedup_by_key(|os| parse_opt(os).unwrap());
-        } else {
-            values.sort_unstable();
-            values.dedup();
-        }
+        // Numeric values are sorted by value, before any others.
+        values.sort_unstable_by(|v1, v2| compare_parameters(*v1, *v2));
+        values.dedup();
 
         // Sort and dedupe functions by name.
         function_ids.sort_unstable();
@@ -263,17 +432,26 @@ impl<'a> BenchmarkGroup<'a> {
     }
 }
 
+#[derive(Serialize)]
+struct TagGroup<'a> {
+    name: &'a str,
+    benchmarks: Vec<ReportLink<'a>>,
+}
+
 #[derive(Serialize)]
 struct IndexContext<'a> {
     groups: Vec<BenchmarkGroup<'a>>,
+    tags: Vec<TagGroup<'a>>,
 }
 
 pub struct Html {
     templates: TinyTemplate<'static>,
-    plotter: RefCell<Box<dyn Plotter>>,
+    // Created on first use when `None`, so that the default plotting backend is only chosen, by
+    // checking whether gnuplot is installed, once plots are actually drawn.
+    plotter: RefCell<Option<Box<dyn Plotter>>>,
 }
 impl Html {
-    pub(crate) fn new(plotter: Box<dyn Plotter>) -> Html {
+    pub(crate) fn new(plotter: Option<Box<dyn Plotter>>) -> Html {
         let mut templates = TinyTemplate::new();
         templates
             .add_template("report_link", include_str!("report_link.html.tt"))
@@ -291,6 +469,12 @@ impl Html {
         let plotter = RefCell::new(plotter);
         Html { templates, plotter }
     }
+
+    fn plotter(&self) -> RefMut<'_, Box<dyn Plotter>> {
+        RefMut::map(self.plotter.borrow_mut(), |plotter| {
+            plotter.get_or_insert_with(|| crate::DEFAULT_PLOTTING_BACKEND.create_plotter())
+        })
+    }
 }
 impl Report for Html {
     fn measurement_complete(
@@ -318,33 +502,38 @@ impl Report for Html {
         };
 
         let data = measurements.data;
+        let tss = Slope::total_sum_of_squares(&data);
 
         elapsed! {
             "Generating plots",
             self.generate_plots(id, report_context, formatter, measurements)
         }
 
-        let mut additional_plots = vec![
-            Plot::new("Typical", "typical.svg"),
-            Plot::new("Mean", "mean.svg"),
-            Plot::new("Std. Dev.", "SD.svg"),
-            Plot::new("Median", "median.svg"),
-            Plot::new("MAD", "MAD.svg"),
-        ];
-        if measurements.absolute_estimates.slope.is_some() {
-            additional_plots.push(Plot::new("Slope", "slope.svg"));
+        let mut additional_plots = vec![];
+        if report_context.plots.contains(PlotSet::DISTRIBUTIONS) {
+            additional_plots.extend(vec![
+                Plot::new("Typical", "typical.svg"),
+                Plot::new("Mean", "mean.svg"),
+                Plot::new("Std. Dev.", "SD.svg"),
+                Plot::new("Median", "median.svg"),
+                Plot::new("MAD", "MAD.svg"),
+            ]);
+            if measurements.absolute_estimates.slope.is_some() {
+                additional_plots.push(Plot::new("Slope", "slope.svg"));
+            }
+        }
+        if report_context.record_sample_order {
+            additional_plots.push(Plot::new("Sample Order", "sample_order.svg"));
         }
 
-        let throughput = measurements
-            .throughput
-            .as_ref()
-            .map(|thr| ConfidenceInterval {
-                lower: formatter
-                    .format_throughput(thr, typical_estimate.confidence_interval.upper_bound),
-                upper: formatter
-                    .format_throughput(thr, typical_estimate.confidence_interval.lower_bound),
-                point: formatter.format_throughput(thr, typical_estimate.point_estimate),
-            });
+        let throughput = measurements.throughput_estimate.as_ref().map(|estimate| {
+            let [lower, point, upper] = estimate.format(formatter);
+            ConfidenceInterval {
+                lower,
+                upper,
+                point,
+            }
+        });
 
         let context = Context {
             title: id.as_title().to_owned(),
@@ -353,8 +542,8 @@ impl Report for Html {
                 typical_estimate.confidence_interval.confidence_level
             ),
 
-            thumbnail_width: THUMBNAIL_SIZE.unwrap().0,
-            thumbnail_height: THUMBNAIL_SIZE.unwrap().1,
+            thumbnail_width: thumbnail_size(report_context).0,
+            thumbnail_height: thumbnail_size(report_context).1,
 
             slope: measurements
                 .absolute_estimates
@@ -364,27 +553,62 @@ impl Report for Html {
             mean: time_interval(&measurements.absolute_estimates.mean),
             median: time_interval(&measurements.absolute_estimates.median),
             mad: time_interval(&measurements.absolute_estimates.median_abs_dev),
+            mode: measurements
+                .absolute_estimates
+                .mode
+                .as_ref()
+                .map(time_interval),
+            trimmed_mean: measurements
+                .absolute_estimates
+                .trimmed_mean
+                .as_ref()
+                .map(time_interval),
             std_dev: time_interval(&measurements.absolute_estimates.std_dev),
+            robust_dispersion: measurements.robust_dispersion,
+            cv: report_context.cv_threshold.map(|threshold| {
+                let cv = measurements.coefficient_of_variation();
+                CoefficientOfVariation {
+                    value: format::percent(cv),
+                    threshold: format::percent(threshold),
+                    unstable: cv > threshold,
+                }
+            }),
+            regression_method: match measurements.regression_method {
+                RegressionMethod::LeastSquares => None,
+                method => Some(method.to_string()),
+            },
             throughput,
+            working_set: id.working_set_bytes.map(format::bytes),
+            cold_start: measurements
+                .cold_start
+                .map(|cold_start| formatter.format_value(cold_start)),
 
             r2: ConfidenceInterval {
                 lower: format!(
                     "{:0.7}",
-                    Slope(typical_estimate.confidence_interval.lower_bound).r_squared(&data)
+                    Slope(typical_estimate.confidence_interval.lower_bound)
+                        .r_squared_with_tss(&data, tss)
                 ),
                 upper: format!(
                     "{:0.7}",
-                    Slope(typical_estimate.confidence_interval.upper_bound).r_squared(&data)
+                    Slope(typical_estimate.confidence_interval.upper_bound)
+                        .r_squared_with_tss(&data, tss)
                 ),
                 point: format!(
                     "{:0.7}",
-                    Slope(typical_estimate.point_estimate).r_squared(&data)
+                    Slope(typical_estimate.point_estimate).r_squared_with_tss(&data, tss)
                 ),
             },
 
             additional_plots,
+            system: measurements.system.as_ref().map(SystemContext::new),
+            accessible: if report_context.accessible_html {
+                Some(AccessiblePlots::new(id, measurements, formatter))
+            } else {
+                None
+            },
 
-            comparison: self.comparison(measurements),
+            comparison: self.comparison(measurements, report_context.plots),
         };
 
         let mut report_path = report_context.output_directory.clone();
@@ -406,7 +630,8 @@ impl Report for Html {
         all_ids: &[BenchmarkId],
         formatter: &dyn ValueFormatter,
     ) {
-        let all_ids = all_ids
+        let saved_ids;
+        let mut all_ids = all_ids
             .iter()
             .filter(|id| {
                 let id_dir = context.output_directory.join(id.as_directory_name());
@@ -419,6 +644,22 @@ impl Report for Html {
 
         let group_id = all_ids[0].group_id.clone();
 
+        // A filtered run may not even register some of the benchmarks of the group, but their
+        // results from earlier runs are still saved. Merge them in so that the summary stays
+        // complete. An unfiltered run registers every benchmark, and any others are stale.
+        if crate::run_is_filtered() {
+            saved_ids = saved_group_ids(&context.output_directory, all_ids[0]);
+            let missing = saved_ids
+                .iter()
+                .filter(|saved| {
+                    !all_ids
+                        .iter()
+                        .any(|id| id.as_directory_name() == saved.as_directory_name())
+                })
+                .collect::<Vec<_>>();
+            all_ids.extend(missing);
+        }
+
         let data = self.load_summary_data(&context.output_directory, &all_ids);
 
         let mut function_ids = BTreeSet::new();
@@ -432,24 +673,10 @@ impl Report for Html {
             }
         }
 
-        fn try_parse(s: &str) -> Option<f64> {
-            s.parse::<f64>().ok()
-        }
-
-        // If all of the value strings can be parsed into a number, sort/dedupe
-        // numerically. Otherwise sort lexicographically.
-        if value_strs.iter().all(|os| try_parse(*os).is_some()) {
-            value_strs.sort_unstable_by(|v1, v2| {
-                let num1 = try_parse(v1);
-                let num2 = try_parse(v2);
-
-                num1.partial_cmp(&num2).unwrap_or(Ordering::Less)
-            });
-            value_strs.dedup_by_key(|os| try_parse(os).unwrap());
-        } else {
-            value_strs.sort_unstable();
-            value_strs.dedup();
-        }
+        // Numeric values are sorted by value, before any others.
+        value_strs
+            .sort_unstable_by(|v1, v2| compare_parameters(Some(v1.as_str()), Some(v2.as_str())));
+        value_strs.dedup();
 
         for function_id in function_ids {
             let samples_with_function: Vec<_> = data
@@ -494,22 +721,10 @@ impl Report for Html {
         }
 
         let mut all_data = data.iter().by_ref().collect::<Vec<_>>();
-        // First sort the ids/data by value.
-        // If all of the value strings can be parsed into a number, sort/dedupe
-        // numerically. Otherwise sort lexicographically.
-        let all_values_numeric = all_data
-            .iter()
-            .all(|(id, _)| id.value_str.as_deref().and_then(try_parse).is_some());
-        if all_values_numeric {
-            all_data.sort_unstable_by(|(a, _), (b, _)| {
-                let num1 = a.value_str.as_deref().and_then(try_parse);
-                let num2 = b.value_str.as_deref().and_then(try_parse);
-
-                num1.partial_cmp(&num2).unwrap_or(Ordering::Less)
-            });
-        } else {
-            all_data.sort_unstable_by_key(|(id, _)| id.value_str.as_ref());
-        }
+        // First sort the ids/data by value, numerically if the value is a number.
+        all_data.sort_unstable_by(|(a, _), (b, _)| {
+            compare_parameters(a.value_str.as_deref(), b.value_str.as_deref())
+        });
         // Next, sort the ids/data by function name. This results in a sorting priority of
         // function name, then value. This one has to be a stable sort.
         all_data.sort_by_key(|(id, _)| id.function_id.as_ref());
@@ -521,7 +736,7 @@ impl Report for Html {
             formatter,
             true,
         );
-        self.plotter.borrow_mut().wait();
+        self.plotter().wait();
     }
 
     fn final_summary(&self, report_context: &ReportContext) {
@@ -531,7 +746,7 @@ impl Report for Html {
         }
 
         let mut found_ids = try_else_return!(fs::list_existing_benchmarks(&output_directory));
-        found_ids.sort_unstable_by_key(|id| id.id().to_owned());
+        found_ids.sort_unstable_by(|a, b| a.cmp_natural(b));
 
         // Group IDs by group id
         let mut id_groups: HashMap<&str, Vec<&BenchmarkId>> = HashMap::new();
@@ -548,11 +763,26 @@ impl Report for Html {
             .collect::<Vec<BenchmarkGroup<'_>>>();
         groups.sort_unstable_by_key(|g| g.group_report.name);
 
+        // Group IDs by tag
+        let mut id_tags: BTreeMap<&str, Vec<ReportLink<'_>>> = BTreeMap::new();
+        for id in found_ids.iter() {
+            for tag in id.tags.iter() {
+                id_tags
+                    .entry(tag)
+                    .or_default()
+                    .push(ReportLink::individual(output_directory, id));
+            }
+        }
+        let tags = id_tags
+            .into_iter()
+            .map(|(name, benchmarks)| TagGroup { name, benchmarks })
+            .collect::<Vec<_>>();
+
         try_else_return!(fs::mkdirp(&output_directory.join("report")));
 
         let report_path = output_directory.join("report").join("index.html");
 
-        let context = IndexContext { groups };
+        let context = IndexContext { groups, tags };
 
         debug_context(&report_path, &context);
 
@@ -564,16 +794,30 @@ impl Report for Html {
     }
 }
 impl Html {
-    fn comparison(&self, measurements: &MeasurementData<'_>) -> Option<Comparison> {
+    fn comparison(&self, measurements: &MeasurementData<'_>, plots: PlotSet) -> Option<Comparison> {
         if let Some(ref comp) = measurements.comparison {
+            let mut additional_plots = vec![];
+            let statistics = comp.relative_distributions.statistics();
+            if plots.contains(PlotSet::CHANGE_DISTRIBUTIONS) {
+                for statistic in &statistics {
+                    additional_plots.push(Plot::new(
+                        &format!("Change in {}", statistic),
+                        &format!("change/{}.svg", statistic),
+                    ));
+                }
+            }
+            if plots.contains(PlotSet::T_TEST) {
+                additional_plots.push(Plot::new("T-Test", "change/t-test.svg"));
+            }
+
             let different_mean = comp.p_value < comp.significance_threshold;
             let mean_est = &comp.relative_estimates.mean;
-            let explanation_str: String;
+            let mut explanation_str: String;
 
             if !different_mean {
                 explanation_str = "No change in performance detected.".to_owned();
             } else {
-                let comparison = compare_to_threshold(mean_est, comp.noise_threshold);
+                let comparison = compare_to_threshold(mean_est, comp);
                 match comparison {
                     ComparisonResult::Improved => {
                         explanation_str = "Performance has improved.".to_owned();
@@ -581,16 +825,47 @@ impl Html {
                     ComparisonResult::Regressed => {
                         explanation_str = "Performance has regressed.".to_owned();
                     }
+                    ComparisonResult::Uncertain => {
+                        explanation_str = format!(
+                            "Change is uncertain: the linear fit is poor (R² = {:.4}).",
+                            comp.r_squared
+                        );
+                    }
                     ComparisonResult::NonSignificant => {
                         explanation_str = "Change within noise threshold.".to_owned();
                     }
                 }
             }
+            let regressed = regressed_statistics(comp);
+            if !regressed.is_empty() && !mean_regressed(comp) {
+                explanation_str = format!(
+                    "Performance has regressed in {}.",
+                    join_statistics(&regressed)
+                );
+            }
+
+            let statistic_changes = statistics
+                .iter()
+                .filter(|&&statistic| statistic != Statistic::Mean)
+                .map(|&statistic| {
+                    let estimate = comp.relative_estimates.get(statistic);
+                    StatisticChange {
+                        name: statistic.to_string(),
+                        change: ConfidenceInterval {
+                            point: format::change(estimate.point_estimate, true),
+                            lower: format::change(estimate.confidence_interval.lower_bound, true),
+                            upper: format::change(estimate.confidence_interval.upper_bound, true),
+                        },
+                    }
+                })
+                .collect();
 
             let comp = Comparison {
                 p_value: format!("{:.2}", comp.p_value),
                 inequality: (if different_mean { "<" } else { ">" }).to_owned(),
-                significance_level: format!("{:.2}", comp.significance_threshold),
+                significance_level: format!("{}", comp.significance_threshold),
+                effect_size: format!("{:.2}", comp.effect_size),
+                effect_size_label: effect_size_label(comp.effect_size).to_owned(),
                 explanation: explanation_str,
 
                 change: ConfidenceInterval {
@@ -614,11 +889,8 @@ impl Html {
                     }
                 }),
 
-                additional_plots: vec![
-                    Plot::new("Change in mean", "change/mean.svg"),
-                    Plot::new("Change in median", "change/median.svg"),
-                    Plot::new("T-Test", "change/t-test.svg"),
-                ],
+                statistic_changes,
+                additional_plots,
             };
             Some(comp)
         } else {
@@ -636,7 +908,7 @@ impl Html {
         let plot_ctx = PlotContext {
             id,
             context,
-            size: None,
+            size: context.plot_size,
             is_thumbnail: false,
         };
 
@@ -646,27 +918,24 @@ impl Html {
             comparison: None,
         };
 
-        let plot_ctx_small = plot_ctx.thumbnail(true).size(THUMBNAIL_SIZE);
+        let plot_ctx_small = plot_ctx.thumbnail(true).size(Some(thumbnail_size(context)));
 
-        self.plotter.borrow_mut().pdf(plot_ctx, plot_data);
-        self.plotter.borrow_mut().pdf(plot_ctx_small, plot_data);
+        self.plotter().pdf(plot_ctx, plot_data);
+        self.plotter().pdf(plot_ctx_small, plot_data);
         if measurements.absolute_estimates.slope.is_some() {
-            self.plotter.borrow_mut().regression(plot_ctx, plot_data);
-            self.plotter
-                .borrow_mut()
-                .regression(plot_ctx_small, plot_data);
+            self.plotter().regression(plot_ctx, plot_data);
+            self.plotter().regression(plot_ctx_small, plot_data);
         } else {
-            self.plotter
-                .borrow_mut()
-                .iteration_times(plot_ctx, plot_data);
-            self.plotter
-                .borrow_mut()
-                .iteration_times(plot_ctx_small, plot_data);
+            self.plotter().iteration_times(plot_ctx, plot_data);
+            self.plotter().iteration_times(plot_ctx_small, plot_data);
         }
 
-        self.plotter
-            .borrow_mut()
-            .abs_distributions(plot_ctx, plot_data);
+        if context.plots.contains(PlotSet::DISTRIBUTIONS) {
+            self.plotter().abs_distributions(plot_ctx, plot_data);
+        }
+        if context.record_sample_order {
+            self.plotter().sample_order(plot_ctx, plot_data);
+        }
 
         if let Some(ref comp) = measurements.comparison {
             try_else_return!({
@@ -687,30 +956,26 @@ impl Html {
 
             let comp_data = plot_data.comparison(comp);
 
-            self.plotter.borrow_mut().pdf(plot_ctx, comp_data);
-            self.plotter.borrow_mut().pdf(plot_ctx_small, comp_data);
+            self.plotter().pdf(plot_ctx, comp_data);
+            self.plotter().pdf(plot_ctx_small, comp_data);
             if measurements.absolute_estimates.slope.is_some()
                 && comp.base_estimates.slope.is_some()
             {
-                self.plotter.borrow_mut().regression(plot_ctx, comp_data);
-                self.plotter
-                    .borrow_mut()
-                    .regression(plot_ctx_small, comp_data);
+                self.plotter().regression(plot_ctx, comp_data);
+                self.plotter().regression(plot_ctx_small, comp_data);
             } else {
-                self.plotter
-                    .borrow_mut()
-                    .iteration_times(plot_ctx, comp_data);
-                self.plotter
-                    .borrow_mut()
-                    .iteration_times(plot_ctx_small, comp_data);
+                self.plotter().iteration_times(plot_ctx, comp_data);
+                self.plotter().iteration_times(plot_ctx_small, comp_data);
+            }
+            if context.plots.contains(PlotSet::T_TEST) {
+                self.plotter().t_test(plot_ctx, comp_data);
+            }
+            if context.plots.contains(PlotSet::CHANGE_DISTRIBUTIONS) {
+                self.plotter().rel_distributions(plot_ctx, comp_data);
             }
-            self.plotter.borrow_mut().t_test(plot_ctx, comp_data);
-            self.plotter
-                .borrow_mut()
-                .rel_distributions(plot_ctx, comp_data);
         }
 
-        self.plotter.borrow_mut().wait();
+        self.plotter().wait();
     }
 
     fn load_summary_data<'a>(
@@ -724,7 +989,7 @@ impl Html {
                 let entry = output_directory.join(id.as_directory_name()).join("new");
 
                 let SavedSample { iters, times, .. } =
-                    try_else_return!(fs::load(&entry.join("sample.json")), || None);
+                    try_else_return!(fs::load(&fs::sample_file(&entry)), || None);
                 let avg_times = iters
                     .into_iter()
                     .zip(times.into_iter())
@@ -747,7 +1012,7 @@ impl Html {
         let plot_ctx = PlotContext {
             id,
             context: report_context,
-            size: None,
+            size: report_context.plot_size,
             is_thumbnail: false,
         };
 
@@ -761,23 +1026,50 @@ impl Html {
             || {}
         );
 
-        self.plotter.borrow_mut().violin(plot_ctx, formatter, data);
+        let violin_data = summary_order(data, report_context.summary_order);
+        self.plotter().violin(plot_ctx, formatter, &violin_data);
 
-        let value_types: Vec<_> = data.iter().map(|&&(id, _)| id.value_type()).collect();
+        let comparison_data = match report_context.summary_top_n {
+            Some(n) => summary_top_n(data, n, report_context.summary_order),
+            None => data.to_vec(),
+        };
+        let value_types: Vec<_> = comparison_data
+            .iter()
+            .map(|&&(id, _)| id.value_type())
+            .collect();
         let mut line_path = None;
 
         if value_types.iter().all(|x| x == &value_types[0]) {
             if let Some(value_type) = value_types[0] {
-                let values: Vec<_> = data.iter().map(|&&(id, _)| id.as_number()).collect();
+                let values: Vec<_> = comparison_data
+                    .iter()
+                    .map(|&&(id, _)| id.as_number())
+                    .collect();
                 if values.iter().any(|x| x != &values[0]) {
-                    self.plotter
-                        .borrow_mut()
-                        .line_comparison(plot_ctx, formatter, data, value_type);
+                    self.plotter().line_comparison(
+                        plot_ctx,
+                        formatter,
+                        &comparison_data,
+                        value_type,
+                    );
                     line_path = Some(plot_ctx.line_comparison_path());
                 }
             }
         }
 
+        // Bars of throughput relative to the fastest function, for comparing implementations.
+        let relative_throughput_path = RelativeThroughput::new(&comparison_data).map(|relative| {
+            self.plotter().relative_throughput(plot_ctx, &relative);
+            plot_ctx.relative_throughput_path()
+        });
+
+        // Time per element against the working set size, to show where it outgrows a cache.
+        let working_set_path = WorkingSet::new(&comparison_data).map(|working_set| {
+            self.plotter()
+                .working_set(plot_ctx, formatter, &working_set);
+            plot_ctx.working_set_path()
+        });
+
         let path_prefix = if full_summary { "../.." } else { "../../.." };
         let benchmarks = data
             .iter()
@@ -789,11 +1081,14 @@ impl Html {
         let context = SummaryContext {
             group_id: id.as_title().to_owned(),
 
-            thumbnail_width: THUMBNAIL_SIZE.unwrap().0,
-            thumbnail_height: THUMBNAIL_SIZE.unwrap().1,
+            thumbnail_width: thumbnail_size(report_context).0,
+            thumbnail_height: thumbnail_size(report_context).1,
 
             violin_plot: Some(plot_ctx.violin_path().to_string_lossy().into_owned()),
             line_chart: line_path.map(|p| p.to_string_lossy().into_owned()),
+            relative_throughput_chart: relative_throughput_path
+                .map(|p| p.to_string_lossy().into_owned()),
+            working_set_chart: working_set_path.map(|p| p.to_string_lossy().into_owned()),
 
             benchmarks,
         };
@@ -812,22 +1107,196 @@ impl Html {
     }
 }
 
-enum ComparisonResult {
-    Improved,
-    Regressed,
-    NonSignificant,
+// Loads the IDs of the benchmarks saved in the output directory which belong to the same group as
+// `id`.
+fn saved_group_ids(output_directory: &Path, id: &BenchmarkId) -> Vec<BenchmarkId> {
+    let group_dir = match Path::new(id.as_directory_name()).components().next() {
+        Some(component) => output_directory.join(component),
+        None => return vec![],
+    };
+    let saved = try_else_return!(fs::list_existing_benchmarks(&group_dir), Vec::new);
+    saved
+        .into_iter()
+        .filter(|saved| saved.group_id == id.group_id)
+        .collect()
 }
 
-fn compare_to_threshold(estimate: &Estimate, noise: f64) -> ComparisonResult {
-    let ci = &estimate.confidence_interval;
-    let lb = ci.lower_bound;
-    let ub = ci.upper_bound;
+// Sorts the benchmarks of a summary in the given order; `data` is in definition order.
+fn summary_order<'a, 'b>(
+    data: &[&'a (&'b BenchmarkId, Vec<f64>)],
+    order: SummaryOrder,
+) -> Vec<&'a (&'b BenchmarkId, Vec<f64>)> {
+    let median = |sample: &Vec<f64>| Sample::new(sample).percentiles().median();
+    let mut sorted = data.to_vec();
+    match order {
+        SummaryOrder::Definition => {}
+        SummaryOrder::MedianAscending => sorted.sort_by(|a, b| {
+            median(&a.1)
+                .partial_cmp(&median(&b.1))
+                .unwrap_or(Ordering::Equal)
+        }),
+        SummaryOrder::MedianDescending => sorted.sort_by(|a, b| {
+            median(&b.1)
+                .partial_cmp(&median(&a.1))
+                .unwrap_or(Ordering::Equal)
+        }),
+        SummaryOrder::Alphabetical => sorted.sort_by_key(|&&(id, _)| id.as_title()),
+    }
+    sorted
+}
 
-    if lb < -noise && ub < -noise {
-        ComparisonResult::Improved
-    } else if lb > noise && ub > noise {
-        ComparisonResult::Regressed
-    } else {
-        ComparisonResult::NonSignificant
+// Keeps the benchmarks of the `n` fastest functions of a summary, or the `n` slowest ones when
+// sorting by descending median, in their original order. Functions are ranked by the mean of their
+// benchmarks' median times.
+fn summary_top_n<'a, 'b>(
+    data: &[&'a (&'b BenchmarkId, Vec<f64>)],
+    n: usize,
+    order: SummaryOrder,
+) -> Vec<&'a (&'b BenchmarkId, Vec<f64>)> {
+    let mut functions: Vec<(&Option<String>, Vec<f64>)> = vec![];
+    for &&(id, ref sample) in data {
+        let median = Sample::new(sample).percentiles().median();
+        match functions
+            .iter_mut()
+            .find(|(function, _)| **function == id.function_id)
+        {
+            Some((_, medians)) => medians.push(median),
+            None => functions.push((&id.function_id, vec![median])),
+        }
+    }
+    if functions.len() <= n {
+        return data.to_vec();
+    }
+
+    let mut ranked: Vec<(&Option<String>, f64)> = functions
+        .iter()
+        .map(|(function, medians)| {
+            (
+                *function,
+                medians.iter().sum::<f64>() / medians.len() as f64,
+            )
+        })
+        .collect();
+    ranked.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(Ordering::Equal));
+    if order == SummaryOrder::MedianDescending {
+        ranked.reverse();
+    }
+    let kept: Vec<&Option<String>> = ranked
+        .iter()
+        .take(n)
+        .map(|&(function, _)| function)
+        .collect();
+    data.iter()
+        .filter(|&&&(id, _)| kept.contains(&&id.function_id))
+        .copied()
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_plotter_created_lazily() {
+        // Choosing the default plotting backend spawns gnuplot, which must only happen once plots
+        // are drawn.
+        let html = Html::new(None);
+        assert!(html.plotter.borrow().is_none());
+    }
+
+    #[test]
+    fn test_plotting_backend_kept_while_plots_disabled() {
+        let criterion = crate::Criterion::default()
+            .without_plots()
+            .plotting_backend(crate::PlottingBackend::Plotters)
+            .with_plots();
+        assert!(criterion.report.html.unwrap().plotter.borrow().is_some());
+    }
+
+    #[test]
+    fn test_summary_order() {
+        let id = |function: &str| {
+            BenchmarkId::new("group".to_owned(), Some(function.to_owned()), None, None)
+        };
+        let ids = [id("b"), id("c"), id("a")];
+        let data = [
+            (&ids[0], vec![2.0, 2.0]),
+            (&ids[1], vec![3.0, 3.0]),
+            (&ids[2], vec![1.0, 4.0]),
+        ];
+        let data: Vec<_> = data.iter().collect();
+        let order = |order| -> Vec<_> {
+            summary_order(&data, order)
+                .iter()
+                .map(|&&(id, _)| id.as_title().to_owned())
+                .collect()
+        };
+
+        assert_eq!(
+            order(SummaryOrder::Definition),
+            ["group/b", "group/c", "group/a"]
+        );
+        assert_eq!(
+            order(SummaryOrder::MedianAscending),
+            ["group/b", "group/a", "group/c"]
+        );
+        assert_eq!(
+            order(SummaryOrder::MedianDescending),
+            ["group/c", "group/a", "group/b"]
+        );
+        assert_eq!(
+            order(SummaryOrder::Alphabetical),
+            ["group/a", "group/b", "group/c"]
+        );
+    }
+
+    #[test]
+    fn test_summary_top_n() {
+        let id = |function: &str, value: &str| {
+            BenchmarkId::new(
+                "group".to_owned(),
+                Some(function.to_owned()),
+                Some(value.to_owned()),
+                None,
+            )
+        };
+        let ids = [
+            id("slow", "1"),
+            id("fast", "1"),
+            id("medium", "1"),
+            id("slow", "2"),
+            id("fast", "2"),
+            id("medium", "2"),
+        ];
+        let data = [
+            (&ids[0], vec![5.0, 5.0]),
+            (&ids[1], vec![1.0, 1.0]),
+            (&ids[2], vec![3.0, 3.0]),
+            (&ids[3], vec![6.0, 6.0]),
+            (&ids[4], vec![2.0, 2.0]),
+            (&ids[5], vec![3.0, 3.0]),
+        ];
+        let data: Vec<_> = data.iter().collect();
+        let top_n = |n, order| -> Vec<_> {
+            summary_top_n(&data, n, order)
+                .iter()
+                .map(|&&(id, _)| id.as_title().to_owned())
+                .collect()
+        };
+
+        assert_eq!(
+            top_n(2, SummaryOrder::Definition),
+            [
+                "group/fast/1",
+                "group/medium/1",
+                "group/fast/2",
+                "group/medium/2"
+            ]
+        );
+        assert_eq!(
+            top_n(1, SummaryOrder::MedianDescending),
+            ["group/slow/1", "group/slow/2"]
+        );
+        assert_eq!(top_n(3, SummaryOrder::Definition).len(), 6);
     }
 }
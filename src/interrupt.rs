@@ -0,0 +1,87 @@
+//! Handles Ctrl-C while benchmarking: the first one lets the current benchmark finish, after which
+//! the results so far are reported and the process exits. A second one exits immediately.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Once;
+
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+static INSTALL: Once = Once::new();
+
+const MESSAGE: &str =
+    "\nInterrupted; finishing the current benchmark. Press Ctrl-C again to exit immediately.\n";
+
+/// Installs the Ctrl-C handler. Does nothing after the first call.
+pub(crate) fn install() {
+    INSTALL.call_once(|| {
+        if let Err(err) = imp::install() {
+            info!("Failed to install the Ctrl-C handler: {}", err);
+        }
+    });
+}
+
+/// Returns true if Ctrl-C has been pressed.
+pub(crate) fn requested() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+}
+
+#[cfg(unix)]
+mod imp {
+    use super::{INTERRUPTED, MESSAGE};
+    use std::io;
+    use std::sync::atomic::Ordering;
+
+    extern "C" fn handle(_: libc::c_int) {
+        // Only async-signal-safe functions may be called here. Restoring the default action means
+        // that a second Ctrl-C terminates the process as usual.
+        INTERRUPTED.store(true, Ordering::SeqCst);
+        unsafe {
+            libc::signal(libc::SIGINT, libc::SIG_DFL);
+            libc::write(2, MESSAGE.as_ptr() as *const libc::c_void, MESSAGE.len());
+        }
+    }
+
+    pub fn install() -> Result<(), String> {
+        let handler = handle as extern "C" fn(libc::c_int) as libc::sighandler_t;
+        if unsafe { libc::signal(libc::SIGINT, handler) } == libc::SIG_ERR {
+            return Err(io::Error::last_os_error().to_string());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(windows)]
+mod imp {
+    use super::{INTERRUPTED, MESSAGE};
+    use std::io::{self, Write};
+    use std::sync::atomic::Ordering;
+
+    const CTRL_C_EVENT: u32 = 0;
+
+    extern "system" {
+        fn SetConsoleCtrlHandler(handler: Option<extern "system" fn(u32) -> i32>, add: i32) -> i32;
+    }
+
+    // Windows runs the handler on a new thread. Returning FALSE passes the event on to the default
+    // handler, which terminates the process.
+    extern "system" fn handle(ctrl_type: u32) -> i32 {
+        if ctrl_type != CTRL_C_EVENT || INTERRUPTED.swap(true, Ordering::SeqCst) {
+            return 0;
+        }
+        let _ = io::stderr().write_all(MESSAGE.as_bytes());
+        1
+    }
+
+    pub fn install() -> Result<(), String> {
+        if unsafe { SetConsoleCtrlHandler(Some(handle), 1) } == 0 {
+            return Err(io::Error::last_os_error().to_string());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+mod imp {
+    pub fn install() -> Result<(), String> {
+        Err("Ctrl-C handling is not supported on this platform".to_owned())
+    }
+}
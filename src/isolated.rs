@@ -0,0 +1,154 @@
+//! Measures each sample of a benchmark in a fresh process, enabled with
+//! `Criterion::isolated_samples`. The benchmark executable is re-run with its original arguments
+//! plus `--isolated-sample <title> --isolated-iters <n>`. The child runs only that benchmark, for
+//! exactly `n` iterations without warming up, prints the measured value on stdout and exits.
+//!
+//! With `Criterion::benchmark_timeout`, a child which runs for longer than the timeout is killed,
+//! which is the only way to stop a benchmark stuck in an infinite loop.
+
+use std::env;
+use std::io::Read;
+use std::path::Path;
+use std::process::{self, Child, Command, ExitStatus, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::measurement::Measurement;
+use crate::routine::{Hooks, Routine};
+use crate::{Criterion, DurationExt};
+
+// Marks the line of the child's output which carries the measurement, since the benchmark itself
+// may print to stdout as well.
+const SAMPLE_PREFIX: &str = "criterion-isolated-sample:";
+
+// How often a child with a timeout is checked for completion.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Measures each of the given iteration counts of the benchmark titled `title` in its own process,
+/// killing any process which runs for longer than `timeout`.
+pub(crate) fn bench(
+    title: &str,
+    iters: &[u64],
+    timeout: Option<Duration>,
+) -> Result<Vec<f64>, String> {
+    let exe = env::current_exe()
+        .map_err(|e| format!("Failed to find the benchmark executable: {}", e))?;
+    iters
+        .iter()
+        .map(|&n| sample(&exe, title, n, timeout))
+        .collect()
+}
+
+fn sample(exe: &Path, title: &str, iters: u64, timeout: Option<Duration>) -> Result<f64, String> {
+    let mut child = Command::new(exe)
+        .args(env::args_os().skip(1))
+        .arg("--isolated-sample")
+        .arg(title)
+        .arg("--isolated-iters")
+        .arg(iters.to_string())
+        // The child must not talk to cargo-criterion; only the parent reports the benchmark.
+        .env_remove("CARGO_CRITERION_PORT")
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .spawn()
+        .map_err(|e| format!("Failed to start the process for an isolated sample: {}", e))?;
+
+    // Read the output on another thread, so that a chatty benchmark can't fill the pipe and block
+    // while the child is being waited for.
+    let mut stdout = child.stdout.take().unwrap();
+    let reader = thread::spawn(move || {
+        let mut output = vec![];
+        let _ = stdout.read_to_end(&mut output);
+        output
+    });
+    let status = wait(&mut child, timeout);
+    let stdout = reader.join().unwrap_or_default();
+
+    let status = status?;
+    if !status.success() {
+        return Err(format!(
+            "The process measuring an isolated sample failed ({})",
+            status
+        ));
+    }
+    parse_sample(&String::from_utf8_lossy(&stdout)).ok_or_else(|| {
+        "The process measuring an isolated sample did not report it. Isolated samples require \
+        the Criterion instance to be configured from the command line, as criterion_group! does."
+            .to_owned()
+    })
+}
+
+/// Waits for the child to exit, killing it if it runs for longer than `timeout`.
+fn wait(child: &mut Child, timeout: Option<Duration>) -> Result<ExitStatus, String> {
+    let error = |e| {
+        format!(
+            "Failed to wait for the process of an isolated sample: {}",
+            e
+        )
+    };
+    let timeout = match timeout {
+        Some(timeout) => timeout,
+        None => return child.wait().map_err(error),
+    };
+
+    let start = Instant::now();
+    loop {
+        if let Some(status) = child.try_wait().map_err(error)? {
+            return Ok(status);
+        }
+        if start.elapsed() > timeout {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(format!(
+                "The process measuring an isolated sample was killed after exceeding the \
+                benchmark timeout of {}",
+                crate::format::time(timeout.to_nanos() as f64, None)
+            ));
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+fn parse_sample(stdout: &str) -> Option<f64> {
+    stdout
+        .lines()
+        .find_map(|line| line.strip_prefix(SAMPLE_PREFIX))
+        .and_then(|value| value.trim().parse().ok())
+}
+
+/// Runs in the child process: measures `iters` iterations of the benchmark, prints the value for
+/// the parent and exits.
+pub(crate) fn measure_and_exit<M: Measurement, T: ?Sized>(
+    routine: &mut dyn Routine<M, T>,
+    criterion: &Criterion<M>,
+    parameter: &T,
+    hooks: &mut Hooks<'_>,
+    iters: u64,
+) -> ! {
+    let measurement = &criterion.measurement;
+    match hooks.around(routine, |routine| {
+        routine.bench(measurement, &[iters], parameter)
+    }) {
+        Ok(values) => {
+            println!("{} {}", SAMPLE_PREFIX, values[0]);
+            process::exit(0)
+        }
+        Err(err) => {
+            eprintln!("{}", err);
+            process::exit(1)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_sample() {
+        let stdout = format!("output of the benchmark\n{} {}\n", SAMPLE_PREFIX, 1234.5);
+        assert_eq!(parse_sample(&stdout), Some(1234.5));
+        assert_eq!(parse_sample("output of the benchmark\n"), None);
+    }
+}
@@ -11,6 +11,32 @@ pub fn sweep(
     (xs, ys)
 }
 
+/// Estimates the mode of the sample as the peak of its KDE, evaluated at `npoints` points across
+/// the range of the sample.
+///
+/// Unlike `sweep`, this evaluates the KDE serially, since it's called once per bootstrap resample.
+pub fn mode(sample: &Sample<f64>, npoints: usize) -> f64 {
+    let x_min = sample.min();
+    let x_max = sample.max();
+
+    let kde = Kde::new(sample, Gaussian, Bandwidth::Silverman);
+    // Every value in the sample is the same, so that value is the mode.
+    if kde.bandwidth() == 0. {
+        return x_min;
+    }
+
+    let step_size = (x_max - x_min) / (npoints - 1) as f64;
+    let mut peak = (x_min, kde.estimate(x_min));
+    for n in 1..npoints {
+        let x = x_min + step_size * n as f64;
+        let y = kde.estimate(x);
+        if y > peak.1 {
+            peak = (x, y);
+        }
+    }
+    peak.0
+}
+
 pub fn sweep_and_estimate(
     sample: &Sample<f64>,
     npoints: usize,
@@ -23,9 +49,19 @@ pub fn sweep_and_estimate(
     let kde = Kde::new(sample, Gaussian, Bandwidth::Silverman);
     let h = kde.bandwidth();
 
+    // If every value in the sample is the same (eg. a benchmark which never allocates, measured
+    // with an allocation counter), the bandwidth is zero and the KDE is undefined. Plot a single
+    // spike at that value instead.
+    let degenerate = h == 0.;
+    let margin = if degenerate {
+        0.1 * x_max.abs().max(1.)
+    } else {
+        3. * h
+    };
+
     let (start, end) = match range {
         Some((start, end)) => (start, end),
-        None => (x_min - 3. * h, x_max + 3. * h),
+        None => (x_min - margin, x_max + margin),
     };
 
     let mut xs: Vec<f64> = Vec::with_capacity(npoints);
@@ -34,8 +70,43 @@ pub fn sweep_and_estimate(
         xs.push(start + (step_size * n as f64));
     }
 
+    if degenerate {
+        let spike = |x: f64| {
+            if (x - x_min).abs() <= step_size / 2. {
+                1.
+            } else {
+                0.
+            }
+        };
+        let ys = xs.iter().map(|&x| spike(x)).collect::<Vec<_>>();
+        let point_estimate = spike(point_to_estimate);
+        return (xs.into_boxed_slice(), ys.into_boxed_slice(), point_estimate);
+    }
+
     let ys = kde.map(&xs);
     let point_estimate = kde.estimate(point_to_estimate);
 
     (xs.into_boxed_slice(), ys, point_estimate)
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn mode_finds_the_larger_peak() {
+        let mut values = vec![10.0; 20];
+        values.extend(vec![20.0; 60]);
+        for (i, value) in values.iter_mut().enumerate() {
+            *value += (i % 5) as f64 * 0.1;
+        }
+        let mode = mode(Sample::new(&values), 200);
+        assert!((mode - 20.2).abs() < 0.5, "mode = {}", mode);
+    }
+
+    #[test]
+    fn mode_of_constant_sample() {
+        let values = vec![3.0; 10];
+        assert_eq!(mode(Sample::new(&values), 64), 3.0);
+    }
+}
@@ -49,6 +49,7 @@ extern crate serde_derive;
 // in order to be usable there.
 #[macro_use]
 mod macros_private;
+mod affinity;
 #[macro_use]
 mod analysis;
 mod benchmark;
@@ -56,6 +57,7 @@ mod benchmark;
 mod benchmark_group;
 pub mod async_executor;
 mod bencher;
+mod config_file;
 mod connection;
 mod csv_report;
 mod error;
@@ -63,23 +65,33 @@ mod estimate;
 mod format;
 mod fs;
 mod html;
+mod interrupt;
+mod isolated;
 mod kde;
 mod macros;
 pub mod measurement;
+mod merge;
 mod plot;
 pub mod profiler;
+mod progress;
 mod report;
 mod routine;
-mod stats;
+pub mod stats;
+mod system;
+mod text_report;
+mod trace;
+mod watchdog;
 
 use std::cell::RefCell;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::default::Default;
 use std::env;
+use std::ffi::{OsStr, OsString};
 use std::fmt;
 use std::iter::IntoIterator;
 use std::marker::PhantomData;
 use std::net::TcpStream;
+use std::ops::BitOr;
 use std::path::{Path, PathBuf};
 use std::process::Command;
 use std::sync::{Mutex, MutexGuard};
@@ -87,6 +99,7 @@ use std::time::Duration;
 
 use criterion_plot::{Version, VersionError};
 
+use crate::analysis::PendingAnalysis;
 use crate::benchmark::BenchmarkConfig;
 use crate::benchmark::NamedRoutine;
 use crate::connection::Connection;
@@ -95,9 +108,12 @@ use crate::csv_report::FileCsvReport;
 use crate::html::Html;
 use crate::measurement::{Measurement, WallTime};
 use crate::plot::{Gnuplot, Plotter, PlottersBackend};
-use crate::profiler::{ExternalProfiler, Profiler};
-use crate::report::{BencherReport, CliReport, Report, ReportContext, Reports};
-use crate::routine::Function;
+use crate::profiler::{default_profiler, Profiler};
+use crate::report::{
+    BencherReport, CliReport, JsonReport, OneLineReport, Report, ReportContext, Reports,
+};
+use crate::routine::{Function, Program};
+use crate::text_report::FileTextReport;
 
 #[cfg(feature = "async")]
 pub use crate::bencher::AsyncBencher;
@@ -105,17 +121,34 @@ pub use crate::bencher::Bencher;
 #[allow(deprecated)]
 pub use crate::benchmark::{Benchmark, BenchmarkDefinition, ParameterizedBenchmark};
 pub use crate::benchmark_group::{BenchmarkGroup, BenchmarkId};
+pub use crate::estimate::Statistic;
+pub use crate::routine::SampleCollector;
 
 lazy_static! {
     static ref DEBUG_ENABLED: bool = std::env::var_os("CRITERION_DEBUG").is_some();
+    /// Whether `--message-format=json` was given. This is read from the raw arguments rather than
+    /// from `configure_from_args`, because some warnings are printed while the benchmarks are
+    /// still being configured.
+    static ref JSON_MESSAGES_ENABLED: bool = CARGO_CRITERION_CONNECTION.is_none()
+        && message_format_is_json(std::env::args_os().skip(1));
+    static ref RUN_OUTCOME: Mutex<RunOutcome> = Mutex::new(RunOutcome::default());
+    static ref EXECUTED_BENCHMARKS: Mutex<ExecutedBenchmarks> =
+        Mutex::new(ExecutedBenchmarks::default());
+    /// The overhead of starting and ending each type of measurement once, calibrated before the
+    /// first benchmark of the run which uses it.
+    static ref TIMER_OVERHEADS: Mutex<HashMap<&'static str, f64>> = Mutex::new(HashMap::new());
     static ref GNUPLOT_VERSION: Result<Version, VersionError> = criterion_plot::version();
+    /// The default label of the plots, looked up the first time a plot is drawn.
+    static ref GIT_ANNOTATION: Option<String> = git_annotation();
     static ref DEFAULT_PLOTTING_BACKEND: PlottingBackend = {
         match &*GNUPLOT_VERSION {
             Ok(_) => PlottingBackend::Gnuplot,
             Err(e) => {
                 match e {
-                    VersionError::Exec(_) => println!("Gnuplot not found, using plotters backend"),
-                    e => println!(
+                    VersionError::Exec(_) => {
+                        human_println!("Gnuplot not found, using plotters backend")
+                    }
+                    e => human_println!(
                         "Gnuplot not found or not usable, using plotters backend\n{}",
                         e
                     ),
@@ -154,6 +187,27 @@ fn debug_enabled() -> bool {
     *DEBUG_ENABLED
 }
 
+/// Whether stdout is reserved for the JSON messages, so that anything meant for people has to go
+/// to stderr instead.
+fn json_messages_enabled() -> bool {
+    *JSON_MESSAGES_ENABLED
+}
+
+fn message_format_is_json<I: IntoIterator<Item = OsString>>(args: I) -> bool {
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == "--" {
+            break;
+        }
+        if arg == "--message-format=json"
+            || (arg == "--message-format" && args.next().as_deref() == Some(OsStr::new("json")))
+        {
+            return true;
+        }
+    }
+    false
+}
+
 /// A function that is opaque to the optimizer, used to prevent the compiler from
 /// optimizing away computations in a benchmark.
 ///
@@ -163,12 +217,40 @@ pub fn black_box<T>(dummy: T) -> T {
     test::black_box(dummy)
 }
 
+/// A function that is opaque to the optimizer, used to prevent the compiler from
+/// optimizing away computations in a benchmark.
+///
+/// This variant is backed by `std::hint::black_box`, which is used automatically when
+/// the crate is built with Rust 1.66 or newer.
+///
+/// Benchmark authors should wrap constant inputs in `black_box` so that the compiler
+/// cannot constant-fold the arguments into the benchmarked function:
+///
+/// ```rust
+/// use criterion::black_box;
+///
+/// fn fibonacci(n: u64) -> u64 {
+///     (1..=n).fold((0, 1), |(a, b), _| (b, a + b)).0
+/// }
+///
+/// let result = fibonacci(black_box(20));
+/// # assert_eq!(result, 6765);
+/// ```
+///
+/// Values returned from the routine passed to `Bencher::iter` and similar timing loops
+/// are passed through `black_box` automatically.
+#[cfg(all(not(feature = "real_blackbox"), criterion_hint_black_box))]
+pub fn black_box<T>(dummy: T) -> T {
+    std::hint::black_box(dummy)
+}
+
 /// A function that is opaque to the optimizer, used to prevent the compiler from
 /// optimizing away computations in a benchmark.
 ///
 /// This variant is stable-compatible, but it may cause some performance overhead
-/// or fail to prevent code from being eliminated.
-#[cfg(not(feature = "real_blackbox"))]
+/// or fail to prevent code from being eliminated. It is only used on compilers older
+/// than Rust 1.66, which do not provide `std::hint::black_box`.
+#[cfg(all(not(feature = "real_blackbox"), not(criterion_hint_black_box)))]
 pub fn black_box<T>(dummy: T) -> T {
     unsafe {
         let ret = std::ptr::read_volatile(&dummy);
@@ -341,6 +423,14 @@ impl Mode {
     }
 }
 
+// The coefficient of variation above which a benchmark is flagged as unstable.
+const DEFAULT_CV_THRESHOLD: f64 = 0.05;
+/// The directory holding the best results of each benchmark, with `Criterion::track_best`.
+const BEST_DIRECTORY: &str = "best";
+
+// A test returning the p-value of a change, set with `Criterion::comparison_test`.
+type ComparisonTest = Box<dyn Fn(&[f64], &[f64]) -> f64>;
+
 /// The benchmark manager
 ///
 /// `Criterion` lets you configure and execute benchmarks
@@ -362,6 +452,7 @@ pub struct Criterion<M: Measurement = WallTime> {
     output_directory: PathBuf,
     baseline_directory: String,
     baseline: Baseline,
+    save_baseline_to: Option<String>,
     load_baseline: Option<String>,
     all_directories: HashSet<String>,
     all_titles: HashSet<String>,
@@ -369,6 +460,57 @@ pub struct Criterion<M: Measurement = WallTime> {
     profiler: Box<RefCell<dyn Profiler>>,
     connection: Option<MutexGuard<'static, Connection>>,
     mode: Mode,
+    // The backend chosen with `plotting_backend`, kept while plots are disabled so that
+    // `with_plots` can use it.
+    plotting_backend: Option<PlottingBackend>,
+    emit_gnuplot_scripts: bool,
+    plot_size: Option<(usize, usize)>,
+    plot_font_size: Option<f64>,
+    plot_annotation: Option<String>,
+    plot_terminal: PlotTerminalConfig,
+    plot_palette: Palette,
+    regression_y_scale: AxisScale,
+    summary_order: SummaryOrder,
+    summary_top_n: Option<usize>,
+    plots: PlotSet,
+    record_sample_order: bool,
+    accessible_html: bool,
+    cv_threshold: Option<f64>,
+    time_unit: Option<TimeUnit>,
+    time_precision: Option<usize>,
+    max_times: Vec<(Regex, Duration)>,
+    max_relative_standard_error: Option<f64>,
+    min_throughputs: Vec<(Regex, Throughput)>,
+    save_distributions: bool,
+    track_best: bool,
+    report_cold_start: bool,
+    record_per_iteration: bool,
+    measurement_conversion: Option<f64>,
+    winsorize: Option<(f64, f64)>,
+    min_r2_for_regression: Option<f64>,
+    prune_stale: bool,
+    dry_run_prune: bool,
+    compress_samples: bool,
+    sample_format: SampleFormat,
+    trace_path: Option<PathBuf>,
+    export_csv_path: Option<PathBuf>,
+    sample_weights: Option<Box<dyn Fn(usize) -> f64>>,
+    comparison_test: Option<ComparisonTest>,
+    progress_bar: bool,
+    subtract_timer_overhead: bool,
+    background_analysis: bool,
+    single_threaded: bool,
+    bootstrap_seed: Option<u64>,
+    pin_to_core: Option<usize>,
+    monitor_system: bool,
+    isolated_samples: bool,
+    isolated_sample: Option<(String, u64)>,
+    pending_analysis: RefCell<Option<PendingAnalysis>>,
+    baseline_path: Option<PathBuf>,
+    tag_filter: Option<String>,
+    working_set_bytes: Option<u64>,
+    benchmark_timeout: Option<Duration>,
+    clear_pattern: Option<Regex>,
 }
 
 /// Returns the Cargo target directory, possibly calling `cargo metadata` to
@@ -391,6 +533,62 @@ fn cargo_target_directory() -> Option<PathBuf> {
         })
 }
 
+/// Resolves the git revision `rev` to the full hash of its commit, by running `git rev-parse` in the
+/// current directory.
+fn git_commit_hash(rev: &str) -> Result<String, String> {
+    let output = Command::new("git")
+        .args(["rev-parse", "--verify", "--quiet"])
+        .arg(format!("{}^{{commit}}", rev))
+        .output()
+        .map_err(|err| format!("failed to run git: {}", err))?;
+    if !output.status.success() {
+        return Err(format!("'{}' is not a commit", rev));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_owned())
+}
+
+/// Returns the short hash and date of the commit checked out in the current directory, like
+/// `abc1234 2026-10-17`, or `None` if it isn't in a git repository.
+fn git_annotation() -> Option<String> {
+    let output = Command::new("git")
+        .args(["log", "-1", "--format=%h %cd", "--date=short"])
+        .output()
+        .ok()?;
+    let annotation = String::from_utf8_lossy(&output.stdout).trim().to_owned();
+    if output.status.success() && !annotation.is_empty() {
+        Some(annotation)
+    } else {
+        None
+    }
+}
+
+/// The label of the plots unless another is set with `Criterion::annotate`.
+pub(crate) fn default_annotation() -> Option<&'static str> {
+    GIT_ANNOTATION.as_deref()
+}
+
+/// The benchmarks run by this process. `criterion_main!` calls `final_summary` on a separate
+/// `Criterion` from the ones that ran the benchmarks, so this is needed to prune stale benchmark
+/// directories.
+#[derive(Default)]
+struct ExecutedBenchmarks {
+    group_ids: HashSet<String>,
+    directory_names: HashSet<String>,
+    // Set if any benchmark was skipped by a filter, in which case `directory_names` is incomplete.
+    filtered: bool,
+    prune_stale: bool,
+}
+
+/// Compiles a benchmark ID pattern given to one of the performance assertions.
+fn parse_id_pattern(pattern: String) -> Regex {
+    Regex::new(&pattern).unwrap_or_else(|err| {
+        panic!(
+            "Unable to parse '{}' as a regular expression: {}",
+            pattern, err
+        )
+    })
+}
+
 impl Default for Criterion {
     /// Creates a benchmark manager with the following default settings:
     ///
@@ -403,16 +601,87 @@ impl Default for Criterion {
     /// - Significance level: 0.05
     /// - Plotting: enabled, using gnuplot if available or plotters if gnuplot is not available
     /// - No filter
+    ///
+    /// Settings given in a `criterion.toml` file in the workspace root are used instead of these
+    /// defaults; see `Criterion::from_config_file`. The workspace root is read from the
+    /// `CARGO_WORKSPACE_DIR` environment variable, and defaults to the package directory
+    /// (`CARGO_MANIFEST_DIR`). If the file is invalid, a warning is printed and it is ignored.
     fn default() -> Criterion {
+        match config_file::find() {
+            Some(path) => Criterion::with_default_config_file(&path),
+            None => Criterion::builtin_defaults(),
+        }
+    }
+}
+
+impl Criterion {
+    /// Creates a benchmark manager with the settings in the configuration file at `path`, and the
+    /// builtin defaults listed for `Criterion::default` for any settings not in the file.
+    /// `Criterion::default` already reads `criterion.toml` from the workspace root, so this is only
+    /// needed for files stored elsewhere.
+    ///
+    /// The file uses [TOML](https://toml.io) syntax, with one `key = value` line per setting:
+    ///
+    /// ```toml
+    /// sample_size = 50
+    /// warm_up_time = 1      # seconds
+    /// measurement_time = 2.5
+    /// nresamples = 10_000
+    /// nresamples_compare = 5_000
+    /// noise_threshold = 0.02
+    /// confidence_level = 0.95
+    /// significance_level = 0.05
+    /// sampling_mode = "flat" # or "auto", "linear"
+    /// plots = false
+    /// progress_bar = true
+    /// single_threaded = false
+    /// subtract_timer_overhead = false
+    /// bootstrap_seed = 42
+    /// ```
+    ///
+    /// The settings can also be placed in a `[criterion]` table, in which case the rest of the
+    /// file is ignored so that it can be shared with other tools.
+    ///
+    /// Settings changed in code with the builder methods take precedence over the file, and
+    /// command-line options take precedence over both.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the file can't be read, isn't valid, or contains an unknown setting or a value
+    /// outside the range accepted by the corresponding builder method.
+    pub fn from_config_file<P: AsRef<Path>>(path: P) -> Criterion {
+        Criterion::builtin_defaults()
+            .apply_config_file(path.as_ref())
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    // The settings of `Criterion::default` with the configuration file found in the workspace
+    // root. Unlike `from_config_file`, an invalid file only prints a warning.
+    fn with_default_config_file(path: &Path) -> Criterion {
+        Criterion::builtin_defaults()
+            .apply_config_file(path)
+            .unwrap_or_else(|err| {
+                warn!("{}; using the default settings", err);
+                Criterion::builtin_defaults()
+            })
+    }
+
+    // The settings of `Criterion::default`, before any configuration file is applied.
+    fn builtin_defaults() -> Criterion {
         let reports = Reports {
             cli_enabled: true,
             cli: CliReport::new(false, false, false),
             bencher_enabled: false,
             bencher: BencherReport,
-            html_enabled: true,
-            html: Html::new(DEFAULT_PLOTTING_BACKEND.create_plotter()),
+            oneline_enabled: false,
+            oneline: OneLineReport,
+            json_enabled: false,
+            json: JsonReport,
+            html: Some(Html::new(None)),
             csv_enabled: true,
             csv: FileCsvReport,
+            text_enabled: true,
+            text: FileTextReport,
         };
 
         let mut criterion = Criterion {
@@ -421,25 +690,84 @@ impl Default for Criterion {
                 measurement_time: Duration::new(5, 0),
                 noise_threshold: 0.01,
                 nresamples: 100_000,
+                nresamples_compare: None,
                 sample_size: 100,
                 significance_level: 0.05,
                 warm_up_time: Duration::new(3, 0),
+                warm_up_iterations: None,
                 sampling_mode: SamplingMode::Auto,
+                trimmed_mean_fraction: 0.1,
+                estimate_trimmed_mean: false,
+                estimate_mode: false,
+                robust_dispersion: false,
+                regression_method: RegressionMethod::LeastSquares,
+                comparison_statistics: vec![],
+                value_transform: None,
             },
             filter: None,
             report: reports,
             baseline_directory: "base".to_owned(),
             baseline: Baseline::Save,
+            save_baseline_to: None,
             load_baseline: None,
             output_directory: DEFAULT_OUTPUT_DIRECTORY.clone(),
             all_directories: HashSet::new(),
             all_titles: HashSet::new(),
             measurement: WallTime,
-            profiler: Box::new(RefCell::new(ExternalProfiler)),
+            profiler: default_profiler(),
             connection: CARGO_CRITERION_CONNECTION
                 .as_ref()
                 .map(|mtx| mtx.lock().unwrap()),
             mode: Mode::Benchmark,
+            plotting_backend: None,
+            emit_gnuplot_scripts: false,
+            plot_size: None,
+            plot_font_size: None,
+            plot_annotation: None,
+            plot_terminal: PlotTerminalConfig::default(),
+            plot_palette: Palette::Default,
+            regression_y_scale: AxisScale::Linear,
+            summary_order: SummaryOrder::Definition,
+            summary_top_n: None,
+            plots: PlotSet::ALL,
+            record_sample_order: false,
+            accessible_html: false,
+            cv_threshold: None,
+            time_unit: None,
+            time_precision: None,
+            max_times: Vec::new(),
+            max_relative_standard_error: None,
+            min_throughputs: Vec::new(),
+            save_distributions: false,
+            track_best: false,
+            report_cold_start: false,
+            record_per_iteration: false,
+            measurement_conversion: None,
+            winsorize: None,
+            min_r2_for_regression: None,
+            prune_stale: false,
+            dry_run_prune: false,
+            compress_samples: false,
+            sample_format: SampleFormat::Json,
+            trace_path: None,
+            export_csv_path: None,
+            sample_weights: None,
+            comparison_test: None,
+            progress_bar: false,
+            subtract_timer_overhead: false,
+            background_analysis: false,
+            single_threaded: false,
+            bootstrap_seed: None,
+            pin_to_core: None,
+            monitor_system: false,
+            isolated_samples: false,
+            isolated_sample: None,
+            pending_analysis: RefCell::new(None),
+            baseline_path: None,
+            tag_filter: None,
+            working_set_bytes: None,
+            benchmark_timeout: None,
+            clear_pattern: None,
         };
 
         if criterion.connection.is_some() {
@@ -447,13 +775,100 @@ impl Default for Criterion {
             criterion.report.cli_enabled = false;
             criterion.report.bencher_enabled = false;
             criterion.report.csv_enabled = false;
-            criterion.report.html_enabled = false;
+            criterion.report.text_enabled = false;
+            criterion.report.html = None;
         }
         criterion
     }
 }
 
 impl<M: Measurement> Criterion<M> {
+    /// Applies the settings in the configuration file at `path`; see `Criterion::from_config_file`.
+    fn apply_config_file(self, path: &Path) -> Result<Criterion<M>, String> {
+        let settings = std::fs::read_to_string(path)
+            .map_err(|err| err.to_string())
+            .and_then(|text| config_file::parse(&text))
+            .map_err(|err| format!("Unable to read {}: {}", path.display(), err))?;
+
+        let mut criterion = self;
+        for (key, value) in &settings {
+            criterion = criterion
+                .apply_setting(key, value)
+                .map_err(|err| format!("Invalid configuration in {}: {}", path.display(), err))?;
+        }
+        Ok(criterion)
+    }
+
+    fn apply_setting(mut self, key: &str, value: &toml::Value) -> Result<Criterion<M>, String> {
+        let invalid = || format!("invalid value {} for `{}`", value, key);
+        let seconds = || {
+            config_file::as_f64(value)
+                .filter(|&secs| secs > 0.0 && secs.is_finite())
+                .map(Duration::from_secs_f64)
+                .ok_or_else(invalid)
+        };
+        let fraction = || {
+            config_file::as_f64(value)
+                .filter(|&x| x > 0.0 && x < 1.0)
+                .ok_or_else(invalid)
+        };
+        let flag = || value.as_bool().ok_or_else(invalid);
+
+        Ok(match key {
+            "sample_size" => {
+                let n = config_file::as_u64(value)
+                    .filter(|&n| n >= 10)
+                    .ok_or_else(invalid)?;
+                self.sample_size(n as usize)
+            }
+            "warm_up_time" => self.warm_up_time(seconds()?),
+            "measurement_time" => self.measurement_time(seconds()?),
+            "nresamples" => {
+                let n = config_file::as_u64(value)
+                    .filter(|&n| n > 0)
+                    .ok_or_else(invalid)?;
+                self.nresamples(n as usize)
+            }
+            "nresamples_compare" => {
+                let n = config_file::as_u64(value)
+                    .filter(|&n| n > 0)
+                    .ok_or_else(invalid)?;
+                self.nresamples_compare(n as usize)
+            }
+            "noise_threshold" => {
+                let threshold = config_file::as_f64(value)
+                    .filter(|&t| t >= 0.0)
+                    .ok_or_else(invalid)?;
+                self.noise_threshold(threshold)
+            }
+            "confidence_level" => self.confidence_level(fraction()?),
+            "significance_level" => self.significance_level(fraction()?),
+            "sampling_mode" => {
+                self.config.sampling_mode = match value.as_str() {
+                    Some("auto") => SamplingMode::Auto,
+                    Some("linear") => SamplingMode::Linear,
+                    Some("flat") => SamplingMode::Flat,
+                    _ => return Err(invalid()),
+                };
+                self
+            }
+            "plots" => {
+                if flag()? {
+                    self.with_plots()
+                } else {
+                    self.without_plots()
+                }
+            }
+            "progress_bar" => self.progress_bar(flag()?),
+            "single_threaded" => self.single_threaded(flag()?),
+            "subtract_timer_overhead" => self.subtract_timer_overhead(flag()?),
+            "bootstrap_seed" => {
+                self.bootstrap_seed(config_file::as_u64(value).ok_or_else(invalid)?)
+            }
+            _ => return Err(format!("unknown setting `{}`", key)),
+        })
+    }
+
     /// Changes the measurement for the benchmarks run with this runner. See the
     /// Measurement trait for more details
     pub fn with_measurement<M2: Measurement>(self, m: M2) -> Criterion<M2> {
@@ -464,6 +879,7 @@ impl<M: Measurement> Criterion<M> {
             report: self.report,
             baseline_directory: self.baseline_directory,
             baseline: self.baseline,
+            save_baseline_to: self.save_baseline_to,
             load_baseline: self.load_baseline,
             output_directory: self.output_directory,
             all_directories: self.all_directories,
@@ -472,6 +888,55 @@ impl<M: Measurement> Criterion<M> {
             profiler: self.profiler,
             connection: self.connection,
             mode: self.mode,
+            plotting_backend: self.plotting_backend,
+            emit_gnuplot_scripts: self.emit_gnuplot_scripts,
+            plot_size: self.plot_size,
+            plot_font_size: self.plot_font_size,
+            plot_annotation: self.plot_annotation,
+            plot_terminal: self.plot_terminal,
+            plot_palette: self.plot_palette,
+            regression_y_scale: self.regression_y_scale,
+            summary_order: self.summary_order,
+            summary_top_n: self.summary_top_n,
+            plots: self.plots,
+            record_sample_order: self.record_sample_order,
+            accessible_html: self.accessible_html,
+            cv_threshold: self.cv_threshold,
+            time_unit: self.time_unit,
+            time_precision: self.time_precision,
+            max_times: self.max_times,
+            max_relative_standard_error: self.max_relative_standard_error,
+            min_throughputs: self.min_throughputs,
+            save_distributions: self.save_distributions,
+            track_best: self.track_best,
+            report_cold_start: self.report_cold_start,
+            record_per_iteration: self.record_per_iteration,
+            measurement_conversion: self.measurement_conversion,
+            winsorize: self.winsorize,
+            min_r2_for_regression: self.min_r2_for_regression,
+            prune_stale: self.prune_stale,
+            dry_run_prune: self.dry_run_prune,
+            compress_samples: self.compress_samples,
+            sample_format: self.sample_format,
+            trace_path: self.trace_path,
+            export_csv_path: self.export_csv_path,
+            sample_weights: self.sample_weights,
+            comparison_test: self.comparison_test,
+            progress_bar: self.progress_bar,
+            subtract_timer_overhead: self.subtract_timer_overhead,
+            background_analysis: self.background_analysis,
+            single_threaded: self.single_threaded,
+            bootstrap_seed: self.bootstrap_seed,
+            pin_to_core: self.pin_to_core,
+            monitor_system: self.monitor_system,
+            isolated_samples: self.isolated_samples,
+            isolated_sample: self.isolated_sample,
+            pending_analysis: self.pending_analysis,
+            baseline_path: self.baseline_path,
+            tag_filter: self.tag_filter,
+            working_set_bytes: self.working_set_bytes,
+            benchmark_timeout: self.benchmark_timeout,
+            clear_pattern: self.clear_pattern,
         }
     }
 
@@ -485,7 +950,8 @@ impl<M: Measurement> Criterion<M> {
     }
 
     /// Set the plotting backend. By default, Criterion will use gnuplot if available, or plotters
-    /// if not.
+    /// if not. If plots are disabled, the backend is used once they are enabled again with
+    /// `with_plots`.
     ///
     /// Panics if `backend` is `PlottingBackend::Gnuplot` and gnuplot is not available.
     pub fn plotting_backend(mut self, backend: PlottingBackend) -> Criterion<M> {
@@ -498,7 +964,10 @@ impl<M: Measurement> Criterion<M> {
             );
         }
 
-        self.report.html = Html::new(backend.create_plotter());
+        self.plotting_backend = Some(backend);
+        if self.report.html.is_some() {
+            self.report.html = Some(Html::new(Some(backend.create_plotter())));
+        }
         self
     }
 
@@ -528,9 +997,34 @@ impl<M: Measurement> Criterion<M> {
         assert!(dur.to_nanos() > 0);
 
         self.config.warm_up_time = dur;
+        self.config.warm_up_iterations = None;
         self
     }
 
+    /// Changes how benchmarks run with this runner are warmed up: either for a length of time,
+    /// like `warm_up_time`, or for a fixed number of iterations. Warming up by iterations is
+    /// useful when the state being warmed depends on how often the code has run rather than for
+    /// how long, such as JIT compilation thresholds or branch predictors.
+    ///
+    /// The mean time per iteration during the warm-up is still used to choose the iteration
+    /// counts of the samples, so very few warm-up iterations make that estimate less reliable.
+    /// A `warm_up_time` set on a benchmark group takes precedence over this setting.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the duration or the number of iterations is zero.
+    pub fn warm_up(mut self, warm_up: WarmUp) -> Criterion<M> {
+        match warm_up {
+            WarmUp::Time(dur) => self.warm_up_time(dur),
+            WarmUp::Iterations(iters) => {
+                assert!(iters > 0);
+
+                self.config.warm_up_iterations = Some(iters);
+                self
+            }
+        }
+    }
+
     /// Changes the default measurement time for benchmarks run with this runner.
     ///
     /// With a longer time, the measurement will become more resilient to transitory peak loads
@@ -554,7 +1048,8 @@ impl<M: Measurement> Criterion<M> {
     /// [bootstrap](http://en.wikipedia.org/wiki/Bootstrapping_(statistics)#Case_resampling)
     ///
     /// A larger number of resamples reduces the random sampling errors, which are inherent to the
-    /// bootstrap method, but also increases the analysis time
+    /// bootstrap method, but also increases the analysis time. The default is 100,000. It can also
+    /// be changed for a single run with the `--nresamples` command-line option.
     ///
     /// # Panics
     ///
@@ -562,13 +1057,35 @@ impl<M: Measurement> Criterion<M> {
     pub fn nresamples(mut self, n: usize) -> Criterion<M> {
         assert!(n > 0);
         if n <= 1000 {
-            println!("\nWarning: It is not recommended to reduce nresamples below 1000.");
+            human_println!("\nWarning: It is not recommended to reduce nresamples below 1000.");
         }
 
         self.config.nresamples = n;
         self
     }
 
+    /// Changes the default number of resamples used to compare benchmarks with their baseline.
+    ///
+    /// The bootstraps of the t-test and of the relative change are often precise enough with fewer
+    /// resamples than the estimates of the benchmark itself, so reducing this speeds up the
+    /// comparisons of large benchmark suites. By default, the number of resamples set with
+    /// `nresamples` is used.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of resamples is set to zero
+    pub fn nresamples_compare(mut self, n: usize) -> Criterion<M> {
+        assert!(n > 0);
+        if n <= 1000 {
+            human_println!(
+                "\nWarning: It is not recommended to reduce nresamples_compare below 1000."
+            );
+        }
+
+        self.config.nresamples_compare = Some(n);
+        self
+    }
+
     /// Changes the default noise threshold for benchmarks run with this runner. The noise threshold
     /// is used to filter out small changes in performance, even if they are statistically
     /// significant. Sometimes benchmarking the same code twice will result in small but
@@ -593,13 +1110,19 @@ impl<M: Measurement> Criterion<M> {
     /// [confidence interval](https://en.wikipedia.org/wiki/Confidence_interval). The default is
     /// 0.95, meaning that the confidence interval should capture the true value 95% of the time.
     ///
+    /// The confidence level only affects the width of the reported confidence intervals. It does
+    /// not affect whether a change is considered statistically significant; that is controlled by
+    /// the significance level.
+    ///
     /// # Panics
     ///
     /// Panics if the confidence level is set to a value outside the `(0, 1)` range
     pub fn confidence_level(mut self, cl: f64) -> Criterion<M> {
         assert!(cl > 0.0 && cl < 1.0);
         if cl < 0.5 {
-            println!("\nWarning: It is not recommended to reduce confidence level below 0.5.");
+            human_println!(
+                "\nWarning: It is not recommended to reduce confidence level below 0.5."
+            );
         }
 
         self.config.confidence_level = cl;
@@ -633,18 +1156,780 @@ impl<M: Measurement> Criterion<M> {
         self
     }
 
-    /// Enables plotting
-    pub fn with_plots(mut self) -> Criterion<M> {
-        // If running under cargo-criterion then don't re-enable the reports; let it do the reporting.
-        if self.connection.is_none() {
-            self.report.html_enabled = true;
-        }
+    /// If enabled, a trimmed mean is estimated and reported along with the other statistics: the
+    /// mean of the samples after discarding a fraction of them from each end, set with
+    /// `trimmed_mean_fraction`. The trimmed mean is less sensitive to outliers than the mean,
+    /// while using more of the data than the median. It requires its own bootstrap, so this is
+    /// disabled by default.
+    pub fn estimate_trimmed_mean(mut self, enabled: bool) -> Criterion<M> {
+        self.config.estimate_trimmed_mean = enabled;
+        self
+    }
+
+    /// If enabled, the mode is estimated and reported along with the other statistics: the most
+    /// common time per iteration, taken as the peak of the kernel density estimate of the sample.
+    /// Unlike the mean and median, it doesn't fall between the clusters of a multimodal
+    /// distribution. Bootstrapping it is far more expensive than the other statistics, so this is
+    /// disabled by default.
+    pub fn estimate_mode(mut self, enabled: bool) -> Criterion<M> {
+        self.config.estimate_mode = enabled;
+        self
+    }
+
+    /// Changes the fraction of the samples discarded from each end before computing the trimmed
+    /// mean, see `estimate_trimmed_mean`, and the trimmed mean compared with
+    /// `comparison_statistics`. The default is 0.1, meaning that the fastest 10% and slowest 10%
+    /// of the samples are ignored.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the fraction is set to a value outside the `[0, 0.5)` range
+    pub fn trimmed_mean_fraction(mut self, fraction: f64) -> Criterion<M> {
+        assert!((0.0..0.5).contains(&fraction));
+
+        self.config.trimmed_mean_fraction = fraction;
+        self
+    }
+
+    /// If enabled, the reported standard deviation is winsorized: samples below the 5th
+    /// percentile or above the 95th percentile are clamped to those percentiles before the
+    /// variance is computed. A single extreme outlier can inflate the classic standard deviation
+    /// considerably, while it has little effect on the winsorized one. The output labels the
+    /// standard deviation as winsorized when this is enabled.
+    ///
+    /// This is disabled by default.
+    pub fn robust_dispersion(mut self, enabled: bool) -> Criterion<M> {
+        self.config.robust_dispersion = enabled;
+        self
+    }
+
+    /// Winsorizes the sample of each benchmark before it is analyzed: the times per iteration
+    /// below the `lower`th percentile or above the `upper`th percentile are clamped to those
+    /// percentiles. Unlike excluding outliers, this keeps the sample size, while bounding the
+    /// influence of extreme samples on every statistic. The winsorized sample is the one which is
+    /// saved, plotted and compared to the baseline.
+    ///
+    /// This is disabled by default. See also `robust_dispersion`, which only winsorizes the
+    /// sample to compute the standard deviation.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the percentiles are outside the `[0, 100]` range, or if `lower` isn't less than
+    /// `upper`
+    pub fn winsorize(mut self, lower: f64, upper: f64) -> Criterion<M> {
+        assert!(0.0 <= lower && lower < upper && upper <= 100.0);
+
+        self.winsorize = Some((lower, upper));
+        self
+    }
+
+    /// Sets the R² below which a change is reported as uncertain rather than as an improvement or
+    /// a regression. The R² measures how well the linear regression of the sample times on the
+    /// iteration counts fits the sample; a poor fit means the typical time per iteration is
+    /// unreliable, and so is any change detected from it. The lower of the R² of the new sample
+    /// and of the baseline is compared with the threshold.
+    ///
+    /// Uncertain changes are counted separately by `Criterion::run`, and don't make it fail.
+    /// This is disabled by default.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the threshold isn't in the `[0, 1]` range
+    pub fn min_r2_for_regression(mut self, threshold: f64) -> Criterion<M> {
+        assert!((0.0..=1.0).contains(&threshold));
+
+        self.min_r2_for_regression = Some(threshold);
+        self
+    }
+
+    /// Selects how the slope of the linear regression of the sample times on the iteration counts
+    /// is estimated. The slope is the typical time per iteration of benchmarks which use linear
+    /// sampling. Defaults to `RegressionMethod::LeastSquares`.
+    ///
+    /// `RegressionMethod::TheilSen` is more robust to a few contaminated samples, eg. samples
+    /// interrupted by another process, but its cost grows quadratically with the sample size, so
+    /// bootstrapping it takes noticeably longer. The output notes which method produced the slope.
+    pub fn regression_method(mut self, method: RegressionMethod) -> Criterion<M> {
+        self.config.regression_method = method;
+        self
+    }
+
+    /// Sets additional statistics to compare against the baseline, such as
+    /// `Statistic::Percentile(99)` to detect regressions in the tail latency. The change in the
+    /// mean and median is always estimated; the change in each additional statistic is
+    /// bootstrapped separately, reported alongside them, and counts as a regression if its whole
+    /// confidence interval lies above the noise threshold.
+    ///
+    /// The additional statistics are printed in the order they are given, after the mean and
+    /// median. Duplicates are ignored.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any of the statistics is `Mode`, `Slope` or `Typical`, which can't be compared
+    /// from the times per iteration alone, or a percentile above 100.
+    pub fn comparison_statistics(mut self, statistics: &[Statistic]) -> Criterion<M> {
+        let mut comparison_statistics = Vec::with_capacity(statistics.len());
+        for &statistic in statistics {
+            assert!(
+                statistic.is_comparable(),
+                "{:?} can't be used as a comparison statistic",
+                statistic
+            );
+            if !comparison_statistics.contains(&statistic) {
+                comparison_statistics.push(statistic);
+            }
+        }
+
+        self.config.comparison_statistics = comparison_statistics;
+        self
+    }
+
+    /// Changes how likely each sample is to be drawn when bootstrapping the statistics of a
+    /// benchmark. `weight(i)` is the weight of the `i`-th sample, in the order the samples were
+    /// measured, and samples are drawn with a probability proportional to their weight. This is
+    /// useful when later samples are more representative, for example because the system only
+    /// reaches a steady state partway through the measurement:
+    ///
+    /// ```rust
+    /// # use criterion::Criterion;
+    /// // Draw the second half of a 100-sample benchmark three times as often as the first half.
+    /// let criterion = Criterion::default().sample_weights(|i| if i < 50 { 1.0 } else { 3.0 });
+    /// ```
+    ///
+    /// The weights affect the bootstrap distributions, and so the confidence intervals and
+    /// standard errors, of the mean, standard deviation, median, MAD, trimmed mean and mode. The
+    /// point estimates, the slope and the comparison with the previous run are unaffected. By
+    /// default, all samples have the same weight.
+    ///
+    /// # Panics
+    ///
+    /// The analysis of a benchmark panics if any weight is negative or not finite, or if all of
+    /// its weights are zero.
+    pub fn sample_weights<F>(mut self, weight: F) -> Criterion<M>
+    where
+        F: Fn(usize) -> f64 + 'static,
+    {
+        self.sample_weights = Some(Box::new(weight));
+        self
+    }
+
+    /// Replaces the test used to decide whether a benchmark changed compared to its baseline.
+    /// `test(new, base)` receives the per-iteration times of the samples of the new run and of the
+    /// baseline, and returns the p-value of the null hypothesis that both were drawn from the same
+    /// distribution. This allows plugging in a permutation test or a test specific to a domain:
+    ///
+    /// ```rust
+    /// # use criterion::Criterion;
+    /// // Never report a change, eg. while the benchmarks are being calibrated.
+    /// let criterion = Criterion::default().comparison_test(|_new, _base| 1.0);
+    /// ```
+    ///
+    /// The p-value is compared against the significance level as usual, and is shown in the
+    /// output and the reports. The bootstrapped t-distribution is still computed for the plots.
+    /// By default, the p-value is that of a two-tailed t-test against the bootstrapped
+    /// t-distribution of the difference of the means.
+    ///
+    /// # Panics
+    ///
+    /// The analysis of a benchmark panics if the test returns a value outside `[0, 1]`.
+    pub fn comparison_test<F>(mut self, test: F) -> Criterion<M>
+    where
+        F: Fn(&[f64], &[f64]) -> f64 + 'static,
+    {
+        self.comparison_test = Some(Box::new(test));
+        self
+    }
+
+    /// Enables plotting, using the backend set with `plotting_backend`, or the default plotting
+    /// backend if none was set, if plotting was disabled.
+    pub fn with_plots(mut self) -> Criterion<M> {
+        // If running under cargo-criterion then don't re-enable the reports; let it do the reporting.
+        if self.connection.is_none() && self.report.html.is_none() {
+            let plotter = self
+                .plotting_backend
+                .map(|backend| backend.create_plotter());
+            self.report.html = Some(Html::new(plotter));
+        }
+        self
+    }
+
+    /// Disables plotting. This removes the HTML report entirely, so that no plots are prepared,
+    /// no plotting processes (such as gnuplot) are spawned and no report directories are created.
+    /// The command-line output and the other reports are unaffected. This is useful in sandboxed
+    /// CI environments where spawning processes is not allowed.
+    pub fn without_plots(mut self) -> Criterion<M> {
+        self.report.html = None;
+        self
+    }
+
+    /// Enables or disables writing the gnuplot scripts used to render each plot alongside the
+    /// generated images. The scripts are saved with a `.gnuplot` extension and can be edited and
+    /// re-run with gnuplot to customize the plots beyond what Criterion.rs exposes.
+    ///
+    /// This only has an effect when using the gnuplot plotting backend.
+    pub fn emit_gnuplot_scripts(mut self, enabled: bool) -> Criterion<M> {
+        self.emit_gnuplot_scripts = enabled;
+        self
+    }
+
+    /// Changes the size, in pixels, of the plots generated for each benchmark and each benchmark
+    /// group; the violin plots of groups only take the width, as their height depends on the
+    /// number of benchmarks. The thumbnails shown on the report pages are scaled in proportion to
+    /// this size, relative to the default of 1280x720. If unset, the plotting backend's default
+    /// size is used.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the width or height is zero.
+    pub fn plot_size(mut self, width: usize, height: usize) -> Criterion<M> {
+        assert!(width > 0 && height > 0);
+        self.plot_size = Some((width, height));
+        self
+    }
+
+    /// Changes the font size used in the generated plots. If unset, the plotting backend's
+    /// default font size is used.
+    ///
+    /// This only has an effect when using the gnuplot plotting backend.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the font size is not positive.
+    pub fn plot_font_size(mut self, size: f64) -> Criterion<M> {
+        assert!(size > 0.0);
+        self.plot_font_size = Some(size);
+        self
+    }
+
+    /// Labels every plot with `label`, drawn in its bottom right corner, so that archived plots
+    /// identify the version of the code they were measured on. By default, plots are labeled with
+    /// the short hash and date of the current git commit, if the benchmarks are run in a git
+    /// repository. An empty label removes the annotation.
+    ///
+    /// This only has an effect when using the gnuplot plotting backend.
+    pub fn annotate<S: Into<String>>(mut self, label: S) -> Criterion<M> {
+        self.plot_annotation = Some(label.into());
+        self
+    }
+
+    /// Configures the gnuplot terminal used to draw the plots, for example to also export them as
+    /// high-resolution PNG files with a particular font for a publication. The size and font size
+    /// of the configuration replace those set with `plot_size` and `plot_font_size`, and any
+    /// setting it leaves unspecified keeps its default.
+    ///
+    /// This only has an effect when using the gnuplot plotting backend.
+    ///
+    /// ```rust
+    /// use criterion::{Criterion, PlotTerminalConfig};
+    ///
+    /// fn publication_plots() -> Criterion {
+    ///     Criterion::default().plot_terminal(
+    ///         PlotTerminalConfig::default()
+    ///             .dpi(300)
+    ///             .font("Times New Roman")
+    ///             .font_size(10.0),
+    ///     )
+    /// }
+    /// ```
+    pub fn plot_terminal(mut self, config: PlotTerminalConfig) -> Criterion<M> {
+        if let Some(size) = config.size {
+            self.plot_size = Some(size);
+        }
+        if let Some(font_size) = config.font_size {
+            self.plot_font_size = Some(font_size);
+        }
+        self.plot_terminal = config;
+        self
+    }
+
+    /// Changes the colors of the plots, for example to `Palette::ColorblindSafe` so that the new
+    /// and base samples and the functions of the summary plots remain distinguishable with color
+    /// vision deficiencies. Defaults to `Palette::Default`.
+    ///
+    /// This only has an effect when using the gnuplot plotting backend.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a `Palette::Custom` has no colors.
+    pub fn plot_palette(mut self, palette: Palette) -> Criterion<M> {
+        if let Palette::Custom(colors) = &palette {
+            assert!(!colors.is_empty());
+        }
+        self.plot_palette = palette;
+        self
+    }
+
+    /// Changes the scale of the y-axis (total sample time) of the regression plot and of its
+    /// comparison with the baseline. A logarithmic scale is clearer when per-iteration costs vary
+    /// by orders of magnitude. Defaults to `AxisScale::Linear`.
+    ///
+    /// Non-positive sample times and confidence bounds cannot be shown on a logarithmic axis, so
+    /// they are clamped to the smallest positive value and a warning is printed.
+    pub fn regression_y_scale(mut self, scale: AxisScale) -> Criterion<M> {
+        self.regression_y_scale = scale;
+        self
+    }
+
+    /// Records the size, in bytes, of the data which the benchmarks touch, such as the combined
+    /// size of their inputs and outputs. It is saved with the results and shown in the HTML
+    /// report; see `BenchmarkGroup::working_set_bytes`, which overrides it for a single group.
+    pub fn working_set_bytes(mut self, bytes: u64) -> Criterion<M> {
+        self.working_set_bytes = Some(bytes);
+        self
+    }
+
+    /// Changes the order of the benchmarks in the violin plots of the summary reports. Sorting
+    /// by median makes it easy to see which implementation is fastest. Defaults to
+    /// `SummaryOrder::Definition`.
+    pub fn summary_order(mut self, order: SummaryOrder) -> Criterion<M> {
+        self.summary_order = order;
+        self
+    }
+
+    /// Limits the line, relative throughput and working set charts of the summary reports to the
+    /// `n` fastest functions of each group, so that they stay legible when a group compares many
+    /// implementations. Functions are ranked by the mean of their benchmarks' median times;
+    /// with `SummaryOrder::MedianDescending`, the `n` slowest functions are shown instead. The
+    /// other functions are omitted from these charts, but are still shown in the violin plot and
+    /// listed in the report. By default, all of the functions are shown.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is zero
+    pub fn summary_top_n(mut self, n: usize) -> Criterion<M> {
+        assert!(n > 0);
+
+        self.summary_top_n = Some(n);
+        self
+    }
+
+    /// Selects which of the additional plots are generated for each benchmark. The PDF and
+    /// regression plots are always generated. Leaving out plots reduces the time spent
+    /// generating reports for large benchmark suites. Defaults to `PlotSet::ALL`.
+    ///
+    /// ```rust
+    /// use criterion::{Criterion, PlotSet};
+    ///
+    /// let c = Criterion::default().plots(PlotSet::T_TEST);
+    /// ```
+    pub fn plots(mut self, plots: PlotSet) -> Criterion<M> {
+        self.plots = plots;
+        self
+    }
+
+    /// Enables or disables the sample order plot, which shows the average iteration time of each
+    /// sample in the order the samples were measured. The other statistics treat the samples as
+    /// interchangeable, but a trend in this plot shows that the performance changed during the
+    /// measurement, eg. because of JIT compilation, caches warming up or thermal throttling.
+    /// Defaults to false.
+    pub fn record_sample_order(mut self, enabled: bool) -> Criterion<M> {
+        self.record_sample_order = enabled;
+        self
+    }
+
+    /// Enables or disables accessible HTML reports. When enabled, the probability density and
+    /// regression (or iteration times) plots of each benchmark report are given a caption
+    /// describing them, and are followed by a collapsible table of the data they were drawn
+    /// from, so that the results can be read with a screen reader. Defaults to false.
+    pub fn accessible_html(mut self, enabled: bool) -> Criterion<M> {
+        self.accessible_html = enabled;
+        self
+    }
+
+    /// Enables or disables reporting the coefficient of variation (the standard deviation divided
+    /// by the mean) of the time per iteration in the command-line output and the HTML report. It
+    /// is a quick indicator of how stable a benchmark is, and is flagged when it exceeds the
+    /// threshold set with `Criterion::cv_threshold`, 5% unless changed. Defaults to false.
+    pub fn report_cv(mut self, enabled: bool) -> Criterion<M> {
+        self.cv_threshold = if enabled {
+            Some(self.cv_threshold.unwrap_or(DEFAULT_CV_THRESHOLD))
+        } else {
+            None
+        };
+        self
+    }
+
+    /// Changes the coefficient of variation above which a benchmark is flagged as unstable, and
+    /// enables reporting it (see `Criterion::report_cv`).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the threshold is not positive.
+    pub fn cv_threshold(mut self, threshold: f64) -> Criterion<M> {
+        assert!(threshold > 0.0);
+        self.cv_threshold = Some(threshold);
+        self
+    }
+
+    /// Displays all times in the command-line output and the HTML reports in the given unit,
+    /// rather than choosing a unit based on the magnitude of each value. This makes reports from
+    /// different runs easier to compare textually. Pass `None` to restore the default automatic
+    /// scaling.
+    ///
+    /// This assumes that the measured values are in nanoseconds, as they are for `WallTime`. It
+    /// should not be used with measurements of anything other than time.
+    pub fn time_unit(mut self, unit: Option<TimeUnit>) -> Criterion<M> {
+        self.time_unit = unit;
+        self
+    }
+
+    /// Displays all times in the command-line output and the HTML reports with the given number
+    /// of decimal places, eg. `1.234 ms` with a precision of 3, rather than with a number of
+    /// decimal places which depends on the magnitude of each value. By default, values are shown
+    /// with four or five significant figures. Combined with `Criterion::time_unit`, this gives a
+    /// fixed format suitable for publication.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the precision is greater than 9.
+    pub fn time_precision(mut self, precision: usize) -> Criterion<M> {
+        assert!(
+            precision <= 9,
+            "The time precision must be between 0 and 9 decimal places"
+        );
+        self.time_precision = Some(precision);
+        self
+    }
+
+    /// Sets an absolute time budget for the benchmarks whose IDs match the regular expression
+    /// `id_pattern`. If the median time per iteration of a matching benchmark exceeds `max`, the
+    /// failure is reported by [`run`](Self::run) after all benchmarks have run, and
+    /// `criterion_main!` exits with a nonzero status. Unlike regression detection, this does not require a saved baseline, which makes
+    /// it suitable for gating CI on performance.
+    ///
+    /// This may be called multiple times to set several budgets. It assumes that the measured
+    /// values are in nanoseconds, as they are for `WallTime`.
+    ///
+    /// ```rust
+    /// use criterion::Criterion;
+    /// use std::time::Duration;
+    ///
+    /// let c = Criterion::default().assert_max("^parse", Duration::from_millis(5));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id_pattern` is not a valid regular expression.
+    pub fn assert_max<S: Into<String>>(mut self, id_pattern: S, max: Duration) -> Criterion<M> {
+        let pattern = parse_id_pattern(id_pattern.into());
+        self.max_times.push((pattern, max));
+        self
+    }
+
+    /// Sets a minimum throughput for the benchmarks whose IDs match the regular expression
+    /// `id_pattern`. `min_per_sec` is the number of bytes or elements which must be processed per
+    /// second. The achieved throughput is computed from the time per iteration (the slope
+    /// estimate, or the mean if no slope is available) and the benchmark's own `Throughput`. If it
+    /// falls below the minimum, the failure is reported by [`run`](Self::run) after all benchmarks
+    /// have run, and `criterion_main!` exits with a nonzero status.
+    ///
+    /// Matching benchmarks which do not set a throughput of the same kind as `min_per_sec` are
+    /// skipped with a warning. Like [`assert_max`](Self::assert_max), this assumes that the
+    /// measured values are in nanoseconds.
+    ///
+    /// ```rust
+    /// use criterion::{Criterion, Throughput};
+    ///
+    /// // Require at least 1 GiB/s when decoding and a million elements per second when sorting
+    /// let c = Criterion::default()
+    ///     .assert_min_throughput("^decode/", Throughput::Bytes(1024 * 1024 * 1024))
+    ///     .assert_min_throughput("^sort/", Throughput::Elements(1_000_000));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id_pattern` is not a valid regular expression or if `min_per_sec` is zero.
+    pub fn assert_min_throughput<S: Into<String>>(
+        mut self,
+        id_pattern: S,
+        min_per_sec: Throughput,
+    ) -> Criterion<M> {
+        assert!(!matches!(
+            min_per_sec,
+            Throughput::Bytes(0) | Throughput::Elements(0)
+        ));
+        let pattern = parse_id_pattern(id_pattern.into());
+        self.min_throughputs.push((pattern, min_per_sec));
+        self
+    }
+
+    /// Fails the run if any benchmark is too noisy to measure reliably: if the standard error of
+    /// a benchmark's mean exceeds `relative_se_threshold` times the mean itself (eg. `0.01` for
+    /// 1%), the failure is reported by [`run`](Self::run) after all benchmarks have run, like the
+    /// other performance assertions. A wide confidence interval makes
+    /// comparisons with the baseline meaningless, so this is useful when gating on performance.
+    ///
+    /// Noisy benchmarks can often be stabilized by increasing the sample size or the measurement
+    /// time, or by reducing the load on the system. This is disabled by default.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the threshold is not positive.
+    pub fn fail_on_noise(mut self, relative_se_threshold: f64) -> Criterion<M> {
+        assert!(relative_se_threshold > 0.0);
+        self.max_relative_standard_error = Some(relative_se_threshold);
+        self
+    }
+
+    /// Enables or disables saving the bootstrap distributions computed when comparing against a
+    /// baseline. If enabled, Criterion.rs writes `change/distributions.json` for each benchmark,
+    /// containing the resampled T statistics used for the t-test and the resampled relative
+    /// changes in the mean and median. These can be used to reconstruct the change plots or for
+    /// other custom analysis.
+    ///
+    /// This is disabled by default, since each distribution holds one value per resample and the
+    /// files can be large.
+    pub fn save_distributions(mut self, enabled: bool) -> Criterion<M> {
+        self.save_distributions = enabled;
+        self
+    }
+
+    /// Enables or disables compressing the saved samples. If enabled, each benchmark's sample is
+    /// written to `sample.json.gz` rather than `sample.json`, which greatly reduces the size of the
+    /// output directory for benchmarks with many samples. Criterion.rs reads either format when
+    /// loading baselines, so this can be changed at any time.
+    ///
+    /// This is disabled by default, since external tools may expect to read `sample.json`.
+    pub fn compress_samples(mut self, enabled: bool) -> Criterion<M> {
+        self.compress_samples = enabled;
+        self
+    }
+
+    /// Changes the format in which each benchmark's measurements are saved. `SampleFormat::Binary`
+    /// is much faster to save and load than JSON, which helps with suites of thousands of
+    /// benchmarks. Criterion.rs reads samples saved in any format when loading baselines, so this
+    /// can be changed at any time. This can be combined with `compress_samples`.
+    ///
+    /// The default is `SampleFormat::Json`, since external tools may expect to read `sample.json`.
+    pub fn sample_format(mut self, format: SampleFormat) -> Criterion<M> {
+        self.sample_format = format;
+        self
+    }
+
+    /// Enables or disables a progress bar for the whole run, which shows how many benchmarks have
+    /// run and an estimate of the remaining time. The expected number of benchmarks is taken from
+    /// the previous run of the same benchmark target, so the first run only shows a count. The
+    /// progress bar is only shown when the output is a terminal, and not with `--verbose`. It can
+    /// also be enabled with the `--progress-bar` command-line option.
+    ///
+    /// This is disabled by default.
+    pub fn progress_bar(mut self, enabled: bool) -> Criterion<M> {
+        self.progress_bar = enabled;
+        self
+    }
+
+    /// Enables or disables subtracting the timer overhead from the measurements. The overhead of
+    /// starting and ending the measurement is calibrated once per run; if this is enabled, it is
+    /// subtracted from each sample once for every time the timing loop started and ended the
+    /// measurement, clamping the result to zero. This can make a difference for benchmarks which
+    /// take only a few nanoseconds per iteration, where the timer makes up a large fraction of the
+    /// measured time. It has no effect on benchmarks using `iter_custom`, which do their own timing.
+    ///
+    /// Since this changes the reported numbers, it is noted when the timer overhead is printed, and
+    /// results measured with and without it should not be compared.
+    ///
+    /// This is disabled by default.
+    pub fn subtract_timer_overhead(mut self, enabled: bool) -> Criterion<M> {
+        self.subtract_timer_overhead = enabled;
+        self
+    }
+
+    /// Enables or disables analyzing benchmarks on a background thread. If enabled, the
+    /// bootstrapped statistics of each benchmark are computed while the next benchmark in the same
+    /// group is warming up and being measured, and its results are reported once that measurement
+    /// is complete. Only one benchmark is ever measured at a time; the last benchmark of a group is
+    /// analyzed before the group is finished.
+    ///
+    /// This can shorten runs with many benchmarks considerably, but the analysis competes with the
+    /// benchmark being measured for CPU time and memory bandwidth, so it can add noise to the
+    /// measurements, especially on machines with few cores.
+    ///
+    /// This is disabled by default.
+    pub fn background_analysis(mut self, enabled: bool) -> Criterion<M> {
+        self.background_analysis = enabled;
+        self
+    }
+
+    /// Enables or disables single-threaded mode. In this mode, the bootstrap and the other
+    /// statistics are computed sequentially on the current thread rather than on rayon's thread
+    /// pool, and background analysis is disabled. Combined with `bootstrap_seed`, this makes the
+    /// analysis of a given set of measurements reproducible bit for bit, which is useful for
+    /// testing and for reproducing published results. It also makes the analysis considerably
+    /// slower on machines with many cores. It can also be enabled with the `--single-threaded`
+    /// command-line option.
+    ///
+    /// This is disabled by default.
+    pub fn single_threaded(mut self, enabled: bool) -> Criterion<M> {
+        self.single_threaded = enabled;
+        self
+    }
+
+    /// Sets the seed of the random number generator used to draw the bootstrap resamples. The
+    /// generator is reseeded before each benchmark is analyzed, so its results don't depend on
+    /// which other benchmarks ran. With multiple threads, the resamples are split between threads
+    /// nondeterministically, so the results are only reproducible in single-threaded mode. It can
+    /// also be set with the `--bootstrap-seed` command-line option.
+    ///
+    /// By default, the generator is seeded from the system clock.
+    pub fn bootstrap_seed(mut self, seed: u64) -> Criterion<M> {
+        self.bootstrap_seed = Some(seed);
+        self
+    }
+
+    /// Enables or disables isolated samples. In this experimental mode, each sample is measured in
+    /// a fresh process: the benchmark executable is run again for every sample, with arguments
+    /// telling it which benchmark to run and for how many iterations, and reports the measurement
+    /// back on its standard output. The samples are then analyzed as usual.
+    ///
+    /// This is useful for benchmarks which are sensitive to the heap layout or the state of the
+    /// memory allocator left behind by earlier iterations. Each sample starts cold; the warm-up
+    /// still runs in the original process, only to choose the iteration counts. Starting a process
+    /// per sample makes benchmarks considerably slower, and the `setup` closure of a group runs in
+    /// each of them. The Criterion instance must be configured from the command line, as
+    /// `criterion_group!` does, for the child processes to recognize their arguments.
+    ///
+    /// This is disabled by default.
+    pub fn isolated_samples(mut self, enabled: bool) -> Criterion<M> {
+        self.isolated_samples = enabled;
+        self
+    }
+
+    /// Pins the benchmark thread to the given core (numbered from zero) while each benchmark is
+    /// warmed up and measured, and restores its original affinity afterward. This keeps the
+    /// scheduler from moving the benchmark between cores, which otherwise adds variance from cold
+    /// caches and differing clock frequencies, especially on busy multi-core machines.
+    ///
+    /// Pinning is supported on Linux and Windows. If the affinity can't be changed, a warning is
+    /// printed and the benchmark runs unpinned.
+    ///
+    /// This is disabled by default.
+    pub fn pin_to_core(mut self, core: usize) -> Criterion<M> {
+        self.pin_to_core = Some(core);
+        self
+    }
+
+    /// Enables or disables sampling the CPU frequency and the one-minute load average of the
+    /// system just before and after each benchmark is measured. The samples are saved in the
+    /// benchmark's `new/benchmark.json` file and shown in its HTML report. Variance often
+    /// correlates with background load, and a large change in frequency across a benchmark hints
+    /// that the CPU was throttled, which can explain anomalous results.
+    ///
+    /// The system is sampled through `/proc` on Linux and `sysctl` on macOS. Values which can't
+    /// be read on the current platform are left out.
+    ///
+    /// This is disabled by default.
+    pub fn monitor_system(mut self, enabled: bool) -> Criterion<M> {
+        self.monitor_system = enabled;
+        self
+    }
+
+    /// Sets a timeout for a single sample. A benchmark with a sample which takes longer is aborted
+    /// with an error, and the run continues with the next benchmark. While warming up, each call
+    /// of the routine is allowed at least twice the warm-up time, since the iteration count grows
+    /// until the warm-up time is used up.
+    ///
+    /// A running closure can't be stopped safely, so this is only detected once the sample
+    /// finishes. If a sample is still running when the timeout elapses, a warning is printed so
+    /// that a benchmark stuck in an infinite loop doesn't hang the run silently. To stop such a
+    /// benchmark, combine this with `Criterion::isolated_samples`: each sample then runs in its
+    /// own process, which is killed once it exceeds the timeout. The warm-up still runs in the
+    /// benchmark process, though.
+    ///
+    /// Benchmarks have no timeout by default.
+    pub fn benchmark_timeout(mut self, timeout: Duration) -> Criterion<M> {
+        self.benchmark_timeout = Some(timeout);
+        self
+    }
+
+    /// Enables or disables pruning stale benchmark directories. If enabled, at the end of the run
+    /// Criterion.rs removes the saved data and reports of every benchmark in the output directory
+    /// which did not run but belongs to a group which did, such as benchmarks which have been
+    /// removed from a group or parameter values which are no longer used. Use the
+    /// `--dry-run-prune` command-line option to list the directories that would be removed
+    /// without removing them.
+    ///
+    /// Groups which did not run at all are left alone, since they may belong to another benchmark
+    /// target. Nothing is removed if any benchmark was skipped by a filter, since it isn't possible
+    /// to tell which of the skipped benchmarks still exist. This is disabled by default.
+    pub fn prune_stale(mut self, enabled: bool) -> Criterion<M> {
+        self.prune_stale = enabled;
+        self
+    }
+
+    /// Enables or disables comparing each benchmark against the best result it has ever
+    /// recorded, rather than against the baseline. The results of a run are saved in the
+    /// benchmark's `best` directory if there were none, or if its median time per iteration is
+    /// lower than the best so far.
+    ///
+    /// Comparing against the previous run can miss a slow regression made up of many changes
+    /// which are each within the noise threshold. Comparing against the best result catches it
+    /// once it adds up to a significant change. The baseline is still saved as usual, but isn't
+    /// compared against. This is disabled by default.
+    pub fn track_best(mut self, enabled: bool) -> Criterion<M> {
+        self.track_best = enabled;
+        self
+    }
+
+    /// Enables or disables reporting the cold start of each benchmark: the value measured for the
+    /// very first iteration of the routine, before the warm-up, when caches are cold and lazily
+    /// initialized state is set up. It is reported separately from the steady-state statistics,
+    /// which are computed from the samples taken after the warm-up and never include it, along
+    /// with how many times the typical value it is. This is disabled by default.
+    ///
+    /// A single iteration can't be measured precisely, so this is only meaningful when the cold
+    /// start is much larger than the timer's resolution.
+    pub fn report_cold_start(mut self, enabled: bool) -> Criterion<M> {
+        self.report_cold_start = enabled;
+        self
+    }
+
+    /// Enables or disables recording the value of every iteration, rather than only the total of
+    /// each sample. The values are saved in order to `new/iterations.json` in the benchmark's
+    /// directory, as a `times` array, for analyses which need the distribution of individual
+    /// iterations, such as latency histograms. This is disabled by default.
+    ///
+    /// Each iteration is timed on its own, so the measurement overhead is paid once per
+    /// iteration instead of once per sample; this is only meaningful for routines much slower
+    /// than the timer's resolution. Only routines which use `Bencher::iter` are recorded, and
+    /// benchmarks which run more than a million iterations in total only save their samples.
+    pub fn record_per_iteration(mut self, enabled: bool) -> Criterion<M> {
+        self.record_per_iteration = enabled;
+        self
+    }
+
+    /// Allows comparing benchmarks against baselines which were recorded with a different
+    /// measurement, such as comparing cycle counts to a baseline measured in wall-clock time.
+    /// `factor` is the number of units of the current measurement per unit of the baseline's
+    /// measurement; the baseline's samples and estimates are multiplied by it before the
+    /// comparison.
+    ///
+    /// The measurement is recorded in each benchmark's `benchmark.json` file. Without a conversion
+    /// factor, a benchmark whose baseline was recorded with a different measurement is not
+    /// compared, and an error is reported instead, since the change between values in different
+    /// units is meaningless. Baselines saved by older versions of Criterion.rs don't record their
+    /// measurement and are always compared.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the factor isn't a positive, finite number.
+    pub fn measurement_conversion(mut self, factor: f64) -> Criterion<M> {
+        assert!(factor > 0.0 && factor.is_finite());
+
+        self.measurement_conversion = Some(factor);
         self
     }
 
-    /// Disables plotting
-    pub fn without_plots(mut self) -> Criterion<M> {
-        self.report.html_enabled = false;
+    /// Clears the history of the benchmarks whose IDs match the regular expression `pattern`.
+    /// Before each matching benchmark runs, its directory in the output directory is removed,
+    /// along with all of its baselines, comparisons and reports, so it starts again as if it had
+    /// never been run. Benchmarks which don't run, eg. because they are skipped by a filter, are
+    /// left alone. This is also available as the `--clear <pattern>` command-line option.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `pattern` is not a valid regular expression.
+    pub fn clear_benchmark<S: Into<String>>(mut self, pattern: S) -> Criterion<M> {
+        self.clear_pattern = Some(parse_id_pattern(pattern.into()));
         self
     }
 
@@ -663,6 +1948,7 @@ impl<M: Measurement> Criterion<M> {
     pub fn save_baseline(mut self, baseline: String) -> Criterion<M> {
         self.baseline_directory = baseline;
         self.baseline = Baseline::Save;
+        self.save_baseline_to = None;
         self
     }
 
@@ -670,6 +1956,53 @@ impl<M: Measurement> Criterion<M> {
     pub fn retain_baseline(mut self, baseline: String) -> Criterion<M> {
         self.baseline_directory = baseline;
         self.baseline = Baseline::Compare;
+        self.save_baseline_to = None;
+        self
+    }
+
+    /// Compares against the baseline named `compare` without overwriting it, like
+    /// `retain_baseline`, and saves the results under the baseline named `save`. This lets a single
+    /// run check for regressions against eg. the results of the main branch while recording its
+    /// own results, like passing both `--baseline` and `--save-baseline`.
+    pub fn compare_and_save_baseline(mut self, compare: String, save: String) -> Criterion<M> {
+        self.baseline_directory = compare;
+        self.baseline = Baseline::Compare;
+        self.save_baseline_to = Some(save);
+        self
+    }
+
+    /// Compares against the baseline named after the commit hash of the git revision `rev`, and
+    /// disables overwriting it, like `retain_baseline`. The revision can be anything that
+    /// `git rev-parse` understands, such as a branch name or `HEAD~1`.
+    ///
+    /// This makes it possible to key baselines by commit: save the results of a commit with
+    /// `--save-baseline $(git rev-parse HEAD)`, then compare a later commit against them, eg. in
+    /// CI against the baseline of the merge base with
+    /// `Criterion::default().baseline_from_git(&merge_base)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `git` can't be run or `rev` does not name a commit.
+    pub fn baseline_from_git<S: AsRef<str>>(self, rev: S) -> Criterion<M> {
+        let rev = rev.as_ref();
+        let hash = git_commit_hash(rev).unwrap_or_else(|err| {
+            panic!(
+                "Unable to resolve git revision '{}' for the baseline: {}",
+                rev, err
+            )
+        });
+        self.retain_baseline(hash)
+    }
+
+    /// Loads baselines from an external directory instead of the output directory. The directory
+    /// must be laid out the same way as the output directory, ie. it must contain
+    /// `<benchmark>/<baseline>/sample.json` (or `sample.json.gz`) and
+    /// `<benchmark>/<baseline>/estimates.json` for each benchmark. New results are still saved to the output directory.
+    ///
+    /// This is useful for comparing against golden baselines stored outside of
+    /// `target/criterion`.
+    pub fn baseline_path<P: Into<PathBuf>>(mut self, path: P) -> Criterion<M> {
+        self.baseline_path = Some(path.into());
         self
     }
 
@@ -688,6 +2021,16 @@ impl<M: Measurement> Criterion<M> {
         self
     }
 
+    /// Filters the benchmarks by tag. Only benchmarks which have been given this tag with
+    /// [`Bencher::tag`](crate::Bencher::tag), or whose group has been given it with
+    /// [`BenchmarkGroup::tag`](crate::BenchmarkGroup::tag), will be executed. When listing,
+    /// testing or profiling the benchmarks, only the tags of their groups are used.
+    pub fn with_tag_filter<S: Into<String>>(mut self, tag: S) -> Criterion<M> {
+        self.tag_filter = Some(tag.into());
+
+        self
+    }
+
     /// Override whether the CLI output will be colored or not. Usually you would use the `--color`
     /// CLI argument, but this is available for programmmatic use as well.
     pub fn with_output_color(mut self, enabled: bool) -> Criterion<M> {
@@ -695,10 +2038,14 @@ impl<M: Measurement> Criterion<M> {
         self
     }
 
-    /// Set the output directory (currently for testing only)
-    #[doc(hidden)]
-    pub fn output_directory(mut self, path: &Path) -> Criterion<M> {
-        self.output_directory = path.to_owned();
+    /// Changes the directory where Criterion.rs saves its measurements and generates reports.
+    ///
+    /// By default, this is `$CRITERION_HOME` if that environment variable is set, or otherwise
+    /// the `criterion` subdirectory of the Cargo target directory (usually `target/criterion`).
+    /// This is useful in CI environments which need the results to be written to a
+    /// workspace-relative path.
+    pub fn output_directory<P: Into<PathBuf>>(mut self, path: P) -> Criterion<M> {
+        self.output_directory = path.into();
 
         self
     }
@@ -720,13 +2067,123 @@ impl<M: Measurement> Criterion<M> {
         if !self.mode.is_benchmark() {
             return;
         }
+        analysis::finish_pending_analysis(self);
 
-        let report_context = ReportContext {
-            output_directory: self.output_directory.clone(),
-            plot_config: PlotConfiguration::default(),
-        };
+        let report_context = self.report_context(PlotConfiguration::default(), None);
 
+        // An interrupted run didn't execute all of the benchmarks, so it says nothing about which
+        // are stale or how many the next run will have.
+        let interrupted = interrupt::requested();
+        if !interrupted {
+            self.prune_stale_benchmarks();
+        }
         self.report.final_summary(&report_context);
+        if self.progress_bar && self.connection.is_none() && !interrupted {
+            let executed = EXECUTED_BENCHMARKS.lock().unwrap();
+            // A filtered run doesn't tell us how many benchmarks the next full run will have.
+            if !executed.filtered && !executed.directory_names.is_empty() {
+                log_if_err!(progress::save_total(
+                    &self.output_directory,
+                    executed.directory_names.len()
+                ));
+            }
+        }
+        if let Some(path) = &self.trace_path {
+            log_if_err!(trace::save(path));
+        }
+        if let (Some(path), None) = (&self.export_csv_path, &self.connection) {
+            log_if_err!(csv_report::export_summary(
+                &self.output_directory,
+                path,
+                self.measurement.formatter()
+            ));
+        }
+    }
+
+    /// Generates the final summary, like `final_summary`, and returns the combined result of
+    /// comparing every benchmark run by this process with its baseline and of checking the
+    /// performance assertions, such as [`assert_max`](Self::assert_max). The regressed
+    /// benchmarks and failed assertions are also printed. `criterion_main!` calls this after
+    /// running all of the benchmark groups, and exits with a nonzero status if the run didn't
+    /// pass.
+    ///
+    /// This is useful for gating CI on performance with a custom `main` function:
+    ///
+    /// ```no_run
+    /// use criterion::Criterion;
+    ///
+    /// fn main() {
+    ///     let mut criterion = Criterion::default().configure_from_args();
+    ///     criterion.bench_function("add", |b| b.iter(|| 1 + 1));
+    ///
+    ///     let outcome = criterion.run();
+    ///     println!("{} improved, {} unchanged", outcome.improved, outcome.unchanged);
+    ///     if !outcome.passed() {
+    ///         std::process::exit(1);
+    ///     }
+    /// }
+    /// ```
+    pub fn run(self) -> RunOutcome {
+        self.final_summary();
+        let outcome = RUN_OUTCOME.lock().unwrap().clone();
+        if outcome.regressed > 0 {
+            for id in &outcome.regressed_ids {
+                error!("Benchmark {} regressed", id);
+            }
+            error!("{} benchmark(s) regressed", outcome.regressed);
+        }
+        if !outcome.failed_assertions.is_empty() {
+            for failure in &outcome.failed_assertions {
+                error!("{}", failure);
+            }
+            error!(
+                "{} performance assertion(s) failed",
+                outcome.failed_assertions.len()
+            );
+        }
+        outcome
+    }
+
+    /// Called once a benchmark has finished if Ctrl-C was pressed during the run. Reports the
+    /// benchmarks which completed, as `final_summary` does at the end of a run, and exits.
+    pub(crate) fn exit_interrupted(&self) -> ! {
+        self.final_summary();
+        let completed = EXECUTED_BENCHMARKS.lock().unwrap().directory_names.len();
+        warn!(
+            "Interrupted after {} benchmark(s); their results have been saved",
+            completed
+        );
+        std::process::exit(130)
+    }
+
+    /// Removes (or, for `--dry-run-prune`, lists) the directories of benchmarks which did not run.
+    fn prune_stale_benchmarks(&self) {
+        let executed = EXECUTED_BENCHMARKS.lock().unwrap();
+        if !(self.prune_stale || self.dry_run_prune || executed.prune_stale)
+            || self.connection.is_some()
+        {
+            return;
+        }
+        if executed.filtered {
+            warn!(
+                "Some benchmarks were skipped by a filter; not pruning stale benchmark directories"
+            );
+            return;
+        }
+
+        let stale = try_else_return!(fs::stale_benchmark_directories(
+            &self.output_directory,
+            &executed.group_ids,
+            &executed.directory_names
+        ));
+        for dir in stale {
+            if self.dry_run_prune {
+                human_println!("Would remove stale benchmark directory {}", dir.display());
+            } else {
+                human_println!("Removing stale benchmark directory {}", dir.display());
+                log_if_err!(fs::rmdir_all(&dir));
+            }
+        }
     }
 
     /// Configure this criterion struct based on the command-line arguments to
@@ -738,6 +2195,10 @@ impl<M: Measurement> Criterion<M> {
             .arg(Arg::with_name("FILTER")
                 .help("Skip benchmarks whose names do not contain FILTER.")
                 .index(1))
+            .arg(Arg::with_name("filter-tag")
+                .long("filter-tag")
+                .takes_value(true)
+                .help("Skip benchmarks which have not been tagged with the given tag."))
             .arg(Arg::with_name("color")
                 .short("c")
                 .long("color")
@@ -763,8 +2224,7 @@ impl<M: Measurement> Criterion<M> {
                 .short("b")
                 .long("baseline")
                 .takes_value(true)
-                .conflicts_with("save-baseline")
-                .help("Compare to a named baseline."))
+                .help("Compare to a named baseline. If --save-baseline is also given, the results are saved under that name instead."))
             .arg(Arg::with_name("list")
                 .long("list")
                 .help("List all benchmarks")
@@ -795,7 +2255,26 @@ impl<M: Measurement> Criterion<M> {
             .arg(Arg::with_name("nresamples")
                 .long("nresamples")
                 .takes_value(true)
-                .help(&format!("Changes the default number of resamples for this run. [default: {}]", self.config.nresamples)))
+                .help(&format!("Changes the default number of bootstrap resamples for this run. Fewer resamples make the analysis faster, but the confidence intervals less precise. Must be at least 1000. [default: {}]", self.config.nresamples)))
+            .arg(Arg::with_name("single-threaded")
+                .long("single-threaded")
+                .help("Compute the statistics on a single thread and disable background analysis. Combined with --bootstrap-seed, this makes the analysis reproducible."))
+            .arg(Arg::with_name("bootstrap-seed")
+                .long("bootstrap-seed")
+                .takes_value(true)
+                .help("Seed the random number generator used for bootstrap resampling with the given integer. The results are only reproducible with --single-threaded."))
+            .arg(Arg::with_name("isolated-sample")
+                .long("isolated-sample")
+                .hidden(true)
+                .takes_value(true)
+                .requires("isolated-iters")
+                .help("Used by Criterion::isolated_samples: measure a single sample of the benchmark with the given title, print it and exit."))
+            .arg(Arg::with_name("isolated-iters")
+                .long("isolated-iters")
+                .hidden(true)
+                .takes_value(true)
+                .requires("isolated-sample")
+                .help("The number of iterations of the isolated sample."))
             .arg(Arg::with_name("noise-threshold")
                 .long("noise-threshold")
                 .takes_value(true)
@@ -816,17 +2295,55 @@ impl<M: Measurement> Criterion<M> {
             .arg(Arg::with_name("bench")
                 .hidden(true)
                 .long("bench"))
+            .arg(Arg::with_name("dry-run-prune")
+                .long("dry-run-prune")
+                .help("List the directories of stale benchmarks which would be removed by Criterion::prune_stale, without removing them."))
+            .arg(Arg::with_name("clear")
+                .long("clear")
+                .takes_value(true)
+                .help("Remove the saved history of the benchmarks matching this regular expression before running them."))
+            .arg(Arg::with_name("merge")
+                .long("merge")
+                .takes_value(true)
+                .multiple(true)
+                .value_name("FILE")
+                .help("Instead of running the benchmarks, merge the results recorded in these files with --message-format=json, eg. by the shards of a distributed run, into one set of reports."))
+            .arg(Arg::with_name("dump-samples")
+                .long("dump-samples")
+                .help("Print every sample of each benchmark after its results: the iteration count, total time and time per iteration, and whether it is an outlier."))
+            .arg(Arg::with_name("progress-bar")
+                .long("progress-bar")
+                .help("Show a progress bar for the whole run, with an estimate of the remaining time. Only shown when the output is a terminal."))
+            .arg(Arg::with_name("trace")
+                .long("trace")
+                .takes_value(true)
+                .help("Write a timeline of the run, showing the time spent warming up, measuring, analyzing and plotting each benchmark, to the given file. The file uses Chrome's trace event format and can be loaded in chrome://tracing or Perfetto."))
+            .arg(Arg::with_name("export-csv")
+                .long("export-csv")
+                .takes_value(true)
+                .help("Write the estimates of every benchmark in the output directory to the given CSV file at the end of the run, one row per benchmark, for analysis in a spreadsheet."))
             .arg(Arg::with_name("plotting-backend")
                  .long("plotting-backend")
                  .takes_value(true)
                  .possible_values(&["gnuplot", "plotters"])
                  .help("Set the plotting backend. By default, Criterion.rs will use the gnuplot backend if gnuplot is available, or the plotters backend if it isn't."))
+            .arg(Arg::with_name("emit-gnuplot-scripts")
+                .long("emit-gnuplot-scripts")
+                .conflicts_with("noplot")
+                .help("Save the gnuplot scripts used to render the plots alongside the generated images, so they can be edited and re-run manually. Only has an effect with the gnuplot plotting backend."))
             .arg(Arg::with_name("output-format")
                 .long("output-format")
                 .takes_value(true)
-                .possible_values(&["criterion", "bencher"])
+                .alias("format")
+                .possible_values(&["criterion", "bencher", "oneline"])
                 .default_value("criterion")
-                .help("Change the CLI output format. By default, Criterion.rs will use its own format. If output format is set to 'bencher', Criterion.rs will print output in a format that resembles the 'bencher' crate."))
+                .help("Change the CLI output format. By default, Criterion.rs will use its own format. If output format is set to 'bencher', Criterion.rs will print output in a format that resembles the 'bencher' crate. If it is set to 'oneline', Criterion.rs will print one line per benchmark with its median, change and p-value, for parsing by other tools."))
+            .arg(Arg::with_name("message-format")
+                .long("message-format")
+                .takes_value(true)
+                .possible_values(&["human", "json"])
+                .default_value("human")
+                .help("Change the format of the messages printed to stdout. If set to 'json', the human-readable output is replaced by one JSON object per line describing each benchmark's progress and results, for consumption by other tools."))
             .arg(Arg::with_name("nocapture")
                 .long("nocapture")
                 .hidden(true)
@@ -869,6 +2386,11 @@ https://bheisler.github.io/criterion.rs/book/faq.html
             if let Some(backend) = matches.value_of("plotting-backend") {
                 println!("Warning: --plotting-backend will be ignored when running with cargo-criterion. Use `cargo criterion --plotting-backend {} -- <args>` instead.", backend);
             }
+            if matches.value_of("message-format") == Some("json") {
+                println!(
+                    "Warning: --message-format will be ignored when running with cargo-criterion."
+                );
+            }
             if let Some(format) = matches.value_of("output-format") {
                 if format != "criterion" {
                     println!("Warning: --output-format will be ignored when running with cargo-criterion. Use `cargo criterion --output-format {} -- <args>` instead.", format);
@@ -901,12 +2423,12 @@ https://bheisler.github.io/criterion.rs/book/faq.html
             Mode::List
         } else if matches.is_present("profile-time") {
             let num_seconds = value_t!(matches.value_of("profile-time"), u64).unwrap_or_else(|e| {
-                println!("{}", e);
+                human_println!("{}", e);
                 std::process::exit(1)
             });
 
             if num_seconds < 1 {
-                println!("Profile time must be at least one second.");
+                human_println!("Profile time must be at least one second.");
                 std::process::exit(1);
             }
 
@@ -924,6 +2446,10 @@ https://bheisler.github.io/criterion.rs/book/faq.html
             self = self.with_filter(filter);
         }
 
+        if let Some(tag) = matches.value_of("filter-tag") {
+            self = self.with_tag_filter(tag);
+        }
+
         match matches.value_of("plotting-backend") {
             // Use plotting_backend() here to re-use the panic behavior if Gnuplot is not available.
             Some("gnuplot") => self = self.plotting_backend(PlottingBackend::Gnuplot),
@@ -934,31 +2460,66 @@ https://bheisler.github.io/criterion.rs/book/faq.html
 
         if matches.is_present("noplot") {
             self = self.without_plots();
-        } else {
-            self = self.with_plots();
+        }
+
+        if matches.is_present("emit-gnuplot-scripts") {
+            self = self.emit_gnuplot_scripts(true);
+        }
+
+        if matches.is_present("dry-run-prune") {
+            self.dry_run_prune = true;
+        }
+
+        if let Some(pattern) = matches.value_of("clear") {
+            self = self.clear_benchmark(pattern);
+        }
+
+        if matches.is_present("progress-bar") {
+            self.progress_bar = true;
+        }
+
+        if let Some(path) = matches.value_of("trace") {
+            trace::enable();
+            self.trace_path = Some(PathBuf::from(path));
+        }
+        if let Some(path) = matches.value_of("export-csv") {
+            self.export_csv_path = Some(PathBuf::from(path));
         }
 
         if let Some(dir) = matches.value_of("save-baseline") {
             self.baseline = Baseline::Save;
-            self.baseline_directory = dir.to_owned()
+            self.baseline_directory = dir.to_owned();
+            self.save_baseline_to = None;
         }
         if let Some(dir) = matches.value_of("baseline") {
             self.baseline = Baseline::Compare;
             self.baseline_directory = dir.to_owned();
+            // An explicit --save-baseline saves the results elsewhere, rather than its default
+            // overwriting the baseline which is compared against.
+            self.save_baseline_to = match matches.occurrences_of("save-baseline") {
+                0 => None,
+                _ => matches.value_of("save-baseline").map(str::to_owned),
+            };
         }
 
         if self.connection.is_some() {
             // disable all reports when connected to cargo-criterion; it will do the reporting.
             self.report.cli_enabled = false;
             self.report.bencher_enabled = false;
+            self.report.oneline_enabled = false;
             self.report.csv_enabled = false;
-            self.report.html_enabled = false;
+            self.report.text_enabled = false;
+            self.report.html = None;
         } else {
             match matches.value_of("output-format") {
                 Some("bencher") => {
                     self.report.bencher_enabled = true;
                     self.report.cli_enabled = false;
                 }
+                Some("oneline") => {
+                    self.report.oneline_enabled = true;
+                    self.report.cli_enabled = false;
+                }
                 _ => {
                     let verbose = matches.is_present("verbose");
                     let stdout_isatty = atty::is(atty::Stream::Stdout);
@@ -975,6 +2536,7 @@ https://bheisler.github.io/criterion.rs/book/faq.html
                         _ => enable_text_coloring = stdout_isatty,
                     };
                     self.report.bencher_enabled = false;
+                    self.report.oneline_enabled = false;
                     self.report.cli_enabled = true;
                     self.report.cli =
                         CliReport::new(enable_text_overwrite, enable_text_coloring, verbose);
@@ -982,13 +2544,26 @@ https://bheisler.github.io/criterion.rs/book/faq.html
             };
         }
 
+        if matches.is_present("dump-samples") {
+            self.report.cli.dump_samples = true;
+        }
+
+        if self.connection.is_none() && matches.value_of("message-format") == Some("json") {
+            // The JSON messages replace the human-readable output, so the two don't interleave.
+            self.report.cli_enabled = false;
+            self.report.bencher_enabled = false;
+            self.report.oneline_enabled = false;
+            self.report.json_enabled = true;
+            report::print_message_format_version();
+        }
+
         if let Some(dir) = matches.value_of("load-baseline") {
             self.load_baseline = Some(dir.to_owned());
         }
 
         if matches.is_present("sample-size") {
             let num_size = value_t!(matches.value_of("sample-size"), usize).unwrap_or_else(|e| {
-                println!("{}", e);
+                human_println!("{}", e);
                 std::process::exit(1)
             });
 
@@ -997,7 +2572,7 @@ https://bheisler.github.io/criterion.rs/book/faq.html
         }
         if matches.is_present("warm-up-time") {
             let num_seconds = value_t!(matches.value_of("warm-up-time"), u64).unwrap_or_else(|e| {
-                println!("{}", e);
+                human_println!("{}", e);
                 std::process::exit(1)
             });
 
@@ -1005,11 +2580,12 @@ https://bheisler.github.io/criterion.rs/book/faq.html
             assert!(dur.to_nanos() > 0);
 
             self.config.warm_up_time = dur;
+            self.config.warm_up_iterations = None;
         }
         if matches.is_present("measurement-time") {
             let num_seconds =
                 value_t!(matches.value_of("measurement-time"), u64).unwrap_or_else(|e| {
-                    println!("{}", e);
+                    human_println!("{}", e);
                     std::process::exit(1)
                 });
 
@@ -1021,18 +2597,41 @@ https://bheisler.github.io/criterion.rs/book/faq.html
         if matches.is_present("nresamples") {
             let num_resamples =
                 value_t!(matches.value_of("nresamples"), usize).unwrap_or_else(|e| {
-                    println!("{}", e);
+                    human_println!("{}", e);
                     std::process::exit(1)
                 });
 
-            assert!(num_resamples > 0);
+            assert!(
+                num_resamples >= 1000,
+                "--nresamples must be at least 1000, but was {}",
+                num_resamples
+            );
 
             self.config.nresamples = num_resamples;
         }
+        if matches.is_present("single-threaded") {
+            self.single_threaded = true;
+        }
+        if matches.is_present("bootstrap-seed") {
+            let seed = value_t!(matches.value_of("bootstrap-seed"), u64).unwrap_or_else(|e| {
+                human_println!("{}", e);
+                std::process::exit(1)
+            });
+
+            self.bootstrap_seed = Some(seed);
+        }
+        if let Some(title) = matches.value_of("isolated-sample") {
+            let iters = value_t!(matches.value_of("isolated-iters"), u64).unwrap_or_else(|e| {
+                human_println!("{}", e);
+                std::process::exit(1)
+            });
+
+            self.isolated_sample = Some((title.to_owned(), iters));
+        }
         if matches.is_present("noise-threshold") {
             let num_noise_threshold = value_t!(matches.value_of("noise-threshold"), f64)
                 .unwrap_or_else(|e| {
-                    println!("{}", e);
+                    human_println!("{}", e);
                     std::process::exit(1)
                 });
 
@@ -1043,7 +2642,7 @@ https://bheisler.github.io/criterion.rs/book/faq.html
         if matches.is_present("confidence-level") {
             let num_confidence_level = value_t!(matches.value_of("confidence-level"), f64)
                 .unwrap_or_else(|e| {
-                    println!("{}", e);
+                    human_println!("{}", e);
                     std::process::exit(1)
                 });
 
@@ -1054,7 +2653,7 @@ https://bheisler.github.io/criterion.rs/book/faq.html
         if matches.is_present("significance-level") {
             let num_significance_level = value_t!(matches.value_of("significance-level"), f64)
                 .unwrap_or_else(|e| {
-                    println!("{}", e);
+                    human_println!("{}", e);
                     std::process::exit(1)
                 });
 
@@ -1063,14 +2662,88 @@ https://bheisler.github.io/criterion.rs/book/faq.html
             self.config.significance_level = num_significance_level;
         }
 
+        if let Some(shards) = matches.values_of("merge") {
+            let shards: Vec<&str> = shards.collect();
+            merge::merge(&mut self, &shards);
+            self.final_summary();
+            std::process::exit(0);
+        }
+
         self
     }
 
-    fn filter_matches(&self, id: &str) -> bool {
-        match &self.filter {
-            Some(regex) => regex.is_match(id),
+    /// Returns the directory that baselines are loaded from.
+    pub(crate) fn baseline_root(&self) -> &Path {
+        self.baseline_path
+            .as_deref()
+            .unwrap_or(&self.output_directory)
+    }
+
+    /// The context of the reports on benchmarks run with this configuration, with the plot
+    /// configuration of their group and the unit of their transformed values, if any.
+    pub(crate) fn report_context(
+        &self,
+        plot_config: PlotConfiguration,
+        value_unit: Option<&'static str>,
+    ) -> ReportContext {
+        ReportContext {
+            output_directory: self.output_directory.clone(),
+            plot_config,
+            emit_gnuplot_scripts: self.emit_gnuplot_scripts,
+            plot_size: self.plot_size,
+            plot_font_size: self.plot_font_size,
+            plot_annotation: self.plot_annotation.clone(),
+            plot_terminal: self.plot_terminal.clone(),
+            plot_palette: self.plot_palette.clone(),
+            regression_y_scale: self.regression_y_scale,
+            summary_order: self.summary_order,
+            summary_top_n: self.summary_top_n,
+            plots: self.plots,
+            record_sample_order: self.record_sample_order,
+            accessible_html: self.accessible_html,
+            cv_threshold: self.cv_threshold,
+            time_unit: self.time_unit,
+            time_precision: self.time_precision,
+            value_unit,
+        }
+    }
+
+    /// The root directory and the name of the results which benchmarks are compared against:
+    /// the best results with `track_best`, or the baseline otherwise.
+    pub(crate) fn comparison_baseline(&self) -> (&Path, &str) {
+        if self.track_best {
+            (&self.output_directory, BEST_DIRECTORY)
+        } else {
+            (self.baseline_root(), &self.baseline_directory)
+        }
+    }
+
+    fn filter_matches(&self, id: &report::BenchmarkId) -> bool {
+        let id_matches = match &self.filter {
+            Some(regex) => regex.is_match(id.id()),
+            None => true,
+        };
+        // A process measuring an isolated sample only runs the benchmark it was started for.
+        if let Some((title, _)) = &self.isolated_sample {
+            return id_matches && id.as_title() == title;
+        }
+        if !id_matches {
+            EXECUTED_BENCHMARKS.lock().unwrap().filtered = true;
+        }
+        id_matches
+    }
+
+    /// Whether a benchmark with these tags is selected by the tag filter. The tags added with
+    /// `Bencher::tag` are only known once the routine has run; see `analysis::tag_filter_matches`.
+    fn tags_match(&self, tags: &[String]) -> bool {
+        let tag_matches = match &self.tag_filter {
+            Some(tag) => tags.contains(tag),
             None => true,
+        };
+        if !tag_matches {
+            EXECUTED_BENCHMARKS.lock().unwrap().filtered = true;
         }
+        tag_matches
     }
 
     /// Return a benchmark group. All benchmarks performed using a benchmark group will be
@@ -1142,6 +2815,39 @@ where
         self
     }
 
+    /// Analyzes and reports samples which weren't measured by Criterion.rs, such as timings
+    /// collected by other means, like any other benchmark. Each sample is an iteration count and
+    /// the measured value of all of those iterations, in the units of this runner's measurement.
+    /// See `BenchmarkGroup::analyze`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use criterion::Criterion;
+    ///
+    /// fn bench(c: &mut Criterion) {
+    ///     // The nanoseconds taken by a device to run 100, 200, ..., 1000 iterations.
+    ///     let samples = [
+    ///         (100, 1_520.0), (200, 3_010.0), (300, 4_570.0), (400, 6_040.0), (500, 7_490.0),
+    ///         (600, 9_050.0), (700, 10_480.0), (800, 12_020.0), (900, 13_550.0), (1000, 15_010.0),
+    ///     ];
+    ///     c.analyze("device_checksum", &samples);
+    /// }
+    /// ```
+    pub fn analyze(&mut self, id: &str, samples: &[(u64, f64)]) -> &mut Criterion<M> {
+        self.benchmark_group(id)
+            .analyze(BenchmarkId::no_function(), samples);
+        self
+    }
+
+    /// Analyzes and reports the benchmarks recorded in each of the `shards` with this runner's
+    /// configuration, as if it had run them; see `merge_reports`. The values must be in the units
+    /// of this runner's measurement, and benchmarks recorded in other units are skipped.
+    pub fn merge_reports<P: AsRef<Path>>(&mut self, shards: &[P]) -> &mut Criterion<M> {
+        merge::merge(self, shards);
+        self
+    }
+
     /// Benchmarks a function with an input. For comparing multiple functions or multiple inputs,
     /// see `benchmark_group`.
     ///
@@ -1318,6 +3024,41 @@ where
         self
     }
 }
+impl Criterion<WallTime> {
+    /// Benchmarks an external program, such as a script or a binary written in another language,
+    /// and analyzes and reports it like any other benchmark.
+    ///
+    /// The program is started once and driven through its standard input and output: for each
+    /// sample, Criterion.rs writes an iteration count as a line to the program's standard input.
+    /// The program must then run the benchmarked routine that many times and write the elapsed
+    /// time in nanoseconds as a line to its standard output. The program is killed once the
+    /// benchmark is complete.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// #[macro_use] extern crate criterion;
+    /// use self::criterion::*;
+    /// use std::process::Command;
+    ///
+    /// fn bench(c: &mut Criterion) {
+    ///     let mut command = Command::new("python3");
+    ///     command.arg("benches/fib.py");
+    ///     c.bench_program("fib.py", command);
+    /// }
+    ///
+    /// criterion_group!(benches, bench);
+    /// criterion_main!(benches);
+    /// ```
+    pub fn bench_program(&mut self, id: &str, program: Command) -> &mut Criterion<WallTime> {
+        self.benchmark_group(id).run_routine(
+            BenchmarkId::no_function(),
+            &(),
+            &mut Program::new(program),
+        );
+        self
+    }
+}
 
 trait DurationExt {
     fn to_nanos(&self) -> u64;
@@ -1349,6 +3090,71 @@ pub enum Throughput {
     Elements(u64),
 }
 
+/// The format in which each benchmark's measurements are saved; see `Criterion::sample_format`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SampleFormat {
+    /// Save the measurements to `sample.json`. This is the default, and can be read by external
+    /// tools.
+    Json,
+    /// Save the measurements to `sample.bin`, using a compact binary encoding which is faster to
+    /// save and load than JSON.
+    Binary,
+}
+
+/// A unit of time, used to display all times in a fixed unit; see `Criterion::time_unit`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeUnit {
+    /// Picoseconds (ps)
+    Picoseconds,
+    /// Nanoseconds (ns)
+    Nanoseconds,
+    /// Microseconds (µs)
+    Microseconds,
+    /// Milliseconds (ms)
+    Milliseconds,
+    /// Seconds (s)
+    Seconds,
+}
+
+impl TimeUnit {
+    /// Chooses the unit that displays the given number of nanoseconds most naturally.
+    pub(crate) fn for_nanoseconds(ns: f64) -> TimeUnit {
+        let ns = ns.abs();
+        if ns < 10f64.powi(0) {
+            TimeUnit::Picoseconds
+        } else if ns < 10f64.powi(3) {
+            TimeUnit::Nanoseconds
+        } else if ns < 10f64.powi(6) {
+            TimeUnit::Microseconds
+        } else if ns < 10f64.powi(9) {
+            TimeUnit::Milliseconds
+        } else {
+            TimeUnit::Seconds
+        }
+    }
+
+    /// The factor which converts nanoseconds to this unit.
+    pub(crate) fn factor(self) -> f64 {
+        match self {
+            TimeUnit::Picoseconds => 10f64.powi(3),
+            TimeUnit::Nanoseconds => 10f64.powi(0),
+            TimeUnit::Microseconds => 10f64.powi(-3),
+            TimeUnit::Milliseconds => 10f64.powi(-6),
+            TimeUnit::Seconds => 10f64.powi(-9),
+        }
+    }
+
+    pub(crate) fn symbol(self) -> &'static str {
+        match self {
+            TimeUnit::Picoseconds => "ps",
+            TimeUnit::Nanoseconds => "ns",
+            TimeUnit::Microseconds => "µs",
+            TimeUnit::Milliseconds => "ms",
+            TimeUnit::Seconds => "s",
+        }
+    }
+}
+
 /// Axis scaling type
 #[derive(Debug, Clone, Copy)]
 pub enum AxisScale {
@@ -1359,6 +3165,189 @@ pub enum AxisScale {
     Logarithmic,
 }
 
+/// Method used to estimate the slope of the linear regression of sample times on iteration
+/// counts. See `Criterion::regression_method`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegressionMethod {
+    /// Ordinary least squares, fitting a line through the origin. This is the default.
+    LeastSquares,
+    /// The Theil-Sen estimator: the median of the slopes between every pair of samples. It is
+    /// barely affected by a minority of outlying samples.
+    TheilSen,
+}
+impl fmt::Display for RegressionMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RegressionMethod::LeastSquares => f.write_str("least squares"),
+            RegressionMethod::TheilSen => f.write_str("Theil-Sen"),
+        }
+    }
+}
+
+/// How a benchmark is warmed up before it is measured. See `Criterion::warm_up`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WarmUp {
+    /// Run the benchmark for (at least) this length of time. This is the default, with a duration
+    /// of three seconds.
+    Time(Duration),
+    /// Run the benchmark for exactly this number of iterations, however long they take.
+    Iterations(u64),
+}
+
+/// The combined result of comparing the benchmarks of a run with their baselines, returned by
+/// `Criterion::run`. Benchmarks without a baseline to compare with are not counted.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RunOutcome {
+    /// Number of benchmarks whose performance improved significantly.
+    pub improved: usize,
+    /// Number of benchmarks whose performance regressed significantly, in the mean or in one of
+    /// the statistics set with `Criterion::comparison_statistics`.
+    pub regressed: usize,
+    /// Number of benchmarks with no significant change, or a change within the noise threshold.
+    pub unchanged: usize,
+    /// Number of benchmarks whose change was discarded as uncertain, because the linear fit of
+    /// their sample was poorer than `Criterion::min_r2_for_regression` allows.
+    pub uncertain: usize,
+    /// The IDs of the benchmarks which regressed, in the order they ran.
+    pub regressed_ids: Vec<String>,
+    /// The failures of the performance assertions set with `Criterion::assert_max`,
+    /// `Criterion::assert_min_throughput` and `Criterion::fail_on_noise`, in the order they
+    /// occurred.
+    pub failed_assertions: Vec<String>,
+}
+impl RunOutcome {
+    /// Returns true if no benchmark regressed and no performance assertion failed.
+    pub fn passed(&self) -> bool {
+        self.regressed == 0 && self.failed_assertions.is_empty()
+    }
+
+    fn record(&mut self, id: &report::BenchmarkId, comparison: &report::ComparisonData) {
+        if report::mean_regressed(comparison)
+            || !report::regressed_statistics(comparison).is_empty()
+        {
+            self.regressed += 1;
+            self.regressed_ids.push(id.as_title().to_owned());
+        } else if report::mean_improved(comparison) {
+            self.improved += 1;
+        } else if report::mean_uncertain(comparison) {
+            self.uncertain += 1;
+        } else {
+            self.unchanged += 1;
+        }
+    }
+}
+
+/// Whether any benchmark run by this process so far was skipped by a filter.
+pub(crate) fn run_is_filtered() -> bool {
+    EXECUTED_BENCHMARKS.lock().unwrap().filtered
+}
+
+/// Records the result of comparing a benchmark with its baseline, for `Criterion::run`.
+pub(crate) fn record_outcome(id: &report::BenchmarkId, comparison: &report::ComparisonData) {
+    RUN_OUTCOME.lock().unwrap().record(id, comparison);
+}
+
+/// Records the failure of a performance assertion, for `Criterion::run`.
+pub(crate) fn record_assertion_failure(failure: String) {
+    RUN_OUTCOME.lock().unwrap().failed_assertions.push(failure);
+}
+
+/// Order of the benchmarks in the violin plots of the summary reports, from top to bottom. See
+/// `Criterion::summary_order`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SummaryOrder {
+    /// The order in which the benchmarks were defined.
+    Definition,
+
+    /// Sorted by median time, fastest first.
+    MedianAscending,
+
+    /// Sorted by median time, slowest first.
+    MedianDescending,
+
+    /// Sorted alphabetically by benchmark ID.
+    Alphabetical,
+}
+
+/// A color of the plots, given by its red, green and blue components.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Color(pub u8, pub u8, pub u8);
+
+/// The colors of the plots; see `Criterion::plot_palette`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Palette {
+    /// The colors used by default: the new sample is blue and the base sample red.
+    Default,
+    /// The Okabe-Ito palette, whose colors can be told apart with the common forms of color
+    /// blindness. The new sample is blue and the base sample vermillion.
+    ColorblindSafe,
+    /// Custom colors. The first color is used for the new sample, the second for the base sample
+    /// and the third for highlights such as mild outliers, cycling through the colors if there
+    /// are fewer. The functions of the summary plots use each of the colors in turn.
+    Custom(Vec<Color>),
+}
+
+/// Configures the gnuplot terminal used to draw the plots; see `Criterion::plot_terminal`. Each
+/// setting which isn't given keeps its default.
+#[derive(Debug, Clone, Default)]
+pub struct PlotTerminalConfig {
+    dpi: Option<u32>,
+    font: Option<String>,
+    font_size: Option<f64>,
+    size: Option<(usize, usize)>,
+}
+
+impl PlotTerminalConfig {
+    /// Also exports each plot as a PNG file next to its SVG file, at the given resolution in dots
+    /// per inch. The plot size is taken to be in pixels at 96 DPI, so at 300 DPI a 1280x720 plot
+    /// becomes a 4000x2250 image with proportionally larger fonts and lines.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the resolution is zero.
+    pub fn dpi(mut self, dpi: u32) -> PlotTerminalConfig {
+        assert!(dpi > 0);
+        self.dpi = Some(dpi);
+        self
+    }
+
+    /// Changes the font of the plots. Defaults to Helvetica.
+    pub fn font<S: Into<String>>(mut self, font: S) -> PlotTerminalConfig {
+        self.font = Some(font.into());
+        self
+    }
+
+    /// Changes the font size of the plots, like `Criterion::plot_font_size`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the font size is not positive.
+    pub fn font_size(mut self, size: f64) -> PlotTerminalConfig {
+        assert!(size > 0.0);
+        self.font_size = Some(size);
+        self
+    }
+
+    /// Changes the size of the plots in pixels, like `Criterion::plot_size`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the width or height is zero.
+    pub fn size(mut self, width: usize, height: usize) -> PlotTerminalConfig {
+        assert!(width > 0 && height > 0);
+        self.size = Some((width, height));
+        self
+    }
+
+    pub(crate) fn dpi_setting(&self) -> Option<u32> {
+        self.dpi
+    }
+
+    pub(crate) fn font_setting(&self) -> Option<&str> {
+        self.font.as_deref()
+    }
+}
+
 /// Contains the configuration options for the plots generated by a particular benchmark
 /// or benchmark group.
 ///
@@ -1397,6 +3386,38 @@ impl PlotConfiguration {
     }
 }
 
+/// Selects the additional plots generated for each benchmark; see `Criterion::plots`. Sets can
+/// be combined with the `|` operator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PlotSet(u8);
+
+impl PlotSet {
+    /// No additional plots.
+    pub const NONE: PlotSet = PlotSet(0);
+    /// The distributions of the typical, mean, median, standard deviation, MAD and slope
+    /// estimates.
+    pub const DISTRIBUTIONS: PlotSet = PlotSet(1);
+    /// The distributions of the change in the mean and median since the previous run.
+    pub const CHANGE_DISTRIBUTIONS: PlotSet = PlotSet(1 << 1);
+    /// The Welch t-test plot comparing this run with the previous run.
+    pub const T_TEST: PlotSet = PlotSet(1 << 2);
+    /// All additional plots. This is the default.
+    pub const ALL: PlotSet = PlotSet(0b111);
+
+    /// Returns true if every plot in `other` is also in this set.
+    pub fn contains(self, other: PlotSet) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl BitOr for PlotSet {
+    type Output = PlotSet;
+
+    fn bitor(self, rhs: PlotSet) -> PlotSet {
+        PlotSet(self.0 | rhs.0)
+    }
+}
+
 /// This enum allows the user to control how Criterion.rs chooses the iteration count when sampling.
 /// The default is Auto, which will choose a method automatically based on the iteration time during
 /// the warm-up phase.
@@ -1470,17 +3491,16 @@ impl ActualSamplingMode {
                     let recommended_sample_size =
                         ActualSamplingMode::recommend_linear_sample_size(m_ns as f64, met);
                     let actual_time = Duration::from_nanos(expected_ns as u64);
-                    print!("\nWarning: Unable to complete {} samples in {:.1?}. You may wish to increase target time to {:.1?}",
-                            n, target_time, actual_time);
-
-                    if recommended_sample_size != n {
-                        println!(
+                    let advice = if recommended_sample_size != n {
+                        format!(
                             ", enable flat sampling, or reduce sample count to {}.",
                             recommended_sample_size
-                        );
+                        )
                     } else {
-                        println!(" or enable flat sampling.");
-                    }
+                        " or enable flat sampling.".to_owned()
+                    };
+                    human_println!("\nWarning: Unable to complete {} samples in {:.1?}. You may wish to increase target time to {:.1?}{}",
+                            n, target_time, actual_time, advice);
                 }
 
                 (1..(n + 1) as u64).map(|a| a * d).collect::<Vec<u64>>()
@@ -1499,14 +3519,13 @@ impl ActualSamplingMode {
                     let recommended_sample_size =
                         ActualSamplingMode::recommend_flat_sample_size(m_ns, met);
                     let actual_time = Duration::from_nanos(expected_ns as u64);
-                    print!("\nWarning: Unable to complete {} samples in {:.1?}. You may wish to increase target time to {:.1?}",
-                            n, target_time, actual_time);
-
-                    if recommended_sample_size != n {
-                        println!(", or reduce sample count to {}.", recommended_sample_size);
+                    let advice = if recommended_sample_size != n {
+                        format!(", or reduce sample count to {}.", recommended_sample_size)
                     } else {
-                        println!(".");
-                    }
+                        ".".to_owned()
+                    };
+                    human_println!("\nWarning: Unable to complete {} samples in {:.1?}. You may wish to increase target time to {:.1?}{}",
+                            n, target_time, actual_time, advice);
                 }
 
                 vec![iterations_per_sample; n as usize]
@@ -1563,13 +3582,41 @@ pub(crate) struct SavedSample {
     times: Vec<f64>,
 }
 
+/// Merges the results of benchmarks which were run in several processes, such as the shards of a
+/// distributed CI job, into one set of reports in `output_directory`, as if they had all been run
+/// by one process. Each of the `shards` is a file holding the output of a run with
+/// `--message-format=json`. The samples of each benchmark are analyzed again with the default
+/// configuration and compared against the baselines in `output_directory`, and the summary
+/// reports cover the benchmarks of every shard. Use `Criterion::merge_reports` to merge them with
+/// another configuration or measurement.
+///
+/// Benchmark IDs must be unique across the shards. A benchmark which was run by more than one
+/// shard is reported as an error, and only its first result is merged. The same can be done from
+/// the command line with `cargo bench -- --merge <FILE>...`, which uses the configuration of the
+/// benchmark executable.
+///
+/// ```no_run
+/// use std::path::{Path, PathBuf};
+///
+/// let shards = [PathBuf::from("shard-1.json"), PathBuf::from("shard-2.json")];
+/// criterion::merge_reports(&shards, Path::new("target/criterion"));
+/// ```
+pub fn merge_reports<P: AsRef<Path>>(shards: &[P], output_directory: &Path) {
+    Criterion::default()
+        .output_directory(output_directory)
+        .merge_reports(shards)
+        .final_summary();
+}
+
 /// Custom-test-framework runner. Should not be called directly.
 #[doc(hidden)]
 pub fn runner(benches: &[&dyn Fn()]) {
     for bench in benches {
         bench();
     }
-    Criterion::default().configure_from_args().final_summary();
+    if !Criterion::default().configure_from_args().run().passed() {
+        std::process::exit(1);
+    }
 }
 
 /// Print a warning informing users about upcoming changes to features
@@ -1577,16 +3624,16 @@ pub fn runner(benches: &[&dyn Fn()]) {
 #[doc(hidden)]
 pub fn __warn_about_html_reports_feature() {
     if CARGO_CRITERION_CONNECTION.is_none() {
-        println!(
+        human_println!(
             "WARNING: HTML report generation will become a non-default optional feature in Criterion.rs 0.4.0."
         );
-        println!(
+        human_println!(
             "This feature is being moved to cargo-criterion \
             (https://github.com/bheisler/cargo-criterion) and will be optional in a future \
             version of Criterion.rs. To silence this warning, either switch to cargo-criterion or \
             enable the 'html_reports' feature in your Cargo.toml."
         );
-        println!();
+        human_println!();
     }
 }
 
@@ -1602,16 +3649,16 @@ pub fn __warn_about_html_reports_feature() {
 #[doc(hidden)]
 pub fn __warn_about_cargo_bench_support_feature() {
     if CARGO_CRITERION_CONNECTION.is_none() {
-        println!(
+        human_println!(
             "WARNING: In Criterion.rs 0.4.0, running criterion benchmarks outside of cargo-criterion will become a default optional feature."
         );
-        println!(
+        human_println!(
             "The statistical analysis and reporting is being moved to cargo-criterion \
             (https://github.com/bheisler/cargo-criterion) and will be optional in a future \
             version of Criterion.rs. To silence this warning, either switch to cargo-criterion or \
             enable the 'cargo_bench_support' feature in your Cargo.toml."
         );
-        println!();
+        human_println!();
     }
 }
 
@@ -114,7 +114,8 @@ macro_rules! criterion_group {
 /// ```
 ///
 /// The `criterion_main` macro expands to a `main` function which runs all of the
-/// benchmarks in the given groups.
+/// benchmarks in the given groups. If any benchmark regressed compared to its baseline, it exits
+/// with a nonzero status after the final summary; see `Criterion::run`.
 ///
 #[macro_export]
 macro_rules! criterion_main {
@@ -127,9 +128,49 @@ macro_rules! criterion_main {
                 $group();
             )+
 
-            $crate::Criterion::default()
+            let outcome = $crate::Criterion::default()
                 .configure_from_args()
-                .final_summary();
+                .run();
+            if !outcome.passed() {
+                ::std::process::exit(1);
+            }
         }
     }
 }
+
+/// Macro which benchmarks the same generic code once for each of a list of types, in a
+/// `BenchmarkGroup`. The type name is used as the function ID of each benchmark, so the benchmarks
+/// of `[u8, u64]` in the group `sum` have the IDs `sum/u8` and `sum/u64`.
+///
+/// A closure can't be generic, so the closure given to the macro names the type instead, like a
+/// type parameter: `|b, T|` runs the body with `b` bound to the `Bencher` and `T` standing for
+/// each of the types in turn.
+///
+/// # Examples:
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate criterion;
+/// # use criterion::{black_box, Criterion};
+/// fn bench_sum(c: &mut Criterion) {
+///     let mut group = c.benchmark_group("sum");
+///     bench_over_types!(group, [u8, u32, u64], |b, T| {
+///         let values = vec![1 as T; 100];
+///         b.iter(|| black_box(&values).iter().fold(0 as T, |sum, &value| sum.wrapping_add(value)))
+///     });
+///     group.finish();
+/// }
+/// #
+/// # fn main() {}
+/// ```
+#[macro_export]
+macro_rules! bench_over_types {
+    ($group:expr, [$( $ty:ty ),+ $(,)*], |$bencher:ident, $param:ident| $body:expr) => {{
+        let group: &mut $crate::BenchmarkGroup<_> = &mut $group;
+        $({
+            #[allow(non_camel_case_types, dead_code)]
+            type $param = $ty;
+            group.bench_function(stringify!($ty), |$bencher| $body);
+        })+
+    }};
+}
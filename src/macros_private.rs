@@ -30,18 +30,37 @@ macro_rules! try_else_return {
     };
 }
 
-/// Print an error message to stdout. Format is the same as println! or format!
+/// Print a message meant for people to stdout, or to stderr when stdout carries the JSON messages
+/// of `--message-format=json`. Format is the same as println! or format!
+macro_rules! human_println {
+    ($($arg:tt)*) => (
+        if $crate::json_messages_enabled() {
+            eprintln!($($arg)*);
+        } else {
+            println!($($arg)*);
+        }
+    )
+}
+
+/// Print an error message to stdout (stderr in JSON mode). Format is the same as println! or format!
 macro_rules! error {
     ($($arg:tt)*) => (
-        println!("Criterion.rs ERROR: {}", &format!($($arg)*))
+        human_println!("Criterion.rs ERROR: {}", &format!($($arg)*))
+    )
+}
+
+/// Print a warning message to stdout (stderr in JSON mode). Format is the same as println! or format!
+macro_rules! warn {
+    ($($arg:tt)*) => (
+        human_println!("Criterion.rs WARNING: {}", &format!($($arg)*))
     )
 }
 
-/// Print a debug message to stdout. Format is the same as println! or format!
+/// Print a debug message to stdout (stderr in JSON mode). Format is the same as println! or format!
 macro_rules! info {
     ($($arg:tt)*) => (
         if $crate::debug_enabled() {
-            println!("Criterion.rs DEBUG: {}", &format!($($arg)*));
+            human_println!("Criterion.rs DEBUG: {}", &format!($($arg)*));
         }
     )
 }
@@ -3,9 +3,15 @@
 //! includes the [WallTime](struct.WallTime.html) struct which defines the default wall-clock time
 //! measurement.
 
-use crate::format::short;
+use crate::format::{short, signed_short};
 use crate::DurationExt;
 use crate::Throughput;
+use crate::TimeUnit;
+#[cfg(feature = "alloc_counter")]
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::any::Any;
+#[cfg(feature = "alloc_counter")]
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::time::{Duration, Instant};
 
 /// Trait providing functions to format measured values to string so that they can be displayed on
@@ -62,6 +68,13 @@ pub trait ValueFormatter {
     /// values slice to apply the desired scaling (if any) and return a string representing the unit
     /// the modified values are in.
     fn scale_for_machines(&self, values: &mut [f64]) -> &'static str;
+
+    /// Returns true if the values can be negative. Plots of signed values use axes which are
+    /// symmetric around zero, and throughput is not reported for them. See
+    /// [SignedMeasurement](struct.SignedMeasurement.html).
+    fn is_signed(&self) -> bool {
+        false
+    }
 }
 
 /// Trait for all types which define something Criterion.rs can measure. The only measurement
@@ -101,6 +114,152 @@ pub trait Measurement {
 
     /// Return a trait-object reference to the value formatter for this measurement.
     fn formatter(&self) -> &dyn ValueFormatter;
+
+    /// Returns true if a measured value of zero is meaningful for this measurement, for example
+    /// because it counts events which may not happen at all. By default, Criterion.rs treats a
+    /// measurement of zero as an error, since no benchmark can take zero time to run.
+    fn allows_zero(&self) -> bool {
+        false
+    }
+
+    /// Identifies the type of measurement, so that the timer overhead is calibrated once per type.
+    /// `BoxedMeasurement` forwards this to the measurement it wraps.
+    #[doc(hidden)]
+    fn type_name(&self) -> &'static str {
+        std::any::type_name::<Self>()
+    }
+
+    /// Returns the names of the quantities measured along with the main value, for measurements
+    /// which record several values per sample, such as
+    /// [MultiMeasurement](struct.MultiMeasurement.html). Each of them is analyzed and reported as
+    /// a separate benchmark, whose function ID is the benchmark's followed by the name. Most
+    /// measurements have none.
+    fn secondary_names(&self) -> Vec<&'static str> {
+        vec![]
+    }
+
+    /// Converts the secondary quantities of the measured value to f64, in the order of
+    /// `secondary_names`.
+    fn secondary_to_f64(&self, _value: &Self::Value) -> Vec<f64> {
+        vec![]
+    }
+
+    /// Returns the value formatter for the secondary quantity at `index` in `secondary_names`.
+    fn secondary_formatter(&self, _index: usize) -> &dyn ValueFormatter {
+        self.formatter()
+    }
+}
+
+/// A measurement whose type is erased, so that a single `Criterion` can run benchmarks using
+/// different measurements. Each `BenchmarkGroup` can then select its own measurement with
+/// `BenchmarkGroup::measurement`, and the reports of its benchmarks use the units of that
+/// measurement's formatter.
+///
+/// The measured values are converted to `f64` with the wrapped measurement's `to_f64` at the end
+/// of each batch of iterations and summed as floating-point numbers. The intermediate value is
+/// boxed, but the box is allocated before the measurement starts, so this doesn't add to the
+/// measured time.
+///
+/// ```rust
+/// use criterion::measurement::{BoxedMeasurement, HighResWallTime, WallTime};
+/// use criterion::Criterion;
+///
+/// fn bench(c: &mut Criterion<BoxedMeasurement>) {
+///     let mut group = c.benchmark_group("high-res");
+///     group.measurement(BoxedMeasurement::new(HighResWallTime));
+///     group.bench_function("sum", |b| b.iter(|| (0..100u64).sum::<u64>()));
+///     group.finish();
+/// }
+///
+/// fn mixed_measurements() -> Criterion<BoxedMeasurement> {
+///     Criterion::default().with_measurement(BoxedMeasurement::new(WallTime))
+/// }
+/// ```
+pub struct BoxedMeasurement {
+    inner: Box<dyn ErasedMeasurement>,
+}
+impl BoxedMeasurement {
+    /// Wraps the given measurement.
+    pub fn new<M>(measurement: M) -> BoxedMeasurement
+    where
+        M: Measurement + 'static,
+        M::Intermediate: 'static,
+    {
+        BoxedMeasurement {
+            inner: Box::new(measurement),
+        }
+    }
+}
+impl Measurement for BoxedMeasurement {
+    type Intermediate = Box<dyn Any>;
+    type Value = f64;
+
+    fn start(&self) -> Self::Intermediate {
+        self.inner.start()
+    }
+    fn end(&self, i: Self::Intermediate) -> Self::Value {
+        self.inner.end(i)
+    }
+    fn add(&self, v1: &Self::Value, v2: &Self::Value) -> Self::Value {
+        *v1 + *v2
+    }
+    fn zero(&self) -> Self::Value {
+        0.0
+    }
+    fn to_f64(&self, val: &Self::Value) -> f64 {
+        *val
+    }
+    fn formatter(&self) -> &dyn ValueFormatter {
+        self.inner.formatter()
+    }
+    fn allows_zero(&self) -> bool {
+        self.inner.allows_zero()
+    }
+    fn type_name(&self) -> &'static str {
+        self.inner.type_name()
+    }
+}
+
+// The object-safe subset of `Measurement` used by `BoxedMeasurement`.
+trait ErasedMeasurement {
+    fn start(&self) -> Box<dyn Any>;
+    fn end(&self, i: Box<dyn Any>) -> f64;
+    fn formatter(&self) -> &dyn ValueFormatter;
+    fn allows_zero(&self) -> bool;
+    fn type_name(&self) -> &'static str;
+}
+impl<M> ErasedMeasurement for M
+where
+    M: Measurement,
+    M::Intermediate: 'static,
+{
+    fn start(&self) -> Box<dyn Any> {
+        // Allocate first, so that the allocation isn't part of the measurement.
+        let mut intermediate: Box<Option<M::Intermediate>> = Box::new(None);
+        *intermediate = Some(Measurement::start(self));
+        intermediate
+    }
+    fn end(&self, i: Box<dyn Any>) -> f64 {
+        let mut slot = match i.downcast::<Option<M::Intermediate>>() {
+            Ok(slot) => slot,
+            Err(_) => {
+                panic!("BoxedMeasurement was given the intermediate value of another measurement")
+            }
+        };
+        // The value is moved out of the box, so that the box is only freed after the measurement.
+        let value = Measurement::end(self, slot.take().unwrap());
+        drop(slot);
+        self.to_f64(&value)
+    }
+    fn formatter(&self) -> &dyn ValueFormatter {
+        Measurement::formatter(self)
+    }
+    fn allows_zero(&self) -> bool {
+        Measurement::allows_zero(self)
+    }
+    fn type_name(&self) -> &'static str {
+        Measurement::type_name(self)
+    }
 }
 
 pub(crate) struct DurationFormatter;
@@ -159,23 +318,14 @@ impl ValueFormatter for DurationFormatter {
     }
 
     fn scale_values(&self, ns: f64, values: &mut [f64]) -> &'static str {
-        let (factor, unit) = if ns < 10f64.powi(0) {
-            (10f64.powi(3), "ps")
-        } else if ns < 10f64.powi(3) {
-            (10f64.powi(0), "ns")
-        } else if ns < 10f64.powi(6) {
-            (10f64.powi(-3), "µs")
-        } else if ns < 10f64.powi(9) {
-            (10f64.powi(-6), "ms")
-        } else {
-            (10f64.powi(-9), "s")
-        };
+        let unit = TimeUnit::for_nanoseconds(ns);
+        let factor = unit.factor();
 
         for val in values {
             *val *= factor;
         }
 
-        unit
+        unit.symbol()
     }
 
     fn scale_for_machines(&self, _values: &mut [f64]) -> &'static str {
@@ -184,6 +334,254 @@ impl ValueFormatter for DurationFormatter {
     }
 }
 
+/// Displays times in a fixed unit and with a fixed number of decimal places if they are given,
+/// and otherwise defers to the wrapped formatter. Used to implement `Criterion::time_unit` and
+/// `Criterion::time_precision`.
+pub(crate) struct FixedTimeUnitFormatter<'a> {
+    pub(crate) inner: &'a dyn ValueFormatter,
+    pub(crate) unit: Option<TimeUnit>,
+    pub(crate) precision: Option<usize>,
+    // Transformed values aren't scaled, and are displayed with this unit instead.
+    pub(crate) value_unit: Option<&'static str>,
+}
+impl<'a> ValueFormatter for FixedTimeUnitFormatter<'a> {
+    fn format_value(&self, value: f64) -> String {
+        if let Some(value_unit) = self.value_unit {
+            return format!(
+                "{:>6} {}",
+                crate::format::with_precision(value, self.precision),
+                value_unit
+            );
+        }
+        match (self.unit, self.precision) {
+            (Some(unit), precision) => {
+                crate::format::time_with_precision(value, Some(unit), precision)
+            }
+            (None, Some(precision)) => {
+                let mut values = [value];
+                let unit = self.inner.scale_values(value, &mut values);
+                format!(
+                    "{:>6} {}",
+                    crate::format::with_precision(values[0], Some(precision)),
+                    unit
+                )
+            }
+            (None, None) => self.inner.format_value(value),
+        }
+    }
+
+    fn format_throughput(&self, throughput: &Throughput, value: f64) -> String {
+        self.inner.format_throughput(throughput, value)
+    }
+
+    fn scale_values(&self, typical_value: f64, values: &mut [f64]) -> &'static str {
+        if let Some(value_unit) = self.value_unit {
+            return value_unit;
+        }
+        match self.unit {
+            Some(unit) => {
+                for val in values {
+                    *val *= unit.factor();
+                }
+                unit.symbol()
+            }
+            None => self.inner.scale_values(typical_value, values),
+        }
+    }
+
+    fn scale_throughputs(
+        &self,
+        typical_value: f64,
+        throughput: &Throughput,
+        values: &mut [f64],
+    ) -> &'static str {
+        self.inner
+            .scale_throughputs(typical_value, throughput, values)
+    }
+
+    fn scale_for_machines(&self, values: &mut [f64]) -> &'static str {
+        match self.value_unit {
+            Some(value_unit) => value_unit,
+            None => self.inner.scale_for_machines(values),
+        }
+    }
+
+    fn is_signed(&self) -> bool {
+        self.inner.is_signed()
+    }
+}
+
+/// Wraps a measurement whose values can be negative, such as the net number of allocations
+/// (allocations minus frees) or a change in temperature. The values are formatted with their
+/// sign, a value of zero is not treated as an error, and the plots use axes which are symmetric
+/// around zero. Throughput is meaningless for such values, so it is not reported even if one is
+/// set.
+///
+/// ```rust
+/// use criterion::measurement::{SignedMeasurement, WallTime};
+/// use criterion::Criterion;
+///
+/// fn signed_measurement() -> Criterion<SignedMeasurement<WallTime>> {
+///     Criterion::default().with_measurement(SignedMeasurement(WallTime))
+/// }
+/// ```
+pub struct SignedMeasurement<M>(pub M);
+impl<M: Measurement> Measurement for SignedMeasurement<M> {
+    type Intermediate = M::Intermediate;
+    type Value = M::Value;
+
+    fn start(&self) -> Self::Intermediate {
+        self.0.start()
+    }
+    fn end(&self, i: Self::Intermediate) -> Self::Value {
+        self.0.end(i)
+    }
+    fn add(&self, v1: &Self::Value, v2: &Self::Value) -> Self::Value {
+        self.0.add(v1, v2)
+    }
+    fn zero(&self) -> Self::Value {
+        self.0.zero()
+    }
+    fn to_f64(&self, val: &Self::Value) -> f64 {
+        self.0.to_f64(val)
+    }
+    fn formatter(&self) -> &dyn ValueFormatter {
+        self
+    }
+    fn allows_zero(&self) -> bool {
+        true
+    }
+    fn type_name(&self) -> &'static str {
+        self.0.type_name()
+    }
+    fn secondary_names(&self) -> Vec<&'static str> {
+        self.0.secondary_names()
+    }
+    fn secondary_to_f64(&self, value: &Self::Value) -> Vec<f64> {
+        self.0.secondary_to_f64(value)
+    }
+    fn secondary_formatter(&self, index: usize) -> &dyn ValueFormatter {
+        self.0.secondary_formatter(index)
+    }
+}
+// The wrapped formatter chooses its unit for positive values, so it is given the magnitude.
+impl<M: Measurement> ValueFormatter for SignedMeasurement<M> {
+    fn format_value(&self, value: f64) -> String {
+        let mut values = [value];
+        let unit = self.scale_values(value, &mut values);
+        format!("{:>7} {}", signed_short(values[0]), unit)
+    }
+
+    fn format_throughput(&self, throughput: &Throughput, value: f64) -> String {
+        self.0.formatter().format_throughput(throughput, value)
+    }
+
+    fn scale_values(&self, typical_value: f64, values: &mut [f64]) -> &'static str {
+        self.0.formatter().scale_values(typical_value.abs(), values)
+    }
+
+    fn scale_throughputs(
+        &self,
+        typical_value: f64,
+        throughput: &Throughput,
+        values: &mut [f64],
+    ) -> &'static str {
+        self.0
+            .formatter()
+            .scale_throughputs(typical_value.abs(), throughput, values)
+    }
+
+    fn scale_for_machines(&self, values: &mut [f64]) -> &'static str {
+        self.0.formatter().scale_for_machines(values)
+    }
+
+    fn is_signed(&self) -> bool {
+        true
+    }
+}
+
+/// Takes two measurements in the same measurement loop, such as the time and the number of cache
+/// misses, so that the benchmark doesn't need to be run once for each. The first measurement is
+/// the main one, which the benchmark's results are about. The second one is analyzed and reported
+/// as a separate benchmark, with its own estimates, plots and comparisons, whose function ID is the
+/// benchmark's followed by `name`. Nesting a `MultiMeasurement` as the second measurement adds
+/// more quantities.
+///
+/// The second measurement is started before and ended after the first, so that the first doesn't
+/// include its overhead. The values returned from `Bencher::iter_custom` are pairs of the values
+/// of both measurements.
+///
+/// ```rust
+/// use criterion::measurement::{Measurement, MultiMeasurement, WallTime};
+/// use criterion::Criterion;
+///
+/// fn with_cache_misses<M: Measurement>(cache_misses: M) -> Criterion<MultiMeasurement<WallTime, M>> {
+///     Criterion::default().with_measurement(MultiMeasurement::new(WallTime, cache_misses, "cache-misses"))
+/// }
+/// ```
+pub struct MultiMeasurement<A, B> {
+    primary: A,
+    secondary: B,
+    name: &'static str,
+}
+impl<A: Measurement, B: Measurement> MultiMeasurement<A, B> {
+    /// Combines the `primary` measurement with a `secondary` one, which is reported under `name`.
+    pub fn new(primary: A, secondary: B, name: &'static str) -> MultiMeasurement<A, B> {
+        MultiMeasurement {
+            primary,
+            secondary,
+            name,
+        }
+    }
+}
+impl<A: Measurement, B: Measurement> Measurement for MultiMeasurement<A, B> {
+    type Intermediate = (A::Intermediate, B::Intermediate);
+    type Value = (A::Value, B::Value);
+
+    fn start(&self) -> Self::Intermediate {
+        let secondary = self.secondary.start();
+        (self.primary.start(), secondary)
+    }
+    fn end(&self, (primary, secondary): Self::Intermediate) -> Self::Value {
+        let primary = self.primary.end(primary);
+        (primary, self.secondary.end(secondary))
+    }
+    fn add(&self, v1: &Self::Value, v2: &Self::Value) -> Self::Value {
+        (
+            self.primary.add(&v1.0, &v2.0),
+            self.secondary.add(&v1.1, &v2.1),
+        )
+    }
+    fn zero(&self) -> Self::Value {
+        (self.primary.zero(), self.secondary.zero())
+    }
+    fn to_f64(&self, value: &Self::Value) -> f64 {
+        self.primary.to_f64(&value.0)
+    }
+    fn formatter(&self) -> &dyn ValueFormatter {
+        self.primary.formatter()
+    }
+    fn allows_zero(&self) -> bool {
+        self.primary.allows_zero()
+    }
+    fn secondary_names(&self) -> Vec<&'static str> {
+        let mut names = vec![self.name];
+        names.extend(self.secondary.secondary_names());
+        names
+    }
+    fn secondary_to_f64(&self, value: &Self::Value) -> Vec<f64> {
+        let mut values = vec![self.secondary.to_f64(&value.1)];
+        values.extend(self.secondary.secondary_to_f64(&value.1));
+        values
+    }
+    fn secondary_formatter(&self, index: usize) -> &dyn ValueFormatter {
+        match index {
+            0 => self.secondary.formatter(),
+            _ => self.secondary.secondary_formatter(index - 1),
+        }
+    }
+}
+
 /// `WallTime` is the default measurement in Criterion.rs. It measures the elapsed time from the
 /// beginning of a series of iterations to the end.
 pub struct WallTime;
@@ -210,3 +608,354 @@ impl Measurement for WallTime {
         &DurationFormatter
     }
 }
+
+/// `HighResWallTime` measures the elapsed wall-clock time like `WallTime`, but reads the
+/// platform's high-resolution monotonic clock directly instead of using `std::time::Instant`:
+/// `clock_gettime(CLOCK_MONOTONIC_RAW)` on Linux, `mach_absolute_time` on macOS and
+/// `QueryPerformanceCounter` on Windows. On platforms where `Instant` has a coarse granularity,
+/// this avoids quantizing the measurements of fast benchmarks. On other platforms it falls back
+/// to `Instant`.
+///
+/// ```rust
+/// use criterion::measurement::HighResWallTime;
+/// use criterion::Criterion;
+///
+/// fn high_res_measurement() -> Criterion<HighResWallTime> {
+///     Criterion::default().with_measurement(HighResWallTime)
+/// }
+/// ```
+pub struct HighResWallTime;
+impl Measurement for HighResWallTime {
+    type Intermediate = u64;
+    type Value = Duration;
+
+    fn start(&self) -> Self::Intermediate {
+        high_res_clock::ticks()
+    }
+    fn end(&self, i: Self::Intermediate) -> Self::Value {
+        let ticks = high_res_clock::ticks().saturating_sub(i);
+        Duration::from_nanos(high_res_clock::ticks_to_nanos(ticks))
+    }
+    fn add(&self, v1: &Self::Value, v2: &Self::Value) -> Self::Value {
+        *v1 + *v2
+    }
+    fn zero(&self) -> Self::Value {
+        Duration::from_secs(0)
+    }
+    fn to_f64(&self, val: &Self::Value) -> f64 {
+        val.to_nanos() as f64
+    }
+    fn formatter(&self) -> &dyn ValueFormatter {
+        &DurationFormatter
+    }
+}
+
+// Reads the monotonic clock used by `HighResWallTime`, in platform-specific ticks.
+mod high_res_clock {
+    #[cfg(target_os = "linux")]
+    pub fn ticks() -> u64 {
+        let mut ts = libc::timespec {
+            tv_sec: 0,
+            tv_nsec: 0,
+        };
+        // CLOCK_MONOTONIC_RAW is not subject to NTP frequency adjustments.
+        let result = unsafe { libc::clock_gettime(libc::CLOCK_MONOTONIC_RAW, &mut ts) };
+        assert_eq!(result, 0, "clock_gettime(CLOCK_MONOTONIC_RAW) failed");
+        ts.tv_sec as u64 * 1_000_000_000 + ts.tv_nsec as u64
+    }
+
+    #[cfg(target_os = "linux")]
+    pub fn ticks_to_nanos(ticks: u64) -> u64 {
+        ticks
+    }
+
+    #[cfg(target_os = "macos")]
+    mod mach {
+        #[repr(C)]
+        pub struct TimebaseInfo {
+            pub numer: u32,
+            pub denom: u32,
+        }
+
+        extern "C" {
+            pub fn mach_absolute_time() -> u64;
+            pub fn mach_timebase_info(info: *mut TimebaseInfo) -> i32;
+        }
+    }
+
+    #[cfg(target_os = "macos")]
+    lazy_static! {
+        // The ratio between mach_absolute_time ticks and nanoseconds.
+        static ref TIMEBASE: (u64, u64) = {
+            let mut info = mach::TimebaseInfo { numer: 0, denom: 0 };
+            let result = unsafe { mach::mach_timebase_info(&mut info) };
+            assert_eq!(result, 0, "mach_timebase_info failed");
+            (u64::from(info.numer), u64::from(info.denom))
+        };
+    }
+
+    #[cfg(target_os = "macos")]
+    pub fn ticks() -> u64 {
+        unsafe { mach::mach_absolute_time() }
+    }
+
+    #[cfg(target_os = "macos")]
+    pub fn ticks_to_nanos(ticks: u64) -> u64 {
+        let (numer, denom) = *TIMEBASE;
+        (u128::from(ticks) * u128::from(numer) / u128::from(denom)) as u64
+    }
+
+    #[cfg(windows)]
+    mod kernel32 {
+        #[link(name = "kernel32")]
+        extern "system" {
+            pub fn QueryPerformanceCounter(count: *mut i64) -> i32;
+            pub fn QueryPerformanceFrequency(frequency: *mut i64) -> i32;
+        }
+    }
+
+    #[cfg(windows)]
+    lazy_static! {
+        // Ticks per second of QueryPerformanceCounter.
+        static ref FREQUENCY: u64 = {
+            let mut frequency = 0;
+            let result = unsafe { kernel32::QueryPerformanceFrequency(&mut frequency) };
+            assert_ne!(result, 0, "QueryPerformanceFrequency failed");
+            frequency as u64
+        };
+    }
+
+    #[cfg(windows)]
+    pub fn ticks() -> u64 {
+        let mut count = 0;
+        let result = unsafe { kernel32::QueryPerformanceCounter(&mut count) };
+        assert_ne!(result, 0, "QueryPerformanceCounter failed");
+        count as u64
+    }
+
+    #[cfg(windows)]
+    pub fn ticks_to_nanos(ticks: u64) -> u64 {
+        (u128::from(ticks) * 1_000_000_000 / u128::from(*FREQUENCY)) as u64
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+    lazy_static! {
+        static ref EPOCH: std::time::Instant = std::time::Instant::now();
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+    pub fn ticks() -> u64 {
+        EPOCH.elapsed().as_nanos() as u64
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+    pub fn ticks_to_nanos(ticks: u64) -> u64 {
+        ticks
+    }
+}
+
+/// A global allocator which wraps the system allocator and keeps track of the number and size of
+/// allocations, for use with the [Allocations](struct.Allocations.html) and
+/// [AllocationCount](struct.AllocationCount.html) measurements.
+///
+/// The allocation measurements only work if this allocator is installed as the global allocator
+/// of the benchmark executable:
+///
+/// ```rust,ignore
+/// use criterion::measurement::CountingAllocator;
+///
+/// #[global_allocator]
+/// static ALLOCATOR: CountingAllocator = CountingAllocator;
+/// ```
+#[cfg(feature = "alloc_counter")]
+pub struct CountingAllocator;
+
+#[cfg(feature = "alloc_counter")]
+static ALLOCATED_BYTES: AtomicUsize = AtomicUsize::new(0);
+#[cfg(feature = "alloc_counter")]
+static ALLOCATION_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+#[cfg(feature = "alloc_counter")]
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATED_BYTES.fetch_add(layout.size(), Ordering::Relaxed);
+        ALLOCATION_COUNT.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        ALLOCATED_BYTES.fetch_add(layout.size(), Ordering::Relaxed);
+        ALLOCATION_COUNT.fetch_add(1, Ordering::Relaxed);
+        System.alloc_zeroed(layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATED_BYTES.fetch_add(new_size, Ordering::Relaxed);
+        ALLOCATION_COUNT.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[cfg(feature = "alloc_counter")]
+pub(crate) struct BytesFormatter;
+#[cfg(feature = "alloc_counter")]
+impl ValueFormatter for BytesFormatter {
+    fn scale_throughputs(
+        &self,
+        _typical: f64,
+        throughput: &Throughput,
+        values: &mut [f64],
+    ) -> &'static str {
+        match *throughput {
+            Throughput::Bytes(bytes) => {
+                for val in values {
+                    *val /= bytes as f64;
+                }
+                "B/byte"
+            }
+            Throughput::Elements(elems) => {
+                for val in values {
+                    *val /= elems as f64;
+                }
+                "B/elem"
+            }
+        }
+    }
+
+    fn scale_values(&self, bytes: f64, values: &mut [f64]) -> &'static str {
+        let (denominator, unit) = if bytes < 1024.0 {
+            (1.0, "  B")
+        } else if bytes < 1024.0 * 1024.0 {
+            (1024.0, "KiB")
+        } else if bytes < 1024.0 * 1024.0 * 1024.0 {
+            (1024.0 * 1024.0, "MiB")
+        } else {
+            (1024.0 * 1024.0 * 1024.0, "GiB")
+        };
+
+        for val in values {
+            *val /= denominator;
+        }
+
+        unit
+    }
+
+    fn scale_for_machines(&self, _values: &mut [f64]) -> &'static str {
+        // no scaling is needed
+        "B"
+    }
+}
+
+/// Measures the number of bytes allocated on the heap by the benchmarked code. Reallocations are
+/// counted as allocations of the new size, and deallocations are not subtracted.
+///
+/// This measurement requires the `alloc_counter` feature, and the
+/// [CountingAllocator](struct.CountingAllocator.html) must be installed as the global allocator of
+/// the benchmark executable; otherwise every benchmark will appear to allocate nothing. The
+/// allocation counter is shared by all threads, so this measurement is only accurate for
+/// single-threaded benchmarks.
+#[cfg(feature = "alloc_counter")]
+pub struct Allocations;
+#[cfg(feature = "alloc_counter")]
+impl Measurement for Allocations {
+    type Intermediate = usize;
+    type Value = usize;
+
+    fn start(&self) -> Self::Intermediate {
+        ALLOCATED_BYTES.load(Ordering::SeqCst)
+    }
+    fn end(&self, i: Self::Intermediate) -> Self::Value {
+        ALLOCATED_BYTES.load(Ordering::SeqCst).wrapping_sub(i)
+    }
+    fn add(&self, v1: &Self::Value, v2: &Self::Value) -> Self::Value {
+        *v1 + *v2
+    }
+    fn zero(&self) -> Self::Value {
+        0
+    }
+    fn to_f64(&self, val: &Self::Value) -> f64 {
+        *val as f64
+    }
+    fn formatter(&self) -> &dyn ValueFormatter {
+        &BytesFormatter
+    }
+    fn allows_zero(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(feature = "alloc_counter")]
+pub(crate) struct CountFormatter;
+#[cfg(feature = "alloc_counter")]
+impl ValueFormatter for CountFormatter {
+    fn scale_throughputs(
+        &self,
+        _typical: f64,
+        throughput: &Throughput,
+        values: &mut [f64],
+    ) -> &'static str {
+        match *throughput {
+            Throughput::Bytes(bytes) => {
+                for val in values {
+                    *val /= bytes as f64;
+                }
+                "allocs/byte"
+            }
+            Throughput::Elements(elems) => {
+                for val in values {
+                    *val /= elems as f64;
+                }
+                "allocs/elem"
+            }
+        }
+    }
+
+    fn scale_values(&self, _count: f64, _values: &mut [f64]) -> &'static str {
+        "allocs"
+    }
+
+    fn scale_for_machines(&self, _values: &mut [f64]) -> &'static str {
+        "allocs"
+    }
+}
+
+/// Measures the number of heap allocations made by the benchmarked code, regardless of their size.
+/// Reallocations are counted as allocations. This is useful for checking that a code path does not
+/// allocate at all, in which case the median will be zero.
+///
+/// Like [Allocations](struct.Allocations.html), this measurement requires the `alloc_counter`
+/// feature and the [CountingAllocator](struct.CountingAllocator.html) to be installed as the global
+/// allocator, and is only accurate for single-threaded benchmarks.
+#[cfg(feature = "alloc_counter")]
+pub struct AllocationCount;
+#[cfg(feature = "alloc_counter")]
+impl Measurement for AllocationCount {
+    type Intermediate = usize;
+    type Value = usize;
+
+    fn start(&self) -> Self::Intermediate {
+        ALLOCATION_COUNT.load(Ordering::SeqCst)
+    }
+    fn end(&self, i: Self::Intermediate) -> Self::Value {
+        ALLOCATION_COUNT.load(Ordering::SeqCst).wrapping_sub(i)
+    }
+    fn add(&self, v1: &Self::Value, v2: &Self::Value) -> Self::Value {
+        *v1 + *v2
+    }
+    fn zero(&self) -> Self::Value {
+        0
+    }
+    fn to_f64(&self, val: &Self::Value) -> f64 {
+        *val as f64
+    }
+    fn formatter(&self) -> &dyn ValueFormatter {
+        &CountFormatter
+    }
+    fn allows_zero(&self) -> bool {
+        true
+    }
+}
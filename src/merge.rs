@@ -0,0 +1,120 @@
+use crate::benchmark_group::{BenchmarkId, IntoBenchmarkId};
+use crate::error::{log_error, Error};
+use crate::measurement::Measurement;
+use crate::{Criterion, Throughput};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+// The messages of the `--message-format=json` protocol which are merged. Every other message is
+// skipped.
+#[derive(Deserialize)]
+#[serde(tag = "reason", rename_all = "kebab-case")]
+enum ShardMessage {
+    BenchmarkComplete(ShardBenchmark),
+    #[serde(other)]
+    Other,
+}
+
+// The parts of a `benchmark-complete` message needed to analyze the benchmark again.
+#[derive(Deserialize)]
+struct ShardBenchmark {
+    id: String,
+    group_id: String,
+    function_id: Option<String>,
+    value_str: Option<String>,
+    throughput: Option<ShardThroughput>,
+    unit: String,
+    iteration_count: Vec<f64>,
+    measured_values: Vec<f64>,
+}
+
+#[derive(Deserialize)]
+struct ShardThroughput {
+    per_iteration: u64,
+    unit: String,
+}
+
+/// Analyzes and reports the benchmarks recorded in each of the `shards` with `criterion`, as if
+/// they had all been run by it. A benchmark which appears more than once is reported as an error,
+/// and only its first result is merged.
+pub(crate) fn merge<M: Measurement, P: AsRef<Path>>(criterion: &mut Criterion<M>, shards: &[P]) {
+    let unit = criterion
+        .measurement
+        .formatter()
+        .scale_for_machines(&mut []);
+
+    // The benchmarks of each group, in the order the groups were first seen.
+    let mut groups: Vec<(String, Vec<ShardBenchmark>)> = vec![];
+    let mut sources: HashMap<String, PathBuf> = HashMap::new();
+    for shard in shards {
+        let shard = shard.as_ref();
+        let text = match std::fs::read_to_string(shard) {
+            Ok(text) => text,
+            Err(inner) => {
+                log_error(&Error::AccessError {
+                    path: shard.to_owned(),
+                    inner,
+                });
+                continue;
+            }
+        };
+
+        // The file may also hold other output, so lines which aren't messages are skipped.
+        for line in text.lines() {
+            let benchmark = match serde_json::from_str(line) {
+                Ok(ShardMessage::BenchmarkComplete(benchmark)) => benchmark,
+                _ => continue,
+            };
+            if let Some(first) = sources.get(&benchmark.id) {
+                error!(
+                    "Benchmark {} was run by both {:?} and {:?}; only the first result is merged",
+                    benchmark.id, first, shard
+                );
+                continue;
+            }
+            if benchmark.unit != unit {
+                error!(
+                    "Benchmark {} in {:?} was measured in {}, but is merged as {}; skipping it",
+                    benchmark.id, shard, benchmark.unit, unit
+                );
+                continue;
+            }
+            sources.insert(benchmark.id.clone(), shard.to_owned());
+            match groups
+                .iter_mut()
+                .find(|(group_id, _)| *group_id == benchmark.group_id)
+            {
+                Some((_, benchmarks)) => benchmarks.push(benchmark),
+                None => groups.push((benchmark.group_id.clone(), vec![benchmark])),
+            }
+        }
+    }
+
+    for (group_id, benchmarks) in groups {
+        let mut group = criterion.benchmark_group(group_id);
+        for benchmark in benchmarks {
+            let throughput = benchmark
+                .throughput
+                .map(|throughput| match &*throughput.unit {
+                    "bytes" => Throughput::Bytes(throughput.per_iteration),
+                    _ => Throughput::Elements(throughput.per_iteration),
+                });
+            group.set_throughput(throughput);
+
+            let id = match (benchmark.function_id, benchmark.value_str) {
+                (Some(function_id), Some(value_str)) => BenchmarkId::new(function_id, value_str),
+                (None, Some(value_str)) => BenchmarkId::from_parameter(value_str),
+                (Some(function_id), None) => function_id.into_benchmark_id(),
+                (None, None) => BenchmarkId::no_function(),
+            };
+            let samples = benchmark
+                .iteration_count
+                .iter()
+                .zip(&benchmark.measured_values)
+                .map(|(&iters, &value)| (iters as u64, value))
+                .collect::<Vec<_>>();
+            group.analyze(id, &samples);
+        }
+        group.finish();
+    }
+}
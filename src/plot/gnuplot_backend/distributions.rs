@@ -21,6 +21,7 @@ fn abs_distribution(
     estimate: &Estimate,
     size: Option<Size>,
 ) -> Child {
+    let colors = colors(context);
     let ci = &estimate.confidence_interval;
     let typical = ci.upper_bound;
     let mut ci_values = [ci.lower_bound, ci.upper_bound, estimate.point_estimate];
@@ -87,7 +88,7 @@ fn abs_distribution(
                 y: &*ys,
             },
             |c| {
-                c.set(DARK_BLUE)
+                c.set(colors.new)
                     .set(LINEWIDTH)
                     .set(Label("Bootstrap distribution"))
                     .set(LineType::Solid)
@@ -100,7 +101,7 @@ fn abs_distribution(
                 y2: zero,
             },
             |c| {
-                c.set(DARK_BLUE)
+                c.set(colors.new)
                     .set(Label("Confidence interval"))
                     .set(Opacity(0.25))
             },
@@ -111,7 +112,7 @@ fn abs_distribution(
                 y: &[0., y_point],
             },
             |c| {
-                c.set(DARK_BLUE)
+                c.set(colors.new)
                     .set(LINEWIDTH)
                     .set(Label("Point estimate"))
                     .set(LineType::Dash)
@@ -119,8 +120,7 @@ fn abs_distribution(
         );
 
     let path = context.report_path(id, &format!("{}.svg", statistic));
-    debug_script(&path, &figure);
-    figure.set(Output(path)).draw().unwrap()
+    draw(&mut figure, context, path)
 }
 
 pub(crate) fn abs_distributions(
@@ -163,6 +163,7 @@ fn rel_distribution(
     noise_threshold: f64,
     size: Option<Size>,
 ) -> Child {
+    let colors = colors(context);
     let ci = &estimate.confidence_interval;
     let (lb, ub) = (ci.lower_bound, ci.upper_bound);
 
@@ -238,7 +239,7 @@ fn rel_distribution(
                 .set(ScaleFactor(100.))
         })
         .plot(Lines { x: &*xs, y: &*ys }, |c| {
-            c.set(DARK_BLUE)
+            c.set(colors.new)
                 .set(LINEWIDTH)
                 .set(Label("Bootstrap distribution"))
                 .set(LineType::Solid)
@@ -250,7 +251,7 @@ fn rel_distribution(
                 y2: zero.clone(),
             },
             |c| {
-                c.set(DARK_BLUE)
+                c.set(colors.new)
                     .set(Label("Confidence interval"))
                     .set(Opacity(0.25))
             },
@@ -261,7 +262,7 @@ fn rel_distribution(
                 y: &[0., y_point],
             },
             |c| {
-                c.set(DARK_BLUE)
+                c.set(colors.new)
                     .set(LINEWIDTH)
                     .set(Label("Point estimate"))
                     .set(LineType::Dash)
@@ -275,15 +276,14 @@ fn rel_distribution(
             },
             |c| {
                 c.set(Axes::BottomXRightY)
-                    .set(DARK_RED)
+                    .set(colors.base)
                     .set(Label("Noise threshold"))
                     .set(Opacity(0.1))
             },
         );
 
     let path = context.report_path(id, &format!("change/{}.svg", statistic));
-    debug_script(&path, &figure);
-    figure.set(Output(path)).draw().unwrap()
+    draw(&mut figure, context, path)
 }
 
 pub(crate) fn rel_distributions(
@@ -293,7 +293,8 @@ pub(crate) fn rel_distributions(
     comparison: &ComparisonData,
     size: Option<Size>,
 ) -> Vec<Child> {
-    crate::plot::CHANGE_STATS
+    let statistics = comparison.relative_distributions.statistics();
+    statistics
         .iter()
         .map(|&statistic| {
             rel_distribution(
@@ -11,6 +11,7 @@ fn iteration_times_figure(
     formatter: &dyn ValueFormatter,
     measurements: &MeasurementData<'_>,
     size: Option<Size>,
+    colors: &Colors,
 ) -> Figure {
     let data = &measurements.avg_times;
     let max_avg_time = data.max();
@@ -27,7 +28,11 @@ fn iteration_times_figure(
         })
         .configure(Axis::LeftY, |a| {
             a.configure(Grid::Major, |g| g.show())
-                .set(Label(format!("Average Iteration Time ({})", unit)))
+                .set(Label(format!("Average Iteration Time ({})", unit)));
+            if let Some((lower, upper)) = crate::plot::symmetric_limits(formatter, scaled_y) {
+                a.set(Range::Limits(lower, upper));
+            }
+            a
         })
         .plot(
             Points {
@@ -35,7 +40,7 @@ fn iteration_times_figure(
                 y: scaled_y.as_ref(),
             },
             |c| {
-                c.set(DARK_BLUE)
+                c.set(colors.new)
                     .set(PointSize(0.5))
                     .set(PointType::FilledCircle)
             },
@@ -50,7 +55,7 @@ pub(crate) fn iteration_times(
     measurements: &MeasurementData<'_>,
     size: Option<Size>,
 ) -> Child {
-    let mut figure = iteration_times_figure(formatter, measurements, size);
+    let mut figure = iteration_times_figure(formatter, measurements, size, &colors(context));
     figure.set(Title(gnuplot_escape(id.as_title())));
     figure.configure(Key, |k| {
         k.set(Justification::Left)
@@ -59,8 +64,43 @@ pub(crate) fn iteration_times(
     });
 
     let path = context.report_path(id, "iteration_times.svg");
-    debug_script(&path, &figure);
-    figure.set(Output(path)).draw().unwrap()
+    draw(&mut figure, context, path)
+}
+
+pub(crate) fn sample_order(
+    id: &BenchmarkId,
+    context: &ReportContext,
+    formatter: &dyn ValueFormatter,
+    measurements: &MeasurementData<'_>,
+    size: Option<Size>,
+) -> Child {
+    let colors = colors(context);
+    let mut scaled_y: Vec<_> = measurements.avg_times.iter().map(|(f, _)| f).collect();
+    formatter.scale_values(measurements.avg_times.max(), &mut scaled_y);
+    let (intercept, slope) = crate::plot::sample_order_trend(&scaled_y);
+    let xs = [1.0, scaled_y.len() as f64];
+    let trend = [intercept + slope * xs[0], intercept + slope * xs[1]];
+
+    let mut figure = iteration_times_figure(formatter, measurements, size, &colors);
+    figure
+        .set(Title(format!(
+            "{}: Sample Order",
+            gnuplot_escape(id.as_title())
+        )))
+        .configure(Key, |k| {
+            k.set(Justification::Left)
+                .set(Order::SampleText)
+                .set(Position::Inside(Vertical::Top, Horizontal::Left))
+        })
+        .plot(Lines { x: &xs, y: &trend }, |c| {
+            c.set(colors.highlight)
+                .set(LINEWIDTH)
+                .set(Label("Trend"))
+                .set(LineType::Solid)
+        });
+
+    let path = context.report_path(id, "sample_order.svg");
+    draw(&mut figure, context, path)
 }
 
 pub(crate) fn iteration_times_small(
@@ -70,12 +110,11 @@ pub(crate) fn iteration_times_small(
     measurements: &MeasurementData<'_>,
     size: Option<Size>,
 ) -> Child {
-    let mut figure = iteration_times_figure(formatter, measurements, size);
+    let mut figure = iteration_times_figure(formatter, measurements, size, &colors(context));
     figure.configure(Key, |k| k.hide());
 
     let path = context.report_path(id, "iteration_times_small.svg");
-    debug_script(&path, &figure);
-    figure.set(Output(path)).draw().unwrap()
+    draw(&mut figure, context, path)
 }
 
 fn iteration_times_comparison_figure(
@@ -83,6 +122,7 @@ fn iteration_times_comparison_figure(
     measurements: &MeasurementData<'_>,
     comparison: &ComparisonData,
     size: Option<Size>,
+    colors: &Colors,
 ) -> Figure {
     let current_data = &measurements.avg_times;
     let base_data = &comparison.base_avg_times;
@@ -106,7 +146,11 @@ fn iteration_times_comparison_figure(
         })
         .configure(Axis::LeftY, |a| {
             a.configure(Grid::Major, |g| g.show())
-                .set(Label(format!("Average Iteration Time ({})", unit)))
+                .set(Label(format!("Average Iteration Time ({})", unit)));
+            if let Some((lower, upper)) = crate::plot::symmetric_limits(formatter, &all_data) {
+                a.set(Range::Limits(lower, upper));
+            }
+            a
         })
         .configure(Key, |k| {
             k.set(Justification::Left)
@@ -119,7 +163,7 @@ fn iteration_times_comparison_figure(
                 y: scaled_base_y.as_ref(),
             },
             |c| {
-                c.set(DARK_RED)
+                c.set(colors.base)
                     .set(Label("Base"))
                     .set(PointSize(0.5))
                     .set(PointType::FilledCircle)
@@ -131,7 +175,7 @@ fn iteration_times_comparison_figure(
                 y: scaled_current_y.as_ref(),
             },
             |c| {
-                c.set(DARK_BLUE)
+                c.set(colors.new)
                     .set(Label("Current"))
                     .set(PointSize(0.5))
                     .set(PointType::FilledCircle)
@@ -148,12 +192,17 @@ pub(crate) fn iteration_times_comparison(
     comparison: &ComparisonData,
     size: Option<Size>,
 ) -> Child {
-    let mut figure = iteration_times_comparison_figure(formatter, measurements, comparison, size);
+    let mut figure = iteration_times_comparison_figure(
+        formatter,
+        measurements,
+        comparison,
+        size,
+        &colors(context),
+    );
     figure.set(Title(gnuplot_escape(id.as_title())));
 
     let path = context.report_path(id, "both/iteration_times.svg");
-    debug_script(&path, &figure);
-    figure.set(Output(path)).draw().unwrap()
+    draw(&mut figure, context, path)
 }
 
 pub(crate) fn iteration_times_comparison_small(
@@ -164,10 +213,15 @@ pub(crate) fn iteration_times_comparison_small(
     comparison: &ComparisonData,
     size: Option<Size>,
 ) -> Child {
-    let mut figure = iteration_times_comparison_figure(formatter, measurements, comparison, size);
+    let mut figure = iteration_times_comparison_figure(
+        formatter,
+        measurements,
+        comparison,
+        size,
+        &colors(context),
+    );
     figure.configure(Key, |k| k.hide());
 
     let path = context.report_path(id, "relative_iteration_times_small.svg");
-    debug_script(&path, &figure);
-    figure.set(Output(path)).draw().unwrap()
+    draw(&mut figure, context, path)
 }
@@ -1,5 +1,5 @@
 use std::iter;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::Child;
 
 use crate::stats::univariate::Sample;
@@ -19,11 +19,12 @@ use self::summary::*;
 use self::t_test::*;
 
 use crate::measurement::ValueFormatter;
-use crate::report::{BenchmarkId, ValueType};
+use crate::report::{BenchmarkId, ReportContext, ValueType};
 use crate::stats::bivariate::Data;
 
-use super::{PlotContext, PlotData, Plotter};
+use super::{PlotContext, PlotData, Plotter, RelativeThroughput, WorkingSet};
 use crate::format;
+use crate::Palette;
 
 fn gnuplot_escape(string: &str) -> String {
     string.replace('_', "\\_").replace('\'', "''")
@@ -40,18 +41,111 @@ const DARK_BLUE: Color = Color::Rgb(31, 120, 180);
 const DARK_ORANGE: Color = Color::Rgb(255, 127, 0);
 const DARK_RED: Color = Color::Rgb(227, 26, 28);
 
-fn debug_script(path: &Path, figure: &Figure) {
-    if crate::debug_enabled() {
+const NUM_COLORS: usize = 8;
+static COMPARISON_COLORS: [Color; NUM_COLORS] = [
+    Color::Rgb(178, 34, 34),
+    Color::Rgb(46, 139, 87),
+    Color::Rgb(0, 139, 139),
+    Color::Rgb(255, 215, 0),
+    Color::Rgb(0, 0, 139),
+    Color::Rgb(220, 20, 60),
+    Color::Rgb(139, 0, 139),
+    Color::Rgb(0, 255, 127),
+];
+
+// The Okabe-Ito palette, ordered so that neighboring functions of the summary plots contrast.
+static OKABE_ITO: [Color; NUM_COLORS] = [
+    Color::Rgb(0, 114, 178),
+    Color::Rgb(213, 94, 0),
+    Color::Rgb(0, 158, 115),
+    Color::Rgb(230, 159, 0),
+    Color::Rgb(204, 121, 167),
+    Color::Rgb(86, 180, 233),
+    Color::Rgb(240, 228, 66),
+    Color::Rgb(0, 0, 0),
+];
+
+// The colors of the palette set with `Criterion::plot_palette`. The new sample is drawn in `new`
+// and the base sample in `base`, while `highlight` marks mild outliers and secondary lines. The
+// functions of the summary plots cycle through `comparison`.
+struct Colors {
+    new: Color,
+    base: Color,
+    highlight: Color,
+    comparison: Vec<Color>,
+}
+impl Colors {
+    fn comparison(&self, index: usize) -> Color {
+        self.comparison[index % self.comparison.len()]
+    }
+}
+
+fn colors(context: &ReportContext) -> Colors {
+    match &context.plot_palette {
+        Palette::Default => Colors {
+            new: DARK_BLUE,
+            base: DARK_RED,
+            highlight: DARK_ORANGE,
+            comparison: COMPARISON_COLORS.to_vec(),
+        },
+        Palette::ColorblindSafe => Colors {
+            new: OKABE_ITO[0],
+            base: OKABE_ITO[1],
+            highlight: OKABE_ITO[3],
+            comparison: OKABE_ITO.to_vec(),
+        },
+        Palette::Custom(colors) => {
+            let comparison = colors
+                .iter()
+                .map(|&crate::Color(r, g, b)| Color::Rgb(r, g, b))
+                .collect::<Vec<_>>();
+            Colors {
+                new: comparison[0],
+                base: comparison[1 % comparison.len()],
+                highlight: comparison[2 % comparison.len()],
+                comparison,
+            }
+        }
+    }
+}
+
+fn write_script(emit_script: bool, path: &Path, figure: &Figure) {
+    if emit_script || crate::debug_enabled() {
         let mut script_path = path.to_path_buf();
         script_path.set_extension("gnuplot");
         info!("Writing gnuplot script to {:?}", script_path);
         let result = figure.save(script_path.as_path());
         if let Err(e) = result {
-            error!("Failed to write debug output: {}", e);
+            error!("Failed to write gnuplot script: {}", e);
         }
     }
 }
 
+// Draws `figure` to the SVG file at `path` with the font configured for the report, and also to a
+// PNG file next to it if a resolution was set with `Criterion::plot_terminal`. Both images are
+// drawn by the same gnuplot process.
+fn draw(figure: &mut Figure, context: &ReportContext, path: PathBuf) -> Child {
+    if let Some(font) = context.plot_terminal.font_setting() {
+        figure.set(Font(font.to_owned()));
+    }
+    if let Some(size) = context.plot_font_size {
+        figure.set(FontSize(size));
+    }
+    if let Some(annotation) = context.annotation() {
+        // criterion-plot escapes the quotes in the label itself.
+        figure.set(Label(annotation.replace('_', "\\_")));
+    }
+    write_script(context.emit_gnuplot_scripts, &path, figure);
+
+    let png_path = path.with_extension("png");
+    let mut child = figure.set(Output(path)).draw().unwrap();
+    if let Some(dpi) = context.plot_terminal.dpi_setting() {
+        figure.set(Terminal::Png(dpi)).set(Output(png_path));
+        figure.dump(child.stdin.as_mut().unwrap()).unwrap();
+    }
+    child
+}
+
 /// Private
 trait Append<T> {
     /// Private
@@ -163,6 +257,17 @@ impl Plotter for Gnuplot {
         });
     }
 
+    fn sample_order(&mut self, ctx: PlotContext<'_>, data: PlotData<'_>) {
+        let size = ctx.size.map(|(w, h)| Size(w, h));
+        self.process_list.push(sample_order(
+            ctx.id,
+            ctx.context,
+            data.formatter,
+            data.measurements,
+            size,
+        ));
+    }
+
     fn abs_distributions(&mut self, ctx: PlotContext<'_>, data: PlotData<'_>) {
         let size = ctx.size.map(|(w, h)| Size(w, h));
         self.process_list.extend(abs_distributions(
@@ -214,6 +319,8 @@ impl Plotter for Gnuplot {
             &path,
             value_type,
             ctx.context.plot_config.summary_scale,
+            ctx.context,
+            ctx.size.map(|(w, h)| Size(w, h)),
         ));
     }
 
@@ -231,6 +338,36 @@ impl Plotter for Gnuplot {
             all_curves,
             &violin_path,
             ctx.context.plot_config.summary_scale,
+            ctx.context,
+            ctx.size.map(|(w, h)| Size(w, h)),
+        ));
+    }
+
+    fn relative_throughput(&mut self, ctx: PlotContext<'_>, data: &RelativeThroughput<'_>) {
+        let path = ctx.relative_throughput_path();
+        self.process_list.push(relative_throughput(
+            ctx.id.as_title(),
+            data,
+            &path,
+            ctx.context,
+            ctx.size.map(|(w, h)| Size(w, h)),
+        ));
+    }
+
+    fn working_set(
+        &mut self,
+        ctx: PlotContext<'_>,
+        formatter: &dyn ValueFormatter,
+        data: &WorkingSet<'_>,
+    ) {
+        let path = ctx.working_set_path();
+        self.process_list.push(working_set(
+            formatter,
+            ctx.id.as_title(),
+            data,
+            &path,
+            ctx.context,
+            ctx.size.map(|(w, h)| Size(w, h)),
         ));
     }
 
@@ -248,7 +385,7 @@ impl Plotter for Gnuplot {
         info!(
             "Waiting for {} gnuplot processes took {}",
             child_count,
-            format::time(crate::DurationExt::to_nanos(elapsed) as f64)
+            format::time(crate::DurationExt::to_nanos(elapsed) as f64, None)
         );
     }
 }
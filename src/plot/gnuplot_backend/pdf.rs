@@ -1,6 +1,7 @@
 use super::*;
 use crate::kde;
 use crate::measurement::ValueFormatter;
+use crate::plot::outliers_on_density;
 use crate::report::{BenchmarkId, ComparisonData, MeasurementData, ReportContext};
 use std::process::Child;
 
@@ -11,6 +12,7 @@ pub(crate) fn pdf(
     measurements: &MeasurementData<'_>,
     size: Option<Size>,
 ) -> Child {
+    let colors = colors(context);
     let avg_times = &measurements.avg_times;
     let typical = avg_times.max();
     let mut scaled_avg_times: Vec<f64> = (avg_times as &Sample<f64>).iter().cloned().collect();
@@ -42,14 +44,17 @@ pub(crate) fn pdf(
     let vertical = &[0., max_iters];
     let zeros = iter::repeat(0);
 
+    let xs_ = Sample::new(&xs);
+    let (x_min, x_max) =
+        crate::plot::symmetric_limits(formatter, &xs).unwrap_or((xs_.min(), xs_.max()));
+
     let mut figure = Figure::new();
     figure
         .set(Font(DEFAULT_FONT))
         .set(size.unwrap_or(SIZE))
         .configure(Axis::BottomX, |a| {
-            let xs_ = Sample::new(&xs);
             a.set(Label(format!("Average time ({})", unit)))
-                .set(Range::Limits(xs_.min(), xs_.max()))
+                .set(Range::Limits(x_min, x_max))
         })
         .configure(Axis::LeftY, |a| {
             a.set(Label(y_label))
@@ -70,7 +75,7 @@ pub(crate) fn pdf(
             },
             |c| {
                 c.set(Axes::BottomXRightY)
-                    .set(DARK_BLUE)
+                    .set(colors.new)
                     .set(Label("PDF"))
                     .set(Opacity(0.25))
             },
@@ -81,7 +86,7 @@ pub(crate) fn pdf(
                 y: vertical,
             },
             |c| {
-                c.set(DARK_BLUE)
+                c.set(colors.new)
                     .set(LINEWIDTH)
                     .set(LineType::Dash)
                     .set(Label("Mean"))
@@ -115,7 +120,7 @@ pub(crate) fn pdf(
                     ),
             },
             |c| {
-                c.set(DARK_BLUE)
+                c.set(colors.new)
                     .set(Label("\"Clean\" sample"))
                     .set(PointType::FilledCircle)
                     .set(POINT_SIZE)
@@ -149,7 +154,7 @@ pub(crate) fn pdf(
                     ),
             },
             |c| {
-                c.set(DARK_ORANGE)
+                c.set(colors.highlight)
                     .set(Label("Mild outliers"))
                     .set(POINT_SIZE)
                     .set(PointType::FilledCircle)
@@ -183,7 +188,7 @@ pub(crate) fn pdf(
                     ),
             },
             |c| {
-                c.set(DARK_RED)
+                c.set(colors.base)
                     .set(Label("Severe outliers"))
                     .set(POINT_SIZE)
                     .set(PointType::FilledCircle)
@@ -194,34 +199,33 @@ pub(crate) fn pdf(
                 x: &[lomt, lomt],
                 y: vertical,
             },
-            |c| c.set(DARK_ORANGE).set(LINEWIDTH).set(LineType::Dash),
+            |c| c.set(colors.highlight).set(LINEWIDTH).set(LineType::Dash),
         )
         .plot(
             Lines {
                 x: &[himt, himt],
                 y: vertical,
             },
-            |c| c.set(DARK_ORANGE).set(LINEWIDTH).set(LineType::Dash),
+            |c| c.set(colors.highlight).set(LINEWIDTH).set(LineType::Dash),
         )
         .plot(
             Lines {
                 x: &[lost, lost],
                 y: vertical,
             },
-            |c| c.set(DARK_RED).set(LINEWIDTH).set(LineType::Dash),
+            |c| c.set(colors.base).set(LINEWIDTH).set(LineType::Dash),
         )
         .plot(
             Lines {
                 x: &[hist, hist],
                 y: vertical,
             },
-            |c| c.set(DARK_RED).set(LINEWIDTH).set(LineType::Dash),
+            |c| c.set(colors.base).set(LINEWIDTH).set(LineType::Dash),
         );
     figure.set(Title(gnuplot_escape(id.as_title())));
 
     let path = context.report_path(id, "pdf.svg");
-    debug_script(&path, &figure);
-    figure.set(Output(path)).draw().unwrap()
+    draw(&mut figure, context, path)
 }
 
 pub(crate) fn pdf_small(
@@ -231,7 +235,8 @@ pub(crate) fn pdf_small(
     measurements: &MeasurementData<'_>,
     size: Option<Size>,
 ) -> Child {
-    let avg_times = &*measurements.avg_times;
+    let colors = colors(context);
+    let avg_times = &measurements.avg_times;
     let typical = avg_times.max();
     let mut scaled_avg_times: Vec<f64> = (avg_times as &Sample<f64>).iter().cloned().collect();
     let unit = formatter.scale_values(typical, &mut scaled_avg_times);
@@ -241,6 +246,7 @@ pub(crate) fn pdf_small(
     let (xs, ys, mean_y) = kde::sweep_and_estimate(scaled_avg_times, KDE_POINTS, None, mean);
     let xs_ = Sample::new(&xs);
     let ys_ = Sample::new(&ys);
+    let (mild, severe) = outliers_on_density(avg_times, scaled_avg_times);
 
     let y_limit = ys_.max() * 1.1;
     let zeros = iter::repeat(0);
@@ -267,7 +273,7 @@ pub(crate) fn pdf_small(
             },
             |c| {
                 c.set(Axes::BottomXRightY)
-                    .set(DARK_BLUE)
+                    .set(colors.new)
                     .set(Label("PDF"))
                     .set(Opacity(0.25))
             },
@@ -277,12 +283,37 @@ pub(crate) fn pdf_small(
                 x: &[mean, mean],
                 y: &[0., mean_y],
             },
-            |c| c.set(DARK_BLUE).set(LINEWIDTH).set(Label("Mean")),
+            |c| c.set(colors.new).set(LINEWIDTH).set(Label("Mean")),
+        )
+        .plot(
+            Points {
+                x: mild.iter().map(|&(x, _)| x),
+                y: mild.iter().map(|&(_, y)| y),
+            },
+            |c| {
+                c.set(Axes::BottomXRightY)
+                    .set(colors.highlight)
+                    .set(Label("Mild outliers"))
+                    .set(POINT_SIZE)
+                    .set(PointType::FilledCircle)
+            },
+        )
+        .plot(
+            Points {
+                x: severe.iter().map(|&(x, _)| x),
+                y: severe.iter().map(|&(_, y)| y),
+            },
+            |c| {
+                c.set(Axes::BottomXRightY)
+                    .set(colors.base)
+                    .set(Label("Severe outliers"))
+                    .set(POINT_SIZE)
+                    .set(PointType::FilledCircle)
+            },
         );
 
     let path = context.report_path(id, "pdf_small.svg");
-    debug_script(&path, &figure);
-    figure.set(Output(path)).draw().unwrap()
+    draw(&mut figure, context, path)
 }
 
 fn pdf_comparison_figure(
@@ -290,6 +321,7 @@ fn pdf_comparison_figure(
     measurements: &MeasurementData<'_>,
     comparison: &ComparisonData,
     size: Option<Size>,
+    colors: &Colors,
 ) -> Figure {
     let base_avg_times = Sample::new(&comparison.base_avg_times);
     let typical = base_avg_times.max().max(measurements.avg_times.max());
@@ -334,14 +366,14 @@ fn pdf_comparison_figure(
                 y1: &*base_ys,
                 y2: zeros.clone(),
             },
-            |c| c.set(DARK_RED).set(Label("Base PDF")).set(Opacity(0.5)),
+            |c| c.set(colors.base).set(Label("Base PDF")).set(Opacity(0.5)),
         )
         .plot(
             Lines {
                 x: &[base_mean, base_mean],
                 y: &[0., base_y_mean],
             },
-            |c| c.set(DARK_RED).set(Label("Base Mean")).set(LINEWIDTH),
+            |c| c.set(colors.base).set(Label("Base Mean")).set(LINEWIDTH),
         )
         .plot(
             FilledCurve {
@@ -349,14 +381,14 @@ fn pdf_comparison_figure(
                 y1: &*ys,
                 y2: zeros,
             },
-            |c| c.set(DARK_BLUE).set(Label("New PDF")).set(Opacity(0.5)),
+            |c| c.set(colors.new).set(Label("New PDF")).set(Opacity(0.5)),
         )
         .plot(
             Lines {
                 x: &[new_mean, new_mean],
                 y: &[0., y_mean],
             },
-            |c| c.set(DARK_BLUE).set(Label("New Mean")).set(LINEWIDTH),
+            |c| c.set(colors.new).set(Label("New Mean")).set(LINEWIDTH),
         );
     figure
 }
@@ -369,11 +401,11 @@ pub(crate) fn pdf_comparison(
     comparison: &ComparisonData,
     size: Option<Size>,
 ) -> Child {
-    let mut figure = pdf_comparison_figure(formatter, measurements, comparison, size);
+    let mut figure =
+        pdf_comparison_figure(formatter, measurements, comparison, size, &colors(context));
     figure.set(Title(gnuplot_escape(id.as_title())));
     let path = context.report_path(id, "both/pdf.svg");
-    debug_script(&path, &figure);
-    figure.set(Output(path)).draw().unwrap()
+    draw(&mut figure, context, path)
 }
 
 pub(crate) fn pdf_comparison_small(
@@ -384,9 +416,9 @@ pub(crate) fn pdf_comparison_small(
     comparison: &ComparisonData,
     size: Option<Size>,
 ) -> Child {
-    let mut figure = pdf_comparison_figure(formatter, measurements, comparison, size);
+    let mut figure =
+        pdf_comparison_figure(formatter, measurements, comparison, size, &colors(context));
     figure.configure(Key, |k| k.hide());
     let path = context.report_path(id, "relative_pdf_small.svg");
-    debug_script(&path, &figure);
-    figure.set(Output(path)).draw().unwrap()
+    draw(&mut figure, context, path)
 }
@@ -10,11 +10,15 @@ use crate::stats::bivariate::Data;
 use crate::estimate::{ConfidenceInterval, Estimate};
 
 use crate::measurement::ValueFormatter;
+use crate::plot::clamp_to_smallest_positive;
+use crate::AxisScale;
 
 fn regression_figure(
     formatter: &dyn ValueFormatter,
     measurements: &MeasurementData<'_>,
     size: Option<Size>,
+    y_scale: AxisScale,
+    colors: &Colors,
 ) -> Figure {
     let slope_estimate = measurements.absolute_estimates.slope.as_ref().unwrap();
     let slope_dist = measurements.distributions.slope.as_ref().unwrap();
@@ -25,12 +29,33 @@ fn regression_figure(
     let (max_iters, typical) = (data.x().max(), data.y().max());
     let mut scaled_y: Vec<f64> = data.y().iter().cloned().collect();
     let unit = formatter.scale_values(typical, &mut scaled_y);
+    if let AxisScale::Logarithmic = y_scale {
+        clamp_to_smallest_positive(&mut scaled_y);
+    }
     let scaled_y = Sample::new(&scaled_y);
 
+    // A logarithmic axis can't show zero, so start the regression line at the smallest
+    // iteration count instead of the origin.
+    let start_iters = match y_scale {
+        AxisScale::Linear => 0.,
+        AxisScale::Logarithmic => data.x().min(),
+    };
+
     let point_estimate = Slope::fit(&measurements.data).0;
-    let mut scaled_points = [point_estimate * max_iters, lb * max_iters, ub * max_iters];
+    let mut scaled_points = [
+        point_estimate * max_iters,
+        lb * max_iters,
+        ub * max_iters,
+        point_estimate * start_iters,
+        lb * start_iters,
+        ub * start_iters,
+    ];
     let _ = formatter.scale_values(typical, &mut scaled_points);
-    let [point, lb, ub] = scaled_points;
+    if let AxisScale::Logarithmic = y_scale {
+        // The lower bound of the confidence interval can be negative.
+        clamp_to_smallest_positive(&mut scaled_points);
+    }
+    let [point, lb, ub, start_point, start_lb, start_ub] = scaled_points;
 
     let exponent = (max_iters.log10() / 3.).floor() as i32 * 3;
     let x_scale = 10f64.powi(-exponent);
@@ -53,41 +78,43 @@ fn regression_figure(
         .configure(Axis::LeftY, |a| {
             a.configure(Grid::Major, |g| g.show())
                 .set(Label(format!("Total sample time ({})", unit)))
+                .set(y_scale.to_gnuplot())
         })
+        // The confidence band is drawn first, so that the line and the samples are on top of it.
         .plot(
-            Points {
-                x: data.x().as_ref(),
-                y: scaled_y.as_ref(),
+            FilledCurve {
+                x: &[start_iters, max_iters],
+                y1: &[start_lb, lb],
+                y2: &[start_ub, ub],
             },
             |c| {
-                c.set(DARK_BLUE)
-                    .set(Label("Sample"))
-                    .set(PointSize(0.5))
-                    .set(PointType::FilledCircle)
+                c.set(colors.new)
+                    .set(Label("Confidence interval"))
+                    .set(Opacity(0.25))
             },
         )
         .plot(
             Lines {
-                x: &[0., max_iters],
-                y: &[0., point],
+                x: &[start_iters, max_iters],
+                y: &[start_point, point],
             },
             |c| {
-                c.set(DARK_BLUE)
+                c.set(colors.new)
                     .set(LINEWIDTH)
                     .set(Label("Linear regression"))
                     .set(LineType::Solid)
             },
         )
         .plot(
-            FilledCurve {
-                x: &[0., max_iters],
-                y1: &[0., lb],
-                y2: &[0., ub],
+            Points {
+                x: data.x().as_ref(),
+                y: scaled_y.as_ref(),
             },
             |c| {
-                c.set(DARK_BLUE)
-                    .set(Label("Confidence interval"))
-                    .set(Opacity(0.25))
+                c.set(colors.new)
+                    .set(Label("Sample"))
+                    .set(PointSize(0.5))
+                    .set(PointType::FilledCircle)
             },
         );
     figure
@@ -100,7 +127,13 @@ pub(crate) fn regression(
     measurements: &MeasurementData<'_>,
     size: Option<Size>,
 ) -> Child {
-    let mut figure = regression_figure(formatter, measurements, size);
+    let mut figure = regression_figure(
+        formatter,
+        measurements,
+        size,
+        context.regression_y_scale,
+        &colors(context),
+    );
     figure.set(Title(gnuplot_escape(id.as_title())));
     figure.configure(Key, |k| {
         k.set(Justification::Left)
@@ -109,8 +142,7 @@ pub(crate) fn regression(
     });
 
     let path = context.report_path(id, "regression.svg");
-    debug_script(&path, &figure);
-    figure.set(Output(path)).draw().unwrap()
+    draw(&mut figure, context, path)
 }
 
 pub(crate) fn regression_small(
@@ -120,12 +152,17 @@ pub(crate) fn regression_small(
     measurements: &MeasurementData<'_>,
     size: Option<Size>,
 ) -> Child {
-    let mut figure = regression_figure(formatter, measurements, size);
+    let mut figure = regression_figure(
+        formatter,
+        measurements,
+        size,
+        context.regression_y_scale,
+        &colors(context),
+    );
     figure.configure(Key, |k| k.hide());
 
     let path = context.report_path(id, "regression_small.svg");
-    debug_script(&path, &figure);
-    figure.set(Output(path)).draw().unwrap()
+    draw(&mut figure, context, path)
 }
 
 fn regression_comparison_figure(
@@ -134,6 +171,8 @@ fn regression_comparison_figure(
     comparison: &ComparisonData,
     base_data: &Data<'_, f64, f64>,
     size: Option<Size>,
+    y_scale: AxisScale,
+    colors: &Colors,
 ) -> Figure {
     let data = &measurements.data;
     let max_iters = base_data.x().max().max(data.x().max());
@@ -170,6 +209,13 @@ fn regression_comparison_figure(
         ..
     } = measurements.absolute_estimates.slope.as_ref().unwrap();
 
+    // As in `regression_figure`, the lines start at the smallest iteration count on a
+    // logarithmic axis.
+    let start_iters = match y_scale {
+        AxisScale::Linear => 0.,
+        AxisScale::Logarithmic => base_data.x().min().min(data.x().min()),
+    };
+
     let mut points = [
         base_lb * max_iters,
         base_point * max_iters,
@@ -177,9 +223,19 @@ fn regression_comparison_figure(
         lb * max_iters,
         point * max_iters,
         ub * max_iters,
+        base_lb * start_iters,
+        base_point * start_iters,
+        base_ub * start_iters,
+        lb * start_iters,
+        point * start_iters,
+        ub * start_iters,
     ];
     let unit = formatter.scale_values(typical, &mut points);
-    let [base_lb, base_point, base_ub, lb, point, ub] = points;
+    if let AxisScale::Logarithmic = y_scale {
+        clamp_to_smallest_positive(&mut points);
+    }
+    let [base_lb, base_point, base_ub, lb, point, ub, start_base_lb, start_base_point, start_base_ub, start_lb, start_point, start_ub] =
+        points;
 
     let mut figure = Figure::new();
     figure
@@ -193,6 +249,7 @@ fn regression_comparison_figure(
         .configure(Axis::LeftY, |a| {
             a.configure(Grid::Major, |g| g.show())
                 .set(Label(format!("Total sample time ({})", unit)))
+                .set(y_scale.to_gnuplot())
         })
         .configure(Key, |k| {
             k.set(Justification::Left)
@@ -201,27 +258,27 @@ fn regression_comparison_figure(
         })
         .plot(
             FilledCurve {
-                x: &[0., max_iters],
-                y1: &[0., base_lb],
-                y2: &[0., base_ub],
+                x: &[start_iters, max_iters],
+                y1: &[start_base_lb, base_lb],
+                y2: &[start_base_ub, base_ub],
             },
-            |c| c.set(DARK_RED).set(Opacity(0.25)),
+            |c| c.set(colors.base).set(Opacity(0.25)),
         )
         .plot(
             FilledCurve {
-                x: &[0., max_iters],
-                y1: &[0., lb],
-                y2: &[0., ub],
+                x: &[start_iters, max_iters],
+                y1: &[start_lb, lb],
+                y2: &[start_ub, ub],
             },
-            |c| c.set(DARK_BLUE).set(Opacity(0.25)),
+            |c| c.set(colors.new).set(Opacity(0.25)),
         )
         .plot(
             Lines {
-                x: &[0., max_iters],
-                y: &[0., base_point],
+                x: &[start_iters, max_iters],
+                y: &[start_base_point, base_point],
             },
             |c| {
-                c.set(DARK_RED)
+                c.set(colors.base)
                     .set(LINEWIDTH)
                     .set(Label("Base sample"))
                     .set(LineType::Solid)
@@ -229,11 +286,11 @@ fn regression_comparison_figure(
         )
         .plot(
             Lines {
-                x: &[0., max_iters],
-                y: &[0., point],
+                x: &[start_iters, max_iters],
+                y: &[start_point, point],
             },
             |c| {
-                c.set(DARK_BLUE)
+                c.set(colors.new)
                     .set(LINEWIDTH)
                     .set(Label("New sample"))
                     .set(LineType::Solid)
@@ -251,13 +308,19 @@ pub(crate) fn regression_comparison(
     base_data: &Data<'_, f64, f64>,
     size: Option<Size>,
 ) -> Child {
-    let mut figure =
-        regression_comparison_figure(formatter, measurements, comparison, base_data, size);
+    let mut figure = regression_comparison_figure(
+        formatter,
+        measurements,
+        comparison,
+        base_data,
+        size,
+        context.regression_y_scale,
+        &colors(context),
+    );
     figure.set(Title(gnuplot_escape(id.as_title())));
 
     let path = context.report_path(id, "both/regression.svg");
-    debug_script(&path, &figure);
-    figure.set(Output(path)).draw().unwrap()
+    draw(&mut figure, context, path)
 }
 
 pub(crate) fn regression_comparison_small(
@@ -269,11 +332,17 @@ pub(crate) fn regression_comparison_small(
     base_data: &Data<'_, f64, f64>,
     size: Option<Size>,
 ) -> Child {
-    let mut figure =
-        regression_comparison_figure(formatter, measurements, comparison, base_data, size);
+    let mut figure = regression_comparison_figure(
+        formatter,
+        measurements,
+        comparison,
+        base_data,
+        size,
+        context.regression_y_scale,
+        &colors(context),
+    );
     figure.configure(Key, |k| k.hide());
 
     let path = context.report_path(id, "relative_regression_small.svg");
-    debug_script(&path, &figure);
-    figure.set(Output(path)).draw().unwrap()
+    draw(&mut figure, context, path)
 }
@@ -1,8 +1,9 @@
-use super::{debug_script, gnuplot_escape};
-use super::{DARK_BLUE, DEFAULT_FONT, KDE_POINTS, LINEWIDTH, POINT_SIZE, SIZE};
+use super::{colors, draw, gnuplot_escape};
+use super::{DEFAULT_FONT, KDE_POINTS, LINEWIDTH, POINT_SIZE, SIZE};
 use crate::kde;
 use crate::measurement::ValueFormatter;
-use crate::report::{BenchmarkId, ValueType};
+use crate::plot::{RelativeThroughput, WorkingSet};
+use crate::report::{BenchmarkId, ReportContext, ValueType};
 use crate::stats::univariate::Sample;
 use crate::AxisScale;
 use criterion_plot::prelude::*;
@@ -11,20 +12,8 @@ use std::cmp::Ordering;
 use std::path::{Path, PathBuf};
 use std::process::Child;
 
-const NUM_COLORS: usize = 8;
-static COMPARISON_COLORS: [Color; NUM_COLORS] = [
-    Color::Rgb(178, 34, 34),
-    Color::Rgb(46, 139, 87),
-    Color::Rgb(0, 139, 139),
-    Color::Rgb(255, 215, 0),
-    Color::Rgb(0, 0, 139),
-    Color::Rgb(220, 20, 60),
-    Color::Rgb(139, 0, 139),
-    Color::Rgb(0, 255, 127),
-];
-
 impl AxisScale {
-    fn to_gnuplot(self) -> Scale {
+    pub(super) fn to_gnuplot(self) -> Scale {
         match self {
             AxisScale::Linear => Scale::Linear,
             AxisScale::Logarithmic => Scale::Logarithmic,
@@ -33,6 +22,7 @@ impl AxisScale {
 }
 
 #[cfg_attr(feature = "cargo-clippy", allow(clippy::explicit_counter_loop))]
+#[allow(clippy::too_many_arguments)]
 pub fn line_comparison(
     formatter: &dyn ValueFormatter,
     title: &str,
@@ -40,7 +30,10 @@ pub fn line_comparison(
     path: &Path,
     value_type: ValueType,
     axis_scale: AxisScale,
+    context: &ReportContext,
+    size: Option<Size>,
 ) -> Child {
+    let colors = colors(context);
     let path = PathBuf::from(path);
     let mut f = Figure::new();
 
@@ -51,7 +44,7 @@ pub fn line_comparison(
     };
 
     f.set(Font(DEFAULT_FONT))
-        .set(SIZE)
+        .set(size.unwrap_or(SIZE))
         .configure(Key, |k| {
             k.set(Justification::Left)
                 .set(Order::SampleText)
@@ -106,19 +99,18 @@ pub fn line_comparison(
             }
             c.set(LINEWIDTH)
                 .set(LineType::Solid)
-                .set(COMPARISON_COLORS[i % NUM_COLORS])
+                .set(colors.comparison(i))
         })
         .plot(Points { x: &xs, y: &ys }, |p| {
             p.set(PointType::FilledCircle)
                 .set(POINT_SIZE)
-                .set(COMPARISON_COLORS[i % NUM_COLORS])
+                .set(colors.comparison(i))
         });
 
         i += 1;
     }
 
-    debug_script(&path, &f);
-    f.set(Output(path)).draw().unwrap()
+    draw(&mut f, context, path)
 }
 
 pub fn violin(
@@ -127,7 +119,10 @@ pub fn violin(
     all_curves: &[&(&BenchmarkId, Vec<f64>)],
     path: &Path,
     axis_scale: AxisScale,
+    context: &ReportContext,
+    size: Option<Size>,
 ) -> Child {
+    let colors = colors(context);
     let path = PathBuf::from(&path);
     let all_curves_vec = all_curves.iter().rev().cloned().collect::<Vec<_>>();
     let all_curves: &[&(&BenchmarkId, Vec<f64>)] = &*all_curves_vec;
@@ -166,7 +161,9 @@ pub fn violin(
     let unit = formatter.scale_values((min + max) / 2.0, &mut one);
 
     let tics = || (0..).map(|x| (f64::from(x)) + 0.5);
-    let size = Size(1280, 200 + (25 * all_curves.len()));
+    // The height depends on the number of benchmarks, so only the width can be configured.
+    let width = size.map_or(SIZE.0, |size| size.0);
+    let size = Size(width, 200 + (25 * all_curves.len()));
     let mut f = Figure::new();
     f.set(Font(DEFAULT_FONT))
         .set(size)
@@ -201,12 +198,141 @@ pub fn violin(
             if is_first {
                 is_first = false;
 
-                c.set(DARK_BLUE).set(Label("PDF"))
+                c.set(colors.new).set(Label("PDF"))
             } else {
-                c.set(DARK_BLUE)
+                c.set(colors.new)
+            }
+        });
+    }
+    draw(&mut f, context, path)
+}
+
+// Fraction of the space reserved for each parameter which is covered by its bars.
+const BAR_GROUP_WIDTH: f64 = 0.8;
+
+pub fn relative_throughput(
+    title: &str,
+    data: &RelativeThroughput<'_>,
+    path: &Path,
+    context: &ReportContext,
+    size: Option<Size>,
+) -> Child {
+    let colors = colors(context);
+    let path = PathBuf::from(path);
+    let mut f = Figure::new();
+
+    let positions: Vec<f64> = (0..data.parameters.len()).map(|i| i as f64).collect();
+    let labels: Vec<String> = data.parameters.iter().map(|p| gnuplot_escape(p)).collect();
+    f.set(Font(DEFAULT_FONT))
+        .set(size.unwrap_or(SIZE))
+        .configure(Key, |k| {
+            k.set(Justification::Left)
+                .set(Order::SampleText)
+                .set(Position::Outside(Vertical::Top, Horizontal::Right))
+        })
+        .set(Title(format!(
+            "{}: Relative Throughput",
+            gnuplot_escape(title)
+        )))
+        .configure(Axis::BottomX, |a| {
+            a.set(Label("Input"))
+                .set(Range::Limits(-0.5, data.parameters.len() as f64 - 0.5))
+                .set(TicLabels {
+                    labels: &labels,
+                    positions: &positions,
+                })
+        })
+        .configure(Axis::LeftY, |a| {
+            a.configure(Grid::Major, |g| g.show())
+                .set(Label("Throughput relative to the fastest"))
+                .set(Range::Limits(0., 1.25))
+        });
+
+    let bar_width = BAR_GROUP_WIDTH / data.functions.len() as f64;
+    for (id, (name, relative)) in data.functions.iter().enumerate() {
+        let mut label = name.map(|name| gnuplot_escape(name));
+        for (i, value) in relative.iter().enumerate() {
+            if let Some(value) = *value {
+                let left = i as f64 - BAR_GROUP_WIDTH / 2.0 + id as f64 * bar_width;
+                f.plot(
+                    FilledCurve {
+                        x: &[left, left + bar_width],
+                        y1: &[0., 0.],
+                        y2: &[value, value],
+                    },
+                    |c| {
+                        // Only the first bar of each function appears in the key.
+                        if let Some(label) = label.take() {
+                            c.set(Label(label));
+                        }
+                        c.set(colors.comparison(id)).set(Opacity(1.))
+                    },
+                );
+            }
+        }
+    }
+
+    draw(&mut f, context, path)
+}
+
+pub fn working_set(
+    formatter: &dyn ValueFormatter,
+    title: &str,
+    data: &WorkingSet<'_>,
+    path: &Path,
+    context: &ReportContext,
+    size: Option<Size>,
+) -> Child {
+    let colors = colors(context);
+    let path = PathBuf::from(path);
+    let mut f = Figure::new();
+
+    let max = data
+        .functions
+        .iter()
+        .flat_map(|(_, points)| points.iter().map(|&(_, time)| time))
+        .fold(f64::NAN, f64::max);
+    let mut dummy = [1.0];
+    let unit = formatter.scale_values(max, &mut dummy);
+
+    // The working set sizes usually grow geometrically, so the x-axis is always logarithmic.
+    f.set(Font(DEFAULT_FONT))
+        .set(size.unwrap_or(SIZE))
+        .configure(Key, |k| {
+            k.set(Justification::Left)
+                .set(Order::SampleText)
+                .set(Position::Outside(Vertical::Top, Horizontal::Right))
+        })
+        .set(Title(format!("{}: Working Set", gnuplot_escape(title))))
+        .configure(Axis::BottomX, |a| {
+            a.set(Label("Working Set Size (Bytes)"))
+                .set(Scale::Logarithmic)
+        })
+        .configure(Axis::LeftY, |a| {
+            a.configure(Grid::Major, |g| g.show())
+                .configure(Grid::Minor, |g| g.hide())
+                .set(Label(format!("Average time per {} ({})", data.per, unit)))
+        });
+
+    for (i, (name, points)) in data.functions.iter().enumerate() {
+        let (xs, mut ys): (Vec<f64>, Vec<f64>) = points.iter().cloned().unzip();
+        formatter.scale_values(max, &mut ys);
+
+        let function_name = name.map(|name| gnuplot_escape(name));
+        f.plot(Lines { x: &xs, y: &ys }, |c| {
+            if let Some(name) = function_name {
+                c.set(Label(name));
             }
+            c.set(LINEWIDTH)
+                .set(LineType::Solid)
+                .set(colors.comparison(i))
+        })
+        .plot(Points { x: &xs, y: &ys }, |p| {
+            p.set(PointType::FilledCircle)
+                .set(POINT_SIZE)
+                .set(colors.comparison(i))
         });
     }
-    debug_script(&path, &f);
-    f.set(Output(path)).draw().unwrap()
+
+    draw(&mut f, context, path)
 }
@@ -14,6 +14,7 @@ pub(crate) fn t_test(
     comparison: &ComparisonData,
     size: Option<Size>,
 ) -> Child {
+    let colors = colors(context);
     let t = comparison.t_value;
     let (xs, ys) = kde::sweep(&comparison.t_distribution, KDE_POINTS, None);
     let zero = iter::repeat(0);
@@ -40,7 +41,7 @@ pub(crate) fn t_test(
                 y2: zero,
             },
             |c| {
-                c.set(DARK_BLUE)
+                c.set(colors.new)
                     .set(Label("t distribution"))
                     .set(Opacity(0.25))
             },
@@ -52,7 +53,7 @@ pub(crate) fn t_test(
             },
             |c| {
                 c.set(Axes::BottomXRightY)
-                    .set(DARK_BLUE)
+                    .set(colors.new)
                     .set(LINEWIDTH)
                     .set(Label("t statistic"))
                     .set(LineType::Solid)
@@ -60,6 +61,5 @@ pub(crate) fn t_test(
         );
 
     let path = context.report_path(id, "change/t-test.svg");
-    debug_script(&path, &figure);
-    figure.set(Output(path)).draw().unwrap()
+    draw(&mut figure, context, path)
 }
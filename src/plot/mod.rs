@@ -7,6 +7,12 @@ pub(crate) use plotters_backend::PlottersBackend;
 use crate::estimate::Statistic;
 use crate::measurement::ValueFormatter;
 use crate::report::{BenchmarkId, ComparisonData, MeasurementData, ReportContext, ValueType};
+use crate::stats::univariate::kde::kernel::Gaussian;
+use crate::stats::univariate::kde::{Bandwidth, Kde};
+use crate::stats::univariate::outliers::tukey::LabeledSample;
+use crate::stats::univariate::Sample;
+use crate::Throughput;
+use itertools::Itertools;
 use std::path::PathBuf;
 
 const REPORT_STATS: [Statistic; 7] = [
@@ -18,7 +24,6 @@ const REPORT_STATS: [Statistic; 7] = [
     Statistic::MedianAbsDev,
     Statistic::StdDev,
 ];
-const CHANGE_STATS: [Statistic; 2] = [Statistic::Mean, Statistic::Median];
 #[derive(Clone, Copy)]
 pub(crate) struct PlotContext<'a> {
     pub(crate) id: &'a BenchmarkId,
@@ -48,6 +53,22 @@ impl<'a> PlotContext<'a> {
         path
     }
 
+    pub fn relative_throughput_path(&self) -> PathBuf {
+        let mut path = self.context.output_directory.clone();
+        path.push(self.id.as_directory_name());
+        path.push("report");
+        path.push("relative_throughput.svg");
+        path
+    }
+
+    pub fn working_set_path(&self) -> PathBuf {
+        let mut path = self.context.output_directory.clone();
+        path.push(self.id.as_directory_name());
+        path.push("report");
+        path.push("working_set.svg");
+        path
+    }
+
     pub fn violin_path(&self) -> PathBuf {
         let mut path = self.context.output_directory.clone();
         path.push(self.id.as_directory_name());
@@ -71,6 +92,238 @@ impl<'a> PlotData<'a> {
     }
 }
 
+/// Fits a line to `values` against their one-based index by least squares, returning its
+/// intercept and slope. This is the trend drawn in the sample order plot.
+pub(crate) fn sample_order_trend(values: &[f64]) -> (f64, f64) {
+    let n = values.len() as f64;
+    let mean_x = (n + 1.0) / 2.0;
+    let mean_y = values.iter().sum::<f64>() / n;
+    let (mut covariance, mut variance) = (0.0, 0.0);
+    for (i, &y) in values.iter().enumerate() {
+        let dx = (i + 1) as f64 - mean_x;
+        covariance += dx * (y - mean_y);
+        variance += dx * dx;
+    }
+    let slope = if variance > 0.0 {
+        covariance / variance
+    } else {
+        0.0
+    };
+    (mean_y - slope * mean_x, slope)
+}
+
+/// The mild and the severe outliers among a benchmark's average iteration times, as points on the
+/// probability density estimate of `scaled_avg_times` (the same times, scaled for display). These
+/// are marked on the small PDF plot, to show whether the shape of the distribution is caused by
+/// a few outliers.
+#[allow(clippy::type_complexity)]
+pub(crate) fn outliers_on_density(
+    avg_times: &LabeledSample<'_, f64>,
+    scaled_avg_times: &Sample<f64>,
+) -> (Vec<(f64, f64)>, Vec<(f64, f64)>) {
+    let kde = Kde::new(scaled_avg_times, Gaussian, Bandwidth::Silverman);
+    let (mut mild, mut severe) = (vec![], vec![]);
+    for ((_, label), &x) in avg_times.iter().zip(scaled_avg_times.iter()) {
+        if label.is_mild() {
+            mild.push((x, kde.estimate(x)));
+        } else if label.is_severe() {
+            severe.push((x, kde.estimate(x)));
+        }
+    }
+    (mild, severe)
+}
+
+/// The limits of a value axis which is symmetric around zero and shows all of `values`, if the
+/// values are signed. Otherwise, the plot keeps its usual limits.
+pub(crate) fn symmetric_limits(
+    formatter: &dyn ValueFormatter,
+    values: &[f64],
+) -> Option<(f64, f64)> {
+    if !formatter.is_signed() {
+        return None;
+    }
+    let limit = values
+        .iter()
+        .fold(0.0, |limit: f64, value| limit.max(value.abs()));
+    Some((-limit, limit))
+}
+
+/// Clamps non-positive values to the smallest positive value in `values` so that they can be
+/// drawn on a logarithmic axis.
+pub(crate) fn clamp_to_smallest_positive(values: &mut [f64]) {
+    let smallest = values
+        .iter()
+        .copied()
+        .filter(|&v| v > 0.0)
+        .fold(f64::INFINITY, f64::min);
+    if !smallest.is_finite() {
+        return;
+    }
+
+    let mut clamped = 0;
+    for value in values.iter_mut().filter(|v| **v <= 0.0) {
+        *value = smallest;
+        clamped += 1;
+    }
+    if clamped > 0 {
+        warn!(
+            "Clamped {} non-positive value(s) to {} for the logarithmic axis",
+            clamped, smallest
+        );
+    }
+}
+
+/// The name of a function, with its throughput for each parameter it was benchmarked with.
+type FunctionThroughputs<'a> = (Option<&'a String>, Vec<(&'a str, f64)>);
+
+/// The throughput of each function in a summary, relative to the fastest function benchmarked with
+/// the same parameter.
+pub(crate) struct RelativeThroughput<'a> {
+    /// The parameters benchmarked with more than one function, in the order they were first run.
+    pub(crate) parameters: Vec<&'a str>,
+    /// The name of each function, with its relative throughput for each of the parameters it was
+    /// benchmarked with.
+    pub(crate) functions: Vec<(Option<&'a String>, Vec<Option<f64>>)>,
+}
+impl<'a> RelativeThroughput<'a> {
+    /// Returns `None` unless every benchmark has a parameter and a throughput, and at least one
+    /// parameter was benchmarked with more than one function.
+    pub(crate) fn new(
+        all_curves: &[&(&'a BenchmarkId, Vec<f64>)],
+    ) -> Option<RelativeThroughput<'a>> {
+        let mut parameters: Vec<&str> = vec![];
+        let mut functions: Vec<FunctionThroughputs<'a>> = vec![];
+        for (key, group) in &all_curves.iter().group_by(|&&&(id, _)| &id.function_id) {
+            let mut throughputs = vec![];
+            for &&(id, ref sample) in group {
+                let parameter = id.value_str.as_deref()?;
+                let amount = match id.throughput.as_ref()? {
+                    Throughput::Bytes(n) | Throughput::Elements(n) => *n as f64,
+                };
+                if !parameters.contains(&parameter) {
+                    parameters.push(parameter);
+                }
+                throughputs.push((parameter, amount / Sample::new(sample).mean()));
+            }
+            // The curves of a function are usually, but not necessarily, next to each other.
+            match functions.iter_mut().find(|(name, _)| *name == key.as_ref()) {
+                Some((_, existing)) => existing.extend(throughputs),
+                None => functions.push((key.as_ref(), throughputs)),
+            }
+        }
+
+        let throughput = |function: &[(&str, f64)], parameter: &str| {
+            function
+                .iter()
+                .find(|&&(p, _)| p == parameter)
+                .map(|&(_, throughput)| throughput)
+        };
+        parameters.retain(|&parameter| {
+            functions
+                .iter()
+                .filter(|(_, throughputs)| throughput(throughputs, parameter).is_some())
+                .count()
+                > 1
+        });
+        if parameters.is_empty() {
+            return None;
+        }
+
+        let fastest: Vec<f64> = parameters
+            .iter()
+            .map(|&parameter| {
+                functions
+                    .iter()
+                    .filter_map(|(_, throughputs)| throughput(throughputs, parameter))
+                    .fold(f64::NAN, f64::max)
+            })
+            .collect();
+        let functions = functions
+            .iter()
+            .map(|(name, throughputs)| {
+                let relative = parameters
+                    .iter()
+                    .zip(&fastest)
+                    .map(|(&parameter, &fastest)| {
+                        throughput(throughputs, parameter).map(|throughput| throughput / fastest)
+                    })
+                    .collect();
+                (*name, relative)
+            })
+            .collect();
+        Some(RelativeThroughput {
+            parameters,
+            functions,
+        })
+    }
+}
+
+/// The average time of each function of a group per unit of throughput, against the working set
+/// size of its benchmarks, for spotting where the working set outgrows a cache.
+#[allow(clippy::type_complexity)]
+pub(crate) struct WorkingSet<'a> {
+    /// What the times are per: "element" or "byte" if all of the benchmarks have that kind of
+    /// throughput, otherwise "iteration".
+    pub(crate) per: &'static str,
+    /// The name of each function, with its (working set size, average time) points in order of
+    /// increasing working set size.
+    pub(crate) functions: Vec<(Option<&'a String>, Vec<(f64, f64)>)>,
+}
+impl<'a> WorkingSet<'a> {
+    /// Returns `None` unless every benchmark has a working set size, and they are not all the
+    /// same.
+    pub(crate) fn new(all_curves: &[&(&'a BenchmarkId, Vec<f64>)]) -> Option<WorkingSet<'a>> {
+        let sizes = all_curves
+            .iter()
+            .map(|&&(id, _)| id.working_set_bytes)
+            .collect::<Option<Vec<u64>>>()?;
+        if sizes.iter().all(|&size| size == sizes[0]) {
+            return None;
+        }
+
+        let per = match all_curves[0].0.throughput {
+            Some(Throughput::Elements(_))
+                if all_curves
+                    .iter()
+                    .all(|&&(id, _)| matches!(id.throughput, Some(Throughput::Elements(_)))) =>
+            {
+                "element"
+            }
+            Some(Throughput::Bytes(_))
+                if all_curves
+                    .iter()
+                    .all(|&&(id, _)| matches!(id.throughput, Some(Throughput::Bytes(_)))) =>
+            {
+                "byte"
+            }
+            _ => "iteration",
+        };
+
+        let mut functions: Vec<(_, Vec<(f64, f64)>)> = vec![];
+        for (&&(id, ref sample), &size) in all_curves.iter().zip(&sizes) {
+            let mut time = Sample::new(sample).mean();
+            if per != "iteration" {
+                match id.throughput {
+                    Some(Throughput::Bytes(n)) | Some(Throughput::Elements(n)) => time /= n as f64,
+                    None => {}
+                }
+            }
+            let point = (size as f64, time);
+            match functions
+                .iter_mut()
+                .find(|(name, _)| *name == id.function_id.as_ref())
+            {
+                Some((_, points)) => points.push(point),
+                None => functions.push((id.function_id.as_ref(), vec![point])),
+            }
+        }
+        for (_, points) in &mut functions {
+            points.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+        }
+        Some(WorkingSet { per, functions })
+    }
+}
+
 pub(crate) trait Plotter {
     fn pdf(&mut self, ctx: PlotContext<'_>, data: PlotData<'_>);
 
@@ -78,6 +331,8 @@ pub(crate) trait Plotter {
 
     fn iteration_times(&mut self, ctx: PlotContext<'_>, data: PlotData<'_>);
 
+    fn sample_order(&mut self, ctx: PlotContext<'_>, data: PlotData<'_>);
+
     fn abs_distributions(&mut self, ctx: PlotContext<'_>, data: PlotData<'_>);
 
     fn rel_distributions(&mut self, ctx: PlotContext<'_>, data: PlotData<'_>);
@@ -97,7 +352,221 @@ pub(crate) trait Plotter {
         all_curves: &[&(&BenchmarkId, Vec<f64>)],
     );
 
+    fn relative_throughput(&mut self, ctx: PlotContext<'_>, data: &RelativeThroughput<'_>);
+
+    fn working_set(
+        &mut self,
+        ctx: PlotContext<'_>,
+        formatter: &dyn ValueFormatter,
+        data: &WorkingSet<'_>,
+    );
+
     fn t_test(&mut self, ctx: PlotContext<'_>, data: PlotData<'_>);
 
     fn wait(&mut self);
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::cell::Cell;
+    use std::rc::Rc;
+    use std::time::Duration;
+
+    #[test]
+    fn test_clamp_to_smallest_positive() {
+        let mut values = [3.0, 0.0, 2.0, -1.0];
+        clamp_to_smallest_positive(&mut values);
+        assert_eq!(values, [3.0, 2.0, 2.0, 2.0]);
+
+        let mut values = [0.0, -1.0];
+        clamp_to_smallest_positive(&mut values);
+        assert_eq!(values, [0.0, -1.0]);
+    }
+
+    #[test]
+    fn test_sample_order_trend() {
+        let (intercept, slope) = sample_order_trend(&[3.0, 5.0, 7.0, 9.0]);
+        assert!((intercept - 1.0).abs() < 1e-12);
+        assert!((slope - 2.0).abs() < 1e-12);
+
+        assert_eq!(sample_order_trend(&[4.0]), (4.0, 0.0));
+    }
+
+    #[test]
+    fn test_outliers_on_density() {
+        let mut times: Vec<f64> = (0..20).map(|i| 10.0 + (i % 5) as f64 * 0.1).collect();
+        times.push(11.0);
+        times.push(100.0);
+        let avg_times = crate::stats::univariate::outliers::tukey::classify(Sample::new(&times));
+        let (mild, severe) = outliers_on_density(&avg_times, Sample::new(&times));
+
+        assert_eq!(mild.len(), 1);
+        assert_eq!(mild[0].0, 11.0);
+        assert_eq!(severe.len(), 1);
+        assert_eq!(severe[0].0, 100.0);
+        assert!(mild[0].1 > 0.0 && severe[0].1 >= 0.0);
+    }
+
+    #[test]
+    fn test_relative_throughput() {
+        let id = |function: &str, size: u64| {
+            BenchmarkId::new(
+                "parse".to_owned(),
+                Some(function.to_owned()),
+                Some(size.to_string()),
+                Some(Throughput::Bytes(size)),
+            )
+        };
+        let ids = [
+            id("a", 100),
+            id("a", 200),
+            id("b", 100),
+            id("b", 200),
+            id("c", 400),
+        ];
+        // Samples need at least two measurements.
+        let times = [10.0, 40.0, 20.0, 20.0, 5.0];
+        let curves: Vec<_> = ids.iter().zip(times.iter().map(|&t| vec![t, t])).collect();
+        let curves: Vec<_> = curves.iter().collect();
+
+        let relative = RelativeThroughput::new(&curves).unwrap();
+        // 400 was only benchmarked with one function, so there is nothing to compare.
+        assert_eq!(relative.parameters, ["100", "200"]);
+        let names: Vec<_> = relative.functions.iter().map(|(name, _)| *name).collect();
+        assert_eq!(
+            names,
+            [
+                Some(&"a".to_owned()),
+                Some(&"b".to_owned()),
+                Some(&"c".to_owned())
+            ]
+        );
+        assert_eq!(relative.functions[0].1, [Some(1.0), Some(0.5)]);
+        assert_eq!(relative.functions[1].1, [Some(0.5), Some(1.0)]);
+        assert_eq!(relative.functions[2].1, [None, None]);
+
+        let without_throughput = BenchmarkId::new("parse".to_owned(), None, None, None);
+        let curves = [
+            (&without_throughput, vec![1.0, 1.0]),
+            (&without_throughput, vec![2.0, 2.0]),
+        ];
+        let curves: Vec<_> = curves.iter().collect();
+        assert!(RelativeThroughput::new(&curves).is_none());
+    }
+
+    #[test]
+    fn test_working_set() {
+        let id = |function: &str, elements: u64, bytes: Option<u64>| {
+            let mut id = BenchmarkId::new(
+                "sum".to_owned(),
+                Some(function.to_owned()),
+                Some(elements.to_string()),
+                Some(Throughput::Elements(elements)),
+            );
+            id.working_set_bytes = bytes;
+            id
+        };
+        let ids = [
+            id("a", 100, Some(800)),
+            id("b", 100, Some(400)),
+            id("a", 10, Some(80)),
+            id("b", 10, Some(40)),
+        ];
+        let times = [200.0, 100.0, 10.0, 10.0];
+        let curves: Vec<_> = ids.iter().zip(times.iter().map(|&t| vec![t, t])).collect();
+        let curves: Vec<_> = curves.iter().collect();
+
+        let working_set = WorkingSet::new(&curves).unwrap();
+        assert_eq!(working_set.per, "element");
+        assert_eq!(working_set.functions.len(), 2);
+        assert_eq!(working_set.functions[0].0, Some(&"a".to_owned()));
+        assert_eq!(working_set.functions[0].1, [(80.0, 1.0), (800.0, 2.0)]);
+        assert_eq!(working_set.functions[1].1, [(40.0, 1.0), (400.0, 1.0)]);
+
+        let ids = [id("a", 10, Some(80)), id("a", 100, None)];
+        let curves: Vec<_> = ids.iter().zip(times.iter().map(|&t| vec![t, t])).collect();
+        let curves: Vec<_> = curves.iter().collect();
+        assert!(WorkingSet::new(&curves).is_none());
+    }
+
+    // Counts the plots drawn, standing in for the backends which spawn a process per plot.
+    struct CountingPlotter(Rc<Cell<usize>>);
+    impl CountingPlotter {
+        fn count(&mut self) {
+            self.0.set(self.0.get() + 1);
+        }
+    }
+    impl Plotter for CountingPlotter {
+        fn pdf(&mut self, _: PlotContext<'_>, _: PlotData<'_>) {
+            self.count();
+        }
+        fn regression(&mut self, _: PlotContext<'_>, _: PlotData<'_>) {
+            self.count();
+        }
+        fn iteration_times(&mut self, _: PlotContext<'_>, _: PlotData<'_>) {
+            self.count();
+        }
+        fn sample_order(&mut self, _: PlotContext<'_>, _: PlotData<'_>) {
+            self.count();
+        }
+        fn abs_distributions(&mut self, _: PlotContext<'_>, _: PlotData<'_>) {
+            self.count();
+        }
+        fn rel_distributions(&mut self, _: PlotContext<'_>, _: PlotData<'_>) {
+            self.count();
+        }
+        fn line_comparison(
+            &mut self,
+            _: PlotContext<'_>,
+            _: &dyn ValueFormatter,
+            _: &[&(&BenchmarkId, Vec<f64>)],
+            _: ValueType,
+        ) {
+            self.count();
+        }
+        fn violin(
+            &mut self,
+            _: PlotContext<'_>,
+            _: &dyn ValueFormatter,
+            _: &[&(&BenchmarkId, Vec<f64>)],
+        ) {
+            self.count();
+        }
+        fn relative_throughput(&mut self, _: PlotContext<'_>, _: &RelativeThroughput<'_>) {
+            self.count();
+        }
+        fn working_set(&mut self, _: PlotContext<'_>, _: &dyn ValueFormatter, _: &WorkingSet<'_>) {
+            self.count();
+        }
+        fn t_test(&mut self, _: PlotContext<'_>, _: PlotData<'_>) {
+            self.count();
+        }
+        fn wait(&mut self) {}
+    }
+
+    #[test]
+    fn test_without_plots_never_plots() {
+        let dir = tempfile::tempdir().unwrap();
+        let criterion = |plots: &Rc<Cell<usize>>| {
+            let mut criterion = crate::Criterion::default()
+                .output_directory(dir.path())
+                .warm_up_time(Duration::from_millis(100))
+                .measurement_time(Duration::from_millis(200))
+                .nresamples(1000);
+            let plotter = CountingPlotter(plots.clone());
+            criterion.report.html = Some(crate::html::Html::new(Some(Box::new(plotter))));
+            criterion
+        };
+
+        let plots = Rc::new(Cell::new(0));
+        criterion(&plots).bench_function("plotted", |b| b.iter(|| 10));
+        assert!(plots.get() > 0);
+
+        let plots = Rc::new(Cell::new(0));
+        criterion(&plots)
+            .without_plots()
+            .bench_function("unplotted", |b| b.iter(|| 10));
+        assert_eq!(plots.get(), 0);
+    }
+}
@@ -295,7 +295,8 @@ pub(crate) fn rel_distributions(
     comparison: &ComparisonData,
     size: Option<(u32, u32)>,
 ) {
-    crate::plot::CHANGE_STATS.iter().for_each(|&statistic| {
+    let statistics = comparison.relative_distributions.statistics();
+    statistics.iter().for_each(|&statistic| {
         rel_distribution(
             id,
             context,
@@ -24,7 +24,10 @@ pub(crate) fn iteration_times_figure(
     }
 
     let x_range = (1.0)..((data.len() + 1) as f64);
-    let y_range = plotters::data::fitting_range(scaled_y.iter());
+    let y_range = match crate::plot::symmetric_limits(formatter, scaled_y) {
+        Some((lower, upper)) => lower..upper,
+        None => plotters::data::fitting_range(scaled_y.iter()),
+    };
 
     let mut chart = cb
         .margin((5).percent())
@@ -60,6 +63,71 @@ pub(crate) fn iteration_times_figure(
     }
 }
 
+pub(crate) fn sample_order_figure(
+    title: &str,
+    path: &Path,
+    formatter: &dyn ValueFormatter,
+    measurements: &MeasurementData<'_>,
+    size: Option<(u32, u32)>,
+) {
+    let data = &measurements.avg_times;
+    let mut scaled_y: Vec<_> = data.iter().map(|(f, _)| f).collect();
+    let unit = formatter.scale_values(data.max(), &mut scaled_y);
+    let (intercept, slope) = crate::plot::sample_order_trend(&scaled_y);
+
+    let size = size.unwrap_or(SIZE);
+    let root_area = SVGBackend::new(path, size).into_drawing_area();
+
+    let x_range = (1.0)..((data.len() + 1) as f64);
+    let y_range = match crate::plot::symmetric_limits(formatter, &scaled_y) {
+        Some((lower, upper)) => lower..upper,
+        None => plotters::data::fitting_range(scaled_y.iter()),
+    };
+
+    let mut chart = ChartBuilder::on(&root_area)
+        .caption(format!("{}: Sample Order", title), (DEFAULT_FONT, 20))
+        .margin((5).percent())
+        .set_label_area_size(LabelAreaPosition::Left, (5).percent_width().min(60))
+        .set_label_area_size(LabelAreaPosition::Bottom, (5).percent_height().min(40))
+        .build_cartesian_2d(x_range, y_range)
+        .unwrap();
+
+    chart
+        .configure_mesh()
+        .x_desc("Sample")
+        .y_desc(format!("Average Iteration Time ({})", unit))
+        .x_label_formatter(&|x| pretty_print_float(*x, true))
+        .light_line_style(TRANSPARENT)
+        .draw()
+        .unwrap();
+
+    chart
+        .draw_series(
+            (1..=data.len())
+                .zip(scaled_y.iter())
+                .map(|(x, y)| Circle::new((x as f64, *y), POINT_SIZE, DARK_BLUE.filled())),
+        )
+        .unwrap()
+        .label("Sample")
+        .legend(|(x, y)| Circle::new((x + 10, y), POINT_SIZE, DARK_BLUE.filled()));
+
+    let trend = |x: f64| (x, intercept + slope * x);
+    chart
+        .draw_series(LineSeries::new(
+            vec![trend(1.0), trend(data.len() as f64)],
+            DARK_ORANGE,
+        ))
+        .unwrap()
+        .label("Trend")
+        .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], DARK_ORANGE));
+
+    chart
+        .configure_series_labels()
+        .position(SeriesLabelPosition::UpperLeft)
+        .draw()
+        .unwrap();
+}
+
 pub(crate) fn iteration_times_comparison_figure(
     title: Option<&str>,
     path: &Path,
@@ -91,7 +159,10 @@ pub(crate) fn iteration_times_comparison_figure(
 
     let max_samples = current_data.len().max(base_data.len()) as f64;
 
-    let y_range = plotters::data::fitting_range(all_data.iter());
+    let y_range = match crate::plot::symmetric_limits(formatter, &all_data) {
+        Some((lower, upper)) => lower..upper,
+        None => plotters::data::fitting_range(all_data.iter()),
+    };
 
     let mut chart = cb
         .margin((5).percent())
@@ -1,4 +1,4 @@
-use super::{PlotContext, PlotData, Plotter};
+use super::{PlotContext, PlotData, Plotter, RelativeThroughput, WorkingSet};
 use crate::measurement::ValueFormatter;
 use crate::report::{BenchmarkId, ComparisonData, MeasurementData, ValueType};
 use plotters::data::float::pretty_print_float;
@@ -108,6 +108,7 @@ impl Plotter for PlottersBackend {
                 cmp,
                 &base_data,
                 convert_size(ctx.size),
+                ctx.context.regression_y_scale,
             );
         } else {
             regression::regression_figure(
@@ -116,6 +117,7 @@ impl Plotter for PlottersBackend {
                 data.formatter,
                 data.measurements,
                 convert_size(ctx.size),
+                ctx.context.regression_y_scale,
             );
         }
     }
@@ -162,6 +164,18 @@ impl Plotter for PlottersBackend {
         }
     }
 
+    fn sample_order(&mut self, ctx: PlotContext<'_>, data: PlotData<'_>) {
+        iteration_times::sample_order_figure(
+            ctx.id.as_title(),
+            ctx.context
+                .report_path(ctx.id, "sample_order.svg")
+                .as_path(),
+            data.formatter,
+            data.measurements,
+            convert_size(ctx.size),
+        );
+    }
+
     fn abs_distributions(&mut self, ctx: PlotContext<'_>, data: PlotData<'_>) {
         distributions::abs_distributions(
             ctx.id,
@@ -197,6 +211,7 @@ impl Plotter for PlottersBackend {
             &path,
             value_type,
             ctx.context.plot_config.summary_scale,
+            convert_size(ctx.size),
         );
     }
 
@@ -214,6 +229,28 @@ impl Plotter for PlottersBackend {
             all_curves,
             &violin_path,
             ctx.context.plot_config.summary_scale,
+            convert_size(ctx.size),
+        );
+    }
+
+    fn relative_throughput(&mut self, ctx: PlotContext<'_>, data: &RelativeThroughput<'_>) {
+        let path = ctx.relative_throughput_path();
+        summary::relative_throughput(ctx.id.as_title(), data, &path, convert_size(ctx.size));
+    }
+
+    fn working_set(
+        &mut self,
+        ctx: PlotContext<'_>,
+        formatter: &dyn ValueFormatter,
+        data: &WorkingSet<'_>,
+    ) {
+        let path = ctx.working_set_path();
+        summary::working_set(
+            formatter,
+            ctx.id.as_title(),
+            data,
+            &path,
+            convert_size(ctx.size),
         );
     }
 
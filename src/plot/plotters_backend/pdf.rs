@@ -1,5 +1,6 @@
 use super::*;
 use crate::measurement::ValueFormatter;
+use crate::plot::outliers_on_density;
 use crate::report::{BenchmarkId, ComparisonData, MeasurementData, ReportContext};
 use plotters::data;
 use plotters::style::RGBAColor;
@@ -116,7 +117,7 @@ pub(crate) fn pdf_small(
     measurements: &MeasurementData<'_>,
     size: Option<(u32, u32)>,
 ) {
-    let avg_times = &*measurements.avg_times;
+    let avg_times = &measurements.avg_times;
     let typical = avg_times.max();
     let mut scaled_avg_times: Vec<f64> = (avg_times as &Sample<f64>).iter().cloned().collect();
     let unit = formatter.scale_values(typical, &mut scaled_avg_times);
@@ -126,6 +127,7 @@ pub(crate) fn pdf_small(
     let (xs, ys, mean_y) = kde::sweep_and_estimate(scaled_avg_times, KDE_POINTS, None, mean);
     let xs_ = Sample::new(&xs);
     let ys_ = Sample::new(&ys);
+    let (mild, severe) = outliers_on_density(avg_times, scaled_avg_times);
 
     let y_limit = ys_.max() * 1.1;
 
@@ -166,6 +168,16 @@ pub(crate) fn pdf_small(
             DARK_BLUE.filled().stroke_width(2),
         )))
         .unwrap();
+
+    for (outliers, color) in [(mild, DARK_ORANGE), (severe, DARK_RED)] {
+        chart
+            .draw_series(
+                outliers
+                    .into_iter()
+                    .map(|point| Circle::new(point, POINT_SIZE, color.filled())),
+            )
+            .unwrap();
+    }
 }
 
 pub(crate) fn pdf(
@@ -206,6 +218,8 @@ pub(crate) fn pdf(
     let path = context.report_path(id, "pdf.svg");
 
     let xs_ = Sample::new(&xs);
+    let (x_min, x_max) =
+        crate::plot::symmetric_limits(formatter, &xs).unwrap_or((xs_.min(), xs_.max()));
 
     let size = size.unwrap_or(SIZE);
     let root_area = SVGBackend::new(&path, (size.0 as u32, size.1 as u32)).into_drawing_area();
@@ -218,9 +232,9 @@ pub(crate) fn pdf(
         .set_label_area_size(LabelAreaPosition::Left, (5).percent_width().min(60))
         .set_label_area_size(LabelAreaPosition::Right, (5).percent_width().min(60))
         .set_label_area_size(LabelAreaPosition::Bottom, (5).percent_height().min(40))
-        .build_cartesian_2d(xs_.min()..xs_.max(), 0.0..max_iters)
+        .build_cartesian_2d(x_min..x_max, 0.0..max_iters)
         .unwrap()
-        .set_secondary_coord(xs_.min()..xs_.max(), 0.0..range.end);
+        .set_secondary_coord(x_min..x_max, 0.0..range.end);
 
     chart
         .configure_mesh()
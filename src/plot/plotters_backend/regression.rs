@@ -1,10 +1,16 @@
 use super::*;
+use crate::plot::clamp_to_smallest_positive;
 
 use std::path::Path;
 
 use crate::estimate::{ConfidenceInterval, Estimate};
 use crate::stats::bivariate::regression::Slope;
 use crate::stats::bivariate::Data;
+use crate::AxisScale;
+use plotters::coord::{
+    ranged1d::{AsRangedCoord, ValueFormatter as PlottersValueFormatter},
+    Shift,
+};
 
 pub(crate) fn regression_figure(
     title: Option<&str>,
@@ -12,6 +18,7 @@ pub(crate) fn regression_figure(
     formatter: &dyn ValueFormatter,
     measurements: &MeasurementData<'_>,
     size: Option<(u32, u32)>,
+    y_scale: AxisScale,
 ) {
     let slope_estimate = measurements.absolute_estimates.slope.as_ref().unwrap();
     let slope_dist = measurements.distributions.slope.as_ref().unwrap();
@@ -22,12 +29,33 @@ pub(crate) fn regression_figure(
     let (max_iters, typical) = (data.x().max(), data.y().max());
     let mut scaled_y: Vec<f64> = data.y().iter().cloned().collect();
     let unit = formatter.scale_values(typical, &mut scaled_y);
+    if let AxisScale::Logarithmic = y_scale {
+        clamp_to_smallest_positive(&mut scaled_y);
+    }
     let scaled_y = Sample::new(&scaled_y);
 
+    // A logarithmic axis can't show zero, so start the regression line at the smallest
+    // iteration count instead of the origin.
+    let start_iters = match y_scale {
+        AxisScale::Linear => 0.0,
+        AxisScale::Logarithmic => data.x().min(),
+    };
+
     let point_estimate = Slope::fit(&measurements.data).0;
-    let mut scaled_points = [point_estimate * max_iters, lb * max_iters, ub * max_iters];
+    let mut scaled_points = [
+        point_estimate * max_iters,
+        lb * max_iters,
+        ub * max_iters,
+        point_estimate * start_iters,
+        lb * start_iters,
+        ub * start_iters,
+    ];
     let _ = formatter.scale_values(typical, &mut scaled_points);
-    let [point, lb, ub] = scaled_points;
+    if let AxisScale::Logarithmic = y_scale {
+        // The lower bound of the confidence interval can be negative.
+        clamp_to_smallest_positive(&mut scaled_points);
+    }
+    let [point, lb, ub, start_point, start_lb, start_ub] = scaled_points;
 
     let exponent = (max_iters.log10() / 3.).floor() as i32 * 3;
 
@@ -41,14 +69,65 @@ pub(crate) fn regression_figure(
     let size = size.unwrap_or(SIZE);
     let root_area = SVGBackend::new(path, size).into_drawing_area();
 
+    let x_range = plotters::data::fitting_range(data.x().iter());
+    let y_range = plotters::data::fitting_range(scaled_y.iter());
+
+    let lines = RegressionLines {
+        start_iters,
+        max_iters,
+        point: (start_point, point),
+        lb: (start_lb, lb),
+        ub: (start_ub, ub),
+    };
+
+    match y_scale {
+        AxisScale::Linear => draw_regression_figure(
+            root_area, title, data, scaled_y, &x_label, x_scale, unit, x_range, y_range, lines,
+        ),
+        AxisScale::Logarithmic => draw_regression_figure(
+            root_area,
+            title,
+            data,
+            scaled_y,
+            &x_label,
+            x_scale,
+            unit,
+            x_range,
+            y_range.log_scale(),
+            lines,
+        ),
+    }
+}
+
+// The (start, end) y values of the regression line and its confidence interval.
+struct RegressionLines {
+    start_iters: f64,
+    max_iters: f64,
+    point: (f64, f64),
+    lb: (f64, f64),
+    ub: (f64, f64),
+}
+
+#[allow(clippy::too_many_arguments)]
+fn draw_regression_figure<YR: AsRangedCoord<Value = f64>>(
+    root_area: DrawingArea<SVGBackend, Shift>,
+    title: Option<&str>,
+    data: &Data<'_, f64, f64>,
+    scaled_y: &Sample<f64>,
+    x_label: &str,
+    x_scale: f64,
+    y_unit: &str,
+    x_range: std::ops::Range<f64>,
+    y_range: YR,
+    lines: RegressionLines,
+) where
+    YR::CoordDescType: PlottersValueFormatter<f64>,
+{
     let mut cb = ChartBuilder::on(&root_area);
     if let Some(title) = title {
         cb.caption(title, (DEFAULT_FONT, 20));
     }
 
-    let x_range = plotters::data::fitting_range(data.x().iter());
-    let y_range = plotters::data::fitting_range(scaled_y.iter());
-
     let mut chart = cb
         .margin((5).percent())
         .set_label_area_size(LabelAreaPosition::Left, (5).percent_width().min(60))
@@ -59,26 +138,40 @@ pub(crate) fn regression_figure(
     chart
         .configure_mesh()
         .x_desc(x_label)
-        .y_desc(format!("Total sample time ({})", unit))
+        .y_desc(format!("Total sample time ({})", y_unit))
         .x_label_formatter(&|x| pretty_print_float(x * x_scale, true))
         .light_line_style(&TRANSPARENT)
         .draw()
         .unwrap();
 
+    let RegressionLines {
+        start_iters,
+        max_iters,
+        point,
+        lb,
+        ub,
+    } = lines;
+
+    // The confidence band is drawn first, so that the line and the samples are on top of it.
     chart
-        .draw_series(
-            data.x()
-                .iter()
-                .zip(scaled_y.iter())
-                .map(|(x, y)| Circle::new((*x, *y), POINT_SIZE, DARK_BLUE.filled())),
-        )
+        .draw_series(std::iter::once(Polygon::new(
+            vec![
+                (start_iters, lb.0),
+                (max_iters, lb.1),
+                (max_iters, ub.1),
+                (start_iters, ub.0),
+            ],
+            DARK_BLUE.mix(0.25).filled(),
+        )))
         .unwrap()
-        .label("Sample")
-        .legend(|(x, y)| Circle::new((x + 10, y), POINT_SIZE, DARK_BLUE.filled()));
+        .label("Confidence interval")
+        .legend(|(x, y)| {
+            Rectangle::new([(x, y - 5), (x + 20, y + 5)], DARK_BLUE.mix(0.25).filled())
+        });
 
     chart
         .draw_series(std::iter::once(PathElement::new(
-            vec![(0.0, 0.0), (max_iters, point)],
+            vec![(start_iters, point.0), (max_iters, point.1)],
             &DARK_BLUE,
         )))
         .unwrap()
@@ -91,15 +184,15 @@ pub(crate) fn regression_figure(
         });
 
     chart
-        .draw_series(std::iter::once(Polygon::new(
-            vec![(0.0, 0.0), (max_iters, lb), (max_iters, ub)],
-            DARK_BLUE.mix(0.25).filled(),
-        )))
+        .draw_series(
+            data.x()
+                .iter()
+                .zip(scaled_y.iter())
+                .map(|(x, y)| Circle::new((*x, *y), POINT_SIZE, DARK_BLUE.filled())),
+        )
         .unwrap()
-        .label("Confidence interval")
-        .legend(|(x, y)| {
-            Rectangle::new([(x, y - 5), (x + 20, y + 5)], DARK_BLUE.mix(0.25).filled())
-        });
+        .label("Sample")
+        .legend(|(x, y)| Circle::new((x + 10, y), POINT_SIZE, DARK_BLUE.filled()));
 
     if title.is_some() {
         chart
@@ -110,6 +203,7 @@ pub(crate) fn regression_figure(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub(crate) fn regression_comparison_figure(
     title: Option<&str>,
     path: &Path,
@@ -118,6 +212,7 @@ pub(crate) fn regression_comparison_figure(
     comparison: &ComparisonData,
     base_data: &Data<'_, f64, f64>,
     size: Option<(u32, u32)>,
+    y_scale: AxisScale,
 ) {
     let data = &measurements.data;
     let max_iters = base_data.x().max().max(data.x().max());
@@ -154,6 +249,13 @@ pub(crate) fn regression_comparison_figure(
         ..
     } = measurements.absolute_estimates.slope.as_ref().unwrap();
 
+    // As in `regression_figure`, the lines start at the smallest iteration count on a
+    // logarithmic axis.
+    let start_iters = match y_scale {
+        AxisScale::Linear => 0.0,
+        AxisScale::Logarithmic => base_data.x().min().min(data.x().min()),
+    };
+
     let mut points = [
         base_lb * max_iters,
         base_point * max_iters,
@@ -161,15 +263,78 @@ pub(crate) fn regression_comparison_figure(
         lb * max_iters,
         point * max_iters,
         ub * max_iters,
+        base_lb * start_iters,
+        base_point * start_iters,
+        base_ub * start_iters,
+        lb * start_iters,
+        point * start_iters,
+        ub * start_iters,
     ];
     let unit = formatter.scale_values(typical, &mut points);
-    let [base_lb, base_point, base_ub, lb, point, ub] = points;
+    if let AxisScale::Logarithmic = y_scale {
+        clamp_to_smallest_positive(&mut points);
+    }
+    let [base_lb, base_point, base_ub, lb, point, ub, start_base_lb, start_base_point, start_base_ub, start_lb, start_point, start_ub] =
+        points;
 
+    let base_lines = RegressionLines {
+        start_iters,
+        max_iters,
+        point: (start_base_point, base_point),
+        lb: (start_base_lb, base_lb),
+        ub: (start_base_ub, base_ub),
+    };
+    let lines = RegressionLines {
+        start_iters,
+        max_iters,
+        point: (start_point, point),
+        lb: (start_lb, lb),
+        ub: (start_ub, ub),
+    };
+
+    let y_min = start_base_lb.min(start_lb);
     let y_max = point.max(base_point);
 
     let size = size.unwrap_or(SIZE);
     let root_area = SVGBackend::new(path, size).into_drawing_area();
 
+    match y_scale {
+        AxisScale::Linear => draw_regression_comparison_figure(
+            root_area,
+            title,
+            &x_label,
+            x_scale,
+            unit,
+            y_min..y_max,
+            base_lines,
+            lines,
+        ),
+        AxisScale::Logarithmic => draw_regression_comparison_figure(
+            root_area,
+            title,
+            &x_label,
+            x_scale,
+            unit,
+            (y_min..y_max).log_scale(),
+            base_lines,
+            lines,
+        ),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn draw_regression_comparison_figure<YR: AsRangedCoord<Value = f64>>(
+    root_area: DrawingArea<SVGBackend, Shift>,
+    title: Option<&str>,
+    x_label: &str,
+    x_scale: f64,
+    y_unit: &str,
+    y_range: YR,
+    base_lines: RegressionLines,
+    lines: RegressionLines,
+) where
+    YR::CoordDescType: PlottersValueFormatter<f64>,
+{
     let mut cb = ChartBuilder::on(&root_area);
     if let Some(title) = title {
         cb.caption(title, (DEFAULT_FONT, 20));
@@ -179,50 +344,50 @@ pub(crate) fn regression_comparison_figure(
         .margin((5).percent())
         .set_label_area_size(LabelAreaPosition::Left, (5).percent_width().min(60))
         .set_label_area_size(LabelAreaPosition::Bottom, (5).percent_height().min(40))
-        .build_cartesian_2d(0.0..max_iters, 0.0..y_max)
+        .build_cartesian_2d(lines.start_iters..lines.max_iters, y_range)
         .unwrap();
 
     chart
         .configure_mesh()
         .x_desc(x_label)
-        .y_desc(format!("Total sample time ({})", unit))
+        .y_desc(format!("Total sample time ({})", y_unit))
         .x_label_formatter(&|x| pretty_print_float(x * x_scale, true))
         .light_line_style(&TRANSPARENT)
         .draw()
         .unwrap();
 
-    chart
-        .draw_series(vec![
-            PathElement::new(vec![(0.0, 0.0), (max_iters, base_point)], &DARK_RED).into_dyn(),
-            Polygon::new(
-                vec![(0.0, 0.0), (max_iters, base_lb), (max_iters, base_ub)],
-                DARK_RED.mix(0.25).filled(),
-            )
-            .into_dyn(),
-        ])
-        .unwrap()
-        .label("Base Sample")
-        .legend(|(x, y)| {
-            PathElement::new(vec![(x, y), (x + 20, y)], DARK_RED.filled().stroke_width(2))
-        });
-
-    chart
-        .draw_series(vec![
-            PathElement::new(vec![(0.0, 0.0), (max_iters, point)], &DARK_BLUE).into_dyn(),
-            Polygon::new(
-                vec![(0.0, 0.0), (max_iters, lb), (max_iters, ub)],
-                DARK_BLUE.mix(0.25).filled(),
-            )
-            .into_dyn(),
-        ])
-        .unwrap()
-        .label("New Sample")
-        .legend(|(x, y)| {
-            PathElement::new(
-                vec![(x, y), (x + 20, y)],
-                DARK_BLUE.filled().stroke_width(2),
-            )
-        });
+    for (lines, color, label) in [
+        (base_lines, DARK_RED, "Base Sample"),
+        (lines, DARK_BLUE, "New Sample"),
+    ] {
+        let RegressionLines {
+            start_iters,
+            max_iters,
+            point,
+            lb,
+            ub,
+        } = lines;
+        chart
+            .draw_series(vec![
+                Polygon::new(
+                    vec![
+                        (start_iters, lb.0),
+                        (max_iters, lb.1),
+                        (max_iters, ub.1),
+                        (start_iters, ub.0),
+                    ],
+                    color.mix(0.25).filled(),
+                )
+                .into_dyn(),
+                PathElement::new(vec![(start_iters, point.0), (max_iters, point.1)], color)
+                    .into_dyn(),
+            ])
+            .unwrap()
+            .label(label)
+            .legend(move |(x, y)| {
+                PathElement::new(vec![(x, y), (x + 20, y)], color.filled().stroke_width(2))
+            });
+    }
 
     if title.is_some() {
         chart
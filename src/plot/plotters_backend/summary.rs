@@ -1,4 +1,5 @@
 use super::*;
+use crate::format;
 use crate::AxisScale;
 use itertools::Itertools;
 use plotters::coord::{
@@ -27,6 +28,7 @@ pub fn line_comparison(
     path: &Path,
     value_type: ValueType,
     axis_scale: AxisScale,
+    size: Option<(u32, u32)>,
 ) {
     let (unit, series_data) = line_comparison_series_data(formatter, all_curves);
 
@@ -34,7 +36,7 @@ pub fn line_comparison(
         plotters::data::fitting_range(series_data.iter().flat_map(|(_, xs, _)| xs.iter()));
     let y_range =
         plotters::data::fitting_range(series_data.iter().flat_map(|(_, _, ys)| ys.iter()));
-    let root_area = SVGBackend::new(&path, SIZE)
+    let root_area = SVGBackend::new(&path, size.unwrap_or(SIZE))
         .into_drawing_area()
         .titled(&format!("{}: Comparison", title), (DEFAULT_FONT, 20))
         .unwrap();
@@ -157,6 +159,7 @@ pub fn violin(
     all_curves: &[&(&BenchmarkId, Vec<f64>)],
     path: &Path,
     axis_scale: AxisScale,
+    size: Option<(u32, u32)>,
 ) {
     let all_curves_vec = all_curves.iter().rev().cloned().collect::<Vec<_>>();
     let all_curves: &[&(&BenchmarkId, Vec<f64>)] = &*all_curves_vec;
@@ -199,7 +202,9 @@ pub fn violin(
     x_range.start = 0.0;
     let y_range = -0.5..all_curves.len() as f64 - 0.5;
 
-    let size = (960, 150 + (18 * all_curves.len() as u32));
+    // The height depends on the number of benchmarks, so only the width can be configured.
+    let width = size.map_or(960, |(width, _)| width);
+    let size = (width, 150 + (18 * all_curves.len() as u32));
 
     let root_area = SVGBackend::new(&path, size)
         .into_drawing_area()
@@ -263,3 +268,144 @@ fn draw_violin_figure<XR: AsRangedCoord<Value = f64>, YR: AsRangedCoord<Value =
             .unwrap();
     }
 }
+
+// Fraction of the space reserved for each parameter which is covered by its bars.
+const BAR_GROUP_WIDTH: f64 = 0.8;
+
+pub fn relative_throughput(
+    title: &str,
+    data: &RelativeThroughput<'_>,
+    path: &Path,
+    size: Option<(u32, u32)>,
+) {
+    let root_area = SVGBackend::new(&path, size.unwrap_or(SIZE))
+        .into_drawing_area()
+        .titled(
+            &format!("{}: Relative Throughput", title),
+            (DEFAULT_FONT, 20),
+        )
+        .unwrap();
+
+    let mut chart = ChartBuilder::on(&root_area)
+        .margin((5).percent())
+        .set_label_area_size(LabelAreaPosition::Left, (5).percent_width().min(60))
+        .set_label_area_size(LabelAreaPosition::Bottom, (5).percent_height().min(40))
+        .build_cartesian_2d(-0.5..data.parameters.len() as f64 - 0.5, 0.0..1.25)
+        .unwrap();
+
+    chart
+        .configure_mesh()
+        .disable_x_mesh()
+        .x_desc("Input")
+        .y_desc("Throughput relative to the fastest")
+        .x_label_formatter(&|x: &f64| {
+            data.parameters
+                .get(x.round() as usize)
+                .map_or_else(String::new, |parameter| parameter.to_string())
+        })
+        .x_labels(data.parameters.len())
+        .draw()
+        .unwrap();
+
+    let bar_width = BAR_GROUP_WIDTH / data.functions.len() as f64;
+    for (id, (name, relative)) in data.functions.iter().enumerate() {
+        let color = COMPARISON_COLORS[id % NUM_COLORS];
+        let bars = relative.iter().enumerate().filter_map(|(i, value)| {
+            let left = i as f64 - BAR_GROUP_WIDTH / 2.0 + id as f64 * bar_width;
+            value.map(|value| {
+                Rectangle::new([(left, 0.0), (left + bar_width, value)], color.filled())
+            })
+        });
+        let series = chart.draw_series(bars).unwrap();
+        if let Some(name) = name {
+            series.label(name.as_str()).legend(move |(x, y)| {
+                Rectangle::new([(x, y - 5), (x + 20, y + 5)], color.filled())
+            });
+        }
+    }
+
+    chart
+        .configure_series_labels()
+        .position(SeriesLabelPosition::UpperRight)
+        .background_style(WHITE.mix(0.8))
+        .border_style(BLACK)
+        .draw()
+        .unwrap();
+}
+
+pub fn working_set(
+    formatter: &dyn ValueFormatter,
+    title: &str,
+    data: &WorkingSet<'_>,
+    path: &Path,
+    size: Option<(u32, u32)>,
+) {
+    let max = data
+        .functions
+        .iter()
+        .flat_map(|(_, points)| points.iter().map(|&(_, time)| time))
+        .fold(f64::NAN, f64::max);
+    let mut dummy = [1.0];
+    let unit = formatter.scale_values(max, &mut dummy);
+    let series_data: Vec<_> = data
+        .functions
+        .iter()
+        .map(|(name, points)| {
+            let (sizes, mut times): (Vec<f64>, Vec<f64>) = points.iter().cloned().unzip();
+            formatter.scale_values(max, &mut times);
+            (name, sizes, times)
+        })
+        .collect();
+
+    let x_range =
+        plotters::data::fitting_range(series_data.iter().flat_map(|(_, xs, _)| xs.iter()));
+    let y_range =
+        plotters::data::fitting_range(series_data.iter().flat_map(|(_, _, ys)| ys.iter()));
+    let root_area = SVGBackend::new(&path, size.unwrap_or(SIZE))
+        .into_drawing_area()
+        .titled(&format!("{}: Working Set", title), (DEFAULT_FONT, 20))
+        .unwrap();
+
+    // The working set sizes usually grow geometrically, so the x-axis is always logarithmic.
+    let mut chart = ChartBuilder::on(&root_area)
+        .margin((5).percent())
+        .set_label_area_size(LabelAreaPosition::Left, (5).percent_width().min(60))
+        .set_label_area_size(LabelAreaPosition::Bottom, (5).percent_height().min(40))
+        .build_cartesian_2d(x_range.log_scale(), 0.0..y_range.end * 1.1)
+        .unwrap();
+
+    chart
+        .configure_mesh()
+        .disable_mesh()
+        .x_desc("Working Set Size")
+        .y_desc(format!("Average time per {} ({})", data.per, unit))
+        .x_label_formatter(&|&x| format::bytes(x.round() as u64))
+        .draw()
+        .unwrap();
+
+    for (id, (name, xs, ys)) in (0..).zip(series_data) {
+        let series = chart
+            .draw_series(
+                LineSeries::new(
+                    xs.into_iter().zip(ys),
+                    COMPARISON_COLORS[id % NUM_COLORS].filled(),
+                )
+                .point_size(POINT_SIZE),
+            )
+            .unwrap();
+        if let Some(name) = name {
+            series.label(name.as_str()).legend(move |(x, y)| {
+                Rectangle::new(
+                    [(x, y - 5), (x + 20, y + 5)],
+                    COMPARISON_COLORS[id % NUM_COLORS].filled(),
+                )
+            });
+        }
+    }
+
+    chart
+        .configure_series_labels()
+        .position(SeriesLabelPosition::UpperLeft)
+        .draw()
+        .unwrap();
+}
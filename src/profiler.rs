@@ -2,6 +2,7 @@
 //! to be hooked into the `--profile-time` argument at compile-time. Users of
 //! out-of-process profilers such as perf don't need to do anything special.
 
+use std::cell::RefCell;
 use std::path::Path;
 
 /// Extension trait for external crates to implement which provides start/stop
@@ -26,3 +27,70 @@ impl Profiler for ExternalProfiler {
     fn start_profiling(&mut self, _benchmark_id: &str, _benchmark_dir: &Path) {}
     fn stop_profiling(&mut self, _benchmark_id: &str, _benchmark_dir: &Path) {}
 }
+
+/// Samples the call stack of the benchmarked code while it is profiled and writes a flamegraph of
+/// the samples to `flamegraph.svg` in the benchmark's profile directory, so that
+/// `cargo bench -- --profile-time 10` profiles a benchmark without any external tools.
+///
+/// This requires the `flamegraph` feature, which also makes it the default profiler, sampling 100
+/// times per second. It is only available on Unix; it is supported on Linux and macOS.
+#[cfg(all(feature = "flamegraph", unix))]
+pub struct FlamegraphProfiler {
+    frequency: i32,
+    guard: Option<pprof::ProfilerGuard<'static>>,
+}
+#[cfg(all(feature = "flamegraph", unix))]
+impl FlamegraphProfiler {
+    /// Creates a profiler which samples the call stack `frequency` times per second.
+    pub fn new(frequency: i32) -> FlamegraphProfiler {
+        assert!(frequency > 0, "The sampling frequency must be positive");
+        FlamegraphProfiler {
+            frequency,
+            guard: None,
+        }
+    }
+}
+#[cfg(all(feature = "flamegraph", unix))]
+impl Profiler for FlamegraphProfiler {
+    fn start_profiling(&mut self, benchmark_id: &str, _benchmark_dir: &Path) {
+        let guard = pprof::ProfilerGuardBuilder::default()
+            .frequency(self.frequency)
+            .blocklist(&["libc", "libgcc", "pthread", "vdso"])
+            .build();
+        match guard {
+            Ok(guard) => self.guard = Some(guard),
+            Err(err) => error!("Failed to start profiling {}: {}", benchmark_id, err),
+        }
+    }
+
+    fn stop_profiling(&mut self, benchmark_id: &str, benchmark_dir: &Path) {
+        let guard = match self.guard.take() {
+            Some(guard) => guard,
+            None => return,
+        };
+        let report = match guard.report().build() {
+            Ok(report) => report,
+            Err(err) => {
+                error!("Failed to profile {}: {}", benchmark_id, err);
+                return;
+            }
+        };
+        try_else_return!(crate::fs::mkdirp(&benchmark_dir));
+        let path = benchmark_dir.join("flamegraph.svg");
+        let written = std::fs::File::create(&path)
+            .map_err(|err| err.to_string())
+            .and_then(|file| report.flamegraph(file).map_err(|err| err.to_string()));
+        if let Err(err) = written {
+            error!("Failed to write the flamegraph {:?}: {}", path, err);
+        }
+    }
+}
+
+// The profiler used unless another is set with `Criterion::with_profiler`.
+pub(crate) fn default_profiler() -> Box<RefCell<dyn Profiler>> {
+    #[cfg(all(feature = "flamegraph", unix))]
+    let profiler = FlamegraphProfiler::new(100);
+    #[cfg(not(all(feature = "flamegraph", unix)))]
+    let profiler = ExternalProfiler;
+    Box::new(RefCell::new(profiler))
+}
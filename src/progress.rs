@@ -0,0 +1,130 @@
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::error::Result;
+use crate::fs;
+
+// Width of the progress bar, in characters.
+const BAR_WIDTH: usize = 20;
+
+lazy_static! {
+    /// The progress of the run, if the progress bar is enabled. It is process-global because each
+    /// `criterion_group!` runs on its own `Criterion` instance.
+    static ref PROGRESS: Mutex<Option<Progress>> = Mutex::new(None);
+}
+
+struct Progress {
+    start: Instant,
+    // When the benchmark currently running was started.
+    current_start: Instant,
+    // Number of benchmarks started so far, including the one currently running.
+    started: usize,
+    // Number of benchmarks in the previous run of this benchmark target, if known.
+    total: Option<usize>,
+}
+impl Progress {
+    fn status(&self) -> String {
+        let completed = self.started.saturating_sub(1);
+        match self.total {
+            Some(total) if self.started <= total => {
+                let filled = BAR_WIDTH * completed / total;
+                let mut status = format!(
+                    "[{}{}] {}/{}",
+                    "=".repeat(filled),
+                    " ".repeat(BAR_WIDTH - filled),
+                    self.started,
+                    total
+                );
+                if completed > 0 {
+                    let per_benchmark =
+                        self.current_start.duration_since(self.start) / completed as u32;
+                    let remaining = (per_benchmark * (total - completed) as u32)
+                        .checked_sub(self.current_start.elapsed())
+                        .unwrap_or_default();
+                    status.push_str(&format!(", ETA {}", eta(remaining)));
+                }
+                status
+            }
+            _ => format!("[benchmark {}]", self.started),
+        }
+    }
+}
+
+fn eta(remaining: Duration) -> String {
+    // Rounded up, so that the estimate only reaches zero when the run is finished.
+    let secs = remaining.as_secs() + u64::from(remaining.subsec_nanos() > 0);
+    if secs >= 3600 {
+        format!("{}h {:02}m", secs / 3600, secs / 60 % 60)
+    } else {
+        format!("{}m {:02}s", secs / 60, secs % 60)
+    }
+}
+
+/// Counts the start of a benchmark. The first call starts showing the progress bar, with the
+/// number of benchmarks in the previous run (as saved by `save_total`) as the expected total.
+pub fn benchmark_started(output_directory: &Path) {
+    let mut progress = PROGRESS.lock().unwrap();
+    let progress = progress.get_or_insert_with(|| Progress {
+        start: Instant::now(),
+        current_start: Instant::now(),
+        started: 0,
+        total: fs::load(&total_path(output_directory)).ok(),
+    });
+    progress.started += 1;
+    progress.current_start = Instant::now();
+}
+
+/// Returns the progress bar to show before the status of the current benchmark, if it is enabled.
+pub fn status() -> Option<String> {
+    PROGRESS.lock().unwrap().as_ref().map(Progress::status)
+}
+
+/// Saves the number of benchmarks run, to be used as the expected total of the next run.
+pub fn save_total(output_directory: &Path, total: usize) -> Result<()> {
+    let path = total_path(output_directory);
+    fs::mkdirp(&path.parent().unwrap())?;
+    fs::save(&total, &path)
+}
+
+// The benchmark count is saved per benchmark target, since each is a separate process. Cargo
+// names the executables `<target>-<hash>`; the hash is dropped so the count survives rebuilds.
+fn total_path(output_directory: &Path) -> PathBuf {
+    let exe = std::env::current_exe().ok();
+    let stem = exe
+        .as_ref()
+        .and_then(|exe| exe.file_stem())
+        .and_then(|stem| stem.to_str())
+        .unwrap_or("benchmarks");
+    let target = match stem.rfind('-') {
+        Some(i) if stem[i + 1..].chars().all(|c| c.is_ascii_hexdigit()) => &stem[..i],
+        _ => stem,
+    };
+    output_directory
+        .join(".progress")
+        .join(format!("{}.json", target))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_status() {
+        let now = Instant::now();
+        let mut progress = Progress {
+            start: now - Duration::from_secs(45),
+            current_start: now - Duration::from_secs(5),
+            started: 5,
+            total: Some(11),
+        };
+        // Four benchmarks took 40s, so the remaining seven should take 70s, of which 5s have
+        // already been spent on the current one.
+        assert_eq!(progress.status(), "[=======             ] 5/11, ETA 1m 05s");
+
+        progress.started = 12;
+        assert_eq!(progress.status(), "[benchmark 12]");
+        progress.total = None;
+        assert_eq!(progress.status(), "[benchmark 12]");
+    }
+}
@@ -1,13 +1,21 @@
-use crate::stats::univariate::outliers::tukey::LabeledSample;
+use crate::stats::univariate::outliers::tukey::{Label, LabeledSample};
 use crate::{csv_report::FileCsvReport, stats::bivariate::regression::Slope};
-use crate::{html::Html, stats::bivariate::Data};
+use crate::{html::Html, stats::bivariate::Data, text_report::FileTextReport};
 
-use crate::estimate::{ChangeDistributions, ChangeEstimates, Distributions, Estimate, Estimates};
+use crate::estimate::{
+    ChangeDistributions, ChangeEstimates, Distributions, Estimate, Estimates, Statistic,
+    ThroughputEstimate,
+};
 use crate::format;
-use crate::measurement::ValueFormatter;
+use crate::measurement::{FixedTimeUnitFormatter, ValueFormatter};
+use crate::progress;
 use crate::stats::univariate::Sample;
 use crate::stats::Distribution;
-use crate::{PlotConfiguration, Throughput};
+use crate::system::SystemSamples;
+use crate::{
+    AxisScale, DurationExt, Palette, PlotConfiguration, PlotSet, PlotTerminalConfig,
+    RegressionMethod, SummaryOrder, Throughput, TimeUnit, WarmUp,
+};
 use std::cell::Cell;
 use std::cmp;
 use std::collections::HashSet;
@@ -15,6 +23,7 @@ use std::fmt;
 use std::io::stdout;
 use std::io::Write;
 use std::path::{Path, PathBuf};
+use std::sync::Once;
 
 const MAX_DIRECTORY_NAME_LEN: usize = 64;
 const MAX_TITLE_LEN: usize = 100;
@@ -23,10 +32,15 @@ pub(crate) struct ComparisonData {
     pub p_value: f64,
     pub t_distribution: Distribution<f64>,
     pub t_value: f64,
+    pub effect_size: f64,
     pub relative_estimates: ChangeEstimates,
     pub relative_distributions: ChangeDistributions,
     pub significance_threshold: f64,
     pub noise_threshold: f64,
+    /// The lower of the R² of the linear fits to the new sample and to the baseline.
+    pub r_squared: f64,
+    /// The R² below which a change is uncertain, set with `Criterion::min_r2_for_regression`.
+    pub min_r_squared: Option<f64>,
     pub base_iter_counts: Vec<f64>,
     pub base_sample_times: Vec<f64>,
     pub base_avg_times: Vec<f64>,
@@ -40,8 +54,21 @@ pub(crate) struct MeasurementData<'a> {
     pub distributions: Distributions,
     pub comparison: Option<ComparisonData>,
     pub throughput: Option<Throughput>,
+    pub throughput_estimate: Option<ThroughputEstimate>,
+    /// Whether `absolute_estimates.std_dev` is the winsorized standard deviation.
+    pub robust_dispersion: bool,
+    pub regression_method: RegressionMethod,
+    /// The state of the system before and after the benchmark, with `Criterion::monitor_system`.
+    pub system: Option<SystemSamples>,
+    /// The value of the very first iteration, with `Criterion::report_cold_start`.
+    pub cold_start: Option<f64>,
 }
 impl<'a> MeasurementData<'a> {
+    /// The standard deviation of the time per iteration relative to its mean, derived from the
+    /// point estimates.
+    pub fn coefficient_of_variation(&self) -> f64 {
+        self.absolute_estimates.std_dev.point_estimate / self.absolute_estimates.mean.point_estimate
+    }
     pub fn iter_counts(&self) -> &Sample<f64> {
         self.data.x()
     }
@@ -64,6 +91,10 @@ pub struct BenchmarkId {
     pub function_id: Option<String>,
     pub value_str: Option<String>,
     pub throughput: Option<Throughput>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub working_set_bytes: Option<u64>,
     full_id: String,
     directory_name: String,
     title: String,
@@ -77,6 +108,27 @@ fn truncate_to_character_boundary(s: &mut String, max_len: usize) {
     s.truncate(boundary);
 }
 
+/// Compares the parameters of two benchmarks, so that numeric parameters are ordered by value
+/// ("2" before "10") and before any others, which are ordered lexically.
+pub(crate) fn compare_parameters(a: Option<&str>, b: Option<&str>) -> cmp::Ordering {
+    fn parse(s: &str) -> Option<f64> {
+        s.parse::<f64>().ok().filter(|n| !n.is_nan())
+    }
+
+    match (a, b) {
+        (Some(a), Some(b)) => match (parse(a), parse(b)) {
+            (Some(x), Some(y)) => x
+                .partial_cmp(&y)
+                .unwrap_or(cmp::Ordering::Equal)
+                .then_with(|| a.cmp(b)),
+            (Some(_), None) => cmp::Ordering::Less,
+            (None, Some(_)) => cmp::Ordering::Greater,
+            (None, None) => a.cmp(b),
+        },
+        _ => a.cmp(&b),
+    }
+}
+
 pub fn make_filename_safe(string: &str) -> String {
     let mut string = string.replace(
         &['?', '"', '/', '\\', '*', '<', '>', ':', '|', '^'][..],
@@ -150,12 +202,32 @@ impl BenchmarkId {
             function_id,
             value_str,
             throughput,
+            tags: vec![],
+            working_set_bytes: None,
             full_id,
             directory_name,
             title,
         }
     }
 
+    /// The ID under which the secondary quantity `name` of this benchmark is reported, see
+    /// `MultiMeasurement`.
+    pub fn for_quantity(&self, name: &str) -> BenchmarkId {
+        let function_id = match &self.function_id {
+            Some(func) => format!("{}/{}", func, name),
+            None => name.to_owned(),
+        };
+        let mut id = BenchmarkId::new(
+            self.group_id.clone(),
+            Some(function_id),
+            self.value_str.clone(),
+            None,
+        );
+        id.tags = self.tags.clone();
+        id.working_set_bytes = self.working_set_bytes;
+        id
+    }
+
     pub fn id(&self) -> &str {
         &self.full_id
     }
@@ -190,6 +262,15 @@ impl BenchmarkId {
         }
     }
 
+    /// Orders benchmarks by group, then function, then parameter, comparing numeric parameters
+    /// by value.
+    pub(crate) fn cmp_natural(&self, other: &BenchmarkId) -> cmp::Ordering {
+        self.group_id
+            .cmp(&other.group_id)
+            .then_with(|| self.function_id.cmp(&other.function_id))
+            .then_with(|| compare_parameters(self.value_str.as_deref(), other.value_str.as_deref()))
+    }
+
     pub fn ensure_directory_name_unique(&mut self, existing_directories: &HashSet<String>) {
         if !existing_directories.contains(self.as_directory_name()) {
             return;
@@ -247,11 +328,58 @@ impl fmt::Debug for BenchmarkId {
     }
 }
 
+#[derive(Clone)]
 pub struct ReportContext {
     pub output_directory: PathBuf,
     pub plot_config: PlotConfiguration,
+    pub emit_gnuplot_scripts: bool,
+    pub plot_size: Option<(usize, usize)>,
+    pub plot_font_size: Option<f64>,
+    /// The label set with `Criterion::annotate`, or `None` to label the plots with the current
+    /// git commit.
+    pub plot_annotation: Option<String>,
+    pub plot_terminal: PlotTerminalConfig,
+    pub plot_palette: Palette,
+    pub regression_y_scale: AxisScale,
+    pub summary_order: SummaryOrder,
+    /// The number of functions shown in the comparison charts of the summary reports, or `None`
+    /// to show all of them.
+    pub summary_top_n: Option<usize>,
+    pub plots: PlotSet,
+    pub record_sample_order: bool,
+    pub accessible_html: bool,
+    /// The coefficient of variation above which a benchmark is flagged, or `None` if it isn't
+    /// reported.
+    pub cv_threshold: Option<f64>,
+    pub time_unit: Option<TimeUnit>,
+    pub time_precision: Option<usize>,
+    /// The unit of values transformed with `BenchmarkGroup::value_transform`, if any.
+    pub value_unit: Option<&'static str>,
 }
 impl ReportContext {
+    /// Wraps `formatter` so that times are displayed in the unit chosen with
+    /// `Criterion::time_unit`, if any, and transformed values in their own unit.
+    pub(crate) fn time_formatter<'a>(
+        &self,
+        formatter: &'a dyn ValueFormatter,
+    ) -> FixedTimeUnitFormatter<'a> {
+        FixedTimeUnitFormatter {
+            inner: formatter,
+            unit: self.time_unit,
+            precision: self.time_precision,
+            value_unit: self.value_unit,
+        }
+    }
+
+    /// Returns the label to draw in the corner of each plot, if any.
+    pub(crate) fn annotation(&self) -> Option<&str> {
+        match &self.plot_annotation {
+            Some(label) if label.is_empty() => None,
+            Some(label) => Some(label),
+            None => crate::default_annotation(),
+        }
+    }
+
     pub fn report_path<P: AsRef<Path> + ?Sized>(&self, id: &BenchmarkId, file_name: &P) -> PathBuf {
         let mut path = self.output_directory.clone();
         path.push(id.as_directory_name());
@@ -266,8 +394,16 @@ pub(crate) trait Report {
     fn test_pass(&self, _id: &BenchmarkId, _context: &ReportContext) {}
 
     fn benchmark_start(&self, _id: &BenchmarkId, _context: &ReportContext) {}
+    fn timer_overhead(
+        &self,
+        _context: &ReportContext,
+        _overhead: f64,
+        _subtracted: bool,
+        _formatter: &dyn ValueFormatter,
+    ) {
+    }
     fn profile(&self, _id: &BenchmarkId, _context: &ReportContext, _profile_ns: f64) {}
-    fn warmup(&self, _id: &BenchmarkId, _context: &ReportContext, _warmup_ns: f64) {}
+    fn warmup(&self, _id: &BenchmarkId, _context: &ReportContext, _warm_up: WarmUp) {}
     fn terminated(&self, _id: &BenchmarkId, _context: &ReportContext) {}
     fn analysis(&self, _id: &BenchmarkId, _context: &ReportContext) {}
     fn measurement_start(
@@ -296,6 +432,7 @@ pub(crate) trait Report {
     }
     fn final_summary(&self, _context: &ReportContext) {}
     fn group_separator(&self) {}
+    fn error(&self, _id: &BenchmarkId, _context: &ReportContext, _message: &str) {}
 }
 
 pub(crate) struct Reports {
@@ -303,10 +440,16 @@ pub(crate) struct Reports {
     pub(crate) cli: CliReport,
     pub(crate) bencher_enabled: bool,
     pub(crate) bencher: BencherReport,
+    pub(crate) oneline_enabled: bool,
+    pub(crate) oneline: OneLineReport,
+    pub(crate) json_enabled: bool,
+    pub(crate) json: JsonReport,
     pub(crate) csv_enabled: bool,
     pub(crate) csv: FileCsvReport,
-    pub(crate) html_enabled: bool,
-    pub(crate) html: Html,
+    pub(crate) text_enabled: bool,
+    pub(crate) text: FileTextReport,
+    // `None` when plots are disabled, so that no plotting work is done at all.
+    pub(crate) html: Option<Html>,
 }
 macro_rules! reports_impl {
     (fn $name:ident(&self, $($argn:ident: $argt:ty),*)) => {
@@ -317,11 +460,20 @@ macro_rules! reports_impl {
             if self.bencher_enabled {
                 self.bencher.$name($($argn),*);
             }
+            if self.oneline_enabled {
+                self.oneline.$name($($argn),*);
+            }
+            if self.json_enabled {
+                self.json.$name($($argn),*);
+            }
             if self.csv_enabled {
                 self.csv.$name($($argn),*);
             }
-            if self.html_enabled {
-                self.html.$name($($argn),*);
+            if self.text_enabled {
+                self.text.$name($($argn),*);
+            }
+            if let Some(html) = &self.html {
+                html.$name($($argn),*);
             }
         }
     };
@@ -331,8 +483,15 @@ impl Report for Reports {
     reports_impl!(fn test_start(&self, id: &BenchmarkId, context: &ReportContext));
     reports_impl!(fn test_pass(&self, id: &BenchmarkId, context: &ReportContext));
     reports_impl!(fn benchmark_start(&self, id: &BenchmarkId, context: &ReportContext));
+    reports_impl!(fn timer_overhead(
+        &self,
+        context: &ReportContext,
+        overhead: f64,
+        subtracted: bool,
+        formatter: &dyn ValueFormatter
+    ));
     reports_impl!(fn profile(&self, id: &BenchmarkId, context: &ReportContext, profile_ns: f64));
-    reports_impl!(fn warmup(&self, id: &BenchmarkId, context: &ReportContext, warmup_ns: f64));
+    reports_impl!(fn warmup(&self, id: &BenchmarkId, context: &ReportContext, warm_up: WarmUp));
     reports_impl!(fn terminated(&self, id: &BenchmarkId, context: &ReportContext));
     reports_impl!(fn analysis(&self, id: &BenchmarkId, context: &ReportContext));
     reports_impl!(fn measurement_start(
@@ -361,12 +520,14 @@ impl Report for Reports {
 
     reports_impl!(fn final_summary(&self, context: &ReportContext));
     reports_impl!(fn group_separator(&self, ));
+    reports_impl!(fn error(&self, id: &BenchmarkId, context: &ReportContext, message: &str));
 }
 
 pub(crate) struct CliReport {
     pub enable_text_overwrite: bool,
     pub enable_text_coloring: bool,
     pub verbose: bool,
+    pub dump_samples: bool,
 
     last_line_len: Cell<usize>,
 }
@@ -380,6 +541,7 @@ impl CliReport {
             enable_text_overwrite,
             enable_text_coloring,
             verbose,
+            dump_samples: false,
 
             last_line_len: Cell::new(0),
         }
@@ -399,6 +561,10 @@ impl CliReport {
     #[cfg_attr(feature = "cargo-clippy", allow(clippy::needless_pass_by_value))]
     fn print_overwritable(&self, s: String) {
         if self.enable_text_overwrite {
+            let s = match progress::status() {
+                Some(progress) => format!("{} {}", progress, s),
+                None => s,
+            };
             self.last_line_len.set(s.len());
             print!("{}", s);
             stdout().flush().unwrap();
@@ -479,6 +645,36 @@ impl CliReport {
         print(him, "high mild");
         print(his, "high severe");
     }
+
+    // Prints every sample of the benchmark, with the outliers labeled.
+    fn samples(&self, meas: &MeasurementData<'_>, formatter: &dyn ValueFormatter) {
+        println!("sample   iterations         time    time/iter  outlier");
+        let samples = meas.data.x().iter().zip(meas.data.y().iter());
+        for (i, ((&iters, &time), (avg_time, label))) in
+            samples.zip(meas.avg_times.iter()).enumerate()
+        {
+            let label = match label {
+                Label::LowSevere => "low severe",
+                Label::LowMild => "low mild",
+                Label::HighMild => "high mild",
+                Label::HighSevere => "high severe",
+                Label::NotAnOutlier => "",
+            };
+            let line = format!(
+                "{:>6} {:>12} {:>12} {:>12}  {}",
+                i,
+                iters,
+                formatter.format_value(time),
+                formatter.format_value(avg_time),
+                label
+            );
+            if label.is_empty() {
+                println!("{}", line.trim_end());
+            } else {
+                println!("{}", self.yellow(line));
+            }
+        }
+    }
 }
 impl Report for CliReport {
     fn test_start(&self, id: &BenchmarkId, _: &ReportContext) {
@@ -492,22 +688,49 @@ impl Report for CliReport {
         self.print_overwritable(format!("Benchmarking {}", id));
     }
 
-    fn profile(&self, id: &BenchmarkId, _: &ReportContext, warmup_ns: f64) {
+    fn timer_overhead(
+        &self,
+        context: &ReportContext,
+        overhead: f64,
+        subtracted: bool,
+        formatter: &dyn ValueFormatter,
+    ) {
+        // The timer overhead is measured before any value transform is applied.
+        let formatter = FixedTimeUnitFormatter {
+            value_unit: None,
+            ..context.time_formatter(formatter)
+        };
+        println!(
+            "Timer overhead: {} per measurement{}",
+            formatter.format_value(overhead),
+            if subtracted {
+                " (subtracted from all measurements)"
+            } else {
+                ""
+            }
+        );
+    }
+
+    fn profile(&self, id: &BenchmarkId, context: &ReportContext, warmup_ns: f64) {
         self.text_overwrite();
         self.print_overwritable(format!(
             "Benchmarking {}: Profiling for {}",
             id,
-            format::time(warmup_ns)
+            format::time_with_precision(warmup_ns, context.time_unit, context.time_precision)
         ));
     }
 
-    fn warmup(&self, id: &BenchmarkId, _: &ReportContext, warmup_ns: f64) {
+    fn warmup(&self, id: &BenchmarkId, context: &ReportContext, warm_up: WarmUp) {
+        let how_long = match warm_up {
+            WarmUp::Time(dur) => format::time_with_precision(
+                dur.to_nanos() as f64,
+                context.time_unit,
+                context.time_precision,
+            ),
+            WarmUp::Iterations(iters) => format::iter_count(iters),
+        };
         self.text_overwrite();
-        self.print_overwritable(format!(
-            "Benchmarking {}: Warming up for {}",
-            id,
-            format::time(warmup_ns)
-        ));
+        self.print_overwritable(format!("Benchmarking {}: Warming up for {}", id, how_long));
     }
 
     fn terminated(&self, id: &BenchmarkId, _: &ReportContext) {
@@ -515,6 +738,15 @@ impl Report for CliReport {
         println!("Benchmarking {}: Complete (Analysis Disabled)", id);
     }
 
+    fn error(&self, id: &BenchmarkId, _: &ReportContext, message: &str) {
+        self.text_overwrite();
+        println!(
+            "Benchmarking {}: {}",
+            id,
+            self.red(format!("Aborted with error: {}", message))
+        );
+    }
+
     fn analysis(&self, id: &BenchmarkId, _: &ReportContext) {
         self.text_overwrite();
         self.print_overwritable(format!("Benchmarking {}: Analyzing", id));
@@ -523,7 +755,7 @@ impl Report for CliReport {
     fn measurement_start(
         &self,
         id: &BenchmarkId,
-        _: &ReportContext,
+        context: &ReportContext,
         sample_count: u64,
         estimate_ns: f64,
         iter_count: u64,
@@ -539,7 +771,7 @@ impl Report for CliReport {
             "Benchmarking {}: Collecting {} samples in estimated {} ({})",
             id,
             sample_count,
-            format::time(estimate_ns),
+            format::time_with_precision(estimate_ns, context.time_unit, context.time_precision),
             iter_string
         ));
     }
@@ -547,7 +779,7 @@ impl Report for CliReport {
     fn measurement_complete(
         &self,
         id: &BenchmarkId,
-        _: &ReportContext,
+        context: &ReportContext,
         meas: &MeasurementData<'_>,
         formatter: &dyn ValueFormatter,
     ) {
@@ -578,22 +810,43 @@ impl Report for CliReport {
             );
         }
 
-        if let Some(ref throughput) = meas.throughput {
+        if let Some(throughput) = &meas.throughput_estimate {
+            let [lower, point, upper] = throughput.format(formatter);
             println!(
                 "{}thrpt:  [{} {} {}]",
                 " ".repeat(24),
-                self.faint(formatter.format_throughput(
-                    throughput,
-                    typical_estimate.confidence_interval.upper_bound
-                )),
-                self.bold(formatter.format_throughput(throughput, typical_estimate.point_estimate)),
-                self.faint(formatter.format_throughput(
-                    throughput,
-                    typical_estimate.confidence_interval.lower_bound
-                )),
+                self.faint(lower),
+                self.bold(point),
+                self.faint(upper),
             )
         }
 
+        if let Some(threshold) = context.cv_threshold {
+            let cv = meas.coefficient_of_variation();
+            if cv > threshold {
+                println!(
+                    "{}cv:     {}",
+                    " ".repeat(24),
+                    self.yellow(format!(
+                        "{} (unstable, above {})",
+                        format::percent(cv),
+                        format::percent(threshold)
+                    ))
+                );
+            } else {
+                println!("{}cv:     {}", " ".repeat(24), format::percent(cv));
+            }
+        }
+
+        if let Some(cold_start) = meas.cold_start {
+            println!(
+                "{}cold:   {} ({:.1}x typical)",
+                " ".repeat(24),
+                formatter.format_value(cold_start),
+                cold_start / typical_estimate.point_estimate
+            );
+        }
+
         if let Some(ref comp) = meas.comparison {
             let different_mean = comp.p_value < comp.significance_threshold;
             let mean_est = &comp.relative_estimates.mean;
@@ -604,12 +857,12 @@ impl Report for CliReport {
             let to_thrpt_estimate = |ratio: f64| 1.0 / (1.0 + ratio) - 1.0;
             let mut thrpt_point_estimate_str =
                 format::change(to_thrpt_estimate(point_estimate), true);
-            let explanation_str: String;
+            let mut explanation_str: String;
 
             if !different_mean {
                 explanation_str = "No change in performance detected.".to_owned();
             } else {
-                let comparison = compare_to_threshold(mean_est, comp.noise_threshold);
+                let comparison = compare_to_threshold(mean_est, comp);
                 match comparison {
                     ComparisonResult::Improved => {
                         point_estimate_str = self.green(self.bold(point_estimate_str));
@@ -623,17 +876,34 @@ impl Report for CliReport {
                         explanation_str =
                             format!("Performance has {}.", self.red("regressed".to_owned()));
                     }
+                    ComparisonResult::Uncertain => {
+                        point_estimate_str = self.yellow(point_estimate_str);
+                        thrpt_point_estimate_str = self.yellow(thrpt_point_estimate_str);
+                        explanation_str = format!(
+                            "Change is {}: the linear fit is poor (R² = {:.4}).",
+                            self.yellow("uncertain".to_owned()),
+                            comp.r_squared
+                        );
+                    }
                     ComparisonResult::NonSignificant => {
                         explanation_str = "Change within noise threshold.".to_owned();
                     }
                 }
             }
+            let regressed = regressed_statistics(comp);
+            if !regressed.is_empty() && !mean_regressed(comp) {
+                explanation_str = format!(
+                    "Performance has {} in {}.",
+                    self.red("regressed".to_owned()),
+                    join_statistics(&regressed)
+                );
+            }
 
             if meas.throughput.is_some() {
                 println!("{}change:", " ".repeat(17));
 
                 println!(
-                    "{}time:   [{} {} {}] (p = {:.2} {} {:.2})",
+                    "{}time:   [{} {} {}] (p = {:.2} {} {}, d = {:.2}, {})",
                     " ".repeat(24),
                     self.faint(format::change(
                         mean_est.confidence_interval.lower_bound,
@@ -646,7 +916,9 @@ impl Report for CliReport {
                     )),
                     comp.p_value,
                     if different_mean { "<" } else { ">" },
-                    comp.significance_threshold
+                    comp.significance_threshold,
+                    comp.effect_size,
+                    effect_size_label(comp.effect_size)
                 );
                 println!(
                     "{}thrpt:  [{} {} {}]",
@@ -663,7 +935,7 @@ impl Report for CliReport {
                 );
             } else {
                 println!(
-                    "{}change: [{} {} {}] (p = {:.2} {} {:.2})",
+                    "{}change: [{} {} {}] (p = {:.2} {} {}, d = {:.2}, {})",
                     " ".repeat(24),
                     self.faint(format::change(
                         mean_est.confidence_interval.lower_bound,
@@ -676,7 +948,39 @@ impl Report for CliReport {
                     )),
                     comp.p_value,
                     if different_mean { "<" } else { ">" },
-                    comp.significance_threshold
+                    comp.significance_threshold,
+                    comp.effect_size,
+                    effect_size_label(comp.effect_size)
+                );
+            }
+            for (statistic, _) in &comp.relative_distributions.other {
+                let estimate = comp.relative_estimates.get(*statistic);
+                let mut point_estimate_str = format::change(estimate.point_estimate, true);
+                match compare_to_threshold(estimate, comp) {
+                    ComparisonResult::Improved => {
+                        point_estimate_str = self.green(self.bold(point_estimate_str))
+                    }
+                    ComparisonResult::Regressed => {
+                        point_estimate_str = self.red(self.bold(point_estimate_str))
+                    }
+                    ComparisonResult::Uncertain => {
+                        point_estimate_str = self.yellow(point_estimate_str)
+                    }
+                    ComparisonResult::NonSignificant => {}
+                }
+                println!(
+                    "{}{:<8}[{} {} {}]",
+                    " ".repeat(24),
+                    format!("{}:", statistic),
+                    self.faint(format::change(
+                        estimate.confidence_interval.lower_bound,
+                        true
+                    )),
+                    point_estimate_str,
+                    self.faint(format::change(
+                        estimate.confidence_interval.upper_bound,
+                        true
+                    )),
                 );
             }
 
@@ -685,6 +989,10 @@ impl Report for CliReport {
 
         self.outliers(&meas.avg_times);
 
+        if self.dump_samples {
+            self.samples(meas, formatter);
+        }
+
         if self.verbose {
             let format_short_estimate = |estimate: &Estimate| -> String {
                 format!(
@@ -696,20 +1004,33 @@ impl Report for CliReport {
 
             let data = &meas.data;
             if let Some(slope_estimate) = meas.absolute_estimates.slope.as_ref() {
+                let tss = Slope::total_sum_of_squares(data);
                 println!(
                     "{:<7}{} {:<15}[{:0.7} {:0.7}]",
                     "slope",
                     format_short_estimate(slope_estimate),
                     "R^2",
-                    Slope(slope_estimate.confidence_interval.lower_bound).r_squared(data),
-                    Slope(slope_estimate.confidence_interval.upper_bound).r_squared(data),
+                    Slope(slope_estimate.confidence_interval.lower_bound)
+                        .r_squared_with_tss(data, tss),
+                    Slope(slope_estimate.confidence_interval.upper_bound)
+                        .r_squared_with_tss(data, tss),
                 );
+                if meas.regression_method != RegressionMethod::LeastSquares {
+                    println!(
+                        "{:<7}estimated with {} regression",
+                        "", meas.regression_method
+                    );
+                }
             }
             println!(
                 "{:<7}{} {:<15}{}",
                 "mean",
                 format_short_estimate(&meas.absolute_estimates.mean),
-                "std. dev.",
+                if meas.robust_dispersion {
+                    "winsorized SD"
+                } else {
+                    "std. dev."
+                },
                 format_short_estimate(&meas.absolute_estimates.std_dev),
             );
             println!(
@@ -719,6 +1040,27 @@ impl Report for CliReport {
                 "med. abs. dev.",
                 format_short_estimate(&meas.absolute_estimates.median_abs_dev),
             );
+            match (
+                meas.absolute_estimates.mode.as_ref(),
+                meas.absolute_estimates.trimmed_mean.as_ref(),
+            ) {
+                (Some(mode_estimate), Some(trimmed_mean_estimate)) => println!(
+                    "{:<7}{} {:<15}{}",
+                    "mode",
+                    format_short_estimate(mode_estimate),
+                    "trimmed mean",
+                    format_short_estimate(trimmed_mean_estimate),
+                ),
+                (Some(mode_estimate), None) => {
+                    println!("{:<7}{}", "mode", format_short_estimate(mode_estimate))
+                }
+                (None, Some(trimmed_mean_estimate)) => println!(
+                    "{:<15}{}",
+                    "trimmed mean",
+                    format_short_estimate(trimmed_mean_estimate)
+                ),
+                (None, None) => {}
+            }
         }
     }
 
@@ -740,6 +1082,10 @@ impl Report for BencherReport {
         print!("test {} ... ", id);
     }
 
+    fn error(&self, id: &BenchmarkId, _: &ReportContext, message: &str) {
+        println!("test {} ... error: {}", id, message);
+    }
+
     fn measurement_complete(
         &self,
         _id: &BenchmarkId,
@@ -766,30 +1112,465 @@ impl Report for BencherReport {
     }
 }
 
-enum ComparisonResult {
+/// Prints exactly one line per benchmark, for `--output-format oneline`:
+/// `BENCH <id> median=<value> change=<percent> p=<p-value>`. The median is in the measurement's
+/// own unit (nanoseconds for wall-clock time) and the fields are always printed in this order,
+/// without color, so that the output is easy to parse with tools like `grep` and `awk`. The ID may
+/// contain spaces, but the last three fields always follow it. `change` and `p` are `none` if there
+/// is no baseline to compare against.
+pub struct OneLineReport;
+impl Report for OneLineReport {
+    fn error(&self, id: &BenchmarkId, _: &ReportContext, message: &str) {
+        println!("BENCH {} error={}", id.as_title(), message);
+    }
+
+    fn measurement_complete(
+        &self,
+        id: &BenchmarkId,
+        _: &ReportContext,
+        meas: &MeasurementData<'_>,
+        _: &dyn ValueFormatter,
+    ) {
+        let comparison = meas
+            .comparison
+            .as_ref()
+            .map(|comp| (comp.relative_estimates.median.point_estimate, comp.p_value));
+        println!(
+            "{}",
+            oneline_result(
+                id.as_title(),
+                meas.absolute_estimates.median.point_estimate,
+                comparison
+            )
+        );
+    }
+}
+
+fn oneline_result(id: &str, median: f64, comparison: Option<(f64, f64)>) -> String {
+    let (change, p_value) = match comparison {
+        Some((change, p_value)) => (
+            format!("{:+.4}%", change * 100.0),
+            format!("{:.4}", p_value),
+        ),
+        None => ("none".to_owned(), "none".to_owned()),
+    };
+    format!(
+        "BENCH {} median={:.3} change={} p={}",
+        id, median, change, p_value
+    )
+}
+
+/// The version of the JSON messages printed with `--message-format=json`. It is increased when a
+/// field is removed or changes meaning; new fields and messages may be added without notice.
+const MESSAGE_FORMAT_VERSION: u32 = 1;
+
+/// Prints the version of the JSON messages, once per process since each `criterion_group!`
+/// configures its own `Criterion` instance.
+pub(crate) fn print_message_format_version() {
+    static PRINTED: Once = Once::new();
+    PRINTED.call_once(|| {
+        JsonMessage::ProtocolVersion {
+            version: MESSAGE_FORMAT_VERSION,
+        }
+        .print()
+    });
+}
+
+/// A message of the `--message-format=json` protocol, printed as a single line of JSON.
+// Each message is printed once and dropped right away, so its size doesn't matter.
+#[allow(clippy::large_enum_variant)]
+#[derive(Serialize)]
+#[serde(tag = "reason", rename_all = "kebab-case")]
+enum JsonMessage<'a> {
+    ProtocolVersion {
+        version: u32,
+    },
+    TestPass {
+        id: &'a str,
+    },
+    BenchmarkStart {
+        id: &'a str,
+    },
+    MeasurementStart {
+        id: &'a str,
+        sample_count: u64,
+        estimated_ns: f64,
+        iteration_count: u64,
+    },
+    BenchmarkComplete {
+        id: &'a str,
+        group_id: &'a str,
+        function_id: Option<&'a str>,
+        value_str: Option<&'a str>,
+        throughput: Option<JsonThroughput>,
+        throughput_estimate: Option<JsonEstimate>,
+        unit: &'static str,
+        iteration_count: Vec<f64>,
+        measured_values: Vec<f64>,
+        typical: JsonEstimate,
+        mean: JsonEstimate,
+        median: JsonEstimate,
+        median_abs_dev: JsonEstimate,
+        std_dev: JsonEstimate,
+        slope: Option<JsonEstimate>,
+        cold_start: Option<f64>,
+        change: Option<JsonChange>,
+    },
+    BenchmarkError {
+        id: &'a str,
+        message: &'a str,
+    },
+    RunComplete,
+}
+impl<'a> JsonMessage<'a> {
+    fn print(&self) {
+        println!("{}", serde_json::to_string(self).unwrap());
+    }
+}
+
+#[derive(Serialize)]
+struct JsonThroughput {
+    per_iteration: u64,
+    unit: &'static str,
+}
+
+#[derive(Serialize)]
+struct JsonEstimate {
+    estimate: f64,
+    lower_bound: f64,
+    upper_bound: f64,
+    unit: &'static str,
+}
+impl JsonEstimate {
+    fn new(estimate: &Estimate, formatter: &dyn ValueFormatter) -> JsonEstimate {
+        let mut values = [
+            estimate.point_estimate,
+            estimate.confidence_interval.lower_bound,
+            estimate.confidence_interval.upper_bound,
+        ];
+        let unit = formatter.scale_for_machines(&mut values);
+        JsonEstimate {
+            estimate: values[0],
+            lower_bound: values[1],
+            upper_bound: values[2],
+            unit,
+        }
+    }
+
+    fn throughput(estimate: &ThroughputEstimate, formatter: &dyn ValueFormatter) -> JsonEstimate {
+        let mut values = estimate.values();
+        let unit = formatter.scale_throughputs(values[1], &estimate.throughput, &mut values);
+        JsonEstimate {
+            estimate: values[1],
+            lower_bound: values[0],
+            upper_bound: values[2],
+            unit,
+        }
+    }
+
+    fn relative(estimate: &Estimate) -> JsonEstimate {
+        JsonEstimate {
+            estimate: estimate.point_estimate,
+            lower_bound: estimate.confidence_interval.lower_bound,
+            upper_bound: estimate.confidence_interval.upper_bound,
+            unit: "%",
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct JsonChange {
+    mean: JsonEstimate,
+    median: JsonEstimate,
+    change: &'static str,
+}
+
+/// Prints the benchmark lifecycle events and results as JSON lines, for `--message-format=json`.
+pub struct JsonReport;
+impl Report for JsonReport {
+    fn test_pass(&self, id: &BenchmarkId, _: &ReportContext) {
+        JsonMessage::TestPass { id: id.as_title() }.print();
+    }
+
+    fn benchmark_start(&self, id: &BenchmarkId, _: &ReportContext) {
+        JsonMessage::BenchmarkStart { id: id.as_title() }.print();
+    }
+
+    fn measurement_start(
+        &self,
+        id: &BenchmarkId,
+        _: &ReportContext,
+        sample_count: u64,
+        estimate_ns: f64,
+        iter_count: u64,
+    ) {
+        JsonMessage::MeasurementStart {
+            id: id.as_title(),
+            sample_count,
+            estimated_ns: estimate_ns,
+            iteration_count: iter_count,
+        }
+        .print();
+    }
+
+    fn measurement_complete(
+        &self,
+        id: &BenchmarkId,
+        _: &ReportContext,
+        meas: &MeasurementData<'_>,
+        formatter: &dyn ValueFormatter,
+    ) {
+        let estimates = &meas.absolute_estimates;
+        let mut measured_values = meas.sample_times().to_vec();
+        let unit = formatter.scale_for_machines(&mut measured_values);
+        let change = meas.comparison.as_ref().map(|comp| {
+            let change = mean_comparison(comp);
+            JsonChange {
+                mean: JsonEstimate::relative(&comp.relative_estimates.mean),
+                median: JsonEstimate::relative(&comp.relative_estimates.median),
+                change: match change {
+                    ComparisonResult::Improved => "Improved",
+                    ComparisonResult::Regressed => "Regressed",
+                    ComparisonResult::Uncertain => "Uncertain",
+                    ComparisonResult::NonSignificant => "NoChange",
+                },
+            }
+        });
+
+        JsonMessage::BenchmarkComplete {
+            id: id.as_title(),
+            group_id: &id.group_id,
+            function_id: id.function_id.as_deref(),
+            value_str: id.value_str.as_deref(),
+            throughput: meas
+                .throughput
+                .as_ref()
+                .map(|throughput| match *throughput {
+                    Throughput::Bytes(bytes) => JsonThroughput {
+                        per_iteration: bytes,
+                        unit: "bytes",
+                    },
+                    Throughput::Elements(elements) => JsonThroughput {
+                        per_iteration: elements,
+                        unit: "elements",
+                    },
+                }),
+            throughput_estimate: meas
+                .throughput_estimate
+                .as_ref()
+                .map(|estimate| JsonEstimate::throughput(estimate, formatter)),
+            unit,
+            iteration_count: meas.iter_counts().to_vec(),
+            measured_values,
+            typical: JsonEstimate::new(estimates.typical(), formatter),
+            mean: JsonEstimate::new(&estimates.mean, formatter),
+            median: JsonEstimate::new(&estimates.median, formatter),
+            median_abs_dev: JsonEstimate::new(&estimates.median_abs_dev, formatter),
+            std_dev: JsonEstimate::new(&estimates.std_dev, formatter),
+            slope: estimates
+                .slope
+                .as_ref()
+                .map(|slope| JsonEstimate::new(slope, formatter)),
+            cold_start: meas.cold_start.map(|cold_start| {
+                let mut values = [cold_start];
+                formatter.scale_for_machines(&mut values);
+                values[0]
+            }),
+            change,
+        }
+        .print();
+    }
+
+    fn final_summary(&self, _: &ReportContext) {
+        JsonMessage::RunComplete.print();
+    }
+
+    fn error(&self, id: &BenchmarkId, _: &ReportContext, message: &str) {
+        JsonMessage::BenchmarkError {
+            id: id.as_title(),
+            message,
+        }
+        .print();
+    }
+}
+
+pub(crate) enum ComparisonResult {
     Improved,
     Regressed,
+    // The change is beyond the noise threshold, but the linear fit of the samples is too poor to
+    // trust it.
+    Uncertain,
     NonSignificant,
 }
 
-fn compare_to_threshold(estimate: &Estimate, noise: f64) -> ComparisonResult {
+// Returns the verdict on the change in the mean, which is only significant below the
+// significance threshold.
+fn mean_comparison(comparison: &ComparisonData) -> ComparisonResult {
+    if comparison.p_value < comparison.significance_threshold {
+        compare_to_threshold(&comparison.relative_estimates.mean, comparison)
+    } else {
+        ComparisonResult::NonSignificant
+    }
+}
+
+// Returns whether the change in the mean is a significant regression.
+pub(crate) fn mean_regressed(comparison: &ComparisonData) -> bool {
+    matches!(mean_comparison(comparison), ComparisonResult::Regressed)
+}
+
+// Returns whether the change in the mean is a significant improvement.
+pub(crate) fn mean_improved(comparison: &ComparisonData) -> bool {
+    matches!(mean_comparison(comparison), ComparisonResult::Improved)
+}
+
+// Returns whether the change in the mean is significant, but uncertain because of a poor fit.
+pub(crate) fn mean_uncertain(comparison: &ComparisonData) -> bool {
+    matches!(mean_comparison(comparison), ComparisonResult::Uncertain)
+}
+
+// Returns the statistics set with `Criterion::comparison_statistics` which regressed, meaning
+// that the whole confidence interval of their change lies above the noise threshold.
+pub(crate) fn regressed_statistics(comparison: &ComparisonData) -> Vec<Statistic> {
+    comparison
+        .relative_distributions
+        .other
+        .iter()
+        .map(|&(statistic, _)| statistic)
+        .filter(|&statistic| {
+            matches!(
+                compare_to_threshold(comparison.relative_estimates.get(statistic), comparison),
+                ComparisonResult::Regressed
+            )
+        })
+        .collect()
+}
+
+pub(crate) fn join_statistics(statistics: &[Statistic]) -> String {
+    statistics
+        .iter()
+        .map(|statistic| statistic.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+// Classifies the change in `estimate` against the noise threshold of the comparison. A change
+// beyond the threshold is downgraded to uncertain when the R² of the fit is below
+// `Criterion::min_r2_for_regression`.
+pub(crate) fn compare_to_threshold(
+    estimate: &Estimate,
+    comparison: &ComparisonData,
+) -> ComparisonResult {
+    let noise = comparison.noise_threshold;
     let ci = &estimate.confidence_interval;
     let lb = ci.lower_bound;
     let ub = ci.upper_bound;
 
-    if lb < -noise && ub < -noise {
-        ComparisonResult::Improved
-    } else if lb > noise && ub > noise {
-        ComparisonResult::Regressed
+    let poor_fit = matches!(
+        comparison.min_r_squared,
+        Some(min_r_squared) if comparison.r_squared < min_r_squared
+    );
+    if (lb < -noise && ub < -noise) || (lb > noise && ub > noise) {
+        if poor_fit {
+            ComparisonResult::Uncertain
+        } else if lb < -noise {
+            ComparisonResult::Improved
+        } else {
+            ComparisonResult::Regressed
+        }
     } else {
         ComparisonResult::NonSignificant
     }
 }
 
+/// Returns the conventional interpretation of the magnitude of a Cohen's d effect size.
+pub(crate) fn effect_size_label(effect_size: f64) -> &'static str {
+    let magnitude = effect_size.abs();
+    if !magnitude.is_finite() {
+        "n/a"
+    } else if magnitude < 0.2 {
+        "negligible"
+    } else if magnitude < 0.5 {
+        "small"
+    } else if magnitude < 0.8 {
+        "medium"
+    } else {
+        "large"
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
 
+    #[test]
+    fn test_compare_parameters() {
+        let mut params = vec![
+            Some("10"),
+            Some("b"),
+            None,
+            Some("2"),
+            Some("1e1"),
+            Some("a"),
+            Some("-1.5"),
+            Some("10a"),
+        ];
+        params.sort_by(|a, b| compare_parameters(*a, *b));
+        assert_eq!(
+            params,
+            vec![
+                None,
+                Some("-1.5"),
+                Some("2"),
+                Some("10"),
+                Some("1e1"),
+                Some("10a"),
+                Some("a"),
+                Some("b"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cmp_natural() {
+        let id = |function: &str, value: &str| {
+            BenchmarkId::new(
+                "group".to_owned(),
+                Some(function.to_owned()),
+                Some(value.to_owned()),
+                None,
+            )
+        };
+        let mut ids = [id("g", "1"), id("f", "10"), id("f", "x"), id("f", "2")];
+        ids.sort_by(|a, b| a.cmp_natural(b));
+        let ids = ids.iter().map(|id| id.id()).collect::<Vec<_>>();
+        assert_eq!(
+            ids,
+            vec!["group/f/2", "group/f/10", "group/f/x", "group/g/1"]
+        );
+    }
+
+    #[test]
+    fn test_effect_size_label() {
+        assert_eq!("negligible", effect_size_label(0.1));
+        assert_eq!("small", effect_size_label(-0.3));
+        assert_eq!("medium", effect_size_label(0.5));
+        assert_eq!("large", effect_size_label(-1.5));
+        assert_eq!("n/a", effect_size_label(f64::NAN));
+        assert_eq!("n/a", effect_size_label(f64::INFINITY));
+    }
+
+    #[test]
+    fn test_oneline_result() {
+        assert_eq!(
+            "BENCH group/fib 20 median=1234.568 change=-2.5000% p=0.0012",
+            oneline_result("group/fib 20", 1234.5678, Some((-0.025, 0.00123)))
+        );
+        assert_eq!(
+            "BENCH fib median=10.000 change=none p=none",
+            oneline_result("fib", 10.0, None)
+        );
+    }
+
     #[test]
     fn test_make_filename_safe_replaces_characters() {
         let input = "?/\\*\"";
@@ -844,4 +1625,24 @@ mod test {
         new_id.ensure_directory_name_unique(&directories);
         assert_ne!(existing_id.as_directory_name(), new_id.as_directory_name());
     }
+
+    #[test]
+    fn test_json_messages() {
+        let json = |message: JsonMessage<'_>| serde_json::to_string(&message).unwrap();
+        assert_eq!(
+            json(JsonMessage::ProtocolVersion { version: 1 }),
+            r#"{"reason":"protocol-version","version":1}"#
+        );
+        assert_eq!(
+            json(JsonMessage::BenchmarkError {
+                id: "group/function",
+                message: "failed"
+            }),
+            r#"{"reason":"benchmark-error","id":"group/function","message":"failed"}"#
+        );
+        assert_eq!(
+            json(JsonMessage::RunComplete),
+            r#"{"reason":"run-complete"}"#
+        );
+    }
 }
@@ -1,21 +1,114 @@
 use crate::benchmark::BenchmarkConfig;
 use crate::connection::OutgoingMessage;
-use crate::measurement::Measurement;
+use crate::measurement::{Measurement, WallTime};
 use crate::report::{BenchmarkId, Report, ReportContext};
-use crate::{ActualSamplingMode, Bencher, Criterion, DurationExt};
+use crate::watchdog::Watchdog;
+use crate::{ActualSamplingMode, Bencher, Criterion, DurationExt, WarmUp};
+use std::any::Any;
+use std::io::{BufRead, BufReader, Write};
 use std::marker::PhantomData;
+use std::panic::{self, AssertUnwindSafe};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+use std::rc::Rc;
 use std::time::Duration;
 
+type SetupFn<'a> = Box<dyn FnMut() -> Rc<dyn Any> + 'a>;
+type TeardownFn<'a> = Box<dyn FnMut(Rc<dyn Any>) + 'a>;
+
+/// The `setup` and `teardown` closures of a benchmark group, which run once around all of the
+/// samples of each benchmark.
+#[derive(Default)]
+pub(crate) struct Hooks<'a> {
+    pub(crate) setup: Option<SetupFn<'a>>,
+    pub(crate) teardown: Option<TeardownFn<'a>>,
+}
+impl<'a> Hooks<'a> {
+    /// Runs the setup closure, makes its value available to `routine` while `f` runs, then runs
+    /// the teardown closure. The teardown closure runs even if `f` panics.
+    pub(crate) fn around<M: Measurement, T: ?Sized, R>(
+        &mut self,
+        routine: &mut dyn Routine<M, T>,
+        f: impl FnOnce(&mut dyn Routine<M, T>) -> R,
+    ) -> R {
+        let value = self.setup.as_mut().map(|setup| setup());
+        routine.set_setup_value(value.clone());
+        let result = panic::catch_unwind(AssertUnwindSafe(|| f(&mut *routine)));
+        routine.set_setup_value(None);
+
+        if let (Some(teardown), Some(value)) = (self.teardown.as_mut(), value) {
+            teardown(value);
+        }
+        match result {
+            Ok(out) => out,
+            Err(payload) => panic::resume_unwind(payload),
+        }
+    }
+}
+
 /// PRIVATE
+///
+/// The `Err` variants carry the message of an error returned by the benchmarked routine (see
+/// `Bencher::try_iter`), which aborts the benchmark.
 pub(crate) trait Routine<M: Measurement, T: ?Sized> {
     /// PRIVATE
-    fn bench(&mut self, m: &M, iters: &[u64], parameter: &T) -> Vec<f64>;
+    fn bench(&mut self, m: &M, iters: &[u64], parameter: &T) -> Result<Vec<f64>, String>;
+    /// PRIVATE
+    fn warm_up(&mut self, m: &M, warm_up: WarmUp, parameter: &T) -> Result<(u64, u64), String>;
+    /// PRIVATE
+    fn set_setup_value(&mut self, value: Option<Rc<dyn Any>>);
+    /// PRIVATE
+    ///
+    /// Makes `bench` and `warm_up` fail once a single call of the routine takes longer than the
+    /// timeout (see `Criterion::benchmark_timeout`).
+    fn set_timeout(&mut self, timeout: Option<Duration>);
+    /// PRIVATE
+    ///
+    /// The number of times the measurement was started and ended during each sample of the last
+    /// call to `bench`, or zero for samples where this is unknown.
+    fn timer_pairs(&self) -> &[u64];
+    /// PRIVATE
+    ///
+    /// The mean number of elements processed per iteration during the last call to `bench`, if the
+    /// routine used `Bencher::iter_stream` or `Bencher::set_throughput_elements`.
+    fn elements_per_iteration(&self) -> Option<u64> {
+        None
+    }
     /// PRIVATE
-    fn warm_up(&mut self, m: &M, how_long: Duration, parameter: &T) -> (u64, u64);
+    ///
+    /// The secondary quantities measured during each sample of the last call to `bench`, for
+    /// measurements which record several values per sample (see `Measurement::secondary_names`).
+    fn secondary_values(&self) -> &[Vec<f64>] {
+        &[]
+    }
+    /// PRIVATE
+    ///
+    /// The value measured for the very first iteration of the routine, which runs cold at the
+    /// start of the warm-up, if known. See `Criterion::report_cold_start`.
+    fn cold_start(&self) -> Option<f64> {
+        None
+    }
+    /// PRIVATE
+    ///
+    /// Makes the following calls to `bench` record the value of each iteration of routines which
+    /// use `Bencher::iter` (see `Criterion::record_per_iteration`).
+    fn set_record_iterations(&mut self, _enabled: bool) {}
+    /// PRIVATE
+    ///
+    /// The value of each iteration during the last call to `bench`, in order, if they were
+    /// recorded.
+    fn iteration_values(&self) -> &[f64] {
+        &[]
+    }
+    /// PRIVATE
+    ///
+    /// The tags added with `Bencher::tag` by the calls of the routine so far.
+    fn tags(&self) -> &[String] {
+        &[]
+    }
 
     /// PRIVATE
-    fn test(&mut self, m: &M, parameter: &T) {
-        self.bench(m, &[1u64], parameter);
+    fn test(&mut self, m: &M, parameter: &T) -> Result<(), String> {
+        self.bench(m, &[1u64], parameter).map(|_| ())
     }
 
     /// Iterates the benchmarked function for a fixed length of time, but takes no measurements.
@@ -31,7 +124,7 @@ pub(crate) trait Routine<M: Measurement, T: ?Sized> {
         report_context: &ReportContext,
         time: Duration,
         parameter: &T,
-    ) {
+    ) -> Result<(), String> {
         criterion
             .report
             .profile(id, report_context, time.to_nanos() as f64);
@@ -57,28 +150,35 @@ pub(crate) trait Routine<M: Measurement, T: ?Sized> {
         // being different code-paths even though they aren't really.
 
         // Get the warmup time for one second
-        let (wu_elapsed, wu_iters) = self.warm_up(measurement, Duration::from_secs(1), parameter);
-        if wu_elapsed < time {
-            // Initial guess for the mean execution time
-            let met = wu_elapsed as f64 / wu_iters as f64;
+        let result = self
+            .warm_up(measurement, WarmUp::Time(Duration::from_secs(1)), parameter)
+            .and_then(|(wu_elapsed, wu_iters)| {
+                if wu_elapsed < time {
+                    // Initial guess for the mean execution time
+                    let met = wu_elapsed as f64 / wu_iters as f64;
 
-            // Guess how many iterations will be required for the remaining time
-            let remaining = (time - wu_elapsed) as f64;
+                    // Guess how many iterations will be required for the remaining time
+                    let remaining = (time - wu_elapsed) as f64;
 
-            let iters = remaining / met;
-            let iters = iters as u64;
+                    let iters = remaining / met;
+                    let iters = iters as u64;
 
-            self.bench(measurement, &[iters], parameter);
-        }
+                    self.bench(measurement, &[iters], parameter)?;
+                }
+                Ok(())
+            });
 
         criterion
             .profiler
             .borrow_mut()
             .stop_profiling(id.id(), &profile_path);
 
+        result?;
         criterion.report.terminated(id, report_context);
+        Ok(())
     }
 
+    #[allow(clippy::type_complexity)]
     fn sample(
         &mut self,
         measurement: &M,
@@ -87,25 +187,33 @@ pub(crate) trait Routine<M: Measurement, T: ?Sized> {
         criterion: &Criterion<M>,
         report_context: &ReportContext,
         parameter: &T,
-    ) -> (ActualSamplingMode, Box<[f64]>, Box<[f64]>) {
-        let wu = config.warm_up_time;
-        let m_ns = config.measurement_time.to_nanos();
+    ) -> Result<(ActualSamplingMode, Box<[f64]>, Box<[f64]>), String> {
+        let wu = config.warm_up();
 
-        criterion
-            .report
-            .warmup(id, report_context, wu.to_nanos() as f64);
+        criterion.report.warmup(id, report_context, wu);
 
         if let Some(conn) = &criterion.connection {
+            // cargo-criterion only knows about warm-ups of a fixed length.
+            let nanos = match wu {
+                WarmUp::Time(dur) => dur.to_nanos() as f64,
+                WarmUp::Iterations(_) => 0.0,
+            };
             conn.send(&OutgoingMessage::Warmup {
                 id: id.into(),
-                nanos: wu.to_nanos() as f64,
+                nanos,
             })
             .unwrap();
         }
 
-        let (wu_elapsed, wu_iters) = self.warm_up(measurement, wu, parameter);
+        self.set_timeout(criterion.benchmark_timeout);
+
+        // Restores the original affinity when dropped, including on the error paths below.
+        let _pinned = criterion.pin_to_core.and_then(crate::affinity::pin_to_core);
+
+        let (wu_elapsed, wu_iters) =
+            elapsed!("Warming up", self.warm_up(measurement, wu, parameter))?;
         if crate::debug_enabled() {
-            println!(
+            human_println!(
                 "\nCompleted {} iterations in {} nanoseconds, estimated execution time is {} ns",
                 wu_iters,
                 wu_elapsed,
@@ -117,12 +225,7 @@ pub(crate) trait Routine<M: Measurement, T: ?Sized> {
         let met = wu_elapsed as f64 / wu_iters as f64;
 
         let n = config.sample_size as u64;
-
-        let actual_sampling_mode = config
-            .sampling_mode
-            .choose_sampling_mode(met, n, m_ns as f64);
-
-        let m_iters = actual_sampling_mode.iteration_counts(met, n, &config.measurement_time);
+        let (actual_sampling_mode, m_iters) = iteration_counts(config, met);
 
         let expected_ns = m_iters
             .iter()
@@ -136,6 +239,18 @@ pub(crate) trait Routine<M: Measurement, T: ?Sized> {
             total_iters = total_iters.saturating_add(count);
         }
 
+        if criterion.record_per_iteration && total_iters > MAX_RECORDED_ITERATIONS {
+            warn!(
+                "Benchmark {} runs {} iterations, too many to record each one; only the samples \
+                 are saved",
+                id.as_title(),
+                total_iters
+            );
+        }
+        self.set_record_iterations(
+            criterion.record_per_iteration && total_iters <= MAX_RECORDED_ITERATIONS,
+        );
+
         criterion
             .report
             .measurement_start(id, report_context, n, expected_ns, total_iters);
@@ -150,15 +265,204 @@ pub(crate) trait Routine<M: Measurement, T: ?Sized> {
             .unwrap();
         }
 
-        let m_elapsed = self.bench(measurement, &m_iters, parameter);
+        let m_elapsed = elapsed!(
+            "Measuring",
+            if criterion.isolated_samples {
+                crate::isolated::bench(id.as_title(), &m_iters, criterion.benchmark_timeout)
+            } else {
+                self.bench(measurement, &m_iters, parameter)
+            }
+        )?;
 
         let m_iters_f: Vec<f64> = m_iters.iter().map(|&x| x as f64).collect();
 
-        (
+        Ok((
             actual_sampling_mode,
             m_iters_f.into_boxed_slice(),
             m_elapsed.into_boxed_slice(),
-        )
+        ))
+    }
+}
+
+/// Chooses the sampling mode and the iteration count of each sample, given the mean execution time
+/// measured during the warm-up.
+fn iteration_counts(config: &BenchmarkConfig, met: f64) -> (ActualSamplingMode, Vec<u64>) {
+    let n = config.sample_size as u64;
+    let m_ns = config.measurement_time.to_nanos();
+
+    let actual_sampling_mode = config
+        .sampling_mode
+        .choose_sampling_mode(met, n, m_ns as f64);
+    let m_iters = actual_sampling_mode.iteration_counts(met, n, &config.measurement_time);
+    (actual_sampling_mode, m_iters)
+}
+
+/// The largest number of iterations of a benchmark whose values are recorded one by one with
+/// `Criterion::record_per_iteration`.
+const MAX_RECORDED_ITERATIONS: u64 = 1_000_000;
+
+/// Whether a warm-up has finished, given the time and the number of iterations spent on it so far.
+fn warm_up_done(warm_up: WarmUp, elapsed_ns: u64, total_iters: u64) -> bool {
+    match warm_up {
+        WarmUp::Time(how_long) => elapsed_ns > how_long.to_nanos(),
+        WarmUp::Iterations(iters) => total_iters >= iters,
+    }
+}
+
+/// The iteration count of the next call during a warm-up. The count doubles with each call, but a
+/// warm-up by iterations stops at exactly the requested number.
+fn next_warm_up_iters(warm_up: WarmUp, iters: u64, total_iters: u64) -> u64 {
+    match warm_up {
+        WarmUp::Time(_) => iters.wrapping_mul(2),
+        WarmUp::Iterations(target) => iters.saturating_mul(2).min(target - total_iters),
+    }
+}
+
+/// Measures the samples of a benchmark one at a time, so that arbitrary code can run between
+/// samples (e.g. to pin the thread to a different core). Created by
+/// `BenchmarkGroup::sample_collector`; the collected samples are then passed to
+/// `BenchmarkGroup::analyze`.
+///
+/// The routine is warmed up when the first sample is requested, and the iteration counts are
+/// chosen the same way as for any other benchmark. Each item is the iteration count of a sample
+/// and the measured value of all of its iterations, or the error returned by the routine (see
+/// `Bencher::try_iter`), after which no more samples are collected.
+pub struct SampleCollector<'a, M: Measurement> {
+    routine: Box<dyn Routine<M, ()> + 'a>,
+    measurement: &'a M,
+    config: BenchmarkConfig,
+    // The iteration counts of the remaining samples, or `None` before the warm-up.
+    iters: Option<std::vec::IntoIter<u64>>,
+}
+impl<'a, M: Measurement> SampleCollector<'a, M> {
+    pub(crate) fn new<F>(
+        measurement: &'a M,
+        config: BenchmarkConfig,
+        iters: Option<Vec<u64>>,
+        mut f: F,
+    ) -> SampleCollector<'a, M>
+    where
+        F: FnMut(&mut Bencher<'_, M>) + 'a,
+    {
+        SampleCollector {
+            routine: Box::new(Function::new(move |b: &mut Bencher<'_, M>, _: &()| f(b))),
+            measurement,
+            config,
+            iters: iters.map(Vec::into_iter),
+        }
+    }
+
+    fn stop(&mut self, err: String) -> Option<Result<(u64, f64), String>> {
+        self.iters = Some(vec![].into_iter());
+        Some(Err(err))
+    }
+}
+impl<'a, M: Measurement> Iterator for SampleCollector<'a, M> {
+    type Item = Result<(u64, f64), String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.iters.is_none() {
+            let warm_up = self
+                .routine
+                .warm_up(self.measurement, self.config.warm_up(), &());
+            let (wu_elapsed, wu_iters) = match warm_up {
+                Ok(warm_up) => warm_up,
+                Err(err) => return self.stop(err),
+            };
+            let (_, iters) = iteration_counts(&self.config, wu_elapsed as f64 / wu_iters as f64);
+            self.iters = Some(iters.into_iter());
+        }
+
+        let iters = self.iters.as_mut().unwrap().next()?;
+        match self.routine.bench(self.measurement, &[iters], &()) {
+            Ok(times) => Some(Ok((iters, times[0]))),
+            Err(err) => self.stop(err),
+        }
+    }
+}
+
+/// An external program benchmarked with `Criterion::bench_program`. The program reads an iteration
+/// count from each line of its standard input, runs its routine that many times, and writes the
+/// elapsed time in nanoseconds as a line to its standard output.
+pub(crate) struct Program {
+    command: Command,
+    // Started on first use, so that nothing is spawned for benchmarks which don't run.
+    process: Option<(Child, ChildStdin, BufReader<ChildStdout>)>,
+}
+impl Program {
+    pub(crate) fn new(command: Command) -> Program {
+        Program {
+            command,
+            process: None,
+        }
+    }
+
+    fn run(&mut self, iters: u64) -> Result<u64, String> {
+        if self.process.is_none() {
+            let mut child = self
+                .command
+                .stdin(Stdio::piped())
+                .stdout(Stdio::piped())
+                .spawn()
+                .map_err(|e| format!("Failed to start the benchmarked program: {}", e))?;
+            let stdin = child.stdin.take().unwrap();
+            let stdout = BufReader::new(child.stdout.take().unwrap());
+            self.process = Some((child, stdin, stdout));
+        }
+        let (_, stdin, stdout) = self.process.as_mut().unwrap();
+
+        writeln!(stdin, "{}", iters)
+            .and_then(|_| stdin.flush())
+            .map_err(|e| format!("Failed to write to the benchmarked program: {}", e))?;
+        let mut line = String::new();
+        stdout
+            .read_line(&mut line)
+            .map_err(|e| format!("Failed to read from the benchmarked program: {}", e))?;
+        line.trim().parse().map_err(|_| {
+            format!(
+                "Expected the benchmarked program to print the elapsed nanoseconds, got {:?}",
+                line.trim()
+            )
+        })
+    }
+}
+impl Drop for Program {
+    fn drop(&mut self) {
+        if let Some((mut child, stdin, _)) = self.process.take() {
+            drop(stdin);
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
+impl Routine<WallTime, ()> for Program {
+    fn bench(&mut self, _: &WallTime, iters: &[u64], _: &()) -> Result<Vec<f64>, String> {
+        iters
+            .iter()
+            .map(|&iters| self.run(iters).map(|nanos| nanos as f64))
+            .collect()
+    }
+
+    fn warm_up(&mut self, _: &WallTime, warm_up: WarmUp, _: &()) -> Result<(u64, u64), String> {
+        let mut iters = 1u64;
+        let mut total_iters = 0;
+        let mut elapsed = 0;
+        loop {
+            elapsed += self.run(iters)?;
+            total_iters += iters;
+            if warm_up_done(warm_up, elapsed, total_iters) {
+                return Ok((elapsed, total_iters));
+            }
+            iters = next_warm_up_iters(warm_up, iters, total_iters);
+        }
+    }
+
+    fn set_setup_value(&mut self, _: Option<Rc<dyn Any>>) {}
+
+    fn set_timeout(&mut self, _: Option<Duration>) {}
+
+    fn timer_pairs(&self) -> &[u64] {
+        &[]
     }
 }
 
@@ -168,6 +472,17 @@ where
     T: ?Sized,
 {
     f: F,
+    setup_value: Option<Rc<dyn Any>>,
+    timeout: Option<Duration>,
+    timer_pairs: Vec<u64>,
+    secondary_values: Vec<Vec<f64>>,
+    cold_start: Option<f64>,
+    record_iterations: bool,
+    iteration_values: Vec<f64>,
+    // The total number of elements processed and of iterations during the last call to `bench`,
+    // for the samples which used `iter_stream` or `Bencher::set_throughput_elements`.
+    elements: Option<(u64, u64)>,
+    tags: Vec<String>,
     // TODO: Is there some way to remove these?
     _phantom: PhantomData<T>,
     _phamtom2: PhantomData<M>,
@@ -180,6 +495,15 @@ where
     pub fn new(f: F) -> Function<M, F, T> {
         Function {
             f,
+            setup_value: None,
+            timeout: None,
+            timer_pairs: vec![],
+            secondary_values: vec![],
+            cold_start: None,
+            record_iterations: false,
+            iteration_values: vec![],
+            elements: None,
+            tags: vec![],
             _phantom: PhantomData,
             _phamtom2: PhantomData,
         }
@@ -191,7 +515,7 @@ where
     F: FnMut(&mut Bencher<'_, M>, &T),
     T: ?Sized,
 {
-    fn bench(&mut self, m: &M, iters: &[u64], parameter: &T) -> Vec<f64> {
+    fn bench(&mut self, m: &M, iters: &[u64], parameter: &T) -> Result<Vec<f64>, String> {
         let f = &mut self.f;
 
         let mut b = Bencher {
@@ -200,7 +524,27 @@ where
             value: m.zero(),
             measurement: m,
             elapsed_time: Duration::from_millis(0),
+            error: None,
+            setup_value: self.setup_value.clone(),
+            timer_pairs: 0,
+            elements: None,
+            iteration_values: if self.record_iterations {
+                Some(vec![])
+            } else {
+                None
+            },
+            tags: vec![],
         };
+        let timer_pairs = &mut self.timer_pairs;
+        timer_pairs.clear();
+        let secondary_values = &mut self.secondary_values;
+        secondary_values.clear();
+        let elements = &mut self.elements;
+        *elements = None;
+        let iteration_values = &mut self.iteration_values;
+        iteration_values.clear();
+        let tags = &mut self.tags;
+        let mut watchdog = self.timeout.map(Watchdog::start);
 
         iters
             .iter()
@@ -208,12 +552,32 @@ where
                 b.iters = *iters;
                 (*f)(&mut b, parameter);
                 b.assert_iterated();
-                m.to_f64(&b.value)
+                timer_pairs.push(b.timer_pairs);
+                if let Some(count) = b.elements.take() {
+                    let (total, total_iters) = elements.get_or_insert((0, 0));
+                    *total += count;
+                    *total_iters += *iters;
+                }
+                if let Some(values) = &mut b.iteration_values {
+                    iteration_values.append(values);
+                }
+                add_tags(tags, &mut b.tags);
+                if let Some(err) = b.error.take() {
+                    return Err(err);
+                }
+                if let Some(watchdog) = &mut watchdog {
+                    watchdog.sample_finished()?;
+                }
+                let secondary = m.secondary_to_f64(&b.value);
+                if !secondary.is_empty() {
+                    secondary_values.push(secondary);
+                }
+                Ok(m.to_f64(&b.value))
             })
             .collect()
     }
 
-    fn warm_up(&mut self, m: &M, how_long: Duration, parameter: &T) -> (u64, u64) {
+    fn warm_up(&mut self, m: &M, warm_up: WarmUp, parameter: &T) -> Result<(u64, u64), String> {
         let f = &mut self.f;
         let mut b = Bencher {
             iterated: false,
@@ -221,22 +585,93 @@ where
             value: m.zero(),
             measurement: m,
             elapsed_time: Duration::from_millis(0),
+            error: None,
+            setup_value: self.setup_value.clone(),
+            timer_pairs: 0,
+            elements: None,
+            iteration_values: None,
+            tags: vec![],
         };
 
+        // The iteration count doubles until the warm-up time is used up, so the last call can
+        // legitimately take nearly as long as the whole warm-up.
+        let mut watchdog = self.timeout.map(|timeout| match warm_up {
+            WarmUp::Time(how_long) => Watchdog::start(timeout.max(how_long * 2)),
+            WarmUp::Iterations(_) => Watchdog::start(timeout),
+        });
         let mut total_iters = 0;
         let mut elapsed_time = Duration::from_millis(0);
         loop {
             (*f)(&mut b, parameter);
 
             b.assert_iterated();
+            add_tags(&mut self.tags, &mut b.tags);
+            if let Some(err) = b.error.take() {
+                return Err(err);
+            }
+            if let Some(watchdog) = &mut watchdog {
+                watchdog.sample_finished()?;
+            }
 
+            // The first call of the warm-up runs a single iteration.
+            if total_iters == 0 && self.cold_start.is_none() {
+                self.cold_start = Some(m.to_f64(&b.value));
+            }
             total_iters += b.iters;
             elapsed_time += b.elapsed_time;
-            if elapsed_time > how_long {
-                return (elapsed_time.to_nanos(), total_iters);
+            if warm_up_done(warm_up, elapsed_time.to_nanos(), total_iters) {
+                return Ok((elapsed_time.to_nanos(), total_iters));
             }
 
-            b.iters = b.iters.wrapping_mul(2);
+            b.iters = next_warm_up_iters(warm_up, b.iters, total_iters);
+        }
+    }
+
+    fn set_setup_value(&mut self, value: Option<Rc<dyn Any>>) {
+        self.setup_value = value;
+    }
+
+    fn set_timeout(&mut self, timeout: Option<Duration>) {
+        self.timeout = timeout;
+    }
+
+    fn timer_pairs(&self) -> &[u64] {
+        &self.timer_pairs
+    }
+
+    fn elements_per_iteration(&self) -> Option<u64> {
+        self.elements
+            .filter(|&(_, iters)| iters > 0)
+            .map(|(total, iters)| (total as f64 / iters as f64).round() as u64)
+    }
+
+    fn secondary_values(&self) -> &[Vec<f64>] {
+        &self.secondary_values
+    }
+
+    fn cold_start(&self) -> Option<f64> {
+        self.cold_start
+    }
+
+    fn set_record_iterations(&mut self, enabled: bool) {
+        self.record_iterations = enabled;
+        self.iteration_values.clear();
+    }
+
+    fn iteration_values(&self) -> &[f64] {
+        &self.iteration_values
+    }
+
+    fn tags(&self) -> &[String] {
+        &self.tags
+    }
+}
+
+// Moves the tags added during a call of the routine to `tags`, skipping the ones it already has.
+fn add_tags(tags: &mut Vec<String>, added: &mut Vec<String>) {
+    for tag in added.drain(..) {
+        if !tags.contains(&tag) {
+            tags.push(tag);
         }
     }
 }
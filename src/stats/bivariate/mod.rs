@@ -6,9 +6,8 @@ mod resamples;
 
 use crate::stats::bivariate::resamples::Resamples;
 use crate::stats::float::Float;
-use crate::stats::tuple::{Tuple, TupledDistributionsBuilder};
+use crate::stats::tuple::Tuple;
 use crate::stats::univariate::Sample;
-use rayon::iter::{IntoParallelIterator, ParallelIterator};
 
 /// Bivariate `(X, Y)` data
 ///
@@ -72,27 +71,11 @@ where
         T::Distributions: Send,
         T::Builder: Send,
     {
-        (0..nresamples)
-            .into_par_iter()
-            .map_init(
-                || Resamples::new(*self),
-                |resamples, _| statistic(resamples.next()),
-            )
-            .fold(
-                || T::Builder::new(0),
-                |mut sub_distributions, sample| {
-                    sub_distributions.push(sample);
-                    sub_distributions
-                },
-            )
-            .reduce(
-                || T::Builder::new(0),
-                |mut a, mut b| {
-                    a.extend(&mut b);
-                    a
-                },
-            )
-            .complete()
+        crate::stats::bootstrap_with(
+            nresamples,
+            || Resamples::new(*self),
+            |resamples| statistic(resamples.next()),
+        )
     }
 
     /// Returns a view into the `X` data
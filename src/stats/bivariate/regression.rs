@@ -27,27 +27,115 @@ where
         Slope(xy / x2)
     }
 
+    /// Fits the data to a straight line using the Theil-Sen estimator, ie. the median of the
+    /// slopes of the lines through every pair of points with different `x`. Unlike ordinary least
+    /// squares, a minority of outlying points has little effect on the slope. The line is not
+    /// forced through the origin, so a constant offset in `y` doesn't affect the slope either.
+    ///
+    /// Falls back to `fit` if all of the points have the same `x`.
+    ///
+    /// - Time: `O(length^2)`
+    pub fn theil_sen(data: &Data<'_, A, A>) -> Slope<A> {
+        let xs = data.0;
+        let ys = data.1;
+
+        let mut slopes = Vec::with_capacity(xs.len() * xs.len().saturating_sub(1) / 2);
+        for i in 0..xs.len() {
+            for j in i + 1..xs.len() {
+                let dx = xs[j] - xs[i];
+                if dx != A::cast(0) {
+                    slopes.push((ys[j] - ys[i]) / dx);
+                }
+            }
+        }
+        if slopes.is_empty() {
+            return Slope::fit(data);
+        }
+
+        // Selecting the middle element(s) is linear, which matters when this is bootstrapped.
+        let cmp = |a: &A, b: &A| a.partial_cmp(b).unwrap();
+        let even = slopes.len() % 2 == 0;
+        let mid = slopes.len() / 2;
+        let (lower, &mut upper, _) = slopes.select_nth_unstable_by(mid, cmp);
+        if even {
+            let below = lower.iter().copied().fold(A::neg_infinity(), A::max);
+            Slope((below + upper) / A::cast(2))
+        } else {
+            Slope(upper)
+        }
+    }
+
     /// Computes the goodness of fit (coefficient of determination) for this data set
     ///
     /// - Time: `O(length)`
     pub fn r_squared(&self, data: &Data<'_, A, A>) -> A {
-        let _0 = A::cast(0);
-        let _1 = A::cast(1);
-        let m = self.0;
-        let xs = data.0;
+        self.r_squared_with_tss(data, Slope::total_sum_of_squares(data))
+    }
+
+    /// Computes the total sum of squares of the `y` values of this data set, which is the same for
+    /// every slope and can be reused across calls to `r_squared_with_tss`
+    ///
+    /// - Time: `O(length)`
+    pub fn total_sum_of_squares(data: &Data<'_, A, A>) -> A {
         let ys = data.1;
+        let y_bar = crate::stats::sum(ys) / A::cast(ys.len());
 
-        let n = A::cast(xs.len());
-        let y_bar = crate::stats::sum(ys) / n;
+        ys.iter()
+            .fold(A::cast(0), |ss_tot, &y| ss_tot + (y - y_bar).powi(2))
+    }
 
-        let mut ss_res = _0;
-        let mut ss_tot = _0;
+    /// Computes the goodness of fit like `r_squared`, using a precomputed total sum of squares
+    ///
+    /// - Time: `O(length)`
+    pub fn r_squared_with_tss(&self, data: &Data<'_, A, A>, tss: A) -> A {
+        let m = self.0;
+        let ss_res = data
+            .iter()
+            .fold(A::cast(0), |ss_res, (&x, &y)| ss_res + (y - m * x).powi(2));
 
-        for (&x, &y) in data.iter() {
-            ss_res = ss_res + (y - m * x).powi(2);
-            ss_tot = ss_res + (y - y_bar).powi(2);
-        }
+        A::cast(1) - ss_res / tss
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Slope;
+    use crate::stats::bivariate::Data;
+
+    #[test]
+    fn theil_sen_ignores_outliers() {
+        let xs = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0];
+        let mut ys: Vec<f64> = xs.iter().map(|x| 2.0 * x + 5.0).collect();
+        ys[6] = 1000.0;
+        let data = Data::new(&xs, &ys);
+
+        assert!((Slope::theil_sen(&data).0 - 2.0).abs() < 1e-12);
+        assert!(Slope::fit(&data).0 > 10.0);
+    }
+
+    #[test]
+    fn theil_sen_median_of_even_count() {
+        // The pairwise slopes are 1, 2, 3, 3, 4 and 5, so the median is the mean of 3 and 3.
+        let xs = [0.0f64, 1.0, 2.0, 3.0];
+        let ys = [0.0, 1.0, 4.0, 9.0];
+        let data = Data::new(&xs, &ys);
+        assert!((Slope::theil_sen(&data).0 - 3.0).abs() < 1e-12);
+
+        let xs = [1.0f64, 1.0];
+        let ys = [2.0, 4.0];
+        assert_eq!(Slope::theil_sen(&Data::new(&xs, &ys)).0, 3.0);
+    }
+
+    #[test]
+    fn r_squared_of_exact_fit() {
+        let xs = [1.0f64, 2.0, 3.0, 4.0];
+        let ys = [2.0, 4.0, 6.0, 8.0];
+        let data = Data::new(&xs, &ys);
+        let tss = Slope::total_sum_of_squares(&data);
 
-        _1 - ss_res / ss_tot
+        assert_eq!(tss, 20.0);
+        assert_eq!(Slope(2.0).r_squared(&data), 1.0);
+        // Residuals are 1, 2, 3 and 4, so R² = 1 - 30 / 20.
+        assert_eq!(Slope(1.0).r_squared_with_tss(&data, tss), -0.5);
     }
 }
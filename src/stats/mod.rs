@@ -1,96 +1,128 @@
-//! [Criterion]'s statistics library.
+//! Statistics on samples loaded by the user.
 //!
-//! [Criterion]: https://github.com/bheisler/criterion.rs
-//!
-//! **WARNING** This library is criterion's implementation detail and there no plans to stabilize
-//! it. In other words, the API may break at any time without notice.
+//! Criterion's own analysis is built on the same statistics library, but only the parts below
+//! are public; the rest is an implementation detail which may change at any time.
 
 #[cfg(test)]
 mod test;
 
-pub mod bivariate;
-pub mod tuple;
-pub mod univariate;
+pub(crate) mod bivariate;
+pub(crate) mod tuple;
+pub(crate) mod univariate;
 
+mod distribution;
 mod float;
 mod rand_util;
 
-use std::mem;
-use std::ops::Deref;
-
-use crate::stats::float::Float;
-use crate::stats::univariate::Sample;
-
-/// The bootstrap distribution of some parameter
-#[derive(Clone)]
-pub struct Distribution<A>(Box<[A]>);
+use std::cell::Cell;
 
-impl<A> Distribution<A>
-where
-    A: Float,
-{
-    /// Create a distribution from the given values
-    pub fn from(values: Box<[A]>) -> Distribution<A> {
-        Distribution(values)
-    }
-
-    /// Computes the confidence interval of the population parameter using percentiles
-    ///
-    /// # Panics
-    ///
-    /// Panics if the `confidence_level` is not in the `(0, 1)` range.
-    pub fn confidence_interval(&self, confidence_level: A) -> (A, A)
-    where
-        usize: cast::From<A, Output = Result<usize, cast::Error>>,
-    {
-        let _0 = A::cast(0);
-        let _1 = A::cast(1);
-        let _50 = A::cast(50);
-
-        assert!(confidence_level > _0 && confidence_level < _1);
-
-        let percentiles = self.percentiles();
-
-        // FIXME(privacy) this should use the `at_unchecked()` method
-        (
-            percentiles.at(_50 * (_1 - confidence_level)),
-            percentiles.at(_50 * (_1 + confidence_level)),
-        )
-    }
+use rayon::prelude::*;
 
-    /// Computes the "likelihood" of seeing the value `t` or "more extreme" values in the
-    /// distribution.
-    pub fn p_value(&self, t: A, tails: &Tails) -> A {
-        use std::cmp;
+use crate::stats::float::Float;
+use crate::stats::tuple::{Tuple, TupledDistributionsBuilder};
 
-        let n = self.0.len();
-        let hits = self.0.iter().filter(|&&x| x < t).count();
+pub use self::univariate::Sample;
 
-        let tails = A::cast(match *tails {
-            Tails::One => 1,
-            Tails::Two => 2,
-        });
+pub(crate) use self::distribution::{Distribution, Tails};
+pub(crate) use self::rand_util::set_seed;
 
-        A::cast(cmp::min(hits, n - hits)) / A::cast(n) * tails
-    }
+thread_local! {
+    // `const` initializers for thread locals require Rust 1.59.
+    #[allow(clippy::missing_const_for_thread_local)]
+    static SINGLE_THREADED: Cell<bool> = Cell::new(false);
 }
 
-impl<A> Deref for Distribution<A> {
-    type Target = Sample<A>;
+/// Runs `f`, computing all of the statistics it needs on the current thread rather than on
+/// rayon's thread pool if `single_threaded` is set. Combined with `set_seed`, this makes the
+/// bootstrap distributions reproducible.
+pub(crate) fn with_single_threaded<T>(single_threaded: bool, f: impl FnOnce() -> T) -> T {
+    let previous = SINGLE_THREADED.with(|s| s.replace(single_threaded));
+    let out = f();
+    SINGLE_THREADED.with(|s| s.set(previous));
+    out
+}
 
-    fn deref(&self) -> &Sample<A> {
-        let slice: &[_] = &self.0;
+fn is_single_threaded() -> bool {
+    SINGLE_THREADED.with(Cell::get)
+}
 
-        unsafe { mem::transmute(slice) }
+// Computes `nresamples` values of the statistic, each from the next resample drawn from the
+// resamples created by `init`. One set of resamples is created per rayon job, or only one in
+// single-threaded mode.
+fn bootstrap_with<R, T, I, F>(nresamples: usize, init: I, statistic: F) -> T::Distributions
+where
+    I: Fn() -> R + Sync + Send,
+    F: Fn(&mut R) -> T + Sync + Send,
+    T: Tuple + Send,
+    T::Builder: Send,
+{
+    if is_single_threaded() {
+        let mut resamples = init();
+        let mut distributions = T::Builder::new(nresamples);
+        for _ in 0..nresamples {
+            distributions.push(statistic(&mut resamples));
+        }
+        return distributions.complete();
     }
+
+    (0..nresamples)
+        .into_par_iter()
+        .map_init(init, |resamples, _| statistic(resamples))
+        .fold(
+            || T::Builder::new(0),
+            |mut sub_distributions, sample| {
+                sub_distributions.push(sample);
+                sub_distributions
+            },
+        )
+        .reduce(
+            || T::Builder::new(0),
+            |mut a, mut b| {
+                a.extend(&mut b);
+                a
+            },
+        )
+        .complete()
 }
 
-/// Number of tails for significance testing
-pub enum Tails {
-    /// One tailed test
-    One,
-    /// Two tailed test
-    Two,
+/// Estimates `statistic` of the population the `sample` was drawn from, returning the lower bound
+/// of the confidence interval, the point estimate and the upper bound, in that order.
+///
+/// The confidence interval is computed the same way as for Criterion's own estimates: the
+/// statistic is evaluated on `nresamples` bootstrap resamples of the sample, and the bounds are
+/// the percentiles of those values enclosing `confidence_level` of them.
+///
+/// ```
+/// use criterion::stats::{self, Sample};
+///
+/// let times = [10.1, 10.3, 9.8, 10.0, 12.5, 10.2, 9.9, 10.4, 10.1, 11.0];
+/// let sample = Sample::new(&times);
+/// let (lower, p99, upper) = stats::bootstrap_ci(sample, |s| s.percentiles().at(99.0), 1000, 0.95);
+/// assert!(lower <= p99 && p99 <= upper);
+/// ```
+///
+/// # Panics
+///
+/// Panics if `nresamples` is zero or if `confidence_level` is not in the `(0, 1)` range.
+pub fn bootstrap_ci<F>(
+    sample: &Sample<f64>,
+    statistic: F,
+    nresamples: usize,
+    confidence_level: f64,
+) -> (f64, f64, f64)
+where
+    F: Fn(&Sample<f64>) -> f64 + Sync,
+{
+    assert!(nresamples > 0, "The number of resamples must be positive");
+    assert!(
+        confidence_level > 0.0 && confidence_level < 1.0,
+        "The confidence level must be between 0 and 1"
+    );
+
+    let point_estimate = statistic(sample);
+    let (distribution,) = sample.bootstrap(nresamples, |resample| (statistic(resample),));
+    let (lower_bound, upper_bound) = distribution.confidence_interval(confidence_level);
+    (lower_bound, point_estimate, upper_bound)
 }
 
 fn dot<A>(xs: &[A], ys: &[A]) -> A
@@ -12,6 +12,12 @@ thread_local! {
     ));
 }
 
+/// Reseeds the random number generator of the current thread, which all of the resamples drawn on
+/// it are seeded from.
+pub fn set_seed(seed: u64) {
+    SEED_RAND.with(|r| *r.borrow_mut() = Rand64::new(u128::from(seed)));
+}
+
 pub fn new_rng() -> Rng {
     SEED_RAND.with(|r| {
         let mut r = r.borrow_mut();
@@ -40,8 +40,12 @@ where
 
     /// Maps the KDE over `xs`
     ///
-    /// - Multihreaded
+    /// - Multihreaded, unless in single-threaded mode
     pub fn map(&self, xs: &[A]) -> Box<[A]> {
+        if crate::stats::is_single_threaded() {
+            return xs.iter().map(|&x| self.estimate(x)).collect();
+        }
+
         xs.par_iter()
             .map(|&x| self.estimate(x))
             .collect::<Vec<_>>()
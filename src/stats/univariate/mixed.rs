@@ -1,10 +1,9 @@
 //! Mixed bootstrap
 
 use crate::stats::float::Float;
-use crate::stats::tuple::{Tuple, TupledDistributionsBuilder};
+use crate::stats::tuple::Tuple;
 use crate::stats::univariate::Resamples;
 use crate::stats::univariate::Sample;
-use rayon::prelude::*;
 
 /// Performs a *mixed* two-sample bootstrap
 pub fn bootstrap<A, T, S>(
@@ -27,31 +26,15 @@ where
     c.extend_from_slice(b);
     let c = Sample::new(&c);
 
-    (0..nresamples)
-        .into_par_iter()
-        .map_init(
-            || Resamples::new(c),
-            |resamples, _| {
-                let resample = resamples.next();
-                let a: &Sample<A> = Sample::new(&resample[..n_a]);
-                let b: &Sample<A> = Sample::new(&resample[n_a..]);
+    crate::stats::bootstrap_with(
+        nresamples,
+        || Resamples::new(c),
+        |resamples| {
+            let resample = resamples.next();
+            let a: &Sample<A> = Sample::new(&resample[..n_a]);
+            let b: &Sample<A> = Sample::new(&resample[n_a..]);
 
-                statistic(a, b)
-            },
-        )
-        .fold(
-            || T::Builder::new(0),
-            |mut sub_distributions, sample| {
-                sub_distributions.push(sample);
-                sub_distributions
-            },
-        )
-        .reduce(
-            || T::Builder::new(0),
-            |mut a, mut b| {
-                a.extend(&mut b);
-                a
-            },
-        )
-        .complete()
+            statistic(a, b)
+        },
+    )
 }
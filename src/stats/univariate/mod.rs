@@ -42,25 +42,32 @@ where
     let nresamples_sqrt = (nresamples as f64).sqrt().ceil() as usize;
     let per_chunk = (nresamples + nresamples_sqrt - 1) / nresamples_sqrt;
 
-    (0..nresamples_sqrt)
-        .into_par_iter()
-        .map_init(
-            || (Resamples::new(a), Resamples::new(b)),
-            |(a_resamples, b_resamples), i| {
-                let start = i * per_chunk;
-                let end = cmp::min((i + 1) * per_chunk, nresamples);
-                let a_resample = a_resamples.next();
+    let chunk = |(a_resamples, b_resamples): &mut (Resamples<'_, A>, Resamples<'_, B>), i| {
+        let start = i * per_chunk;
+        let end = cmp::min((i + 1) * per_chunk, nresamples);
+        let a_resample = a_resamples.next();
 
-                let mut sub_distributions: T::Builder =
-                    TupledDistributionsBuilder::new(end - start);
+        let mut sub_distributions: T::Builder = TupledDistributionsBuilder::new(end - start);
 
-                for _ in start..end {
-                    let b_resample = b_resamples.next();
-                    sub_distributions.push(statistic(a_resample, b_resample));
-                }
-                sub_distributions
-            },
-        )
+        for _ in start..end {
+            let b_resample = b_resamples.next();
+            sub_distributions.push(statistic(a_resample, b_resample));
+        }
+        sub_distributions
+    };
+
+    if crate::stats::is_single_threaded() {
+        let mut resamples = (Resamples::new(a), Resamples::new(b));
+        let mut distributions: T::Builder = TupledDistributionsBuilder::new(nresamples);
+        for i in 0..nresamples_sqrt {
+            distributions.extend(&mut chunk(&mut resamples, i));
+        }
+        return distributions.complete();
+    }
+
+    (0..nresamples_sqrt)
+        .into_par_iter()
+        .map_init(|| (Resamples::new(a), Resamples::new(b)), chunk)
         .reduce(
             || T::Builder::new(0),
             |mut a, mut b| {
@@ -10,6 +10,8 @@ where
 {
     rng: Rng,
     sample: &'a [A],
+    // Running totals of the weights of the sample, if the sample is not drawn from uniformly.
+    cumulative_weights: Option<&'a [f64]>,
     stage: Option<Vec<A>>,
 }
 
@@ -24,29 +26,47 @@ where
         Resamples {
             rng: new_rng(),
             sample: slice,
+            cumulative_weights: None,
             stage: None,
         }
     }
 
+    /// Resamples which draw each element of `sample` with a probability proportional to its
+    /// weight. `cumulative_weights[i]` must be the sum of the weights of elements `0..=i`.
+    pub fn weighted(sample: &'a Sample<A>, cumulative_weights: &'a [f64]) -> Resamples<'a, A> {
+        assert_eq!(sample.len(), cumulative_weights.len());
+
+        Resamples {
+            cumulative_weights: Some(cumulative_weights),
+            ..Resamples::new(sample)
+        }
+    }
+
     pub fn next(&mut self) -> &Sample<A> {
         let n = self.sample.len();
         let rng = &mut self.rng;
+        let cumulative_weights = self.cumulative_weights;
+        let mut index = || match cumulative_weights {
+            None => rng.rand_range(0u64..(n as u64)) as usize,
+            Some(cumulative) => {
+                let x = rng.rand_float() * cumulative[n - 1];
+                cumulative.partition_point(|&c| c <= x).min(n - 1)
+            }
+        };
 
         match self.stage {
             None => {
                 let mut stage = Vec::with_capacity(n);
 
                 for _ in 0..n {
-                    let idx = rng.rand_range(0u64..(self.sample.len() as u64));
-                    stage.push(self.sample[idx as usize])
+                    stage.push(self.sample[index()])
                 }
 
                 self.stage = Some(stage);
             }
             Some(ref mut stage) => {
                 for elem in stage.iter_mut() {
-                    let idx = rng.rand_range(0u64..(self.sample.len() as u64));
-                    *elem = self.sample[idx as usize]
+                    *elem = self.sample[index()]
                 }
             }
         }
@@ -116,4 +136,23 @@ mod test {
             panic!("Found {} duplicate samples", num_duplicated);
         }
     }
+
+    #[test]
+    fn weighted_skips_zero_weights() {
+        let v = [1.0f64, 2.0, 3.0, 4.0];
+        let cumulative = [0.0, 1.0, 1.0, 3.0];
+        let mut resamples = Resamples::weighted(Sample::new(&v), &cumulative);
+
+        let mut counts = [0usize; 4];
+        for _ in 0..1000 {
+            for &x in resamples.next().iter() {
+                counts[x as usize - 1] += 1;
+            }
+        }
+
+        assert_eq!(counts[0], 0);
+        assert_eq!(counts[2], 0);
+        // The last element has twice the weight of the second.
+        assert!(counts[3] > counts[1] * 3 / 2 && counts[3] < counts[1] * 5 / 2);
+    }
 }
@@ -1,7 +1,7 @@
 use std::{mem, ops};
 
 use crate::stats::float::Float;
-use crate::stats::tuple::{Tuple, TupledDistributionsBuilder};
+use crate::stats::tuple::Tuple;
 use crate::stats::univariate::Percentiles;
 use crate::stats::univariate::Resamples;
 use rayon::prelude::*;
@@ -127,7 +127,11 @@ where
         }
 
         let mut v = self.to_vec().into_boxed_slice();
-        v.par_sort_unstable_by(cmp);
+        if crate::stats::is_single_threaded() {
+            v.sort_unstable_by(cmp);
+        } else {
+            v.par_sort_unstable_by(cmp);
+        }
 
         // NB :-1: to intra-crate privacy rules
         unsafe { mem::transmute(v) }
@@ -174,6 +178,89 @@ where
         num / den
     }
 
+    /// Returns Cohen's d, the difference between the means of these two samples divided by their
+    /// pooled standard deviation
+    ///
+    /// If neither sample varies, eg. because they are counts which are the same on every
+    /// iteration, the effect size is undefined and 0 is returned.
+    ///
+    /// - Time: `O(length)`
+    pub fn cohens_d(&self, other: &Sample<A>) -> A {
+        let (x_bar, y_bar) = (self.mean(), other.mean());
+        let (s2_x, s2_y) = (self.var(Some(x_bar)), other.var(Some(y_bar)));
+        let n_x = A::cast(self.len());
+        let n_y = A::cast(other.len());
+        let one = A::cast(1);
+        let two = A::cast(2);
+        let pooled_var = ((n_x - one) * s2_x + (n_y - one) * s2_y) / (n_x + n_y - two);
+        if pooled_var == A::cast(0) {
+            return A::cast(0);
+        }
+
+        (x_bar - y_bar) / pooled_var.sqrt()
+    }
+
+    /// Returns the mean of the sample after discarding `fraction` of the data points from each end
+    ///
+    /// The number of data points discarded from each end is rounded down. `fraction` must be in the
+    /// range `[0, 0.5)`.
+    ///
+    /// - Time: `O(N log N) where N = length`
+    /// - Memory: `O(length)`
+    pub fn trimmed_mean(&self, fraction: f64) -> A {
+        use std::cmp::Ordering;
+
+        debug_assert!((0.0..0.5).contains(&fraction));
+        let trim = (self.len() as f64 * fraction) as usize;
+
+        let mut v = self.to_vec();
+        // NB This function assumes that there are no `NaN`s in the sample
+        v.sort_unstable_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+        let kept = &v[trim..v.len() - trim];
+
+        crate::stats::sum(kept) / A::cast(kept.len())
+    }
+
+    /// Returns the data points of the sample, in their original order, with those below the
+    /// `lower`th percentile or above the `upper`th percentile clamped to those percentiles
+    ///
+    /// Unlike excluding outliers, this keeps the number of data points, while bounding the
+    /// influence of extreme ones.
+    ///
+    /// - Time: `O(N log N) where N = length`
+    /// - Memory: `O(length)`
+    ///
+    /// # Panics
+    ///
+    /// Panics if the percentiles are outside the closed `[0, 100]` range, or if `lower` is greater
+    /// than `upper`
+    pub fn winsorize(&self, lower: A, upper: A) -> Vec<A>
+    where
+        usize: cast::From<A, Output = Result<usize, cast::Error>>,
+    {
+        assert!(lower <= upper);
+
+        let percentiles = self.percentiles();
+        let (lower, upper) = (percentiles.at(lower), percentiles.at(upper));
+        self.iter().map(|&x| x.max(lower).min(upper)).collect()
+    }
+
+    /// Returns the winsorized variance of the sample
+    ///
+    /// Data points below the `p`th percentile or above the `(100 - p)`th percentile are clamped to
+    /// those percentiles before computing the variance, which limits the influence of outliers.
+    ///
+    /// - Time: `O(N log N) where N = length`
+    /// - Memory: `O(length)`
+    pub fn winsorized_var(&self, p: A) -> A
+    where
+        usize: cast::From<A, Output = Result<usize, cast::Error>>,
+    {
+        let clamped = self.winsorize(p, A::cast(100) - p);
+
+        Sample::new(&clamped).var(None)
+    }
+
     /// Returns the variance of the sample
     ///
     /// The `mean` can be optionally passed along to speed up (2X) the computation
@@ -206,29 +293,76 @@ where
         T::Distributions: Send,
         T::Builder: Send,
     {
-        (0..nresamples)
-            .into_par_iter()
-            .map_init(
-                || Resamples::new(self),
-                |resamples, _| statistic(resamples.next()),
-            )
-            .fold(
-                || T::Builder::new(0),
-                |mut sub_distributions, sample| {
-                    sub_distributions.push(sample);
-                    sub_distributions
-                },
-            )
-            .reduce(
-                || T::Builder::new(0),
-                |mut a, mut b| {
-                    a.extend(&mut b);
-                    a
-                },
-            )
-            .complete()
+        self.bootstrap_with(None, nresamples, statistic)
+    }
+
+    /// Like `bootstrap`, but each resample draws the `i`-th data point with a probability
+    /// proportional to `weights[i]`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `weights` does not have one weight per data point, or if the weights are not
+    /// finite and non-negative with a positive sum.
+    pub fn bootstrap_weighted<T, S>(
+        &self,
+        weights: &[f64],
+        nresamples: usize,
+        statistic: S,
+    ) -> T::Distributions
+    where
+        S: Fn(&Sample<A>) -> T + Sync,
+        T: Tuple + Send,
+        T::Distributions: Send,
+        T::Builder: Send,
+    {
+        assert_eq!(
+            weights.len(),
+            self.len(),
+            "There must be one weight per data point"
+        );
+        assert!(
+            weights.iter().all(|&w| w.is_finite() && w >= 0.0),
+            "Weights must be finite and non-negative"
+        );
+
+        let cumulative: Vec<f64> = weights
+            .iter()
+            .scan(0.0, |total, &w| {
+                *total += w;
+                Some(*total)
+            })
+            .collect();
+        assert!(
+            cumulative[cumulative.len() - 1] > 0.0,
+            "Weights must not all be zero"
+        );
+
+        self.bootstrap_with(Some(&cumulative), nresamples, statistic)
+    }
+
+    fn bootstrap_with<T, S>(
+        &self,
+        cumulative_weights: Option<&[f64]>,
+        nresamples: usize,
+        statistic: S,
+    ) -> T::Distributions
+    where
+        S: Fn(&Sample<A>) -> T + Sync,
+        T: Tuple + Send,
+        T::Distributions: Send,
+        T::Builder: Send,
+    {
+        crate::stats::bootstrap_with(
+            nresamples,
+            || match cumulative_weights {
+                Some(cumulative) => Resamples::weighted(self, cumulative),
+                None => Resamples::new(self),
+            },
+            |resamples| statistic(resamples.next()),
+        )
     }
 
+    /// Returns the interquartile range of the sample
     #[cfg(test)]
     pub fn iqr(&self) -> A
     where
@@ -237,6 +371,7 @@ where
         self.percentiles().iqr()
     }
 
+    /// Returns the median of the sample
     #[cfg(test)]
     pub fn median(&self) -> A
     where
@@ -253,3 +388,87 @@ impl<A> ops::Deref for Sample<A> {
         &self.0
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::Sample;
+
+    #[test]
+    fn trimmed_mean() {
+        let data = [1000.0, 5.0, 3.0, 4.0, 2.0, 1.0, 6.0, 8.0, 7.0, -1000.0];
+        let sample = Sample::new(&data);
+
+        assert_eq!(sample.trimmed_mean(0.0), sample.mean());
+        assert_eq!(sample.trimmed_mean(0.1), 4.5);
+        assert_eq!(sample.trimmed_mean(0.45), 4.5);
+    }
+
+    #[test]
+    fn cohens_d_without_variance() {
+        let a = Sample::new(&[4.0, 4.0, 4.0, 4.0]);
+        let b = Sample::new(&[5.0, 5.0, 5.0]);
+
+        assert_eq!(a.cohens_d(a), 0.0);
+        assert_eq!(a.cohens_d(b), 0.0);
+    }
+
+    #[test]
+    fn winsorized_var() {
+        let data: [f64; 21] = [
+            -1000.0, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 10.0, 11.0, 12.0, 13.0, 14.0,
+            15.0, 16.0, 17.0, 18.0, 19.0, 1000.0,
+        ];
+        let sample = Sample::new(&data);
+        let clamped = data
+            .iter()
+            .map(|&x| x.clamp(1.0, 19.0))
+            .collect::<Vec<f64>>();
+
+        assert_eq!(sample.winsorized_var(5.0), Sample::new(&clamped).var(None));
+        assert_eq!(sample.winsorized_var(0.0), sample.var(None));
+    }
+
+    #[test]
+    fn winsorize() {
+        let data = [1000.0, 5.0, 3.0, 4.0, 2.0, 1.0, 6.0, 8.0, 7.0, -1000.0, 9.0];
+        let sample = Sample::new(&data);
+
+        assert_eq!(
+            sample.winsorize(10.0, 90.0),
+            [9.0, 5.0, 3.0, 4.0, 2.0, 1.0, 6.0, 8.0, 7.0, 1.0, 9.0]
+        );
+        assert_eq!(sample.winsorize(0.0, 100.0), data);
+    }
+
+    #[test]
+    fn bootstrap_weighted() {
+        let data: Vec<f64> = (0..20).map(|i| if i < 10 { 1.0 } else { 10.0 }).collect();
+        let weights: Vec<f64> = (0..20).map(|i| if i < 10 { 1.0 } else { 9.0 }).collect();
+        let sample = Sample::new(&data);
+
+        let (uniform,) = sample.bootstrap(1000, |s| (s.mean(),));
+        let (weighted,) = sample.bootstrap_weighted(&weights, 1000, |s| (s.mean(),));
+
+        // Uniformly, the resampled means are centered on 5.5; with the later data points drawn
+        // nine times as often, they are centered on 9.1.
+        assert!((uniform.mean() - 5.5).abs() < 0.5);
+        assert!((weighted.mean() - 9.1).abs() < 0.5);
+    }
+
+    #[test]
+    fn bootstrap_single_threaded_is_reproducible() {
+        let data: Vec<f64> = (0..50).map(|i| ((i * 37) % 23) as f64).collect();
+        let sample = Sample::new(&data);
+        let bootstrap = || {
+            crate::stats::with_single_threaded(true, || {
+                crate::stats::set_seed(42);
+                sample.bootstrap(1000, |s| (s.mean(), s.median()))
+            })
+        };
+
+        let (mean_a, median_a) = bootstrap();
+        let (mean_b, median_b) = bootstrap();
+        assert_eq!(&mean_a[..], &mean_b[..]);
+        assert_eq!(&median_a[..], &median_b[..]);
+    }
+}
@@ -0,0 +1,110 @@
+//! Samples the state of the system at the start and end of each benchmark, enabled with
+//! `Criterion::monitor_system`.
+//!
+//! Background load and CPU frequency scaling are common causes of noisy or anomalous results; a
+//! large change of frequency during a benchmark hints that the CPU was throttled.
+
+/// The CPU frequency and load of the system at one point in time. Each value is `None` if it
+/// can't be read on this platform.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SystemState {
+    /// The mean current frequency of the CPU cores, in MHz.
+    pub cpu_frequency_mhz: Option<f64>,
+    /// The load average over the last minute.
+    pub load_average: Option<f64>,
+}
+
+/// The state of the system when a benchmark started and finished being measured.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SystemSamples {
+    pub start: SystemState,
+    pub end: SystemState,
+}
+
+/// Reads the current state of the system.
+pub fn sample() -> SystemState {
+    SystemState {
+        cpu_frequency_mhz: imp::cpu_frequency_mhz(),
+        load_average: imp::load_average(),
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod imp {
+    use std::fs;
+
+    pub fn cpu_frequency_mhz() -> Option<f64> {
+        let cpuinfo = fs::read_to_string("/proc/cpuinfo").ok()?;
+        let frequencies: Vec<f64> = cpuinfo
+            .lines()
+            .filter(|line| line.starts_with("cpu MHz"))
+            .filter_map(|line| line.split(':').nth(1)?.trim().parse().ok())
+            .collect();
+        if frequencies.is_empty() {
+            return None;
+        }
+        Some(frequencies.iter().sum::<f64>() / frequencies.len() as f64)
+    }
+
+    pub fn load_average() -> Option<f64> {
+        let loadavg = fs::read_to_string("/proc/loadavg").ok()?;
+        loadavg.split_whitespace().next()?.parse().ok()
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod imp {
+    use std::ffi::CString;
+    use std::mem;
+    use std::ptr;
+
+    pub fn cpu_frequency_mhz() -> Option<f64> {
+        // Not available on Apple silicon, where the frequency isn't exposed.
+        let name = CString::new("hw.cpufrequency").unwrap();
+        let mut hz: u64 = 0;
+        let mut len = mem::size_of::<u64>();
+        let result = unsafe {
+            libc::sysctlbyname(
+                name.as_ptr(),
+                &mut hz as *mut u64 as *mut libc::c_void,
+                &mut len,
+                ptr::null_mut(),
+                0,
+            )
+        };
+        if result != 0 || hz == 0 {
+            return None;
+        }
+        Some(hz as f64 / 1e6)
+    }
+
+    pub fn load_average() -> Option<f64> {
+        let mut loadavg = [0f64; 1];
+        if unsafe { libc::getloadavg(loadavg.as_mut_ptr(), 1) } != 1 {
+            return None;
+        }
+        Some(loadavg[0])
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+mod imp {
+    pub fn cpu_frequency_mhz() -> Option<f64> {
+        None
+    }
+
+    pub fn load_average() -> Option<f64> {
+        None
+    }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_sample_load_average() {
+        let state = sample();
+        assert!(state.load_average.unwrap() >= 0.0);
+    }
+}
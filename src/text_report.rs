@@ -0,0 +1,75 @@
+use crate::error::Result;
+use crate::estimate::Estimate;
+use crate::fs;
+use crate::measurement::ValueFormatter;
+use crate::report::{BenchmarkId, MeasurementData, Report, ReportContext};
+use std::fmt::Write;
+use std::path::Path;
+
+/// Writes the estimates of each benchmark to `new/report.txt` as an aligned plain-text table, with
+/// the values formatted as in the command-line output.
+pub struct FileTextReport;
+impl FileTextReport {
+    fn write_file(
+        &self,
+        path: &Path,
+        id: &BenchmarkId,
+        measurements: &MeasurementData<'_>,
+        formatter: &dyn ValueFormatter,
+    ) -> Result<()> {
+        fs::save_string(&table(id, measurements, formatter), &path)
+    }
+}
+
+impl Report for FileTextReport {
+    fn measurement_complete(
+        &self,
+        id: &BenchmarkId,
+        context: &ReportContext,
+        measurements: &MeasurementData<'_>,
+        formatter: &dyn ValueFormatter,
+    ) {
+        let mut path = context.output_directory.clone();
+        path.push(id.as_directory_name());
+        path.push("new");
+        path.push("report.txt");
+        log_if_err!(self.write_file(&path, id, measurements, formatter));
+    }
+}
+
+fn table(id: &BenchmarkId, meas: &MeasurementData<'_>, formatter: &dyn ValueFormatter) -> String {
+    let estimates = &meas.absolute_estimates;
+    let mut rows: Vec<(&str, &Estimate)> = vec![];
+    if let Some(slope) = &estimates.slope {
+        rows.push(("Slope", slope));
+    }
+    rows.push(("Mean", &estimates.mean));
+    if meas.robust_dispersion {
+        rows.push(("Winsorized SD", &estimates.std_dev));
+    } else {
+        rows.push(("Std. Dev.", &estimates.std_dev));
+    }
+    rows.push(("Median", &estimates.median));
+    rows.push(("MAD", &estimates.median_abs_dev));
+    if let Some(trimmed_mean) = &estimates.trimmed_mean {
+        rows.push(("Trimmed Mean", trimmed_mean));
+    }
+    if let Some(mode) = &estimates.mode {
+        rows.push(("Mode", mode));
+    }
+
+    let mut text = format!("{}\n\n", id.as_title());
+    let mut row = |name: &str, lower: &str, point: &str, upper: &str| {
+        writeln!(text, "{:<15}{:<15}{:<15}{}", name, lower, point, upper).unwrap();
+    };
+    row("", "Lower bound", "Estimate", "Upper bound");
+    for (name, estimate) in rows {
+        row(
+            name,
+            &formatter.format_value(estimate.confidence_interval.lower_bound),
+            &formatter.format_value(estimate.point_estimate),
+            &formatter.format_value(estimate.confidence_interval.upper_bound),
+        );
+    }
+    text
+}
@@ -0,0 +1,135 @@
+use std::path::Path;
+use std::process;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::error::Result;
+use crate::fs;
+
+lazy_static! {
+    /// The timeline of this run, if `--trace` was given. It is process-global because each
+    /// `criterion_group!` runs on its own `Criterion` instance.
+    static ref TRACE: Mutex<Option<Trace>> = Mutex::new(None);
+}
+
+/// A single complete (`"ph": "X"`) event in Chrome's trace event format, with timestamps in
+/// microseconds since the start of the run.
+#[derive(Debug, Serialize)]
+struct TraceEvent {
+    name: String,
+    cat: String,
+    ph: &'static str,
+    ts: f64,
+    dur: f64,
+    pid: u32,
+    tid: u32,
+    args: TraceArgs,
+}
+
+#[derive(Debug, Serialize)]
+struct TraceArgs {
+    phase: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TraceFile<'a> {
+    trace_events: &'a [TraceEvent],
+    display_time_unit: &'static str,
+}
+
+struct Trace {
+    start: Instant,
+    benchmark: String,
+    events: Vec<TraceEvent>,
+}
+impl Trace {
+    fn new() -> Trace {
+        Trace {
+            start: Instant::now(),
+            benchmark: String::new(),
+            events: vec![],
+        }
+    }
+
+    fn record(&mut self, phase: &str, start: Instant, elapsed: Duration) {
+        let ts = start.saturating_duration_since(self.start);
+        self.events.push(TraceEvent {
+            name: self.benchmark.clone(),
+            cat: phase.to_owned(),
+            ph: "X",
+            ts: ts.as_secs_f64() * 1e6,
+            dur: elapsed.as_secs_f64() * 1e6,
+            pid: process::id(),
+            tid: 0,
+            args: TraceArgs {
+                phase: phase.to_owned(),
+            },
+        });
+    }
+
+    fn save(&self, path: &Path) -> Result<()> {
+        let file = TraceFile {
+            trace_events: &self.events,
+            display_time_unit: "ms",
+        };
+        fs::save(&file, &path)
+    }
+}
+
+/// Starts recording the timeline of the run. Does nothing if it is already being recorded.
+pub fn enable() {
+    let mut trace = TRACE.lock().unwrap();
+    if trace.is_none() {
+        *trace = Some(Trace::new());
+    }
+}
+
+/// Sets the benchmark that the following phases belong to.
+pub fn set_benchmark(title: &str) {
+    if let Some(trace) = TRACE.lock().unwrap().as_mut() {
+        trace.benchmark = title.to_owned();
+    }
+}
+
+/// Records a phase of the current benchmark which began at `start` and took `elapsed`.
+pub fn record(phase: &str, start: Instant, elapsed: Duration) {
+    if let Some(trace) = TRACE.lock().unwrap().as_mut() {
+        trace.record(phase, start, elapsed);
+    }
+}
+
+/// Writes the timeline recorded so far to `path`.
+pub fn save(path: &Path) -> Result<()> {
+    match TRACE.lock().unwrap().as_ref() {
+        Some(trace) => trace.save(path),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_trace_file() {
+        let mut trace = Trace::new();
+        trace.benchmark = "group/function".to_owned();
+        let start = trace.start + Duration::from_millis(2);
+        trace.record("Warming up", start, Duration::from_millis(3));
+
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("trace.json");
+        trace.save(&path).unwrap();
+
+        let json: serde_json::Value = fs::load(&path).unwrap();
+        let event = &json["traceEvents"][0];
+        assert_eq!(event["name"], "group/function");
+        assert_eq!(event["cat"], "Warming up");
+        assert_eq!(event["ph"], "X");
+        assert!((event["ts"].as_f64().unwrap() - 2000.0).abs() < 1e-6);
+        assert!((event["dur"].as_f64().unwrap() - 3000.0).abs() < 1e-6);
+        assert_eq!(json["displayTimeUnit"], "ms");
+    }
+}
@@ -0,0 +1,95 @@
+//! Detects samples which run for longer than the benchmark timeout, set with
+//! `Criterion::benchmark_timeout`.
+//!
+//! A running closure can't be stopped safely, so a sample which exceeds the timeout is only
+//! reported as an error once it returns. A background thread prints a warning as soon as the
+//! timeout elapses, so that a benchmark stuck in an infinite loop doesn't hang silently.
+
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use crate::format;
+use crate::DurationExt;
+
+pub(crate) struct Watchdog {
+    timeout: Duration,
+    sample_start: Instant,
+    // Signals the end of each sample; dropping it stops the thread.
+    ticks: Option<Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Watchdog {
+    /// Starts timing the first sample.
+    pub(crate) fn start(timeout: Duration) -> Watchdog {
+        let (ticks, rx) = mpsc::channel();
+        let thread = thread::Builder::new()
+            .name("criterion-watchdog".to_owned())
+            .spawn(move || loop {
+                match rx.recv_timeout(timeout) {
+                    Ok(()) => {}
+                    Err(RecvTimeoutError::Timeout) => {
+                        warn!(
+                            "A sample has been running for more than {}. The benchmark will be \
+                            aborted when the sample finishes; if it never does, use \
+                            Criterion::isolated_samples to kill it instead.",
+                            format::time(timeout.to_nanos() as f64, None)
+                        );
+                        // Stay quiet until the sample finally ends.
+                        if rx.recv().is_err() {
+                            return;
+                        }
+                    }
+                    Err(RecvTimeoutError::Disconnected) => return,
+                }
+            })
+            .ok();
+        Watchdog {
+            timeout,
+            sample_start: Instant::now(),
+            ticks: Some(ticks),
+            thread,
+        }
+    }
+
+    /// Marks the end of a sample and starts timing the next one. Returns an error if the sample
+    /// took longer than the timeout.
+    pub(crate) fn sample_finished(&mut self) -> Result<(), String> {
+        let elapsed = self.sample_start.elapsed();
+        if let Some(ticks) = &self.ticks {
+            let _ = ticks.send(());
+        }
+        if elapsed > self.timeout {
+            return Err(format!(
+                "A sample took {}, longer than the benchmark timeout of {}",
+                format::time(elapsed.to_nanos() as f64, None),
+                format::time(self.timeout.to_nanos() as f64, None)
+            ));
+        }
+        self.sample_start = Instant::now();
+        Ok(())
+    }
+}
+
+impl Drop for Watchdog {
+    fn drop(&mut self) {
+        self.ticks = None;
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_sample_over_timeout() {
+        let mut watchdog = Watchdog::start(Duration::from_millis(20));
+        assert!(watchdog.sample_finished().is_ok());
+        thread::sleep(Duration::from_millis(40));
+        assert!(watchdog.sample_finished().is_err());
+    }
+}
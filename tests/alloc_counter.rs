@@ -0,0 +1,61 @@
+#![cfg(feature = "alloc_counter")]
+
+use criterion::measurement::{AllocationCount, Allocations, CountingAllocator};
+use criterion::{black_box, Criterion};
+use serde_json::value::Value;
+use std::fs::File;
+use std::time::Duration;
+use tempfile::tempdir;
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+#[test]
+fn test_allocations_measurement() {
+    let dir = tempdir().unwrap();
+    Criterion::default()
+        .output_directory(dir.path())
+        .warm_up_time(Duration::from_millis(250))
+        .measurement_time(Duration::from_millis(500))
+        .nresamples(2000)
+        .without_plots()
+        .with_measurement(Allocations)
+        .bench_function("test_allocations_measurement", |b| {
+            b.iter(|| black_box(Vec::<u8>::with_capacity(1024)))
+        });
+
+    let estimates_file = dir
+        .path()
+        .join("test_allocations_measurement/new/estimates.json");
+    let estimates: Value = serde_json::from_reader(File::open(estimates_file).unwrap()).unwrap();
+    let median = estimates["median"]["point_estimate"].as_f64().unwrap();
+    assert_eq!(median, 1024.0);
+}
+
+#[test]
+fn test_allocation_count_measurement() {
+    let dir = tempdir().unwrap();
+    // Run twice so that the second run is compared against the first.
+    for _ in 0..2 {
+        let mut c = Criterion::default()
+            .output_directory(dir.path())
+            .warm_up_time(Duration::from_millis(250))
+            .measurement_time(Duration::from_millis(500))
+            .nresamples(2000)
+            .with_plots()
+            .with_measurement(AllocationCount);
+        c.bench_function("test_allocation_count", |b| {
+            b.iter(|| black_box(vec![1u8; 16]).into_boxed_slice())
+        });
+        c.bench_function("test_no_allocations", |b| b.iter(|| black_box(1u8) + 1));
+    }
+
+    let median = |id: &str| {
+        let estimates_file = dir.path().join(id).join("new/estimates.json");
+        let estimates: Value =
+            serde_json::from_reader(File::open(estimates_file).unwrap()).unwrap();
+        estimates["median"]["point_estimate"].as_f64().unwrap()
+    };
+    assert_eq!(median("test_allocation_count"), 1.0);
+    assert_eq!(median("test_no_allocations"), 0.0);
+}
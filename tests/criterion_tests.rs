@@ -4,8 +4,15 @@ use criterion;
 use serde_json;
 
 use criterion::{
-    criterion_group, criterion_main, profiler::Profiler, BatchSize, Benchmark, BenchmarkId,
-    Criterion, Fun, ParameterizedBenchmark, SamplingMode, Throughput,
+    bench_over_types, criterion_group, criterion_main,
+    measurement::{
+        BoxedMeasurement, HighResWallTime, Measurement, MultiMeasurement, SignedMeasurement,
+        ValueFormatter, WallTime,
+    },
+    profiler::Profiler,
+    stats, AxisScale, BatchSize, Benchmark, BenchmarkId, Criterion, Fun, Palette,
+    ParameterizedBenchmark, PlotSet, PlotTerminalConfig, RegressionMethod, SampleFormat,
+    SamplingMode, Statistic, Throughput, TimeUnit, WarmUp,
 };
 use serde_json::value::Value;
 use std::cell::{Cell, RefCell};
@@ -89,6 +96,7 @@ fn verify_stats(dir: &PathBuf, baseline: &str) {
     verify_json(&dir, &format!("{}/tukey.json", baseline));
     verify_json(&dir, &format!("{}/benchmark.json", baseline));
     verify_file(&dir, &format!("{}/raw.csv", baseline));
+    verify_file(dir, &format!("{}/report.txt", baseline));
 }
 
 fn verify_not_exists(dir: &PathBuf, path: &str) {
@@ -137,6 +145,9 @@ fn test_without_plots() {
             entry.unwrap().file_name()
         );
     }
+    // The HTML report is removed entirely, so none of its directories are created either.
+    verify_not_exists(&dir.path().join("test_without_plots"), "report");
+    verify_not_exists(&dir.path().to_path_buf(), "report");
 }
 
 #[test]
@@ -172,6 +183,50 @@ fn test_retain_baseline() {
     assert_eq!(pre_modified, post_modified, "baseline modified by retain");
 }
 
+#[test]
+fn test_compare_and_save_baseline() {
+    let dir = temp_dir();
+    short_benchmark(&dir)
+        .save_baseline("main".to_owned())
+        .bench_function("test_compare_and_save_baseline", |b| b.iter(|| 10));
+
+    let pre_modified = latest_modified(&dir.path().join("test_compare_and_save_baseline/main"));
+
+    short_benchmark(&dir)
+        .compare_and_save_baseline("main".to_owned(), "pr-123".to_owned())
+        .bench_function("test_compare_and_save_baseline", |b| b.iter(|| 10));
+
+    let dir = dir.path().join("test_compare_and_save_baseline");
+    let post_modified = latest_modified(&dir.join("main"));
+    assert_eq!(pre_modified, post_modified, "compared baseline modified");
+    verify_stats(&dir, "pr-123");
+    verify_json(&dir, "change/estimates.json");
+    verify_not_exists(&dir, "base");
+}
+
+#[test]
+fn test_clear_benchmark() {
+    let dir = temp_dir();
+    for name in &["test_clear_benchmark", "test_clear_benchmark_kept"] {
+        short_benchmark(&dir)
+            .save_baseline("some-baseline".to_owned())
+            .bench_function(name, |b| b.iter(|| 10));
+    }
+
+    let mut c = short_benchmark(&dir).clear_benchmark("^test_clear_benchmark$");
+    c.bench_function("test_clear_benchmark", |b| b.iter(|| 10));
+    c.bench_function("test_clear_benchmark_kept", |b| b.iter(|| 10));
+
+    let cleared = dir.path().join("test_clear_benchmark");
+    verify_not_exists(&cleared, "some-baseline");
+    verify_stats(&cleared, "base");
+    verify_not_exists(&cleared, "change");
+    verify_stats(
+        &dir.path().join("test_clear_benchmark_kept"),
+        "some-baseline",
+    );
+}
+
 #[test]
 #[should_panic(expected = "Baseline 'some-baseline' must exist before comparison is allowed")]
 fn test_compare_baseline() {
@@ -182,6 +237,188 @@ fn test_compare_baseline() {
         .bench_function("test_compare_baseline", |b| b.iter(|| 10));
 }
 
+#[test]
+fn test_baseline_from_git() {
+    let head = match std::process::Command::new("git")
+        .args(["rev-parse", "HEAD"])
+        .output()
+    {
+        Ok(output) if output.status.success() => String::from_utf8(output.stdout).unwrap(),
+        // Not running in a git checkout.
+        _ => return,
+    };
+    let head = head.trim().to_owned();
+
+    let dir = temp_dir();
+    short_benchmark(&dir)
+        .save_baseline(head.clone())
+        .bench_function("test_baseline_from_git", |b| b.iter(|| 10));
+    short_benchmark(&dir)
+        .baseline_from_git("HEAD")
+        .bench_function("test_baseline_from_git", |b| b.iter(|| 10));
+
+    let dir = dir.path().join("test_baseline_from_git");
+    verify_json(&dir, &format!("{}/estimates.json", head));
+    verify_json(&dir, "change/estimates.json");
+}
+
+#[test]
+#[should_panic(expected = "Unable to resolve git revision")]
+fn test_baseline_from_invalid_git_revision() {
+    short_benchmark(&temp_dir()).baseline_from_git("not-a-revision");
+}
+
+#[test]
+fn test_bench_over_types() {
+    let dir = temp_dir();
+    let mut criterion = short_benchmark(&dir);
+    let mut group = criterion.benchmark_group("test_bench_over_types");
+    bench_over_types!(group, [u8, u64], |b, T| b.iter(|| T::MAX));
+    group.finish();
+
+    for type_name in &["u8", "u64"] {
+        verify_stats(
+            &dir.path().join("test_bench_over_types").join(type_name),
+            "new",
+        );
+    }
+}
+
+#[test]
+fn test_record_per_iteration() {
+    let dir = temp_dir();
+    short_benchmark(&dir)
+        .record_per_iteration(true)
+        .bench_function("test_record_per_iteration", |b| {
+            b.iter(|| (0..criterion::black_box(100u64)).sum::<u64>())
+        });
+    short_benchmark(&dir).bench_function("test_record_per_iteration_disabled", |b| {
+        b.iter(|| (0..criterion::black_box(100u64)).sum::<u64>())
+    });
+
+    // Every iteration of every sample is recorded.
+    let bench_dir = dir.path().join("test_record_per_iteration");
+    let sample: Value =
+        serde_json::from_reader(File::open(bench_dir.join("new/sample.json")).unwrap()).unwrap();
+    let total_iters = sample["iters"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|iters| iters.as_f64().unwrap())
+        .sum::<f64>();
+    let iterations: Value =
+        serde_json::from_reader(File::open(bench_dir.join("new/iterations.json")).unwrap())
+            .unwrap();
+    let times = iterations["times"].as_array().unwrap();
+    assert_eq!(times.len() as f64, total_iters);
+    assert!(times.iter().all(|time| time.as_f64().unwrap() >= 0.0));
+
+    verify_not_exists(
+        &dir.path().join("test_record_per_iteration_disabled"),
+        "new/iterations.json",
+    );
+}
+
+#[test]
+fn test_run_outcome() {
+    let dir = temp_dir();
+    short_benchmark(&dir)
+        .bench_function("test_run_outcome", |b| b.iter(|| criterion::black_box(1)));
+    short_benchmark(&dir).bench_function("test_run_outcome", |b| {
+        b.iter(|| (0..criterion::black_box(10_000u64)).sum::<u64>())
+    });
+
+    // The outcome covers every benchmark run by the test process, so only check for this one.
+    let outcome = short_benchmark(&dir).run();
+    assert!(!outcome.passed());
+    assert!(outcome
+        .regressed_ids
+        .contains(&"test_run_outcome".to_owned()));
+}
+
+#[test]
+fn test_min_r2_for_regression() {
+    let dir = temp_dir();
+    let baseline = (1..=20u64)
+        .map(|i| (i * 100, (i * 100) as f64 * 10.0))
+        .collect::<Vec<_>>();
+    short_benchmark(&dir).analyze("test_min_r2_for_regression", &baseline);
+
+    // Twice as slow on average, but alternating between 10ns and 30ns per iteration, which the
+    // linear regression fits poorly.
+    let noisy = (1..=20u64)
+        .map(|i| {
+            let per_iter = if i % 2 == 0 { 10.0 } else { 30.0 };
+            (i * 100, (i * 100) as f64 * per_iter)
+        })
+        .collect::<Vec<_>>();
+    short_benchmark(&dir)
+        .min_r2_for_regression(0.99)
+        .analyze("test_min_r2_for_regression", &noisy);
+
+    let outcome = short_benchmark(&dir).run();
+    assert!(outcome.uncertain >= 1);
+    assert!(!outcome
+        .regressed_ids
+        .contains(&"test_min_r2_for_regression".to_owned()));
+}
+
+#[test]
+fn test_comparison_test() {
+    let dir = temp_dir();
+    short_benchmark(&dir).bench_function("test_comparison_test", |b| {
+        b.iter(|| criterion::black_box(1))
+    });
+
+    let calls = Rc::new(Cell::new(0));
+    let counter = calls.clone();
+    short_benchmark(&dir)
+        .comparison_test(move |new, base| {
+            assert_eq!(new.len(), base.len());
+            counter.set(counter.get() + 1);
+            1.0
+        })
+        .bench_function("test_comparison_test", |b| {
+            b.iter(|| (0..criterion::black_box(10_000u64)).sum::<u64>())
+        });
+    assert_eq!(calls.get(), 1);
+
+    // The custom test never rejects the null hypothesis, so the slowdown isn't a regression.
+    let outcome = short_benchmark(&dir).run();
+    assert!(!outcome
+        .regressed_ids
+        .contains(&"test_comparison_test".to_owned()));
+}
+
+#[test]
+fn test_external_baseline_path() {
+    let baseline_dir = temp_dir();
+    short_benchmark(&baseline_dir)
+        .save_baseline("some-baseline".to_owned())
+        .bench_function("test_external_baseline_path", |b| b.iter(|| 10));
+
+    let dir = temp_dir();
+    short_benchmark(&dir)
+        .baseline_path(baseline_dir.path())
+        .retain_baseline("some-baseline".to_owned())
+        .bench_function("test_external_baseline_path", |b| b.iter(|| 10));
+
+    let dir = dir.path().join("test_external_baseline_path");
+    verify_json(&dir, "change/estimates.json");
+    verify_not_exists(&dir, "some-baseline");
+}
+
+#[test]
+#[should_panic(expected = "was not found in external baseline path")]
+fn test_missing_external_baseline_path() {
+    let baseline_dir = temp_dir();
+    let dir = temp_dir();
+    short_benchmark(&dir)
+        .baseline_path(baseline_dir.path())
+        .retain_baseline("some-baseline".to_owned())
+        .bench_function("test_missing_external_baseline_path", |b| b.iter(|| 10));
+}
+
 #[test]
 fn test_sample_size() {
     let dir = temp_dir();
@@ -285,6 +522,114 @@ fn test_filtering() {
     assert!(!dir.path().join("test_filtering").is_dir());
 }
 
+#[test]
+fn test_tag_filtering() {
+    let dir = temp_dir();
+    let mut c = short_benchmark(&dir).with_tag_filter("parser");
+
+    let mut group = c.benchmark_group("test_tag_filtering");
+    group
+        .tag("parser")
+        .bench_function("tagged", |b| b.iter(|| 10));
+    group.finish();
+
+    c.bench_function("test_tag_filtering_bencher", |b| {
+        b.tag("parser");
+        b.iter(|| 10)
+    });
+
+    let counter = Counter::default();
+    let clone = counter.clone();
+    c.bench_function("test_tag_filtering_untagged", move |b| {
+        b.tag("codegen");
+        b.iter(|| clone.count())
+    });
+
+    // A single iteration is run to find the tags added by the routine.
+    assert_eq!(counter.read(), 1);
+    assert!(!dir.path().join("test_tag_filtering_untagged").is_dir());
+
+    for benchmark in &["test_tag_filtering/tagged", "test_tag_filtering_bencher"] {
+        let benchmark_file = dir.path().join(benchmark).join("new/benchmark.json");
+        let json: Value = serde_json::from_reader(File::open(benchmark_file).unwrap()).unwrap();
+        assert_eq!(json["tags"], serde_json::json!(["parser"]));
+    }
+}
+
+#[test]
+fn test_tag_filtering_without_benchmarking() {
+    let dir = temp_dir();
+    let counter = Counter::default();
+    let clone = counter.clone();
+    // When profiling, the routine isn't run just to find its tags.
+    short_benchmark(&dir)
+        .with_tag_filter("parser")
+        .profile_time(Some(Duration::from_millis(100)))
+        .bench_function("test_tag_filtering_profile", move |b| {
+            b.tag("parser");
+            b.iter(|| clone.count())
+        });
+
+    assert_eq!(counter.read(), 0);
+}
+
+#[test]
+fn test_bencher_tags() {
+    let dir = temp_dir();
+    let mut c = short_benchmark(&dir);
+    let mut group = c.benchmark_group("test_bencher_tags");
+    group.tag("parser").bench_function("both", |b| {
+        b.tag("header");
+        b.tag("parser");
+        b.iter(|| 10)
+    });
+    group.finish();
+
+    let benchmark_file = dir.path().join("test_bencher_tags/both/new/benchmark.json");
+    let json: Value = serde_json::from_reader(File::open(benchmark_file).unwrap()).unwrap();
+    assert_eq!(json["tags"], serde_json::json!(["parser", "header"]));
+}
+
+#[test]
+#[should_panic(expected = "Benchmark IDs must be unique within a group.")]
+fn test_duplicate_ids_with_different_tags() {
+    let dir = temp_dir();
+    let mut c = short_benchmark(&dir).with_filter("^none$");
+    let mut group = c.benchmark_group("test_duplicate_ids");
+    group.bench_function("function", |b| b.iter(|| 10));
+    group
+        .tag("parser")
+        .bench_function("function", |b| b.iter(|| 10));
+}
+
+#[test]
+fn test_try_iter_aborts_on_error() {
+    let dir = temp_dir();
+    let mut c = short_benchmark(&dir);
+
+    let counter = Counter::default();
+    let clone = counter.clone();
+    c.bench_function("test_try_iter_error", move |b| {
+        b.try_iter(|| {
+            clone.count();
+            if clone.read() < 10 {
+                Ok(())
+            } else {
+                Err("routine failed")
+            }
+        })
+    });
+    c.bench_function("test_try_iter_ok", |b| b.try_iter(|| "10".parse::<u32>()));
+
+    // The failing benchmark stops iterating as soon as the error is returned.
+    assert_eq!(counter.read(), 10);
+    verify_not_exists(
+        &dir.path().join("test_try_iter_error"),
+        "new/estimates.json",
+    );
+    verify_stats(&dir.path().join("test_try_iter_ok"), "new");
+}
+
 #[test]
 fn test_timing_loops() {
     let dir = temp_dir();
@@ -294,6 +639,17 @@ fn test_timing_loops() {
             .with_function("iter_with_setup", |b| {
                 b.iter_with_setup(|| vec![10], |v| v[0])
             })
+            .with_function("iter_with_fresh_input", |b| {
+                b.iter_with_fresh_input(|| vec![10], |v| v[0])
+            })
+            .with_function("iter_custom_measured", |b: &mut criterion::Bencher| {
+                b.iter_custom_measured(|iters, start, measurement| {
+                    for _ in 0..iters {
+                        criterion::black_box(10);
+                    }
+                    measurement.end(start)
+                })
+            })
             .with_function("iter_with_large_setup", |b| {
                 b.iter_with_large_setup(|| vec![10], |v| v[0])
             })
@@ -351,129 +707,1236 @@ fn test_throughput() {
     );
 }
 
-// Verify that all expected output files are present
 #[test]
-fn test_output_files() {
-    let tempdir = temp_dir();
-    // Run benchmarks twice to produce comparisons
-    for _ in 0..2 {
-        short_benchmark(&tempdir).bench(
-            "test_output",
-            Benchmark::new("output_1", |b| b.iter(|| 10))
-                .with_function("output_2", |b| b.iter(|| 20))
-                .with_function("output_\\/*\"?", |b| b.iter(|| 30))
-                .sampling_mode(SamplingMode::Linear),
-        );
-    }
-
-    // For each benchmark, assert that the expected files are present.
-    for x in 0..3 {
-        let dir = if x == 2 {
-            // Check that certain special characters are replaced with underscores
-            tempdir.path().join("test_output/output______")
-        } else {
-            tempdir.path().join(format!("test_output/output_{}", x + 1))
-        };
-
-        verify_stats(&dir, "new");
-        verify_stats(&dir, "base");
-        verify_json(&dir, "change/estimates.json");
-
-        if short_benchmark(&tempdir).can_plot() {
-            verify_svg(&dir, "report/MAD.svg");
-            verify_svg(&dir, "report/mean.svg");
-            verify_svg(&dir, "report/median.svg");
-            verify_svg(&dir, "report/pdf.svg");
-            verify_svg(&dir, "report/regression.svg");
-            verify_svg(&dir, "report/SD.svg");
-            verify_svg(&dir, "report/slope.svg");
-            verify_svg(&dir, "report/typical.svg");
-            verify_svg(&dir, "report/both/pdf.svg");
-            verify_svg(&dir, "report/both/regression.svg");
-            verify_svg(&dir, "report/change/mean.svg");
-            verify_svg(&dir, "report/change/median.svg");
-            verify_svg(&dir, "report/change/t-test.svg");
-
-            verify_svg(&dir, "report/pdf_small.svg");
-            verify_svg(&dir, "report/regression_small.svg");
-            verify_svg(&dir, "report/relative_pdf_small.svg");
-            verify_svg(&dir, "report/relative_regression_small.svg");
-            verify_html(&dir, "report/index.html");
-        }
+fn test_throughput_fn() {
+    let dir = temp_dir();
+    let mut c = short_benchmark(&dir);
+    let mut group = c.benchmark_group("test_throughput_fn");
+    group.throughput(Throughput::Bytes(1));
+    group.throughput_fn(|size: &u64| Throughput::Elements(*size));
+    for size in [10u64, 100].iter() {
+        group.bench_with_input(BenchmarkId::from_parameter(size), size, |b, s| {
+            b.iter(|| s + 1)
+        });
     }
+    group.bench_function(BenchmarkId::new("unparsed", "large"), |b| b.iter(|| 10));
+    group.finish();
 
-    // Check for overall report files
-    if short_benchmark(&tempdir).can_plot() {
-        let dir = tempdir.path().join("test_output");
+    let throughput = |name: &str| {
+        let path = dir
+            .path()
+            .join(format!("test_throughput_fn/{}/new/benchmark.json", name));
+        let benchmark: Value = serde_json::from_reader(File::open(path).unwrap()).unwrap();
+        benchmark["throughput"].clone()
+    };
+    assert_eq!(throughput("10")["Elements"], 10);
+    assert_eq!(throughput("100")["Elements"], 100);
+    assert_eq!(throughput("unparsed/large")["Bytes"], 1);
+}
 
-        verify_svg(&dir, "report/violin.svg");
-        verify_html(&dir, "report/index.html");
+#[test]
+fn test_relative_throughput() {
+    let dir = temp_dir();
+    let mut c = short_benchmark(&dir);
+    let mut group = c.benchmark_group("test_relative_throughput");
+    for size in [10u64, 100].iter() {
+        group.throughput(Throughput::Bytes(*size));
+        group.bench_with_input(BenchmarkId::new("sum", size), size, |b, s| {
+            b.iter(|| (0..*s).sum::<u64>())
+        });
+        group.bench_with_input(BenchmarkId::new("formula", size), size, |b, s| {
+            b.iter(|| s * (s - 1) / 2)
+        });
     }
+    group.finish();
 
-    // Run the final summary process and check for the report that produces
-    short_benchmark(&tempdir).final_summary();
-    if short_benchmark(&tempdir).can_plot() {
-        let dir = tempdir.path().to_owned();
-
-        verify_html(&dir, "report/index.html");
+    if c.can_plot() {
+        let report_dir = dir.path().join("test_relative_throughput/report");
+        verify_svg(&report_dir, "relative_throughput.svg");
+        let report = std::fs::read_to_string(report_dir.join("index.html")).unwrap();
+        assert!(report.contains("Relative Throughput"));
     }
 }
 
 #[test]
-fn test_output_files_flat_sampling() {
-    let tempdir = temp_dir();
-    // Run benchmark twice to produce comparisons
-    for _ in 0..2 {
-        short_benchmark(&tempdir).bench(
-            "test_output",
-            Benchmark::new("output_flat", |b| b.iter(|| 10)).sampling_mode(SamplingMode::Flat),
-        );
+fn test_working_set() {
+    let dir = temp_dir();
+    let mut c = short_benchmark(&dir);
+    let mut group = c.benchmark_group("test_working_set");
+    for len in [16usize, 1024].iter() {
+        let input = vec![1u64; *len];
+        group.throughput(Throughput::Elements(*len as u64));
+        group.working_set_bytes((*len * 8) as u64);
+        group.bench_with_input(BenchmarkId::from_parameter(len), &input, |b, i| {
+            b.iter(|| i.iter().sum::<u64>())
+        });
     }
+    group.finish();
 
-    let dir = tempdir.path().join("test_output/output_flat");
-
-    verify_stats(&dir, "new");
-    verify_stats(&dir, "base");
-    verify_json(&dir, "change/estimates.json");
-
-    if short_benchmark(&tempdir).can_plot() {
-        verify_svg(&dir, "report/MAD.svg");
-        verify_svg(&dir, "report/mean.svg");
-        verify_svg(&dir, "report/median.svg");
-        verify_svg(&dir, "report/pdf.svg");
-        verify_svg(&dir, "report/iteration_times.svg");
-        verify_svg(&dir, "report/SD.svg");
-        verify_svg(&dir, "report/typical.svg");
-        verify_svg(&dir, "report/both/pdf.svg");
-        verify_svg(&dir, "report/both/iteration_times.svg");
-        verify_svg(&dir, "report/change/mean.svg");
-        verify_svg(&dir, "report/change/median.svg");
-        verify_svg(&dir, "report/change/t-test.svg");
-
-        verify_svg(&dir, "report/pdf_small.svg");
-        verify_svg(&dir, "report/iteration_times_small.svg");
-        verify_svg(&dir, "report/relative_pdf_small.svg");
-        verify_svg(&dir, "report/relative_iteration_times_small.svg");
-        verify_html(&dir, "report/index.html");
+    let benchmark: Value = serde_json::from_reader(
+        File::open(dir.path().join("test_working_set/1024/new/benchmark.json")).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(benchmark["working_set_bytes"], 8192);
+
+    if c.can_plot() {
+        let report_dir = dir.path().join("test_working_set/report");
+        verify_svg(&report_dir, "working_set.svg");
+        let report =
+            std::fs::read_to_string(dir.path().join("test_working_set/1024/report/index.html"))
+                .unwrap();
+        assert!(report.contains("8 KiB"));
     }
 }
 
 #[test]
-#[should_panic(expected = "Benchmark function must call Bencher::iter or related method.")]
-fn test_bench_with_no_iteration_panics() {
+fn test_record_sample_order() {
     let dir = temp_dir();
-    short_benchmark(&dir).bench("test_no_iter", Benchmark::new("no_iter", |_b| {}));
+    short_benchmark(&dir)
+        .record_sample_order(true)
+        .bench_function("test_record_sample_order", |b| b.iter(|| 10));
+
+    let report_dir = dir.path().join("test_record_sample_order/report");
+    verify_svg(&report_dir, "sample_order.svg");
+    let report = std::fs::read_to_string(report_dir.join("index.html")).unwrap();
+    assert!(report.contains("sample_order.svg"));
 }
 
 #[test]
-fn test_benchmark_group_with_input() {
+fn test_sample_order_preserved() {
     let dir = temp_dir();
-    let mut c = short_benchmark(&dir);
-    let mut group = c.benchmark_group("Test Group");
-    for x in 0..2 {
-        group.bench_with_input(BenchmarkId::new("Test 1", x), &x, |b, i| b.iter(|| i));
-        group.bench_with_input(BenchmarkId::new("Test 2", x), &x, |b, i| b.iter(|| i));
-    }
+    // Each sample is slower per iteration than the one before it.
+    let calls = Cell::new(0u64);
+    short_benchmark(&dir)
+        .warm_up(WarmUp::Iterations(1000))
+        .bench_function("test_sample_order_preserved", |b| {
+            b.iter_custom(|iters| {
+                calls.set(calls.get() + 1);
+                Duration::from_nanos(iters * calls.get())
+            })
+        });
+
+    // The saved samples, which the sample order plot is drawn from, are in measurement order.
+    let path = dir
+        .path()
+        .join("test_sample_order_preserved/new/sample.json");
+    let sample: Value = serde_json::from_reader(File::open(path).unwrap()).unwrap();
+    let values = |key: &str| {
+        sample[key]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|value| value.as_f64().unwrap())
+            .collect::<Vec<f64>>()
+    };
+    let avg_times = values("iters")
+        .iter()
+        .zip(values("times"))
+        .map(|(iters, time)| time / iters)
+        .collect::<Vec<f64>>();
+    assert!(avg_times.windows(2).all(|pair| pair[0] < pair[1]));
+}
+
+#[test]
+fn test_benchmark_timeout() {
+    let dir = temp_dir();
+    let mut c = short_benchmark(&dir).benchmark_timeout(Duration::from_millis(50));
+    c.bench_function("test_benchmark_timeout_hung", |b| {
+        b.iter(|| std::thread::sleep(Duration::from_millis(100)))
+    });
+    // The run continues with the next benchmark.
+    c.bench_function("test_benchmark_timeout_fast", |b| b.iter(|| 10));
+
+    assert!(!dir
+        .path()
+        .join("test_benchmark_timeout_hung/new/estimates.json")
+        .exists());
+    assert!(dir
+        .path()
+        .join("test_benchmark_timeout_fast/new/estimates.json")
+        .is_file());
+}
+
+#[test]
+fn test_warm_up_iterations() {
+    let dir = temp_dir();
+    let calls = Rc::new(RefCell::new(vec![]));
+    let calls2 = calls.clone();
+    short_benchmark(&dir)
+        .warm_up(WarmUp::Iterations(1000))
+        .bench_function("test_warm_up_iterations", move |b| {
+            b.iter_custom(|iters| {
+                calls2.borrow_mut().push(iters);
+                Duration::from_micros(iters)
+            })
+        });
+
+    // The iteration count doubles with each warm-up call, stopping at exactly 1000 iterations.
+    let calls = calls.borrow();
+    assert_eq!(calls[..10], [1, 2, 4, 8, 16, 32, 64, 128, 256, 489]);
+}
+
+#[test]
+fn test_report_cv() {
+    let dir = temp_dir();
+    short_benchmark(&dir)
+        .report_cv(true)
+        .bench_function("test_report_cv", |b| b.iter(|| 10));
+    // Every benchmark has some variation, so this threshold is always exceeded.
+    short_benchmark(&dir)
+        .cv_threshold(1e-12)
+        .bench_function("test_report_cv_unstable", |b| b.iter(|| 10));
+
+    let report = dir.path().join("test_report_cv/report/index.html");
+    let report = std::fs::read_to_string(report).unwrap();
+    assert!(report.contains("Coeff. of Variation"));
+    let report = dir.path().join("test_report_cv_unstable/report/index.html");
+    let report = std::fs::read_to_string(report).unwrap();
+    assert!(report.contains("class=\"unstable\""));
+}
+
+#[test]
+fn test_relative_standard_error() {
+    let dir = temp_dir();
+    short_benchmark(&dir).bench_function("test_relative_standard_error", |b| b.iter(|| 10));
+
+    let path = dir
+        .path()
+        .join("test_relative_standard_error/new/estimates.json");
+    let estimates: Value = serde_json::from_reader(File::open(path).unwrap()).unwrap();
+    let mean = &estimates["mean"];
+    let expected =
+        mean["standard_error"].as_f64().unwrap() / mean["point_estimate"].as_f64().unwrap();
+    let relative_se = mean["relative_standard_error"].as_f64().unwrap();
+    assert!((relative_se - expected).abs() < 1e-12);
+}
+
+#[test]
+fn test_estimate_mode() {
+    let dir = temp_dir();
+    short_benchmark(&dir).bench_function("test_estimate_mode_default", |b| b.iter(|| 10));
+    short_benchmark(&dir)
+        .estimate_mode(true)
+        .bench_function("test_estimate_mode", |b| b.iter(|| 10));
+
+    let estimates = |name: &str| -> Value {
+        let path = dir.path().join(name).join("new/estimates.json");
+        serde_json::from_reader(File::open(path).unwrap()).unwrap()
+    };
+    assert!(estimates("test_estimate_mode_default")["mode"].is_null());
+    assert!(estimates("test_estimate_mode")["mode"]["point_estimate"].is_f64());
+}
+
+#[test]
+fn test_estimate_trimmed_mean() {
+    let dir = temp_dir();
+    short_benchmark(&dir).bench_function("test_estimate_trimmed_mean_default", |b| b.iter(|| 10));
+    short_benchmark(&dir)
+        .estimate_trimmed_mean(true)
+        .bench_function("test_estimate_trimmed_mean", |b| b.iter(|| 10));
+
+    let estimates = |name: &str| -> Value {
+        let path = dir.path().join(name).join("new/estimates.json");
+        serde_json::from_reader(File::open(path).unwrap()).unwrap()
+    };
+    assert!(estimates("test_estimate_trimmed_mean_default")["trimmed_mean"].is_null());
+    assert!(estimates("test_estimate_trimmed_mean")["trimmed_mean"]["point_estimate"].is_f64());
+}
+
+#[test]
+fn test_value_transform() {
+    let dir = temp_dir();
+    let mut c = short_benchmark(&dir).warm_up(WarmUp::Iterations(1000));
+    let mut group = c.benchmark_group("test_value_transform");
+    group.value_transform("ns/elem", |ns| ns / 100.0);
+    let mut calls = 0;
+    group.bench_function("sum", |b| {
+        b.iter_custom(|iters| {
+            calls += 1;
+            Duration::from_nanos(iters * (100 + calls % 3))
+        })
+    });
+    group.finish();
+
+    // Each iteration takes 100-102ns, so the transformed values are close to one.
+    let path = dir
+        .path()
+        .join("test_value_transform/sum/new/estimates.json");
+    let estimates: Value = serde_json::from_reader(File::open(path).unwrap()).unwrap();
+    let median = estimates["median"]["point_estimate"].as_f64().unwrap();
+    assert!((1.0..=1.02).contains(&median), "median = {}", median);
+
+    let report = dir
+        .path()
+        .join("test_value_transform/sum/report/index.html");
+    let report = std::fs::read_to_string(report).unwrap();
+    assert!(report.contains("ns/elem"));
+}
+
+#[test]
+fn test_bootstrap_ci() {
+    let values: Vec<f64> = (1..=100).map(f64::from).collect();
+    let sample = stats::Sample::new(&values);
+
+    let (lower, mean, upper) = stats::bootstrap_ci(sample, |s| s.mean(), 1000, 0.95);
+    assert_eq!(mean, 50.5);
+    assert!(lower < mean && mean < upper);
+    assert!(lower > 40.0 && upper < 61.0);
+
+    // Every resample's maximum is at most the maximum of the sample.
+    let (_, max, upper) = stats::bootstrap_ci(sample, |s| s.max(), 1000, 0.95);
+    assert_eq!(max, 100.0);
+    assert!(upper <= max);
+}
+
+#[test]
+fn test_track_best() {
+    let dir = temp_dir();
+    let best_median = || {
+        let path = dir.path().join("test_track_best/best/estimates.json");
+        let estimates: Value = serde_json::from_reader(File::open(path).unwrap()).unwrap();
+        estimates["median"]["point_estimate"].as_f64().unwrap()
+    };
+    let run = |micros: u64| {
+        short_benchmark(&dir)
+            .track_best(true)
+            .bench_function("test_track_best", move |b| {
+                b.iter(|| std::thread::sleep(Duration::from_micros(micros)))
+            });
+    };
+
+    run(500);
+    let slow = best_median();
+    // A faster run becomes the best result, but a slower one doesn't replace it.
+    run(100);
+    let fast = best_median();
+    assert!(fast < slow);
+    run(500);
+    assert_eq!(best_median(), fast);
+}
+
+#[test]
+fn test_deterministic_output_order() {
+    // The names in the saved estimates, in the order they appear in the file.
+    let keys = |path: PathBuf| {
+        let text = std::fs::read_to_string(path).unwrap();
+        text.split('"')
+            .skip(1)
+            .step_by(2)
+            .map(str::to_owned)
+            .collect::<Vec<_>>()
+    };
+    let run = || {
+        let dir = temp_dir();
+        for _ in 0..2 {
+            short_benchmark(&dir)
+                .comparison_statistics(&[
+                    Statistic::Percentile(99),
+                    Statistic::StdDev,
+                    Statistic::Percentile(90),
+                ])
+                .bench_function("test_deterministic_output_order", |b| b.iter(|| 10));
+        }
+        let bench_dir = dir.path().join("test_deterministic_output_order");
+        (
+            keys(bench_dir.join("new/estimates.json")),
+            keys(bench_dir.join("change/estimates.json")),
+        )
+    };
+
+    // Two runs save the statistics in the same order, and the comparison statistics in
+    // alphabetical order.
+    let (estimates, change) = run();
+    assert_eq!((estimates, change.clone()), run());
+    let names = ["mean", "median", "SD", "p90", "p99"];
+    let change_names = change
+        .iter()
+        .filter(|key| names.contains(&key.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(change_names, names);
+}
+
+#[test]
+fn test_iter_stream() {
+    let dir = temp_dir();
+    let items = Counter::default();
+    let counter = items.clone();
+    short_benchmark(&dir).bench_function("test_iter_stream", move |b| {
+        b.iter_stream(|| (0..100).inspect(|_| counter.count()))
+    });
+    assert!(items.read() > 0);
+    assert_eq!(items.read() % 100, 0);
+
+    // The number of items per iteration is reported as the throughput.
+    let report = dir.path().join("test_iter_stream/report/index.html");
+    let report = std::fs::read_to_string(report).unwrap();
+    assert!(report.contains("elem/s"));
+}
+
+#[test]
+fn test_set_throughput_elements() {
+    let dir = temp_dir();
+    short_benchmark(&dir).bench_function("test_set_throughput_elements", |b| {
+        let mut elements = 0;
+        b.iter(|| {
+            let data = criterion::black_box(vec![3u64; 10]);
+            elements += data.len() as u64;
+        });
+        b.set_throughput_elements(elements);
+    });
+
+    // The mean number of elements per iteration is reported as the throughput.
+    let report = dir
+        .path()
+        .join("test_set_throughput_elements/report/index.html");
+    let report = std::fs::read_to_string(report).unwrap();
+    assert!(report.contains("elem/s"));
+}
+
+#[test]
+fn test_monitor_system() {
+    let dir = temp_dir();
+    short_benchmark(&dir)
+        .monitor_system(true)
+        .bench_function("test_monitor_system", |b| b.iter(|| 10));
+
+    let benchmark: Value = serde_json::from_reader(
+        File::open(dir.path().join("test_monitor_system/new/benchmark.json")).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(benchmark["group_id"], "test_monitor_system");
+    assert!(benchmark["system"]["start"].is_object());
+    assert!(benchmark["system"]["end"].is_object());
+    if cfg!(target_os = "linux") {
+        assert!(benchmark["system"]["end"]["load_average"].is_f64());
+        let report = dir.path().join("test_monitor_system/report/index.html");
+        let report = std::fs::read_to_string(report).unwrap();
+        assert!(report.contains("Load Average"));
+    }
+}
+
+#[test]
+fn test_accessible_html() {
+    let dir = temp_dir();
+    short_benchmark(&dir)
+        .accessible_html(true)
+        .bench_function("test_accessible_html", |b| b.iter(|| 10));
+    short_benchmark(&dir).bench_function("test_accessible_html_off", |b| b.iter(|| 10));
+
+    let report = dir.path().join("test_accessible_html/report/index.html");
+    let report = std::fs::read_to_string(report).unwrap();
+    assert!(report.contains("<figcaption id=\"pdf-caption\">"));
+    assert!(report.contains("aria-describedby=\"samples-caption\""));
+    assert!(report.contains("<summary>Data table</summary>"));
+    let report = dir
+        .path()
+        .join("test_accessible_html_off/report/index.html");
+    let report = std::fs::read_to_string(report).unwrap();
+    assert!(!report.contains("<figcaption"));
+}
+
+#[test]
+fn test_filtered_run_keeps_summary_complete() {
+    let dir = temp_dir();
+    let mut c = short_benchmark(&dir);
+    let mut group = c.benchmark_group("test_filtered_summary");
+    group.bench_function("a", |b| b.iter(|| 10));
+    group.bench_function("b", |b| b.iter(|| 20));
+    group.finish();
+
+    // "b" isn't registered by this run, but its saved results stay in the group summary.
+    let mut c = short_benchmark(&dir).with_filter("test_filtered_summary/a");
+    let mut group = c.benchmark_group("test_filtered_summary");
+    group.bench_function("a", |b| b.iter(|| 10));
+    group.bench_function("c", |b| b.iter(|| 30));
+    group.finish();
+
+    let report = dir.path().join("test_filtered_summary/report/index.html");
+    let report = std::fs::read_to_string(report).unwrap();
+    assert!(report.contains("test_filtered_summary/a"));
+    assert!(report.contains("test_filtered_summary/b"));
+    assert!(!report.contains("test_filtered_summary/c"));
+}
+
+#[test]
+fn test_theil_sen_regression() {
+    let dir = temp_dir();
+    let mut c = short_benchmark(&dir).regression_method(RegressionMethod::TheilSen);
+    let mut group = c.benchmark_group("test_theil_sen_regression");
+    group.sampling_mode(SamplingMode::Linear);
+    group.bench_function("theil_sen", |b| b.iter(|| 10));
+    group.finish();
+
+    let estimates = dir
+        .path()
+        .join("test_theil_sen_regression/theil_sen/new/estimates.json");
+    let estimates = std::fs::read_to_string(estimates).unwrap();
+    assert!(estimates.contains("\"slope\""));
+    let report = dir
+        .path()
+        .join("test_theil_sen_regression/theil_sen/report/index.html");
+    let report = std::fs::read_to_string(report).unwrap();
+    assert!(report.contains("Slope (Theil-Sen)"));
+}
+
+// Verify that all expected output files are present
+#[test]
+fn test_output_files() {
+    let tempdir = temp_dir();
+    // Run benchmarks twice to produce comparisons
+    for _ in 0..2 {
+        short_benchmark(&tempdir).bench(
+            "test_output",
+            Benchmark::new("output_1", |b| b.iter(|| 10))
+                .with_function("output_2", |b| b.iter(|| 20))
+                .with_function("output_\\/*\"?", |b| b.iter(|| 30))
+                .sampling_mode(SamplingMode::Linear),
+        );
+    }
+
+    // For each benchmark, assert that the expected files are present.
+    for x in 0..3 {
+        let dir = if x == 2 {
+            // Check that certain special characters are replaced with underscores
+            tempdir.path().join("test_output/output______")
+        } else {
+            tempdir.path().join(format!("test_output/output_{}", x + 1))
+        };
+
+        verify_stats(&dir, "new");
+        verify_stats(&dir, "base");
+        verify_json(&dir, "change/estimates.json");
+
+        if short_benchmark(&tempdir).can_plot() {
+            verify_svg(&dir, "report/MAD.svg");
+            verify_svg(&dir, "report/mean.svg");
+            verify_svg(&dir, "report/median.svg");
+            verify_svg(&dir, "report/pdf.svg");
+            verify_svg(&dir, "report/regression.svg");
+            verify_svg(&dir, "report/SD.svg");
+            verify_svg(&dir, "report/slope.svg");
+            verify_svg(&dir, "report/typical.svg");
+            verify_svg(&dir, "report/both/pdf.svg");
+            verify_svg(&dir, "report/both/regression.svg");
+            verify_svg(&dir, "report/change/mean.svg");
+            verify_svg(&dir, "report/change/median.svg");
+            verify_svg(&dir, "report/change/t-test.svg");
+
+            verify_svg(&dir, "report/pdf_small.svg");
+            verify_svg(&dir, "report/regression_small.svg");
+            verify_svg(&dir, "report/relative_pdf_small.svg");
+            verify_svg(&dir, "report/relative_regression_small.svg");
+            verify_html(&dir, "report/index.html");
+        }
+    }
+
+    // Check for overall report files
+    if short_benchmark(&tempdir).can_plot() {
+        let dir = tempdir.path().join("test_output");
+
+        verify_svg(&dir, "report/violin.svg");
+        verify_html(&dir, "report/index.html");
+    }
+
+    // Run the final summary process and check for the report that produces
+    short_benchmark(&tempdir).final_summary();
+    if short_benchmark(&tempdir).can_plot() {
+        let dir = tempdir.path().to_owned();
+
+        verify_html(&dir, "report/index.html");
+    }
+}
+
+#[test]
+fn test_output_files_flat_sampling() {
+    let tempdir = temp_dir();
+    // Run benchmark twice to produce comparisons
+    for _ in 0..2 {
+        short_benchmark(&tempdir).bench(
+            "test_output",
+            Benchmark::new("output_flat", |b| b.iter(|| 10)).sampling_mode(SamplingMode::Flat),
+        );
+    }
+
+    let dir = tempdir.path().join("test_output/output_flat");
+
+    verify_stats(&dir, "new");
+    verify_stats(&dir, "base");
+    verify_json(&dir, "change/estimates.json");
+
+    if short_benchmark(&tempdir).can_plot() {
+        verify_svg(&dir, "report/MAD.svg");
+        verify_svg(&dir, "report/mean.svg");
+        verify_svg(&dir, "report/median.svg");
+        verify_svg(&dir, "report/pdf.svg");
+        verify_svg(&dir, "report/iteration_times.svg");
+        verify_svg(&dir, "report/SD.svg");
+        verify_svg(&dir, "report/typical.svg");
+        verify_svg(&dir, "report/both/pdf.svg");
+        verify_svg(&dir, "report/both/iteration_times.svg");
+        verify_svg(&dir, "report/change/mean.svg");
+        verify_svg(&dir, "report/change/median.svg");
+        verify_svg(&dir, "report/change/t-test.svg");
+
+        verify_svg(&dir, "report/pdf_small.svg");
+        verify_svg(&dir, "report/iteration_times_small.svg");
+        verify_svg(&dir, "report/relative_pdf_small.svg");
+        verify_svg(&dir, "report/relative_iteration_times_small.svg");
+        verify_html(&dir, "report/index.html");
+    }
+}
+
+#[test]
+fn test_logarithmic_regression_plot() {
+    let tempdir = temp_dir();
+    // The second run also draws the comparison with the first.
+    for _ in 0..2 {
+        short_benchmark(&tempdir)
+            .regression_y_scale(AxisScale::Logarithmic)
+            .bench_function("test_log_regression", |b| b.iter(|| 10));
+    }
+
+    let dir = tempdir.path().join("test_log_regression");
+    verify_svg(&dir, "report/regression.svg");
+    verify_svg(&dir, "report/regression_small.svg");
+    verify_svg(&dir, "report/both/regression.svg");
+    verify_svg(&dir, "report/relative_regression_small.svg");
+}
+
+#[test]
+fn test_plot_set() {
+    let tempdir = temp_dir();
+    for _ in 0..2 {
+        short_benchmark(&tempdir)
+            .plots(PlotSet::T_TEST)
+            .bench_function("test_plot_set", |b| b.iter(|| 10));
+    }
+
+    let dir = tempdir.path().join("test_plot_set");
+    verify_svg(&dir, "report/pdf.svg");
+    verify_svg(&dir, "report/regression.svg");
+    verify_svg(&dir, "report/change/t-test.svg");
+    verify_not_exists(&dir, "report/mean.svg");
+    verify_not_exists(&dir, "report/typical.svg");
+    verify_not_exists(&dir, "report/change/mean.svg");
+}
+
+#[test]
+fn test_save_distributions() {
+    let tempdir = temp_dir();
+    for _ in 0..2 {
+        short_benchmark(&tempdir).bench_function("test_save_distributions", |b| b.iter(|| 10));
+    }
+    let dir = tempdir.path().join("test_save_distributions");
+    verify_not_exists(&dir, "change/distributions.json");
+
+    short_benchmark(&tempdir)
+        .save_distributions(true)
+        .bench_function("test_save_distributions", |b| b.iter(|| 10));
+    let path = verify_file(&dir, "change/distributions.json");
+    let json: Value = serde_json::from_reader(File::open(path).unwrap()).unwrap();
+    assert!(json["t_statistic"].is_number());
+    assert!(!json["t_distribution"].as_array().unwrap().is_empty());
+    assert_eq!(json["mean"].as_array().unwrap().len(), 2000);
+    assert_eq!(json["median"].as_array().unwrap().len(), 2000);
+}
+
+#[test]
+fn test_nresamples_compare() {
+    let tempdir = temp_dir();
+    short_benchmark(&tempdir).bench_function("test_nresamples_compare", |b| b.iter(|| 10));
+    short_benchmark(&tempdir)
+        .nresamples_compare(1500)
+        .save_distributions(true)
+        .bench_function("test_nresamples_compare", |b| b.iter(|| 10));
+
+    let dir = tempdir.path().join("test_nresamples_compare");
+    let path = verify_file(&dir, "change/distributions.json");
+    let json: Value = serde_json::from_reader(File::open(path).unwrap()).unwrap();
+    assert_eq!(json["mean"].as_array().unwrap().len(), 1500);
+    assert_eq!(json["median"].as_array().unwrap().len(), 1500);
+}
+
+#[test]
+fn test_compress_samples() {
+    let tempdir = temp_dir();
+    for _ in 0..2 {
+        short_benchmark(&tempdir)
+            .compress_samples(true)
+            .bench_function("test_compress_samples", |b| b.iter(|| 10));
+    }
+    let dir = tempdir.path().join("test_compress_samples");
+    verify_file(&dir, "new/sample.json.gz");
+    verify_file(&dir, "base/sample.json.gz");
+    verify_not_exists(&dir, "new/sample.json");
+    verify_json(&dir, "change/estimates.json");
+
+    // Switching back reads the compressed baseline and replaces the compressed sample.
+    short_benchmark(&tempdir).bench_function("test_compress_samples", |b| b.iter(|| 10));
+    verify_json(&dir, "new/sample.json");
+    verify_not_exists(&dir, "new/sample.json.gz");
+}
+
+#[test]
+fn test_binary_sample_format() {
+    let tempdir = temp_dir();
+    for _ in 0..2 {
+        short_benchmark(&tempdir)
+            .sample_format(SampleFormat::Binary)
+            .bench_function("test_binary_sample_format", |b| b.iter(|| 10));
+    }
+    let dir = tempdir.path().join("test_binary_sample_format");
+    verify_file(&dir, "new/sample.bin");
+    verify_file(&dir, "base/sample.bin");
+    verify_not_exists(&dir, "new/sample.json");
+    verify_json(&dir, "change/estimates.json");
+
+    // Switching back reads the binary baseline and replaces the binary sample.
+    short_benchmark(&tempdir).bench_function("test_binary_sample_format", |b| b.iter(|| 10));
+    verify_json(&dir, "new/sample.json");
+    verify_not_exists(&dir, "new/sample.bin");
+}
+
+#[test]
+fn test_background_analysis() {
+    let dir = temp_dir();
+    for _ in 0..2 {
+        let mut c = short_benchmark(&dir).background_analysis(true);
+        let mut group = c.benchmark_group("test_background_analysis");
+        for i in 0..3u64 {
+            group.bench_with_input(BenchmarkId::from_parameter(i), &i, |b, i| b.iter(|| i + 1));
+        }
+        group.finish();
+    }
+
+    // Every benchmark, including the last one of the group, was analyzed and compared.
+    for i in 0..3 {
+        let bench_dir = dir.path().join(format!("test_background_analysis/{}", i));
+        verify_stats(&bench_dir, "new");
+        verify_json(&bench_dir, "change/estimates.json");
+    }
+    verify_html(
+        &dir.path().join("test_background_analysis"),
+        "report/index.html",
+    );
+}
+
+#[test]
+fn test_sample_collector() {
+    let dir = temp_dir();
+    let mut c = short_benchmark(&dir).sample_size(10);
+    let mut group = c.benchmark_group("test_sample_collector");
+    let mut between = 0;
+    let mut samples = vec![];
+    for sample in group.sample_collector("collected", |b| b.iter(|| 10)) {
+        between += 1;
+        samples.push(sample.unwrap());
+    }
+    assert_eq!(between, 10);
+    // Linear sampling by default: every sample runs more iterations than the previous one.
+    assert!(samples.windows(2).all(|w| w[0].0 < w[1].0));
+
+    group.analyze("collected", &samples);
+    group.finish();
+
+    verify_stats(&dir.path().join("test_sample_collector/collected"), "new");
+}
+
+#[test]
+fn test_report_cold_start() {
+    let dir = temp_dir();
+    let mut cold = true;
+    short_benchmark(&dir)
+        .report_cold_start(true)
+        .bench_function("test_report_cold_start", |b| {
+            b.iter(|| {
+                if cold {
+                    cold = false;
+                    std::thread::sleep(Duration::from_millis(20));
+                }
+            })
+        });
+
+    // Only the first iteration is slow, so it doesn't show in the steady-state statistics.
+    let bench_dir = dir.path().join("test_report_cold_start");
+    let estimates: Value =
+        serde_json::from_reader(File::open(bench_dir.join("new/estimates.json")).unwrap()).unwrap();
+    assert!(estimates["median"]["point_estimate"].as_f64().unwrap() < 1_000_000.0);
+    let report = std::fs::read_to_string(bench_dir.join("report/index.html")).unwrap();
+    assert!(report.contains("Cold Start"));
+}
+
+#[test]
+fn test_analyze_external_samples() {
+    let dir = temp_dir();
+    // About 15ns per iteration, as if measured by an external device.
+    let samples = (1..=20u64)
+        .map(|i| (i * 100, (i * 100) as f64 * 15.0 + (i % 3) as f64))
+        .collect::<Vec<_>>();
+    short_benchmark(&dir)
+        .subtract_timer_overhead(true)
+        .analyze("test_analyze_external_samples", &samples);
+
+    // The samples are analyzed as they are, without subtracting the timer overhead.
+    let bench_dir = dir.path().join("test_analyze_external_samples");
+    verify_stats(&bench_dir, "new");
+    let estimates: Value =
+        serde_json::from_reader(File::open(bench_dir.join("new/estimates.json")).unwrap()).unwrap();
+    let slope = estimates["slope"]["point_estimate"].as_f64().unwrap();
+    assert!((15.0..15.01).contains(&slope), "slope: {}", slope);
+}
+
+#[test]
+fn test_analyze_invalid_samples() {
+    let dir = temp_dir();
+    let mut c = short_benchmark(&dir);
+    let mut group = c.benchmark_group("test_analyze_invalid_samples");
+    group.analyze("zero_iterations", &[(0, 10.0), (1, 10.0), (2, 20.0)]);
+    group.analyze("nan", &[(1, 10.0), (2, f64::NAN), (3, 30.0)]);
+    group.analyze("infinite", &[(1, 10.0), (2, f64::INFINITY), (3, 30.0)]);
+    group.finish();
+
+    let group_dir = dir.path().join("test_analyze_invalid_samples");
+    for name in &["zero_iterations", "nan", "infinite"] {
+        verify_not_exists(&group_dir.join(name), "new");
+    }
+}
+
+#[test]
+fn test_merge_reports() {
+    let dir = temp_dir();
+    // A benchmark-complete message of the JSON output, with about 10ns per iteration.
+    let message = |function: &str, value: &str| {
+        let iteration_count = (1..=10).map(|i| i * 100).collect::<Vec<u64>>();
+        let measured_values = iteration_count
+            .iter()
+            .map(|&iters| iters as f64 * 10.0 + (iters % 3) as f64)
+            .collect::<Vec<f64>>();
+        serde_json::json!({
+            "reason": "benchmark-complete",
+            "id": format!("test_merge_reports/{}/{}", function, value),
+            "group_id": "test_merge_reports",
+            "function_id": function,
+            "value_str": value,
+            "throughput": null,
+            "unit": "ns",
+            "iteration_count": iteration_count,
+            "measured_values": measured_values,
+        })
+        .to_string()
+    };
+    let shard = |name: &str, lines: &[String]| {
+        let path = dir.path().join(name);
+        std::fs::write(&path, lines.join("\n")).unwrap();
+        path
+    };
+    let shards = [
+        shard(
+            "shard-1.json",
+            &[
+                "Benchmarking...".to_owned(),
+                message("a", "1"),
+                message("a", "2"),
+            ],
+        ),
+        // The first result of a benchmark run by two shards is kept.
+        shard("shard-2.json", &[message("b", "1"), message("a", "1")]),
+    ];
+    short_benchmark(&dir).merge_reports(&shards);
+
+    let group_dir = dir.path().join("test_merge_reports");
+    for benchmark in &["a/1", "a/2", "b/1"] {
+        verify_stats(&group_dir.join(benchmark), "new");
+    }
+    // The summary covers the benchmarks of both shards.
+    let summary = std::fs::read_to_string(group_dir.join("report/index.html")).unwrap();
+    assert!(summary.contains("a/1") && summary.contains("b/1"));
+}
+
+#[test]
+fn test_winsorize() {
+    let dir = temp_dir();
+    // 15ns per iteration, except for one sample which is a thousand times slower.
+    let samples = (1..=20u64)
+        .map(|i| {
+            let per_iter = if i == 20 { 15_000.0 } else { 15.0 };
+            (i * 100, (i * 100) as f64 * per_iter)
+        })
+        .collect::<Vec<_>>();
+    short_benchmark(&dir)
+        .winsorize(0.0, 90.0)
+        .analyze("test_winsorize", &samples);
+
+    // The outlier is clamped to the 90th percentile, but the sample keeps its size.
+    let bench_dir = dir.path().join("test_winsorize");
+    let sample: Value =
+        serde_json::from_reader(File::open(bench_dir.join("new/sample.json")).unwrap()).unwrap();
+    assert_eq!(sample["times"].as_array().unwrap().len(), 20);
+    let estimates: Value =
+        serde_json::from_reader(File::open(bench_dir.join("new/estimates.json")).unwrap()).unwrap();
+    let mean = estimates["mean"]["point_estimate"].as_f64().unwrap();
+    assert!((15.0..15.01).contains(&mean), "mean: {}", mean);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_bench_program() {
+    // Follows the protocol of bench_program: reports about 10ns per iteration.
+    let mut command = std::process::Command::new("sh");
+    command
+        .arg("-c")
+        .arg("while read n; do echo $((n * 10 + n % 7)); done");
+
+    let dir = temp_dir();
+    short_benchmark(&dir).bench_program("test_bench_program", command);
+
+    let bench_dir = dir.path().join("test_bench_program");
+    verify_stats(&bench_dir, "new");
+    let estimates: Value =
+        serde_json::from_reader(File::open(bench_dir.join("new/estimates.json")).unwrap()).unwrap();
+    let mean = estimates["mean"]["point_estimate"].as_f64().unwrap();
+    assert!((10.0..11.0).contains(&mean), "mean: {}", mean);
+}
+
+#[test]
+fn test_comparison_statistics() {
+    let dir = temp_dir();
+    for _ in 0..2 {
+        short_benchmark(&dir)
+            .comparison_statistics(&[Statistic::Percentile(99), Statistic::StdDev])
+            .bench_function("test_comparison_statistics", |b| b.iter(|| 10));
+    }
+
+    let bench_dir = dir.path().join("test_comparison_statistics");
+    let estimates: Value =
+        serde_json::from_reader(File::open(bench_dir.join("change/estimates.json")).unwrap())
+            .unwrap();
+    for statistic in &["mean", "median", "p99", "SD"] {
+        assert!(
+            estimates[statistic]["point_estimate"].is_number(),
+            "{} is missing",
+            statistic
+        );
+    }
+    verify_svg(&bench_dir, "report/change/p99.svg");
+    let report = std::fs::read_to_string(bench_dir.join("report/index.html")).unwrap();
+    assert!(report.contains("Change in p99"));
+}
+
+#[test]
+fn test_high_res_wall_time() {
+    let dir = temp_dir();
+    Criterion::default()
+        .output_directory(dir.path())
+        .warm_up_time(Duration::from_millis(250))
+        .measurement_time(Duration::from_millis(500))
+        .nresamples(2000)
+        .with_measurement(HighResWallTime)
+        .bench_function("test_high_res_wall_time", |b| {
+            b.iter(|| std::thread::sleep(Duration::from_micros(10)))
+        });
+
+    let dir = dir.path().join("test_high_res_wall_time");
+    verify_stats(&dir, "new");
+    let estimates: serde_json::Value =
+        serde_json::from_reader(File::open(dir.join("new/estimates.json")).unwrap()).unwrap();
+    assert!(estimates["mean"]["point_estimate"].as_f64().unwrap() >= 10_000.0);
+}
+
+// Measures wall-clock time, but labels the values as ticks.
+struct Ticks;
+impl ValueFormatter for Ticks {
+    fn scale_values(&self, _typical: f64, _values: &mut [f64]) -> &'static str {
+        "ticks"
+    }
+    fn scale_throughputs(&self, _: f64, _: &Throughput, _: &mut [f64]) -> &'static str {
+        "ticks/elem"
+    }
+    fn scale_for_machines(&self, _values: &mut [f64]) -> &'static str {
+        "ticks"
+    }
+}
+impl Measurement for Ticks {
+    type Intermediate = std::time::Instant;
+    type Value = Duration;
+
+    fn start(&self) -> Self::Intermediate {
+        std::time::Instant::now()
+    }
+    fn end(&self, i: Self::Intermediate) -> Self::Value {
+        i.elapsed()
+    }
+    fn add(&self, v1: &Self::Value, v2: &Self::Value) -> Self::Value {
+        *v1 + *v2
+    }
+    fn zero(&self) -> Self::Value {
+        Duration::from_secs(0)
+    }
+    fn to_f64(&self, val: &Self::Value) -> f64 {
+        val.as_nanos() as f64
+    }
+    fn formatter(&self) -> &dyn ValueFormatter {
+        self
+    }
+}
+
+// Counts events which can be undone, so that the net count can be negative.
+struct NetEvents;
+impl ValueFormatter for NetEvents {
+    fn scale_values(&self, _typical: f64, _values: &mut [f64]) -> &'static str {
+        "events"
+    }
+    fn scale_throughputs(&self, _: f64, _: &Throughput, _: &mut [f64]) -> &'static str {
+        "events/elem"
+    }
+    fn scale_for_machines(&self, _values: &mut [f64]) -> &'static str {
+        "events"
+    }
+}
+impl Measurement for NetEvents {
+    type Intermediate = ();
+    type Value = f64;
+
+    fn start(&self) -> Self::Intermediate {}
+    fn end(&self, _i: Self::Intermediate) -> Self::Value {
+        0.0
+    }
+    fn add(&self, v1: &Self::Value, v2: &Self::Value) -> Self::Value {
+        *v1 + *v2
+    }
+    fn zero(&self) -> Self::Value {
+        0.0
+    }
+    fn to_f64(&self, val: &Self::Value) -> f64 {
+        *val
+    }
+    fn formatter(&self) -> &dyn ValueFormatter {
+        self
+    }
+}
+
+#[test]
+fn test_signed_measurement() {
+    let dir = temp_dir();
+    let mut c = short_benchmark(&dir)
+        .warm_up(WarmUp::Iterations(1000))
+        .with_measurement(SignedMeasurement(NetEvents));
+    let mut group = c.benchmark_group("test_signed_measurement");
+    group.throughput(Throughput::Elements(1));
+    let mut round = 0;
+    group.bench_function("net_events", |b| {
+        b.iter_custom(|iters| {
+            round += 1;
+            let count = (0..iters).map(criterion::black_box).count();
+            -(count as f64) * (2.0 + (round % 7) as f64 * 0.1)
+        })
+    });
+    group.finish();
+
+    let dir = dir.path().join("test_signed_measurement/net_events");
+    verify_stats(&dir, "new");
+    verify_svg(&dir, "report/pdf.svg");
+    let estimates: Value =
+        serde_json::from_reader(File::open(dir.join("new/estimates.json")).unwrap()).unwrap();
+    assert!(estimates["mean"]["point_estimate"].as_f64().unwrap() < 0.0);
+    let benchmark: Value =
+        serde_json::from_reader(File::open(dir.join("new/benchmark.json")).unwrap()).unwrap();
+    assert!(benchmark["id"]["throughput"].is_null());
+}
+
+#[test]
+fn test_multi_measurement() {
+    let dir = temp_dir();
+    let mut c =
+        short_benchmark(&dir).with_measurement(MultiMeasurement::new(WallTime, Ticks, "ticks"));
+    let mut group = c.benchmark_group("test_multi_measurement");
+    group.bench_function("sum", |b| {
+        b.iter(|| (0..100u64).map(criterion::black_box).sum::<u64>())
+    });
+    group.finish();
+
+    // The ticks are reported as a benchmark of their own.
+    let dir = dir.path().join("test_multi_measurement");
+    verify_stats(&dir.join("sum"), "new");
+    verify_stats(&dir.join("sum_ticks"), "new");
+    let report = std::fs::read_to_string(dir.join("sum_ticks/report/index.html")).unwrap();
+    assert!(report.contains("ticks"));
+}
+
+#[test]
+fn test_group_measurement() {
+    let dir = temp_dir();
+    let mut c = short_benchmark(&dir).with_measurement(BoxedMeasurement::new(WallTime));
+    let mut group = c.benchmark_group("test_group_measurement");
+    group.measurement(BoxedMeasurement::new(Ticks));
+    group.bench_function("ticks", |b| b.iter(|| 10));
+    group.finish();
+    c.bench_function("test_group_measurement_wall_time", |b| b.iter(|| 10));
+
+    let unit = |path: PathBuf| {
+        let mut reader = csv::Reader::from_path(path).unwrap();
+        let record = reader.records().next().unwrap().unwrap();
+        let unit_column = reader.headers().unwrap().iter().position(|h| h == "unit");
+        record[unit_column.unwrap()].to_owned()
+    };
+    let group_dir = dir.path().join("test_group_measurement/ticks");
+    verify_stats(&group_dir, "new");
+    assert_eq!(unit(group_dir.join("new/raw.csv")), "ticks");
+    let wall_time_dir = dir.path().join("test_group_measurement_wall_time");
+    assert_eq!(unit(wall_time_dir.join("new/raw.csv")), "ns");
+}
+
+#[test]
+fn test_measurement_conversion() {
+    let dir = temp_dir();
+    let bench_dir = dir.path().join("test_measurement_conversion");
+
+    short_benchmark(&dir).bench_function("test_measurement_conversion", |b| b.iter(|| 10));
+    let benchmark: Value =
+        serde_json::from_reader(File::open(bench_dir.join("new/benchmark.json")).unwrap()).unwrap();
+    assert!(benchmark["measurement"]
+        .as_str()
+        .unwrap()
+        .ends_with("WallTime"));
+
+    // A baseline recorded with a different measurement isn't compared against...
+    short_benchmark(&dir)
+        .with_measurement(Ticks)
+        .bench_function("test_measurement_conversion", |b| b.iter(|| 10));
+    verify_not_exists(&bench_dir, "change");
+
+    // ...unless there is a conversion factor.
+    short_benchmark(&dir)
+        .measurement_conversion(1.0)
+        .bench_function("test_measurement_conversion", |b| b.iter(|| 10));
+    verify_json(&bench_dir, "change/estimates.json");
+}
+
+#[test]
+fn test_fixed_time_unit() {
+    let tempdir = temp_dir();
+    short_benchmark(&tempdir)
+        .time_unit(Some(TimeUnit::Seconds))
+        .bench_function("test_fixed_time_unit", |b| b.iter(|| 10));
+
+    let report = std::fs::read_to_string(
+        tempdir
+            .path()
+            .join("test_fixed_time_unit/report/index.html"),
+    )
+    .unwrap();
+    assert!(report.contains(" s<"));
+    assert!(!report.contains(" ps<") && !report.contains(" ns<"));
+    // The times are too small to show in seconds with four decimal places, so they keep their
+    // significant digits in scientific notation.
+    for (end, _) in report.match_indices(" s<") {
+        let value = report[..end].rsplit(&['>', ' '][..]).next().unwrap();
+        assert!(
+            value.contains("e-"),
+            "{} s has no significant digits",
+            value
+        );
+    }
+}
+
+#[test]
+fn test_time_precision() {
+    let tempdir = temp_dir();
+    short_benchmark(&tempdir)
+        .time_precision(3)
+        .bench_function("test_time_precision", |b| b.iter(|| 10));
+
+    let report =
+        std::fs::read_to_string(tempdir.path().join("test_time_precision/new/report.txt")).unwrap();
+    // Every value in the table has exactly three decimal places.
+    for line in report.lines().skip(3) {
+        for value in line[15..].split_whitespace().step_by(2) {
+            let decimals = value.split('.').nth(1).unwrap();
+            assert_eq!(decimals.len(), 3, "{}", line);
+        }
+    }
+}
+
+#[test]
+#[should_panic]
+fn test_time_precision_out_of_range() {
+    Criterion::default().time_precision(10);
+}
+
+#[test]
+fn test_plot_size() {
+    let tempdir = temp_dir();
+    short_benchmark(&tempdir)
+        .plot_size(1920, 1080)
+        .plot_font_size(16.0)
+        .bench_function("test_plot_size", |b| b.iter(|| 10));
+
+    let dir = tempdir.path().join("test_plot_size");
+    let read_svg = |path: &str| std::fs::read_to_string(dir.join(path)).unwrap();
+
+    assert!(read_svg("report/pdf.svg").contains(r#"width="1920""#));
+    // Thumbnails are scaled up from the default 450x300 by the same factor as the plots.
+    assert!(read_svg("report/pdf_small.svg").contains(r#"width="675""#));
+    assert!(read_svg("report/index.html").contains(r#"width="675""#));
+}
+
+#[test]
+fn test_plot_size_summary() {
+    let tempdir = temp_dir();
+    let mut c = short_benchmark(&tempdir).plot_size(1920, 1080);
+    let mut group = c.benchmark_group("test_plot_size_summary");
+    for i in [10, 20].iter() {
+        group.bench_with_input(BenchmarkId::from_parameter(i), i, |b, i| b.iter(|| i));
+    }
+    group.finish();
+
+    let report = tempdir.path().join("test_plot_size_summary/report");
+    let read_svg = |path: &str| std::fs::read_to_string(report.join(path)).unwrap();
+    assert!(read_svg("lines.svg").contains(r#"width="1920" height="1080""#));
+    // The height of the violin plot depends on the number of benchmarks.
+    assert!(read_svg("violin.svg").contains(r#"width="1920""#));
+}
+
+#[test]
+fn test_plot_terminal_size() {
+    let tempdir = temp_dir();
+    short_benchmark(&tempdir)
+        .plot_terminal(PlotTerminalConfig::default().dpi(300).size(1600, 900))
+        .bench_function("test_plot_terminal_size", |b| b.iter(|| 10));
+
+    let report = tempdir.path().join("test_plot_terminal_size/report");
+    let pdf = std::fs::read_to_string(report.join("pdf.svg")).unwrap();
+    assert!(pdf.contains(r#"width="1600""#));
+}
+
+#[test]
+#[should_panic]
+fn test_plot_terminal_zero_dpi_panics() {
+    PlotTerminalConfig::default().dpi(0);
+}
+
+#[test]
+#[should_panic]
+fn test_empty_custom_palette_panics() {
+    Criterion::default().plot_palette(Palette::Custom(vec![]));
+}
+
+#[test]
+#[should_panic(expected = "Benchmark function must call Bencher::iter or related method.")]
+fn test_bench_with_no_iteration_panics() {
+    let dir = temp_dir();
+    short_benchmark(&dir).bench("test_no_iter", Benchmark::new("no_iter", |_b| {}));
+}
+
+#[test]
+fn test_benchmark_group_with_input() {
+    let dir = temp_dir();
+    let mut c = short_benchmark(&dir);
+    let mut group = c.benchmark_group("Test Group");
+    for x in 0..2 {
+        group.bench_with_input(BenchmarkId::new("Test 1", x), &x, |b, i| b.iter(|| i));
+        group.bench_with_input(BenchmarkId::new("Test 2", x), &x, |b, i| b.iter(|| i));
+    }
     group.finish();
 }
 
@@ -487,6 +1950,57 @@ fn test_benchmark_group_without_input() {
     group.finish();
 }
 
+#[test]
+fn test_benchmark_group_setup_and_teardown() {
+    let dir = temp_dir();
+    let setups = Cell::new(0);
+    let teardowns = RefCell::new(vec![]);
+    {
+        let mut c = short_benchmark(&dir);
+        let mut group = c.benchmark_group("test_setup_and_teardown");
+        group.setup(|| {
+            setups.set(setups.get() + 1);
+            Cell::new(0u64)
+        });
+        group.teardown(|calls: Cell<u64>| teardowns.borrow_mut().push(calls.get()));
+        group.bench_function("Test 1", |b| {
+            let calls = b.setup_value::<Cell<u64>>();
+            b.iter(|| calls.set(calls.get() + 1))
+        });
+        group.bench_function("Test 2", |b| b.iter(|| 10));
+        group.finish();
+    }
+
+    // The setup value is created once per benchmark and shared by all of its samples.
+    assert_eq!(setups.get(), 2);
+    let teardowns = teardowns.into_inner();
+    assert_eq!(teardowns.len(), 2);
+    assert!(teardowns[0] > 10);
+    assert_eq!(teardowns[1], 0);
+}
+
+#[test]
+fn test_benchmark_group_teardown_runs_on_panic() {
+    let dir = temp_dir();
+    let torn_down = Cell::new(false);
+    let mut c = short_benchmark(&dir);
+    let mut group = c.benchmark_group("test_teardown_on_panic");
+    group.setup(|| 10u64);
+    group.teardown(|_: u64| torn_down.set(true));
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        group.bench_function("Test", |b| {
+            let value = b.setup_value::<u64>();
+            b.iter(|| {
+                if *value == 10 {
+                    panic!("benchmark failed");
+                }
+            })
+        });
+    }));
+    assert!(result.is_err());
+    assert!(torn_down.get());
+}
+
 #[test]
 fn test_criterion_doesnt_panic_if_measured_time_is_zero() {
     let dir = temp_dir();
@@ -592,3 +2106,98 @@ fn test_profiler_called() {
     assert_eq!(1, started.get());
     assert_eq!(1, stopped.get());
 }
+
+#[cfg(all(feature = "flamegraph", unix))]
+#[test]
+fn test_flamegraph_profiler() {
+    let dir = temp_dir();
+    let mut criterion = short_benchmark(&dir)
+        .with_profiler(criterion::profiler::FlamegraphProfiler::new(1000))
+        .profile_time(Some(Duration::from_secs(1)));
+    criterion.bench_function("test_flamegraph_profiler", |b| {
+        b.iter(|| (0..1000u64).map(criterion::black_box).sum::<u64>())
+    });
+
+    verify_svg(
+        &dir.path().join("test_flamegraph_profiler"),
+        "profile/flamegraph.svg",
+    );
+}
+
+// The outcome covers every benchmark run by the test process, so only count the failures of the
+// given test's benchmarks.
+fn failed_assertions(dir: &TempDir, prefix: &str) -> Vec<String> {
+    let outcome = short_benchmark(dir).run();
+    assert!(!outcome.passed());
+    outcome
+        .failed_assertions
+        .into_iter()
+        .filter(|failure| failure.contains(prefix))
+        .collect()
+}
+
+#[test]
+fn test_assert_max() {
+    let dir = temp_dir();
+    let mut c = short_benchmark(&dir)
+        .without_plots()
+        .assert_max("^assert_max_slow$", Duration::from_micros(1))
+        .assert_max("^assert_max_fast$", Duration::from_secs(1));
+    let sleep = || std::thread::sleep(Duration::from_micros(10));
+    c.bench_function("assert_max_slow", |b| b.iter(sleep));
+    c.bench_function("assert_max_fast", |b| b.iter(sleep));
+
+    let failures = failed_assertions(&dir, "assert_max_");
+    assert_eq!(failures.len(), 1, "{:?}", failures);
+    assert!(failures[0].contains("assert_max_slow"));
+}
+
+#[test]
+fn test_fail_on_noise() {
+    let dir = temp_dir();
+    let sleep = || std::thread::sleep(Duration::from_micros(10));
+    // No benchmark has a standard error this small.
+    short_benchmark(&dir)
+        .without_plots()
+        .fail_on_noise(1e-12)
+        .bench_function("fail_on_noise_noisy", |b| b.iter(sleep));
+    short_benchmark(&dir)
+        .without_plots()
+        .fail_on_noise(1.0)
+        .bench_function("fail_on_noise_stable", |b| b.iter(sleep));
+
+    let failures = failed_assertions(&dir, "fail_on_noise_");
+    assert_eq!(failures.len(), 1, "{:?}", failures);
+    assert!(failures[0].contains("fail_on_noise_noisy"));
+    assert!(failures[0].contains("sample size"));
+}
+
+#[test]
+fn test_assert_min_throughput() {
+    let dir = temp_dir();
+    let mut c = short_benchmark(&dir)
+        .without_plots()
+        .assert_min_throughput(
+            "^min_throughput/slow$",
+            Throughput::Bytes(1024 * 1024 * 1024),
+        )
+        .assert_min_throughput("^min_throughput/fast$", Throughput::Bytes(1))
+        .assert_min_throughput("^min_elements/", Throughput::Elements(1_000_000_000));
+    let sleep = || std::thread::sleep(Duration::from_micros(10));
+    let mut group = c.benchmark_group("min_throughput");
+    group.throughput(Throughput::Bytes(1024));
+    group.bench_function("slow", |b| b.iter(sleep));
+    group.bench_function("fast", |b| b.iter(sleep));
+    group.finish();
+    let mut group = c.benchmark_group("min_elements");
+    group.throughput(Throughput::Elements(10));
+    group.bench_function("slow", |b| b.iter(sleep));
+    group.finish();
+
+    let failures = failed_assertions(&dir, "Benchmark min_");
+    assert_eq!(failures.len(), 2, "{:?}", failures);
+    assert!(failures[0].contains("min_throughput/slow"));
+    assert!(failures[0].contains("GiB/s"));
+    assert!(failures[1].contains("min_elements/slow"));
+    assert!(failures[1].contains("Gelem/s"));
+}